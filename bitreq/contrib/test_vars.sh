@@ -5,7 +5,7 @@
 # shellcheck disable=SC2034
 
 # Test all these features with "std" enabled.
-FEATURES_WITH_STD="log https https-rustls proxy async async-https"
+FEATURES_WITH_STD="log https https-rustls proxy async async-https download happy-eyeballs"
 
 # Test all these features without "std" enabled.
 FEATURES_WITHOUT_STD="log https https-rustls"
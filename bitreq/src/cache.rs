@@ -0,0 +1,464 @@
+//! On-disk HTTP caching.
+//!
+//! A [`Cache`] can be set with
+//! [`Request::with_cache`](crate::Request::with_cache) or
+//! [`Client::with_cache`](crate::Client::with_cache) to store the
+//! responses to `GET` requests on disk, following the caching rules of
+//! [RFC 9111](https://datatracker.ietf.org/doc/html/rfc9111) for a private
+//! cache.
+
+use core::fmt::Write as _;
+use core::time::Duration;
+use std::fs;
+#[cfg(feature = "async-core")]
+use std::future::Future;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::header::parse_http_date;
+use crate::{Error, HeaderMap, Method, Request, Response, Url};
+
+/// Header fields of a `304 Not Modified` response which are not copied to
+/// the cached response, as they describe the (empty) 304 body.
+const BODY_HEADERS: [&str; 3] = ["content-length", "content-encoding", "transfer-encoding"];
+
+/// The first line of every cache file, to ignore files written in another
+/// format.
+const MAGIC: &str = "bitreq-cache 1";
+
+/// A directory of cached responses.
+///
+/// Only successful (`200 OK`) responses to `GET` requests are stored,
+/// keyed by URL. A cached response is returned without contacting the
+/// server while it is fresh according to its `Cache-Control: max-age` or
+/// `Expires` header. Once stale, or if it has `Cache-Control: no-cache`,
+/// it is revalidated with an `If-None-Match` or `If-Modified-Since`
+/// request, and returned again if the server answers
+/// `304 Not Modified`.
+///
+/// Responses with `Cache-Control: no-store`, a `Vary` header, or neither
+/// freshness information nor a validator (`ETag` or `Last-Modified`) are
+/// not stored. Requests with `Cache-Control: no-store` bypass the cache,
+/// and requests with `Cache-Control: no-cache` always revalidate.
+///
+/// # Example
+///
+/// ```no_run
+/// let cache = bitreq::Cache::new("target/http-cache");
+/// let response = bitreq::get("https://bitcoincore.org/bin/bitcoin-core-29.0/SHA256SUMS")
+///     .with_cache(cache)
+///     .send();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Creates a cache storing its entries in `dir`, which is created
+    /// when the first response is stored.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Cache { Cache { dir: dir.into() } }
+
+    /// Returns the directory the cache is stored in.
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    /// Removes the cached response for `url`, if there is one.
+    pub fn remove(&self, url: &str) -> io::Result<()> {
+        let key = Url::parse(url)
+            .map(|url| cache_key(&url))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        match fs::remove_file(self.path(&key)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            entries => entries?,
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "entry") {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf { self.dir.join(format!("{:016x}.entry", fnv1a(key))) }
+
+    fn load(&self, key: &str) -> Option<Entry> {
+        let entry = Entry::decode(&fs::read(self.path(key)).ok()?)?;
+        // Different URLs may hash to the same file.
+        if entry.key == key {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    fn store(&self, entry: &Entry) {
+        let result = fs::create_dir_all(&self.dir).and_then(|()| {
+            // Write to a temporary file first so readers never see a partial entry.
+            let path = self.path(&entry.key);
+            let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+            fs::File::create(&tmp)?.write_all(&entry.encode())?;
+            fs::rename(tmp, path)
+        });
+        #[cfg(feature = "log")]
+        if let Err(e) = result {
+            log::warn!("Failed to store {} in the HTTP cache: {}", entry.key, e);
+        }
+        #[cfg(not(feature = "log"))]
+        let _ = result;
+    }
+}
+
+/// A cached response.
+#[derive(Debug, PartialEq)]
+struct Entry {
+    key: String,
+    url: String,
+    stored_at: SystemTime,
+    status_code: i32,
+    reason_phrase: String,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Entry {
+    fn new(key: String, response: &Response) -> Entry {
+        Entry {
+            key,
+            url: response.url.clone(),
+            stored_at: SystemTime::now(),
+            status_code: response.status_code,
+            reason_phrase: response.reason_phrase.clone(),
+            headers: response.headers.clone(),
+            body: response.as_bytes().to_vec(),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let stored_at = self.stored_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut head = String::new();
+        writeln!(head, "{}\n{}\n{}\n{}", MAGIC, self.key, self.url, stored_at).unwrap();
+        writeln!(head, "{} {}", self.status_code, self.reason_phrase).unwrap();
+        for (name, value) in self.headers.iter() {
+            writeln!(head, "{}: {}", name, value).unwrap();
+        }
+        head.push('\n');
+        let mut encoded = head.into_bytes();
+        encoded.extend_from_slice(&self.body);
+        encoded
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Entry> {
+        let head_len = bytes.windows(2).position(|w| w == b"\n\n")?;
+        let mut lines = core::str::from_utf8(&bytes[..head_len]).ok()?.lines();
+        if lines.next()? != MAGIC {
+            return None;
+        }
+        let key = lines.next()?.to_string();
+        let url = lines.next()?.to_string();
+        let stored_at = UNIX_EPOCH + Duration::from_secs(lines.next()?.parse().ok()?);
+        let (status_code, reason_phrase) = lines.next()?.split_once(' ')?;
+        let mut headers = HeaderMap::new();
+        for line in lines {
+            let (name, value) = line.split_once(": ")?;
+            headers.append(name, value);
+        }
+        Some(Entry {
+            key,
+            url,
+            stored_at,
+            status_code: status_code.parse().ok()?,
+            reason_phrase: reason_phrase.to_string(),
+            headers,
+            body: bytes[head_len + 2..].to_vec(),
+        })
+    }
+
+    /// Returns whether the entry can be used without revalidating it at
+    /// `now`.
+    fn is_fresh(&self, now: SystemTime) -> bool {
+        if has_directive(&self.headers, "no-cache") {
+            return false;
+        }
+        let age = self.headers.get("age").and_then(|age| age.trim().parse().ok()).unwrap_or(0);
+        let age = Duration::from_secs(age) + now.duration_since(self.stored_at).unwrap_or_default();
+        freshness_lifetime(&self.headers, self.stored_at).is_some_and(|lifetime| age < lifetime)
+    }
+
+    /// Adds the headers to revalidate the entry to `request`. Returns
+    /// false if the entry has no validator or the request already has
+    /// conditional headers.
+    fn add_validators(&self, request: &mut Request) -> bool {
+        let headers = request.headers_mut();
+        if headers.contains_key("if-none-match") || headers.contains_key("if-modified-since") {
+            return false;
+        }
+        if let Some(etag) = self.headers.etag() {
+            headers.insert("If-None-Match", etag);
+        } else if let Some(last_modified) = self.headers.get("last-modified") {
+            headers.insert("If-Modified-Since", last_modified);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Updates the entry with the headers of a `304 Not Modified`
+    /// response revalidating it.
+    fn refresh(&mut self, not_modified: &HeaderMap) {
+        for (name, _) in not_modified.iter() {
+            if BODY_HEADERS.iter().any(|header| name.eq_ignore_ascii_case(header)) {
+                continue;
+            }
+            self.headers.remove(name);
+            for value in not_modified.get_all(name) {
+                self.headers.append(name, value);
+            }
+        }
+        self.stored_at = SystemTime::now();
+    }
+
+    fn to_response(&self) -> Response {
+        Response {
+            status_code: self.status_code,
+            reason_phrase: self.reason_phrase.clone(),
+            headers: self.headers.clone(),
+            url: self.url.clone(),
+            redirects: Vec::new(),
+            attempts: 1,
+            body: self.body.clone(),
+        }
+    }
+}
+
+/// Returns the URL without its fragment, which is not sent to the server.
+fn cache_key(url: &Url) -> String {
+    let mut key = String::new();
+    url.write_base_url_to(&mut key).unwrap();
+    key.push_str(if url.path().is_empty() { "/" } else { url.path() });
+    if let Some(query) = url.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+    key
+}
+
+/// The 64-bit FNV-1a hash, used to name cache files.
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns the directives of the `Cache-Control` headers, with lowercase
+/// names and unquoted values.
+fn directives(headers: &HeaderMap) -> impl Iterator<Item = (String, Option<&str>)> {
+    headers.get_all("cache-control").flat_map(|value| value.split(',')).filter_map(|directive| {
+        let (name, value) = match directive.split_once('=') {
+            Some((name, value)) => (name, Some(value.trim().trim_matches('"'))),
+            None => (directive, None),
+        };
+        let name = name.trim().to_ascii_lowercase();
+        if name.is_empty() {
+            None
+        } else {
+            Some((name, value))
+        }
+    })
+}
+
+fn has_directive(headers: &HeaderMap, directive: &str) -> bool {
+    directives(headers).any(|(name, _)| name == directive)
+}
+
+/// Returns how long a response stored at `stored_at` is fresh for, or
+/// `None` if it has no explicit freshness information.
+fn freshness_lifetime(headers: &HeaderMap, stored_at: SystemTime) -> Option<Duration> {
+    if let Some((_, max_age)) = directives(headers).find(|(name, _)| name == "max-age") {
+        // An invalid max-age makes the response stale.
+        return Some(Duration::from_secs(max_age?.parse().unwrap_or(0)));
+    }
+    let expires = headers.get("expires")?;
+    // An invalid Expires date (eg. "0") means the response has already expired.
+    let expires = parse_http_date(expires).unwrap_or(UNIX_EPOCH);
+    let date = headers.get("date").and_then(parse_http_date).unwrap_or(stored_at);
+    Some(expires.duration_since(date).unwrap_or_default())
+}
+
+/// Returns whether `response` to a request with `request_headers` can be
+/// stored.
+fn is_storable(request_headers: &HeaderMap, response: &Response) -> bool {
+    response.status_code == 200
+        && !has_directive(request_headers, "no-store")
+        && !has_directive(&response.headers, "no-store")
+        && !response.headers.contains_key("vary")
+        && (freshness_lifetime(&response.headers, SystemTime::now()).is_some()
+            || response.headers.etag().is_some()
+            || response.headers.contains_key("last-modified"))
+}
+
+/// What to do with a request before sending it.
+enum Lookup {
+    /// The request is not cached.
+    Bypass,
+    /// A fresh response was found.
+    Hit(Response),
+    /// The request needs to be sent, and its response may be cached.
+    Miss(Miss),
+}
+
+struct Miss {
+    cache: Cache,
+    key: String,
+    request_headers: HeaderMap,
+    /// The stale entry being revalidated by the request, if any.
+    revalidating: Option<Entry>,
+}
+
+fn lookup(request: &mut Request) -> Lookup {
+    let cache = match &request.cache {
+        Some(cache) if *request.method() == Method::Get => cache.clone(),
+        _ => return Lookup::Bypass,
+    };
+    let key = match Url::parse(request.url()) {
+        Ok(mut url) => {
+            for (key, value) in &request.params {
+                url.append_query_param(key, value);
+            }
+            cache_key(&url)
+        }
+        Err(_) => return Lookup::Bypass,
+    };
+    if has_directive(request.headers(), "no-store") {
+        return Lookup::Bypass;
+    }
+    let mut revalidating = None;
+    if let Some(entry) = cache.load(&key) {
+        if !has_directive(request.headers(), "no-cache") && entry.is_fresh(SystemTime::now()) {
+            #[cfg(feature = "log")]
+            log::debug!("Serving {} from the HTTP cache", key);
+            return Lookup::Hit(entry.to_response());
+        }
+        if entry.add_validators(request) {
+            revalidating = Some(entry);
+        }
+    }
+    Lookup::Miss(Miss { cache, key, request_headers: request.headers().clone(), revalidating })
+}
+
+impl Miss {
+    fn complete(self, result: Result<Response, Error>) -> Result<Response, Error> {
+        let response = result?;
+        if let (304, Some(mut entry)) = (response.status_code, self.revalidating) {
+            entry.refresh(&response.headers);
+            self.cache.store(&entry);
+            let mut cached = entry.to_response();
+            cached.attempts = response.attempts;
+            return Ok(cached);
+        }
+        if is_storable(&self.request_headers, &response) {
+            self.cache.store(&Entry::new(self.key, &response));
+        }
+        Ok(response)
+    }
+}
+
+/// Sends `request` with `send`, unless a fresh response is cached in its
+/// [`Cache`], and caches the response.
+pub(crate) fn send<F>(mut request: Request, send: F) -> Result<Response, Error>
+where
+    F: FnOnce(Request) -> Result<Response, Error>,
+{
+    match lookup(&mut request) {
+        Lookup::Bypass => send(request),
+        Lookup::Hit(response) => Ok(response),
+        Lookup::Miss(miss) => miss.complete(send(request)),
+    }
+}
+
+/// Async version of [`send`].
+#[cfg(feature = "async-core")]
+pub(crate) async fn send_async<F, Fut>(mut request: Request, send: F) -> Result<Response, Error>
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<Response, Error>>,
+{
+    match lookup(&mut request) {
+        Lookup::Bypass => send(request).await,
+        Lookup::Hit(response) => Ok(response),
+        Lookup::Miss(miss) => miss.complete(send(request).await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::{freshness_lifetime, Entry};
+    use crate::HeaderMap;
+
+    fn headers(fields: &[(&str, &str)]) -> HeaderMap { fields.iter().copied().collect() }
+
+    fn entry(fields: &[(&str, &str)]) -> Entry {
+        Entry {
+            key: "http://example.com/a?b".to_string(),
+            url: "http://example.com/a?b".to_string(),
+            stored_at: UNIX_EPOCH + Duration::from_secs(784_111_777),
+            status_code: 200,
+            reason_phrase: "OK".to_string(),
+            headers: headers(fields),
+            body: b"\n\nbody\n".to_vec(),
+        }
+    }
+
+    #[test]
+    fn freshness() {
+        let stored_at = UNIX_EPOCH;
+        let lifetime = |fields| freshness_lifetime(&headers(fields), stored_at);
+        assert_eq!(
+            lifetime(&[("Cache-Control", "public, max-age=60")]),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(lifetime(&[("Cache-Control", "max-age=\"x\"")]), Some(Duration::ZERO));
+        let expires = [
+            ("Date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+            ("Expires", "Sun, 06 Nov 1994 09:49:37 GMT"),
+        ];
+        assert_eq!(lifetime(&expires), Some(Duration::from_secs(3600)));
+        assert_eq!(lifetime(&[("Expires", "0")]), Some(Duration::ZERO));
+        assert_eq!(lifetime(&[("ETag", "\"v1\"")]), None);
+
+        let entry = entry(&[("Cache-Control", "max-age=60"), ("Age", "30")]);
+        assert!(entry.is_fresh(entry.stored_at + Duration::from_secs(29)));
+        assert!(!entry.is_fresh(entry.stored_at + Duration::from_secs(30)));
+        let entry = self::entry(&[("Cache-Control", "max-age=60, no-cache")]);
+        assert!(!entry.is_fresh(entry.stored_at));
+    }
+
+    #[test]
+    fn refresh_keeps_body_headers() {
+        let mut entry = entry(&[("Content-Length", "7"), ("ETag", "\"v1\""), ("X-A", "1")]);
+        entry.refresh(&headers(&[("Content-Length", "0"), ("ETag", "\"v2\"")]));
+        assert_eq!(entry.headers.get("content-length"), Some("7"));
+        assert_eq!(entry.headers.etag(), Some("\"v2\""));
+        assert_eq!(entry.headers.get("x-a"), Some("1"));
+        assert!(entry.stored_at > SystemTime::now() - Duration::from_secs(60));
+    }
+
+    #[test]
+    fn entry_round_trip() {
+        let entry = entry(&[("Set-Cookie", "a=1"), ("Set-Cookie", "b=2")]);
+        assert_eq!(Entry::decode(&entry.encode()), Some(entry));
+        assert_eq!(Entry::decode(b"bitreq-cache 0\n\n"), None);
+    }
+}
@@ -2,25 +2,49 @@
 //!
 //! The `Client` caches connections to avoid repeated TCP handshakes and TLS negotiations.
 //!
-//! Due to std limitations, `Client` currently only supports async requests.
-
-#![cfg(feature = "async")]
+//! Due to std limitations, `Client` currently only pools connections for async requests.
 
+#[cfg(feature = "async-core")]
 use std::collections::{hash_map, HashMap, VecDeque};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+#[cfg(feature = "async-core")]
+use std::sync::Mutex;
+use std::time::Instant;
 
+#[cfg(feature = "async-core")]
 use crate::connection::AsyncConnection;
+use crate::limit::{HostLimits, Permit, RateLimiter, Semaphore};
+#[cfg(feature = "async-core")]
 use crate::request::{OwnedConnectionParams as ConnectionKey, ParsedRequest};
-use crate::{Error, Request, Response};
+#[cfg(feature = "async-core")]
+use crate::runtime::RuntimeHandle;
+#[cfg(feature = "rustls")]
+use crate::tls::TlsConfig;
+use crate::trace::TracerHandle;
+#[cfg(feature = "async-core")]
+use crate::Runtime;
+use crate::{
+    cache, retry, Cache, Error, Exchange, Method, Middleware, Request, Response, RetryPolicy,
+    Tracer,
+};
 
-/// A client that caches connections for reuse.
+/// A client that caches connections for reuse, and applies shared settings
+/// such as a [`RetryPolicy`], a [`Cache`] and [`Middleware`] to every
+/// request sent through it.
 ///
 /// The client maintains a pool of up to `capacity` connections, evicting
-/// the least recently used connection when the cache is full.
+/// the least recently used connection when the cache is full. Blocking
+/// requests sent with [`send`](Client::send) are not pooled.
+///
+/// The client can also limit how many requests it sends at once, overall
+/// and to each host, and how fast they read and write, so that it does not
+/// overwhelm a small server. Requests beyond the concurrency limits wait
+/// until earlier requests complete.
 ///
 /// # Example
 ///
 /// ```no_run
+/// # #[cfg(feature = "async-core")]
 /// # async fn request() {
 /// use bitreq::{Client, RequestExt};
 ///
@@ -32,9 +56,23 @@ use crate::{Error, Request, Response};
 /// ```
 #[derive(Clone)]
 pub struct Client {
+    #[cfg(feature = "async-core")]
     r#async: Arc<Mutex<ClientImpl<AsyncConnection>>>,
+    retry_policy: Option<RetryPolicy>,
+    cache: Option<Cache>,
+    #[cfg(feature = "async-core")]
+    runtime: Option<RuntimeHandle>,
+    #[cfg(feature = "rustls")]
+    tls: Option<TlsConfig>,
+    tracer: Option<TracerHandle>,
+    max_requests: Option<Arc<Semaphore>>,
+    host_limits: Option<Arc<HostLimits>>,
+    read_limit: Option<RateLimiter>,
+    write_limit: Option<RateLimiter>,
+    middleware: Vec<Arc<dyn Middleware>>,
 }
 
+#[cfg(feature = "async-core")]
 struct ClientImpl<T> {
     connections: HashMap<ConnectionKey, Arc<T>>,
     lru_order: VecDeque<ConnectionKey>,
@@ -48,18 +86,229 @@ impl Client {
     ///
     /// * `capacity` - Maximum number of cached connections. When this limit is
     ///   reached, the least recently used connection is evicted.
+    #[allow(unused_variables)]
     pub fn new(capacity: usize) -> Self {
         Client {
+            #[cfg(feature = "async-core")]
             r#async: Arc::new(Mutex::new(ClientImpl {
                 connections: HashMap::new(),
                 lru_order: VecDeque::new(),
                 capacity,
             })),
+            retry_policy: None,
+            cache: None,
+            #[cfg(feature = "async-core")]
+            runtime: None,
+            #[cfg(feature = "rustls")]
+            tls: None,
+            tracer: None,
+            max_requests: None,
+            host_limits: None,
+            read_limit: None,
+            write_limit: None,
+            middleware: Vec::new(),
+        }
+    }
+
+    /// Sets the retry policy used for requests which don't have their own
+    /// [`RetryPolicy`] set with
+    /// [`Request::with_retry_policy`](crate::Request::with_retry_policy).
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Sets the [`Cache`] used for `GET` requests which don't have their
+    /// own cache set with [`Request::with_cache`](crate::Request::with_cache).
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sets the [`Runtime`] used for async requests which don't have their
+    /// own runtime set with
+    /// [`Request::with_runtime`](crate::Request::with_runtime).
+    #[cfg(feature = "async-core")]
+    pub fn with_runtime<R: Runtime + 'static>(mut self, runtime: R) -> Self {
+        self.runtime = Some(RuntimeHandle(Arc::new(runtime)));
+        self
+    }
+
+    /// Sets the TLS settings used for HTTPS requests which don't have their
+    /// own settings set with
+    /// [`Request::with_tls_config`](crate::Request::with_tls_config).
+    #[cfg(feature = "rustls")]
+    pub fn with_tls_config(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Sets the [`Tracer`] which requests without their own tracer set with
+    /// [`Request::with_tracer`](crate::Request::with_tracer) are traced to.
+    pub fn with_tracer<T: Tracer + 'static>(mut self, tracer: T) -> Self {
+        self.tracer = Some(TracerHandle(Arc::new(tracer)));
+        self
+    }
+
+    /// Limits how many requests are in flight at once. Further requests
+    /// wait until one completes. At least one request is always allowed.
+    ///
+    /// A request is in flight from when it is sent until its response has
+    /// been received, including any redirects and retries.
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_requests = Some(Semaphore::new(max.max(1)));
+        self
+    }
+
+    /// Limits how many requests are in flight at once to each host (and
+    /// port), like
+    /// [`with_max_concurrent_requests`](Client::with_max_concurrent_requests).
+    pub fn with_max_concurrent_requests_per_host(mut self, max: usize) -> Self {
+        self.host_limits = Some(Arc::new(HostLimits::new(max.max(1))));
+        self
+    }
+
+    /// Limits how fast responses are read, in bytes per second, across all
+    /// requests sent through this client.
+    ///
+    /// Up to a second's worth of bytes can be read at full speed after the
+    /// client was idle.
+    pub fn with_read_rate_limit(mut self, bytes_per_second: u64) -> Self {
+        self.read_limit = Some(RateLimiter::new(bytes_per_second));
+        self
+    }
+
+    /// Limits how fast requests are written, in bytes per second, across
+    /// all requests sent through this client. See
+    /// [`with_read_rate_limit`](Client::with_read_rate_limit).
+    pub fn with_write_rate_limit(mut self, bytes_per_second: u64) -> Self {
+        self.write_limit = Some(RateLimiter::new(bytes_per_second));
+        self
+    }
+
+    /// Adds a [`Middleware`] which is run around every request sent through
+    /// this client, after all previously added middleware.
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Applies the client's settings to `request`.
+    fn prepare(&self, mut request: Request) -> Request {
+        if request.retry_policy.is_none() {
+            request.retry_policy = self.retry_policy.clone();
+        }
+        if request.cache.is_none() {
+            request.cache = self.cache.clone();
+        }
+        #[cfg(feature = "async-core")]
+        if request.runtime.is_none() {
+            request.runtime = self.runtime.clone();
+        }
+        #[cfg(feature = "rustls")]
+        if request.tls.is_none() {
+            request.tls = self.tls.clone();
+        }
+        if request.tracer.is_none() {
+            request.tracer = self.tracer.clone();
+        }
+        request.read_limit = self.read_limit.clone();
+        request.write_limit = self.write_limit.clone();
+        request
+    }
+
+    /// Applies the middleware's [`on_request`](Middleware::on_request) hooks
+    /// to an attempt to send `request`, recording the method and URL of the
+    /// request to send in `sent`.
+    fn on_request(&self, request: Request, sent: &mut (Method, String)) -> Result<Request, Error> {
+        let request = self
+            .middleware
+            .iter()
+            .try_fold(request, |request, middleware| middleware.on_request(request))?;
+        *sent = (request.method().clone(), request.url().to_owned());
+        Ok(request)
+    }
+
+    /// Blocks until the concurrency limits allow `request` to be sent,
+    /// returning the permits to hold until it completes.
+    fn acquire_blocking(&self, request: &Request) -> Vec<Permit> {
+        // The host's permit is acquired first, so requests waiting on a busy
+        // host don't hold up requests to other hosts.
+        let host = self.host_limits.as_ref().and_then(|limits| limits.semaphore(request));
+        host.iter()
+            .chain(&self.max_requests)
+            .map(|semaphore| semaphore.acquire_blocking())
+            .collect()
+    }
+
+    /// Waits until the concurrency limits allow `request` to be sent, like
+    /// [`acquire_blocking`](Client::acquire_blocking).
+    #[cfg(feature = "async-core")]
+    async fn acquire(&self, request: &Request) -> Vec<Permit> {
+        let host = self.host_limits.as_ref().and_then(|limits| limits.semaphore(request));
+        let mut permits = Vec::new();
+        for semaphore in host.iter().chain(&self.max_requests) {
+            permits.push(semaphore.acquire().await);
+        }
+        permits
+    }
+
+    /// Runs the middleware's [`on_response`](Middleware::on_response) hooks.
+    fn complete(
+        &self,
+        method: &Method,
+        url: &str,
+        started: Instant,
+        result: &Result<Response, Error>,
+    ) {
+        let exchange =
+            Exchange { method, url, elapsed: started.elapsed(), result: result.as_ref() };
+        for middleware in self.middleware.iter().rev() {
+            middleware.on_response(&exchange);
+        }
+    }
+
+    /// Sends a request, blocking until the response is received.
+    ///
+    /// The request is sent on a new connection, as connections are only
+    /// pooled for async requests.
+    pub fn send(&self, request: Request) -> Result<Response, Error> {
+        let request = self.prepare(request);
+        let _permits = self.acquire_blocking(&request);
+        if self.middleware.is_empty() {
+            return request.send();
         }
+        let mut sent = (request.method().clone(), request.url().to_owned());
+        let started = Instant::now();
+        let result = cache::send(request, |request| {
+            retry::send(request, |request| self.on_request(request, &mut sent)?.send_once())
+        });
+        self.complete(&sent.0, &sent.1, started, &result);
+        result
     }
 
     /// Sends a request asynchronously using a cached connection if available.
+    #[cfg(feature = "async-core")]
     pub async fn send_async(&self, request: Request) -> Result<Response, Error> {
+        let request = self.prepare(request);
+        let _permits = self.acquire(&request).await;
+        let mut sent = (request.method().clone(), request.url().to_owned());
+        let started = Instant::now();
+        let result = cache::send_async(request, |request| {
+            retry::send_async(request, |request| {
+                let request = self.on_request(request, &mut sent);
+                async move { self.send_async_once(request?).await }
+            })
+        })
+        .await;
+        if !self.middleware.is_empty() {
+            self.complete(&sent.0, &sent.1, started, &result);
+        }
+        result
+    }
+
+    #[cfg(feature = "async-core")]
+    async fn send_async_once(&self, request: Request) -> Result<Response, Error> {
         let parsed_request = ParsedRequest::new(request)?;
         let key = parsed_request.connection_params();
         let owned_key = key.into();
@@ -77,7 +326,9 @@ impl Client {
         let conn = if let Some(conn) = conn_opt {
             conn
         } else {
-            let connection = AsyncConnection::new(key, parsed_request.timeout_at).await?;
+            let connection = AsyncConnection::new(key, parsed_request.timeout_at)
+                .await
+                .map_err(|e| e.with_url(&parsed_request.url))?;
             let connection = Arc::new(connection);
 
             let mut state = self.r#async.lock().unwrap();
@@ -94,11 +345,13 @@ impl Client {
         };
 
         // Send the request
-        conn.send(parsed_request).await
+        let url = parsed_request.url.clone();
+        conn.send(parsed_request).await.map_err(|e| e.with_url(&url))
     }
 }
 
 /// Extension trait for `Request` to use with `Client`.
+#[cfg(feature = "async-core")]
 pub trait RequestExt {
     /// Sends this request asynchronously using the provided client's connection pool.
     fn send_async_with_client(
@@ -107,6 +360,7 @@ pub trait RequestExt {
     ) -> impl std::future::Future<Output = Result<Response, Error>>;
 }
 
+#[cfg(feature = "async-core")]
 impl RequestExt for Request {
     fn send_async_with_client(
         self,
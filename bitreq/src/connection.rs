@@ -1,62 +1,104 @@
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
 use core::time::Duration;
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use std::future::Future;
-use std::io::{self, Read, Write};
-use std::net::{TcpStream, ToSocketAddrs};
-#[cfg(feature = "async")]
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+use std::io::Write;
+use std::io::{self, Read};
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use std::net::SocketAddr;
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+use std::net::TcpStream;
+#[cfg(feature = "async-core")]
 use std::pin::Pin;
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use std::sync::atomic::{AtomicUsize, Ordering};
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use std::sync::{Arc, Mutex};
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use std::task::{Context, Poll};
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
 use std::time::Instant;
 
-#[cfg(all(feature = "async", feature = "proxy"))]
+#[cfg(all(feature = "async-core", feature = "proxy"))]
 use tokio::io::AsyncReadExt;
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
-#[cfg(feature = "async")]
-use tokio::net::TcpStream as AsyncTcpStream;
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use tokio::sync::Mutex as AsyncMutex;
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+use crate::connector::{connect_unix, Stream};
+#[cfg(feature = "async-core")]
+use crate::connector::{connect_unix_async, AsyncStream};
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use crate::limit::Throttled;
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
 use crate::request::{ConnectionParams, OwnedConnectionParams, ParsedRequest};
-#[cfg(feature = "async")]
-use crate::Response;
-use crate::{Error, Method, ResponseLazy};
-
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use crate::resolver::{interleave_address_families, ResolverHandle};
+#[cfg(feature = "async-core")]
+use crate::runtime::{yield_now, IoStream as AsyncTcpStream, RuntimeFuture, RuntimeHandle};
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use crate::trace::{TraceReader, WireTrace};
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+use crate::ResponseLazy;
+#[cfg(feature = "async-core")]
+use crate::{AsyncIo, AsyncResponseLazy, Method, Response};
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use crate::{Error, Resolve, SystemResolver};
+
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 type UnsecuredStream = TcpStream;
 
+/// How long to wait for a connection attempt to succeed before starting one to the next address,
+/// as recommended by [RFC 8305 section 5](https://datatracker.ietf.org/doc/html/rfc8305#section-5).
+#[cfg(any(feature = "async-core", all(feature = "happy-eyeballs", not(target_family = "wasm"))))]
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
 #[cfg(feature = "rustls")]
 mod rustls_stream;
 #[cfg(feature = "rustls")]
 type SecuredStream = rustls_stream::SecuredStream;
 
 pub(crate) enum HttpStream {
+    #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
     Unsecured(UnsecuredStream, Option<Instant>),
-    #[cfg(feature = "rustls")]
+    #[cfg(all(feature = "rustls", not(all(feature = "wasm", target_family = "wasm"))))]
     Secured(Box<SecuredStream>, Option<Instant>),
-    #[cfg(feature = "async")]
+    #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+    Custom(Box<dyn Stream>, Option<Instant>),
+    #[cfg(any(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
     Buffer(std::io::Cursor<Vec<u8>>),
 }
 
 impl HttpStream {
+    #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
     fn create_unsecured(reader: UnsecuredStream, timeout_at: Option<Instant>) -> HttpStream {
         HttpStream::Unsecured(reader, timeout_at)
     }
 
-    #[cfg(feature = "async")]
+    #[cfg(any(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
     pub(crate) fn create_buffer(buffer: Vec<u8>) -> HttpStream {
         HttpStream::Buffer(std::io::Cursor::new(buffer))
     }
 }
 
-fn timeout_err() -> io::Error {
+pub(crate) fn timeout_err() -> io::Error {
     io::Error::new(io::ErrorKind::TimedOut, "the timeout of the request was reached")
 }
 
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+/// The error returned for HTTPS requests sent over a custom stream, which
+/// can't be wrapped in TLS.
+fn custom_stream_tls_err() -> Error {
+    Error::IoError(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "HTTPS requests cannot be sent over a custom connector or Unix socket",
+    ))
+}
+
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 fn timeout_at_to_duration(timeout_at: Option<Instant>) -> Result<Option<Duration>, io::Error> {
     if let Some(timeout_at) = timeout_at {
         if let Some(duration) = timeout_at.checked_duration_since(Instant::now()) {
@@ -71,22 +113,29 @@ fn timeout_at_to_duration(timeout_at: Option<Instant>) -> Result<Option<Duration
 
 impl Read for HttpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
         let timeout = |tcp: &TcpStream, timeout_at: Option<Instant>| -> io::Result<()> {
             let _ = tcp.set_read_timeout(timeout_at_to_duration(timeout_at)?);
             Ok(())
         };
 
         let result = match self {
+            #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
             HttpStream::Unsecured(inner, timeout_at) => {
                 timeout(inner, *timeout_at)?;
                 inner.read(buf)
             }
-            #[cfg(feature = "rustls")]
+            #[cfg(all(feature = "rustls", not(all(feature = "wasm", target_family = "wasm"))))]
             HttpStream::Secured(inner, timeout_at) => {
                 timeout(inner.get_ref(), *timeout_at)?;
                 inner.read(buf)
             }
-            #[cfg(feature = "async")]
+            #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+            HttpStream::Custom(inner, timeout_at) => {
+                let _ = inner.set_read_timeout(timeout_at_to_duration(*timeout_at)?);
+                inner.read(buf)
+            }
+            #[cfg(any(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
             HttpStream::Buffer(cursor) => std::io::Read::read(cursor, buf),
         };
         match result {
@@ -99,24 +148,32 @@ impl Read for HttpStream {
     }
 }
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 fn set_socket_write_timeout(tcp: &TcpStream, timeout_at: Option<Instant>) -> io::Result<()> {
     tcp.set_write_timeout(timeout_at_to_duration(timeout_at)?)?;
     Ok(())
 }
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 impl Write for HttpStream {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let result = match self {
+            #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
             HttpStream::Unsecured(inner, timeout_at) => {
                 set_socket_write_timeout(inner, *timeout_at)?;
                 inner.write(buf)
             }
-            #[cfg(feature = "rustls")]
+            #[cfg(all(feature = "rustls", not(all(feature = "wasm", target_family = "wasm"))))]
             HttpStream::Secured(inner, timeout_at) => {
                 set_socket_write_timeout(inner.get_ref(), *timeout_at)?;
                 inner.write(buf)
             }
-            #[cfg(feature = "async")]
+            #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+            HttpStream::Custom(inner, timeout_at) => {
+                inner.set_write_timeout(timeout_at_to_duration(*timeout_at)?)?;
+                inner.write(buf)
+            }
+            #[cfg(any(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
             HttpStream::Buffer(_) => {
                 debug_assert!(false, "We shouldn't write to a pre-loaded stream");
                 Ok(buf.len())
@@ -133,16 +190,22 @@ impl Write for HttpStream {
 
     fn flush(&mut self) -> io::Result<()> {
         let result = match self {
+            #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
             HttpStream::Unsecured(inner, timeout_at) => {
                 set_socket_write_timeout(inner, *timeout_at)?;
                 inner.flush()
             }
-            #[cfg(feature = "rustls")]
+            #[cfg(all(feature = "rustls", not(all(feature = "wasm", target_family = "wasm"))))]
             HttpStream::Secured(inner, timeout_at) => {
                 set_socket_write_timeout(inner.get_ref(), *timeout_at)?;
                 inner.flush()
             }
-            #[cfg(feature = "async")]
+            #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+            HttpStream::Custom(inner, timeout_at) => {
+                inner.set_write_timeout(timeout_at_to_duration(*timeout_at)?)?;
+                inner.flush()
+            }
+            #[cfg(any(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
             HttpStream::Buffer(_) => {
                 debug_assert!(false, "We shouldn't write to a pre-loaded stream");
                 Ok(())
@@ -161,14 +224,19 @@ impl Write for HttpStream {
 #[cfg(feature = "tokio-rustls")]
 type AsyncSecuredStream = rustls_stream::AsyncSecuredStream;
 
-#[cfg(feature = "async")]
+/// A pending connection attempt to an address, raced by `tcp_connect`.
+#[cfg(feature = "async-core")]
+type ConnectAttempt<'a> = (SocketAddr, RuntimeFuture<'a, io::Result<Box<dyn AsyncIo>>>);
+
+#[cfg(feature = "async-core")]
 pub(crate) enum AsyncHttpStream {
     Unsecured(AsyncTcpStream),
     #[cfg(feature = "tokio-rustls")]
     Secured(Box<AsyncSecuredStream>),
+    Custom(Box<dyn AsyncStream>),
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 impl AsyncRead for AsyncHttpStream {
     fn poll_read(
         mut self: Pin<&mut Self>,
@@ -179,11 +247,12 @@ impl AsyncRead for AsyncHttpStream {
             AsyncHttpStream::Unsecured(inner) => Pin::new(inner).poll_read(cx, buf),
             #[cfg(feature = "tokio-rustls")]
             AsyncHttpStream::Secured(inner) => Pin::new(inner).poll_read(cx, buf),
+            AsyncHttpStream::Custom(inner) => Pin::new(inner).poll_read(cx, buf),
         }
     }
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 impl AsyncWrite for AsyncHttpStream {
     fn poll_write(
         mut self: Pin<&mut Self>,
@@ -194,6 +263,7 @@ impl AsyncWrite for AsyncHttpStream {
             AsyncHttpStream::Unsecured(inner) => Pin::new(inner).poll_write(cx, buf),
             #[cfg(feature = "tokio-rustls")]
             AsyncHttpStream::Secured(inner) => Pin::new(inner).poll_write(cx, buf),
+            AsyncHttpStream::Custom(inner) => Pin::new(inner).poll_write(cx, buf),
         }
     }
 
@@ -202,6 +272,7 @@ impl AsyncWrite for AsyncHttpStream {
             AsyncHttpStream::Unsecured(inner) => Pin::new(inner).poll_flush(cx),
             #[cfg(feature = "tokio-rustls")]
             AsyncHttpStream::Secured(inner) => Pin::new(inner).poll_flush(cx),
+            AsyncHttpStream::Custom(inner) => Pin::new(inner).poll_flush(cx),
         }
     }
 
@@ -210,11 +281,12 @@ impl AsyncWrite for AsyncHttpStream {
             AsyncHttpStream::Unsecured(inner) => Pin::new(inner).poll_shutdown(cx),
             #[cfg(feature = "tokio-rustls")]
             AsyncHttpStream::Secured(inner) => Pin::new(inner).poll_shutdown(cx),
+            AsyncHttpStream::Custom(inner) => Pin::new(inner).poll_shutdown(cx),
         }
     }
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 struct AsyncConnectionState {
     write: AsyncMutex<WriteHalf<AsyncHttpStream>>,
     read: AsyncMutex<ReadHalf<AsyncHttpStream>>,
@@ -240,13 +312,13 @@ struct AsyncConnectionState {
     socket_new_requests_timeout: Mutex<Instant>,
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 struct PendingReader<'a> {
     min_dropped_reader_id: &'a AtomicUsize,
     id: Option<usize>,
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 impl<'a> Drop for PendingReader<'a> {
     fn drop(&mut self) {
         if let Some(reader_id) = self.id {
@@ -257,35 +329,17 @@ impl<'a> Drop for PendingReader<'a> {
 
 /// An async connection to the server for sending
 /// [`Request`](struct.Request.html)s.
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 pub struct AsyncConnection(Mutex<Arc<AsyncConnectionState>>);
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 impl AsyncConnection {
     /// Creates a new `AsyncConnection`.
     pub(crate) async fn new(
         params: ConnectionParams<'_>,
         timeout_at: Option<Instant>,
     ) -> Result<AsyncConnection, Error> {
-        let future = async move {
-            let socket = Self::connect(params).await?;
-
-            if params.https {
-                #[cfg(not(feature = "tokio-rustls"))]
-                return Err(Error::HttpsFeatureNotEnabled);
-                #[cfg(feature = "tokio-rustls")]
-                rustls_stream::wrap_async_stream(socket, params.host).await
-            } else {
-                Ok(AsyncHttpStream::Unsecured(socket))
-            }
-        };
-        let stream = if let Some(timeout_at) = timeout_at {
-            tokio::time::timeout_at(timeout_at.into(), future)
-                .await
-                .unwrap_or(Err(Error::IoError(timeout_err())))?
-        } else {
-            future.await?
-        };
+        let stream = Self::connect_stream(params, timeout_at).await?;
         let (read, write) = tokio::io::split(stream);
 
         Ok(AsyncConnection(Mutex::new(Arc::new(AsyncConnectionState {
@@ -298,47 +352,189 @@ impl AsyncConnection {
         }))))
     }
 
-    async fn tcp_connect(host: &str, port: u16) -> Result<AsyncTcpStream, Error> {
+    /// Opens the (possibly TLS-wrapped) stream for a new connection.
+    async fn connect_stream(
+        params: ConnectionParams<'_>,
+        timeout_at: Option<Instant>,
+    ) -> Result<AsyncHttpStream, Error> {
+        let runtime = RuntimeHandle::or_default(params.runtime)
+            .map_err(|e| e.connecting_to(params.host, params.port))?;
+        let future = async {
+            let custom = match params.connector {
+                Some(connector) => Some(connector.0.connect_async(params.host, params.port).await),
+                None if params.unix => Some(connect_unix_async(&runtime, params.host).await),
+                None => None,
+            };
+            if let Some(stream) = custom {
+                if params.https {
+                    return Err(custom_stream_tls_err());
+                }
+                return Ok(AsyncHttpStream::Custom(stream?));
+            }
+
+            let socket = Self::connect(&runtime, params).await?;
+
+            if params.https {
+                #[cfg(not(feature = "tokio-rustls"))]
+                return Err(Error::HttpsFeatureNotEnabled);
+                #[cfg(feature = "tokio-rustls")]
+                rustls_stream::wrap_async_stream(socket, params.host, params.tls).await
+            } else {
+                Ok(AsyncHttpStream::Unsecured(socket))
+            }
+        };
+        let (host, port) = (params.host, params.port);
+        let result = runtime.timeout_at(timeout_at, future).await.and_then(|result| result);
+        result.map_err(|e| e.connecting_to(host, port))
+    }
+
+    /// Sends the [`Request`](struct.Request.html) on a new connection
+    /// which is not shared with any other request, returning as soon as
+    /// the response's headers have been read so the body can be streamed.
+    pub(crate) fn send_lazy(
+        request: ParsedRequest,
+    ) -> Pin<Box<dyn Future<Output = Result<AsyncResponseLazy, Error>> + Send>> {
+        Box::pin(async move {
+            let runtime = RuntimeHandle::or_default(request.config.runtime.as_ref())?;
+            let mut stream =
+                Self::connect_stream(request.connection_params(), request.timeout_at).await?;
+
+            #[cfg(feature = "log")]
+            log::trace!("Writing HTTP request.");
+            let trace = WireTrace::start(&request);
+            let limit = request.config.write_limit.clone();
+            let mut writer = Throttled::new_async(&mut stream, limit, &runtime);
+            Self::timeout(&request, writer.write_all(&request.as_bytes())).await??;
+
+            #[cfg(feature = "log")]
+            log::trace!("Reading HTTP response.");
+            let limit = request.config.read_limit.clone();
+            let stream = Throttled::new_async(stream, limit, &runtime);
+            let mut response = AsyncResponseLazy::from_stream(
+                TraceReader::new(stream, trace),
+                request.config.max_headers_size,
+                request.config.max_status_line_len,
+                request.config.max_body_size,
+                request.timeout_at,
+                runtime,
+            )
+            .await?;
+
+            let status_code = response.status_code;
+            let url = response.headers.location();
+            match async_get_redirect(request, status_code, url) {
+                NextHopAsync::Redirect(request) => {
+                    let (request, _) = request?;
+                    Self::send_lazy(request).await
+                }
+                NextHopAsync::Destination(request) => {
+                    let dst_url = &request.url;
+                    dst_url.write_base_url_to(&mut response.url).unwrap();
+                    dst_url.write_resource_to(&mut response.url).unwrap();
+                    response.redirects = request.redirect_history();
+                    Ok(response)
+                }
+            }
+        })
+    }
+
+    async fn tcp_connect(
+        runtime: &RuntimeHandle,
+        resolver: Option<&ResolverHandle>,
+        host: &str,
+        port: u16,
+    ) -> Result<AsyncTcpStream, Error> {
         #[cfg(feature = "log")]
         log::trace!("Looking up host {host}");
 
-        let addrs = tokio::net::lookup_host((host, port)).await.map_err(Error::IoError)?;
-        let addrs: Vec<_> = addrs.collect();
-        let addrs_count = addrs.len();
-
+        let resolver = resolver.cloned();
+        let owned_host = host.to_owned();
+        let addrs = runtime
+            .spawn_blocking(move || match resolver {
+                Some(resolver) => resolver.0.resolve(&owned_host, port),
+                None => SystemResolver.resolve(&owned_host, port),
+            })
+            .await??;
         if addrs.is_empty() {
             return Err(Error::AddressNotFound);
         }
 
-        // Try all resolved addresses. Return the first one to which we could connect. If all
-        // failed return the last error encountered.
-        for (i, addr) in addrs.iter().enumerate() {
+        let addrs = interleave_address_families(addrs);
+        if let [addr] = addrs[..] {
             #[cfg(feature = "log")]
             log::trace!("Attempting to connect to {addr} for {host}");
-
-            match AsyncTcpStream::connect(addr).await {
-                Ok(s) => {
+            return runtime.0.connect(addr).await.map(AsyncTcpStream).map_err(Error::IoError);
+        }
+        let mut addrs = addrs.into_iter();
+
+        // Race connection attempts to all resolved addresses as described in RFC 8305, starting a
+        // new attempt whenever the previous one failed or has not succeeded within
+        // `CONNECTION_ATTEMPT_DELAY`. Return the first one to which we could connect. If all
+        // failed return the last error encountered. The attempts which lost the race are dropped
+        // (and thus cancelled) along with this future.
+        let mut attempts: Vec<ConnectAttempt<'_>> = Vec::new();
+        let mut delay: Option<RuntimeFuture<'static, ()>> = None;
+        let mut attempt_failed = false;
+        let mut last_err = None;
+        core::future::poll_fn(|cx| loop {
+            let delay_elapsed =
+                delay.as_mut().is_some_and(|delay| delay.as_mut().poll(cx).is_ready());
+            let mut progress = false;
+            if attempts.is_empty() || delay_elapsed || attempt_failed {
+                attempt_failed = false;
+                delay = None;
+                if let Some(addr) = addrs.next() {
                     #[cfg(feature = "log")]
-                    log::trace!("Connected to {addr} for {host}");
-                    return Ok(s);
+                    log::trace!("Attempting to connect to {addr} for {host}");
+                    attempts.push((addr, runtime.0.connect(addr)));
+                    if addrs.len() > 0 {
+                        delay = Some(runtime.0.sleep(CONNECTION_ATTEMPT_DELAY));
+                    }
+                    progress = true;
                 }
-                Err(e) =>
-                    if i == addrs_count - 1 {
-                        return Err(Error::IoError(e));
-                    },
             }
-        }
 
-        Err(Error::AddressNotFound)
+            let mut i = 0;
+            while i < attempts.len() {
+                match attempts[i].1.as_mut().poll(cx) {
+                    Poll::Ready(Ok(stream)) => {
+                        #[cfg(feature = "log")]
+                        log::trace!("Connected to {} for {host}", attempts[i].0);
+                        return Poll::Ready(Ok(AsyncTcpStream(stream)));
+                    }
+                    Poll::Ready(Err(e)) => {
+                        drop(attempts.swap_remove(i));
+                        last_err = Some(e);
+                        attempt_failed = true;
+                        progress = true;
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+            if attempts.is_empty() && addrs.len() == 0 {
+                return Poll::Ready(Err(last_err
+                    .take()
+                    .map(Error::IoError)
+                    .unwrap_or(Error::AddressNotFound)));
+            }
+            if !progress {
+                return Poll::Pending;
+            }
+        })
+        .await
     }
 
     /// Asynchronously connect to the server.
-    async fn connect(params: ConnectionParams<'_>) -> Result<AsyncTcpStream, Error> {
+    async fn connect(
+        runtime: &RuntimeHandle,
+        params: ConnectionParams<'_>,
+    ) -> Result<AsyncTcpStream, Error> {
         #[cfg(feature = "proxy")]
         match &params.proxy {
             Some(proxy) => {
                 // do proxy things
-                let mut tcp = Self::tcp_connect(&proxy.server, proxy.port).await?;
+                let mut tcp =
+                    Self::tcp_connect(runtime, params.resolver, &proxy.server, proxy.port).await?;
 
                 let proxy_request = proxy.connect(params.host, params.port);
                 tcp.write_all(proxy_request.as_bytes()).await?;
@@ -369,19 +565,17 @@ impl AsyncConnection {
 
                 Ok(tcp)
             }
-            None => Self::tcp_connect(params.host, params.port).await,
+            None => Self::tcp_connect(runtime, params.resolver, params.host, params.port).await,
         }
 
         #[cfg(not(feature = "proxy"))]
-        Self::tcp_connect(&params.host, params.port).await
+        Self::tcp_connect(runtime, params.resolver, params.host, params.port).await
     }
 
-    async fn timeout<O, F: Future<Output = O>>(timeout: Option<Instant>, f: F) -> Result<O, Error> {
-        if let Some(time) = timeout {
-            tokio::time::timeout_at(time.into(), f).await.map_err(|_| Error::IoError(timeout_err()))
-        } else {
-            Ok(f.await)
-        }
+    /// Awaits `f`, failing once the timeout of `request` is reached.
+    async fn timeout<O, F: Future<Output = O>>(request: &ParsedRequest, f: F) -> Result<O, Error> {
+        let runtime = RuntimeHandle::or_default(request.config.runtime.as_ref())?;
+        runtime.timeout_at(request.timeout_at, f).await
     }
 
     /// Sends the [`Request`](struct.Request.html) asynchronously using HTTP.
@@ -390,6 +584,7 @@ impl AsyncConnection {
         request: ParsedRequest,
     ) -> Pin<Box<dyn Future<Output = Result<Response, Error>> + Send + 'a>> {
         Box::pin(async move {
+            let runtime = RuntimeHandle::or_default(request.config.runtime.as_ref())?;
             let conn = Arc::clone(&*self.0.lock().unwrap());
             #[cfg(debug_assertions)]
             {
@@ -415,8 +610,8 @@ impl AsyncConnection {
                 // new connection (because `next_request_id` is `usize::MAX`) or there are no
                 // pending readers (because `next_request_id` and `readable_request_id` are the
                 // same).
-                read = Some(Self::timeout(request.timeout_at, conn.read.lock()).await?);
-                write = Some(Self::timeout(request.timeout_at, conn.write.lock()).await?);
+                read = Some(Self::timeout(&request, conn.read.lock()).await?);
+                write = Some(Self::timeout(&request, conn.write.lock()).await?);
                 while {
                     let next_read = conn.readable_request_id.load(Ordering::Relaxed);
                     let next_request = conn.next_request_id.load(Ordering::Relaxed);
@@ -424,9 +619,9 @@ impl AsyncConnection {
                 } {
                     read.take();
                     write.take();
-                    tokio::task::yield_now().await;
-                    read = Some(Self::timeout(request.timeout_at, conn.read.lock()).await?);
-                    write = Some(Self::timeout(request.timeout_at, conn.write.lock()).await?);
+                    yield_now().await;
+                    read = Some(Self::timeout(&request, conn.read.lock()).await?);
+                    write = Some(Self::timeout(&request, conn.write.lock()).await?);
                 }
             }
 
@@ -459,13 +654,14 @@ impl AsyncConnection {
             }
 
             let request_id;
+            let mut trace;
             let mut this_request =
                 PendingReader { min_dropped_reader_id: &conn.min_dropped_reader_id, id: None };
             {
                 let mut write = if let Some(write) = write {
                     write
                 } else {
-                    Self::timeout(request.timeout_at, conn.write.lock()).await?
+                    Self::timeout(&request, conn.write.lock()).await?
                 };
 
                 let socket_timeout = *conn.socket_new_requests_timeout.lock().unwrap();
@@ -483,8 +679,11 @@ impl AsyncConnection {
                     request.connection_params(),
                 );
                 this_request.id = Some(request_id);
+                trace = WireTrace::start(&request);
+                let limit = request.config.write_limit.clone();
+                let mut writer = Throttled::new_async(&mut *write, limit, &runtime);
                 let write_res =
-                    Self::timeout(request.timeout_at, write.write_all(&request.as_bytes())).await;
+                    Self::timeout(&request, writer.write_all(&request.as_bytes())).await;
                 match write_res {
                     Err(e) => {
                         // If we failed to write the request, mark the socket as dead for future
@@ -501,9 +700,9 @@ impl AsyncConnection {
             }
 
             let mut should_retry = false;
-            let response = Self::timeout(request.timeout_at, async {
+            let response = Self::timeout(&request, async {
                 if read.is_none() {
-                    read = Some(Self::timeout(request.timeout_at, conn.read.lock()).await?);
+                    read = Some(Self::timeout(&request, conn.read.lock()).await?);
                 }
 
                 while {
@@ -535,7 +734,7 @@ impl AsyncConnection {
                         should_retry = true;
                         return Err(Error::Other("Retrying pipelining failure"));
                     }
-                    tokio::task::yield_now().await;
+                    yield_now().await;
                     read = Some(conn.read.lock().await);
                 }
                 let mut read = read.take().unwrap();
@@ -547,8 +746,10 @@ impl AsyncConnection {
                     request.connection_params(),
                 );
 
+                let limit = request.config.read_limit.clone();
+                let reader = Throttled::new_async(&mut *read, limit, &runtime);
                 let response = Response::create_async(
-                    &mut *read,
+                    TraceReader::new(reader, trace.take()),
                     request.config.method == Method::Head,
                     request.config.max_headers_size,
                     request.config.max_status_line_len,
@@ -637,12 +838,14 @@ impl AsyncConnection {
     }
 }
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 /// A connection to the server for sending
 /// [`Request`](struct.Request.html)s.
 pub struct Connection {
     stream: HttpStream,
 }
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 impl Connection {
     /// Creates a new `Connection`. See [Request] and [ParsedRequest]
     /// for specifics about *what* is being sent.
@@ -650,14 +853,29 @@ impl Connection {
         params: ConnectionParams<'_>,
         timeout_at: Option<Instant>,
     ) -> Result<Connection, Error> {
-        let socket = Self::connect(params, timeout_at)?;
+        let (host, port) = (params.host, params.port);
+        let custom = match params.connector {
+            Some(connector) => Some(connector.0.connect(params.host, params.port)),
+            None if params.unix => Some(connect_unix(params.host)),
+            None => None,
+        };
+        if let Some(stream) = custom {
+            if params.https {
+                return Err(custom_stream_tls_err().connecting_to(host, port));
+            }
+            let stream = stream.map_err(|e| Error::IoError(e).connecting_to(host, port))?;
+            return Ok(Connection { stream: HttpStream::Custom(stream, timeout_at) });
+        }
+
+        let socket = Self::connect(params, timeout_at).map_err(|e| e.connecting_to(host, port))?;
 
         let stream = if params.https {
             #[cfg(not(feature = "rustls"))]
             return Err(Error::HttpsFeatureNotEnabled);
             #[cfg(feature = "rustls")]
             {
-                let tls = rustls_stream::wrap_stream(socket, params.host)?;
+                let tls = rustls_stream::wrap_stream(socket, host, params.tls, timeout_at)
+                    .map_err(|e| e.connecting_to(host, port))?;
                 HttpStream::Secured(Box::new(tls), timeout_at)
             }
         } else {
@@ -667,39 +885,36 @@ impl Connection {
         Ok(Connection { stream })
     }
 
-    fn tcp_connect(host: &str, port: u16, timeout_at: Option<Instant>) -> Result<TcpStream, Error> {
+    fn tcp_connect(
+        resolver: Option<&ResolverHandle>,
+        host: &str,
+        port: u16,
+        timeout_at: Option<Instant>,
+    ) -> Result<TcpStream, Error> {
         #[cfg(feature = "log")]
         log::trace!("Looking up host {host}");
 
-        let addrs = (host, port).to_socket_addrs().map_err(Error::IoError)?;
-        let addrs_count = addrs.len();
+        let addrs = match resolver {
+            Some(resolver) => resolver.0.resolve(host, port),
+            None => SystemResolver.resolve(host, port),
+        }
+        .map_err(Error::IoError)?;
+        if addrs.is_empty() {
+            return Err(Error::AddressNotFound);
+        }
 
-        // Try all resolved addresses. Return the first one to which we could connect. If all
-        // failed return the last error encountered.
-        for (i, addr) in addrs.enumerate() {
+        let addrs = interleave_address_families(addrs);
+        if let [addr] = addrs[..] {
             #[cfg(feature = "log")]
             log::trace!("Attempting to connect to {addr} for {host}");
-
-            let stream = if let Some(timeout) = timeout_at_to_duration(timeout_at)? {
-                TcpStream::connect_timeout(&addr, timeout)
-            } else {
-                TcpStream::connect(addr)
-            };
-
-            match stream {
-                Ok(s) => {
-                    #[cfg(feature = "log")]
-                    log::trace!("Connected to {addr} for {host}");
-                    return Ok(s);
-                }
-                Err(e) =>
-                    if i == addrs_count - 1 {
-                        return Err(Error::IoError(e));
-                    },
-            }
+            return tcp_connect_addr(addr, timeout_at).map_err(Error::IoError);
         }
-
-        Err(Error::AddressNotFound)
+        let result = race_connect(addrs, timeout_at);
+        #[cfg(feature = "log")]
+        if let Ok(stream) = &result {
+            log::trace!("Connected to {:?} for {host}", stream.peer_addr());
+        }
+        result
     }
 
     /// Connect to the server.
@@ -711,7 +926,8 @@ impl Connection {
         match &params.proxy {
             Some(proxy) => {
                 // do proxy things
-                let mut tcp = Self::tcp_connect(&proxy.server, proxy.port, timeout_at)?;
+                let mut tcp =
+                    Self::tcp_connect(params.resolver, &proxy.server, proxy.port, timeout_at)?;
 
                 write!(tcp, "{}", proxy.connect(params.host, params.port))?;
                 tcp.flush()?;
@@ -741,11 +957,11 @@ impl Connection {
 
                 Ok(tcp)
             }
-            None => Self::tcp_connect(params.host, params.port, timeout_at),
+            None => Self::tcp_connect(params.resolver, params.host, params.port, timeout_at),
         }
 
         #[cfg(not(feature = "proxy"))]
-        Self::tcp_connect(params.host, params.port, timeout_at)
+        Self::tcp_connect(params.resolver, params.host, params.port, timeout_at)
     }
 
     /// Sends the [`Request`](struct.Request.html), consumes this
@@ -755,13 +971,16 @@ impl Connection {
             // Send request
             #[cfg(feature = "log")]
             log::trace!("Writing HTTP request.");
-            self.stream.write_all(&request.as_bytes())?;
+            let trace = WireTrace::start(&request);
+            let limit = request.config.write_limit.clone();
+            Throttled::new(&mut self.stream, limit).write_all(&request.as_bytes())?;
 
             // Receive response
             #[cfg(feature = "log")]
             log::trace!("Reading HTTP response.");
+            let limit = request.config.read_limit.clone();
             let response = ResponseLazy::from_stream(
-                self.stream,
+                TraceReader::new(Throttled::new(self.stream, limit), trace),
                 request.config.max_headers_size,
                 request.config.max_status_line_len,
                 request.config.max_body_size,
@@ -771,34 +990,150 @@ impl Connection {
     }
 }
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+fn tcp_connect_addr(addr: SocketAddr, timeout_at: Option<Instant>) -> io::Result<TcpStream> {
+    if let Some(timeout) = timeout_at_to_duration(timeout_at)? {
+        TcpStream::connect_timeout(&addr, timeout)
+    } else {
+        TcpStream::connect(addr)
+    }
+}
+
+/// Races connection attempts to `addrs` as described in RFC 8305, starting a new attempt whenever
+/// the previous one failed or has not succeeded within `CONNECTION_ATTEMPT_DELAY`. Returns the
+/// first connection established, or the last error encountered if all attempts failed.
+///
+/// The attempts are non-blocking connects polled together, so the ones which lost the race are
+/// closed when this returns.
+#[cfg(all(feature = "happy-eyeballs", not(target_family = "wasm")))]
+fn race_connect(addrs: Vec<SocketAddr>, timeout_at: Option<Instant>) -> Result<TcpStream, Error> {
+    use mio::net::TcpStream as MioTcpStream;
+    use mio::{Events, Interest, Poll, Token};
+
+    let mut poll = Poll::new()?;
+    let mut events = Events::with_capacity(addrs.len());
+    // The attempts in flight, indexed by their token.
+    let mut attempts: Vec<Option<MioTcpStream>> = Vec::new();
+    let mut in_flight = 0;
+    let mut addrs = addrs.into_iter();
+    let mut next_attempt_at = Instant::now();
+    let mut last_err = None;
+    loop {
+        let now = Instant::now();
+        if in_flight == 0 || now >= next_attempt_at {
+            if let Some(addr) = addrs.next() {
+                #[cfg(feature = "log")]
+                log::trace!("Attempting to connect to {addr}");
+                match MioTcpStream::connect(addr) {
+                    Ok(mut stream) => {
+                        let token = Token(attempts.len());
+                        poll.registry().register(&mut stream, token, Interest::WRITABLE)?;
+                        attempts.push(Some(stream));
+                        in_flight += 1;
+                        next_attempt_at = now + CONNECTION_ATTEMPT_DELAY;
+                    }
+                    Err(e) => last_err = Some(e),
+                }
+                continue;
+            }
+            if in_flight == 0 {
+                break;
+            }
+        }
+
+        let remaining = timeout_at_to_duration(timeout_at)?;
+        let wait = match (addrs.len() > 0, remaining) {
+            (true, Some(remaining)) => Some(remaining.min(next_attempt_at - now)),
+            (true, None) => Some(next_attempt_at - now),
+            (false, remaining) => remaining,
+        };
+        match poll.poll(&mut events, wait) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            result => result?,
+        }
+        for event in &events {
+            let Some(mut stream) = attempts[event.token().0].take() else { continue };
+            match is_connected(&stream) {
+                Ok(true) => {
+                    poll.registry().deregister(&mut stream)?;
+                    let stream = TcpStream::from(stream);
+                    stream.set_nonblocking(false)?;
+                    return Ok(stream);
+                }
+                Ok(false) => attempts[event.token().0] = Some(stream),
+                Err(e) => {
+                    in_flight -= 1;
+                    last_err = Some(e);
+                    next_attempt_at = Instant::now();
+                }
+            }
+        }
+    }
+
+    Err(last_err.map(Error::IoError).unwrap_or(Error::AddressNotFound))
+}
+
+/// Returns whether the non-blocking connect of `stream` has completed, or the error it failed
+/// with.
+#[cfg(all(feature = "happy-eyeballs", not(target_family = "wasm")))]
+fn is_connected(stream: &mio::net::TcpStream) -> io::Result<bool> {
+    if let Some(e) = stream.take_error()? {
+        return Err(e);
+    }
+    match stream.peer_addr() {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotConnected => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Tries to connect to `addrs` one after the other, used without the `happy-eyeballs` feature and
+/// on WebAssembly, which `mio` doesn't support.
+#[cfg(all(
+    any(not(feature = "happy-eyeballs"), target_family = "wasm"),
+    not(all(feature = "wasm", target_family = "wasm"))
+))]
+fn race_connect(addrs: Vec<SocketAddr>, timeout_at: Option<Instant>) -> Result<TcpStream, Error> {
+    let mut last_err = None;
+    for addr in addrs {
+        match tcp_connect_addr(addr, timeout_at) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.map(Error::IoError).unwrap_or(Error::AddressNotFound))
+}
+
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 fn handle_redirects(
     request: ParsedRequest,
     mut response: ResponseLazy,
 ) -> Result<ResponseLazy, Error> {
     let status_code = response.status_code;
-    let url = response.headers.get("location");
+    let url = response.headers.location();
     match get_redirect(request, status_code, url) {
         NextHop::Redirect(request) => {
             let (request, _) = request?;
             Connection::new(request.connection_params(), request.timeout_at)?.send(request)
         }
         NextHop::Destination(request) => {
-            let dst_url = request.url;
+            let dst_url = &request.url;
             dst_url.write_base_url_to(&mut response.url).unwrap();
             dst_url.write_resource_to(&mut response.url).unwrap();
+            response.redirects = request.redirect_history();
             Ok(response)
         }
     }
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 async fn async_handle_redirects(
     connection: &AsyncConnection,
     request: ParsedRequest,
     mut response: Response,
 ) -> Result<Response, Error> {
     let status_code = response.status_code;
-    let url = response.headers.get("location");
+    let url = response.headers.location();
     match async_get_redirect(request, status_code, url) {
         NextHopAsync::Redirect(request) => {
             let (request, needs_new_connection) = request?;
@@ -812,14 +1147,16 @@ async fn async_handle_redirects(
             connection.send(request).await
         }
         NextHopAsync::Destination(request) => {
-            let dst_url = request.url;
+            let dst_url = &request.url;
             dst_url.write_base_url_to(&mut response.url).unwrap();
             dst_url.write_resource_to(&mut response.url).unwrap();
+            response.redirects = request.redirect_history();
             Ok(response)
         }
     }
 }
 
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
 macro_rules! redirect_utils {
     ($get_redirect: ident, $NextHop: ident, $Response: ident) => {
         enum $NextHop {
@@ -830,34 +1167,27 @@ macro_rules! redirect_utils {
         fn $get_redirect(
             mut request: ParsedRequest,
             status_code: i32,
-            url: Option<&String>,
+            url: Option<&str>,
         ) -> $NextHop {
             match status_code {
-                301 | 302 | 303 | 307 => {
+                301 | 302 | 303 | 307 | 308 if request.follows_redirects() => {
                     let url = match url {
                         Some(url) => url,
                         None => return $NextHop::Redirect(Err(Error::RedirectLocationMissing)),
                     };
-                    #[cfg(feature = "log")]
-                    log::debug!("Redirecting ({}) to: {}", status_code, url);
 
                     // TODO: Do this check without allocating a whole new params object
                     let previous_params: OwnedConnectionParams = request.connection_params().into();
 
-                    match request.redirect_to(url.as_str()) {
-                        Ok(()) => {
-                            if status_code == 303 {
-                                match request.config.method {
-                                    Method::Post | Method::Put | Method::Delete => {
-                                        request.config.method = Method::Get;
-                                    }
-                                    _ => {}
-                                }
-                            }
+                    match request.redirect_to(status_code, url) {
+                        Ok(true) => {
+                            #[cfg(feature = "log")]
+                            log::debug!("Redirecting ({}) to: {}", status_code, url);
 
                             let needs_new_conn = previous_params != request.connection_params();
                             $NextHop::Redirect(Ok((request, needs_new_conn)))
                         }
+                        Ok(false) => $NextHop::Destination(request),
                         Err(err) => $NextHop::Redirect(Err(err)),
                     }
                 }
@@ -867,10 +1197,12 @@ macro_rules! redirect_utils {
     };
 }
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 redirect_utils!(get_redirect, NextHop, ResponseLazy);
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 redirect_utils!(async_get_redirect, NextHopAsync, Response);
 
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
 /// Enforce the timeout by running the function in a new thread and
 /// parking the current one with a timeout.
 ///
@@ -5,9 +5,10 @@
 use alloc::sync::Arc;
 #[cfg(feature = "rustls")]
 use core::convert::TryFrom;
-use std::io;
 use std::net::TcpStream;
 use std::sync::OnceLock;
+#[cfg(feature = "rustls")]
+use std::time::Instant;
 
 #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
 use native_tls::{HandshakeError, TlsConnector, TlsStream};
@@ -17,13 +18,15 @@ use rustls::{self, ClientConfig, ClientConnection, RootCertStore, ServerName, St
 use tokio_native_tls::TlsConnector as AsyncTlsConnector;
 #[cfg(feature = "tokio-rustls")]
 use tokio_rustls::{client::TlsStream, TlsConnector};
-#[cfg(feature = "rustls-webpki")]
+#[cfg(feature = "webpki-roots")]
 use webpki_roots::TLS_SERVER_ROOTS;
 
 #[cfg(feature = "tokio-rustls")]
 use super::{AsyncHttpStream, AsyncTcpStream};
 #[cfg(all(feature = "native-tls", not(feature = "rustls"), feature = "tokio-native-tls"))]
 use super::{AsyncHttpStream, AsyncTcpStream};
+#[cfg(feature = "rustls")]
+use crate::tls::TlsConfig;
 use crate::Error;
 
 #[cfg(feature = "rustls")]
@@ -33,77 +36,142 @@ pub type SecuredStream = StreamOwned<ClientConnection, TcpStream>;
 static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
 
 #[cfg(feature = "rustls")]
-fn build_client_config() -> Arc<ClientConfig> {
+fn build_client_config(tls: Option<&TlsConfig>) -> Result<Arc<ClientConfig>, Error> {
     let mut root_certificates = RootCertStore::empty();
 
-    // Try to load native certs
-    #[cfg(feature = "https-rustls-probe")]
-    if let Ok(os_roots) = rustls_native_certs::load_native_certs() {
-        for root_cert in os_roots {
-            // Ignore erroneous OS certificates, there's nothing
-            // to do differently in that situation anyways.
-            let _ = root_certificates.add(&rustls::Certificate(root_cert.0));
+    if tls.map_or(true, |tls| tls.builtin_roots) {
+        // Try to load native certs
+        #[cfg(feature = "https-rustls-probe")]
+        if let Ok(os_roots) = rustls_native_certs::load_native_certs() {
+            for root_cert in os_roots {
+                // Ignore erroneous OS certificates, there's nothing
+                // to do differently in that situation anyways.
+                let _ = root_certificates.add(&rustls::Certificate(root_cert.0));
+            }
         }
+
+        #[cfg(feature = "webpki-roots")]
+        #[allow(deprecated)]
+        // Need to use add_server_trust_anchors to compile with rustls 0.21.1
+        root_certificates.add_server_trust_anchors(TLS_SERVER_ROOTS.iter().map(|ta| {
+            rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
     }
 
-    #[cfg(feature = "rustls-webpki")]
-    #[allow(deprecated)] // Need to use add_server_trust_anchors to compile with rustls 0.21.1
-    root_certificates.add_server_trust_anchors(TLS_SERVER_ROOTS.iter().map(|ta| {
-        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
-            ta.subject,
-            ta.spki,
-            ta.name_constraints,
-        )
-    }));
-
-    let config = ClientConfig::builder()
-        .with_safe_defaults()
-        .with_root_certificates(root_certificates)
-        .with_no_client_auth();
-    Arc::new(config)
+    if let Some(tls) = tls {
+        for root_cert in &tls.root_certificates {
+            root_certificates
+                .add(&rustls::Certificate(root_cert.clone()))
+                .map_err(Error::RustlsCreateConnection)?;
+        }
+    }
+
+    let builder =
+        ClientConfig::builder().with_safe_defaults().with_root_certificates(root_certificates);
+    let config = match tls.and_then(|tls| tls.client_auth.as_ref()) {
+        Some((cert_chain, key)) => {
+            let cert_chain = cert_chain.iter().cloned().map(rustls::Certificate).collect();
+            #[allow(deprecated)] // Need to use with_single_cert to compile with rustls 0.21.1
+            let config = builder.with_single_cert(cert_chain, rustls::PrivateKey(key.clone()));
+            config.map_err(Error::RustlsCreateConnection)?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    Ok(Arc::new(config))
+}
+
+/// Returns the (cached) `rustls` configuration for `tls`, or the default one.
+#[cfg(feature = "rustls")]
+fn client_config(tls: Option<&TlsConfig>) -> Result<Arc<ClientConfig>, Error> {
+    match tls {
+        Some(tls) => {
+            if let Some(config) = tls.client_config.get() {
+                return Ok(Arc::clone(config));
+            }
+            let config = build_client_config(Some(tls))?;
+            let _ = tls.client_config.set(Arc::clone(&config));
+            Ok(config)
+        }
+        None => Ok(Arc::clone(CONFIG.get_or_init(|| {
+            build_client_config(None).expect("the default config has no fallible parts")
+        }))),
+    }
 }
 
 #[cfg(feature = "rustls")]
-pub(super) fn wrap_stream(tcp: TcpStream, host: &str) -> Result<SecuredStream, Error> {
+fn server_name(host: &str, tls: Option<&TlsConfig>) -> Result<ServerName, Error> {
+    let host = tls.and_then(|tls| tls.server_name.as_deref()).unwrap_or(host);
+    ServerName::try_from(host)
+        .map_err(|err| Error::RustlsCreateConnection(rustls::Error::General(err.to_string())))
+}
+
+#[cfg(feature = "rustls")]
+fn verify_pins(tls: Option<&TlsConfig>, conn: &ClientConnection) -> Result<(), Error> {
+    match tls {
+        Some(tls) if !tls.matches_pins(conn.peer_certificates().unwrap_or(&[])) =>
+            Err(Error::CertificatePinMismatch),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "rustls")]
+pub(super) fn wrap_stream(
+    tcp: TcpStream,
+    host: &str,
+    tls: Option<&TlsConfig>,
+    timeout_at: Option<Instant>,
+) -> Result<SecuredStream, Error> {
     #[cfg(feature = "log")]
     log::trace!("Setting up TLS parameters for {host}.");
-    let dns_name = match ServerName::try_from(host) {
-        Ok(result) => result,
-        Err(err) => return Err(Error::IoError(io::Error::new(io::ErrorKind::Other, err))),
-    };
-    let sess = ClientConnection::new(CONFIG.get_or_init(build_client_config).clone(), dns_name)
+    let dns_name = server_name(host, tls)?;
+    let sess = ClientConnection::new(client_config(tls)?, dns_name)
         .map_err(Error::RustlsCreateConnection)?;
 
     #[cfg(feature = "log")]
     log::trace!("Establishing TLS session to {host}.");
-    Ok(StreamOwned::new(sess, tcp))
+    let mut stream = StreamOwned::new(sess, tcp);
+    if tls.is_some_and(|tls| !tls.spki_pins.is_empty()) {
+        // The handshake usually happens as part of the first write, but the pins have to be
+        // checked before we send anything.
+        let timeout = super::timeout_at_to_duration(timeout_at)?;
+        stream.sock.set_read_timeout(timeout)?;
+        stream.sock.set_write_timeout(timeout)?;
+        while stream.conn.is_handshaking() {
+            stream.conn.complete_io(&mut stream.sock)?;
+        }
+        verify_pins(tls, &stream.conn)?;
+    }
+    Ok(stream)
 }
 
 // Async rustls TLS implementation
 
 #[cfg(all(feature = "rustls", feature = "tokio-rustls"))]
-pub type AsyncSecuredStream = TlsStream<tokio::net::TcpStream>;
+pub type AsyncSecuredStream = TlsStream<AsyncTcpStream>;
 
 #[cfg(all(feature = "rustls", feature = "tokio-rustls"))]
 pub(super) async fn wrap_async_stream(
     tcp: AsyncTcpStream,
     host: &str,
+    tls: Option<&TlsConfig>,
 ) -> Result<AsyncHttpStream, Error> {
     #[cfg(feature = "log")]
     log::trace!("Setting up TLS parameters for {host}.");
-    let dns_name = match ServerName::try_from(host) {
-        Ok(result) => result,
-        Err(err) => return Err(Error::IoError(io::Error::new(io::ErrorKind::Other, err))),
-    };
+    let dns_name = server_name(host, tls)?;
 
-    let connector = TlsConnector::from(CONFIG.get_or_init(build_client_config).clone());
+    let connector = TlsConnector::from(client_config(tls)?);
 
     #[cfg(feature = "log")]
     log::trace!("Establishing TLS session to {host}.");
 
-    let tls = connector.connect(dns_name, tcp).await.map_err(Error::IoError)?;
+    let stream = connector.connect(dns_name, tcp).await.map_err(Error::IoError)?;
+    verify_pins(tls, stream.get_ref().1)?;
 
-    Ok(AsyncHttpStream::Secured(Box::new(tls)))
+    Ok(AsyncHttpStream::Secured(Box::new(stream)))
 }
 
 #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
@@ -144,7 +212,7 @@ pub(super) fn wrap_stream(tcp: TcpStream, host: &str) -> Result<SecuredStream, E
 }
 
 #[cfg(all(feature = "native-tls", not(feature = "rustls"), feature = "tokio-native-tls"))]
-pub type AsyncSecuredStream = tokio_native_tls::TlsStream<tokio::net::TcpStream>;
+pub type AsyncSecuredStream = tokio_native_tls::TlsStream<AsyncTcpStream>;
 
 #[cfg(all(feature = "native-tls", not(feature = "rustls"), feature = "tokio-native-tls"))]
 pub(super) async fn wrap_async_stream(
@@ -0,0 +1,280 @@
+//! Custom transports.
+//!
+//! By default bitreq connects to the host of a request over TCP (through
+//! the request's [`Proxy`](crate::Proxy), if any). A [`Connector`] can be
+//! set per request with
+//! [`Request::with_connector`](crate::Request::with_connector) to supply
+//! the stream instead, eg. a Unix domain socket, an already connected
+//! socket, or an in-memory pipe in tests.
+//!
+//! Requests to `http+unix://` URLs are sent over the Unix domain socket
+//! whose percent-encoded path is the host of the URL, eg.
+//! `http+unix://%2Fhome%2Fuser%2F.bitcoin%2Frpc.sock/`.
+
+use alloc::sync::Arc;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::time::Duration;
+#[cfg(feature = "async-core")]
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "async-core")]
+use std::pin::Pin;
+
+#[cfg(feature = "async-core")]
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[cfg(all(feature = "async-core", unix))]
+use crate::runtime::IoStream;
+#[cfg(feature = "async-core")]
+use crate::runtime::RuntimeHandle;
+
+/// A blocking stream a request can be sent over.
+///
+/// The timeout methods are called before every read and write with the
+/// time left until the request's timeout, so that a blocked read or write
+/// does not outlive the request. Their default implementations do
+/// nothing, which is fine for streams that never block indefinitely.
+pub trait Stream: Read + Write + Send {
+    /// Sets the read timeout of the stream, `None` meaning no timeout.
+    ///
+    /// A read which times out should fail with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) or
+    /// [`TimedOut`](io::ErrorKind::TimedOut).
+    fn set_read_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> { Ok(()) }
+
+    /// Sets the write timeout of the stream, `None` meaning no timeout.
+    fn set_write_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> { Ok(()) }
+}
+
+impl Stream for TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_write_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl Stream for UnixStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_write_timeout(self, timeout)
+    }
+}
+
+/// An async stream a request can be sent over.
+///
+/// This is implemented for every type implementing tokio's
+/// [`AsyncRead`] and [`AsyncWrite`], eg. `tokio::net::UnixStream` or the
+/// in-memory pipes returned by `tokio::io::duplex`.
+#[cfg(feature = "async-core")]
+pub trait AsyncStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+#[cfg(feature = "async-core")]
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncStream for T {}
+
+/// The future returned by [`Connector::connect_async`].
+#[cfg(feature = "async-core")]
+pub type ConnectFuture<'a> =
+    Pin<Box<dyn Future<Output = io::Result<Box<dyn AsyncStream>>> + Send + 'a>>;
+
+/// Opens the streams requests are sent over, in place of bitreq's TCP
+/// connections.
+///
+/// Connectors are given the host and port of the request's URL. Proxies
+/// and [resolvers](crate::Resolve) are not used for requests with a
+/// connector, and the stream is used as-is: HTTPS requests cannot be sent
+/// over a custom stream.
+///
+/// # Example
+///
+/// Sending a request over a socket that was connected beforehand:
+///
+/// ```
+/// use std::io;
+/// use std::net::TcpStream;
+/// use std::sync::Mutex;
+///
+/// use bitreq::Stream;
+///
+/// struct PreConnected(Mutex<Option<TcpStream>>);
+///
+/// impl bitreq::Connector for PreConnected {
+///     fn connect(&self, _host: &str, _port: u16) -> io::Result<Box<dyn Stream>> {
+///         match self.0.lock().unwrap().take() {
+///             Some(stream) => Ok(Box::new(stream)),
+///             None => Err(io::ErrorKind::NotConnected.into()),
+///         }
+///     }
+/// }
+///
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let stream = TcpStream::connect("127.0.0.1:8332")?;
+/// let response = bitreq::get("http://127.0.0.1:8332")
+///     .with_connector(PreConnected(Mutex::new(Some(stream))))
+///     .send()?;
+/// # Ok(()) }
+/// ```
+pub trait Connector: Send + Sync {
+    /// Opens a stream to send a blocking request for `host` over.
+    fn connect(&self, host: &str, port: u16) -> io::Result<Box<dyn Stream>>;
+
+    /// Opens a stream to send an async request for `host` over.
+    ///
+    /// The default implementation fails with
+    /// [`Unsupported`](io::ErrorKind::Unsupported), so connectors only
+    /// used for blocking requests need not implement it.
+    #[cfg(feature = "async-core")]
+    fn connect_async<'a>(&'a self, _host: &'a str, _port: u16) -> ConnectFuture<'a> {
+        Box::pin(async { Err(unsupported("this connector does not support async requests")) })
+    }
+}
+
+/// Connects to a Unix domain socket, whatever the host of the request.
+///
+/// See also the `http+unix://` URLs described in the
+/// [module documentation](crate::Connector), which don't need a connector.
+/// Async requests with this connector are sent with tokio, and thus need
+/// the `async` feature. `http+unix://` URLs use the request's
+/// [`Runtime`](crate::Runtime) instead.
+///
+/// # Example
+///
+/// ```no_run
+/// let connector = bitreq::UnixConnector::new("/home/user/.bitcoin/rpc.sock");
+/// let response = bitreq::post("http://localhost/").with_connector(connector).send();
+/// ```
+#[cfg(unix)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnixConnector {
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl UnixConnector {
+    /// Creates a connector for the socket at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> UnixConnector { UnixConnector { path: path.into() } }
+
+    /// Returns the path of the socket.
+    pub fn path(&self) -> &Path { &self.path }
+}
+
+#[cfg(unix)]
+impl Connector for UnixConnector {
+    fn connect(&self, _host: &str, _port: u16) -> io::Result<Box<dyn Stream>> {
+        Ok(Box::new(UnixStream::connect(&self.path)?))
+    }
+
+    #[cfg(feature = "async")]
+    fn connect_async<'a>(&'a self, _host: &'a str, _port: u16) -> ConnectFuture<'a> {
+        Box::pin(async move {
+            let stream: Box<dyn AsyncStream> =
+                Box::new(tokio::net::UnixStream::connect(&self.path).await?);
+            Ok(stream)
+        })
+    }
+}
+
+/// Opens the stream for an `http+unix://` URL with the given host.
+#[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub(crate) fn connect_unix(host: &str) -> io::Result<Box<dyn Stream>> {
+    #[cfg(unix)]
+    return UnixConnector::new(unix_socket_path(host)?).connect(host, 0);
+    #[cfg(not(unix))]
+    Err(unsupported("http+unix:// URLs are only supported on Unix"))
+}
+
+/// Async version of [`connect_unix`], connecting with `runtime`.
+#[cfg(feature = "async-core")]
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub(crate) async fn connect_unix_async(
+    runtime: &RuntimeHandle,
+    host: &str,
+) -> io::Result<Box<dyn AsyncStream>> {
+    #[cfg(unix)]
+    return Ok(Box::new(IoStream(runtime.0.connect_unix(&unix_socket_path(host)?).await?)));
+    #[cfg(not(unix))]
+    Err(unsupported("http+unix:// URLs are only supported on Unix"))
+}
+
+/// Percent-decodes the host of an `http+unix://` URL into a socket path.
+#[cfg(unix)]
+fn unix_socket_path(host: &str) -> io::Result<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "invalid http+unix:// host");
+    let mut path = Vec::with_capacity(host.len());
+    let mut bytes = host.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next().ok_or_else(invalid)?, bytes.next().ok_or_else(invalid)?];
+            let hex = core::str::from_utf8(&hex).map_err(|_| invalid())?;
+            path.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+        } else {
+            path.push(byte);
+        }
+    }
+    Ok(PathBuf::from(OsString::from_vec(path)))
+}
+
+#[cfg(any(
+    feature = "async-core",
+    all(not(unix), not(all(feature = "wasm", target_family = "wasm")))
+))]
+fn unsupported(msg: &'static str) -> io::Error { io::Error::new(io::ErrorKind::Unsupported, msg) }
+
+/// A shared [`Connector`] stored in a [`Request`](crate::Request).
+///
+/// Two handles are only equal if they point to the same connector, so
+/// requests with different connectors never share a pooled connection.
+#[derive(Clone)]
+pub(crate) struct ConnectorHandle(pub(crate) Arc<dyn Connector>);
+
+impl ConnectorHandle {
+    fn addr(&self) -> *const () { Arc::as_ptr(&self.0) as *const () }
+}
+
+impl fmt::Debug for ConnectorHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ConnectorHandle").field(&self.addr()).finish()
+    }
+}
+
+impl PartialEq for ConnectorHandle {
+    fn eq(&self, other: &Self) -> bool { self.addr() == other.addr() }
+}
+
+impl Eq for ConnectorHandle {}
+
+impl Hash for ConnectorHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.addr().hash(state) }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::path::Path;
+
+    use super::unix_socket_path;
+
+    #[test]
+    fn unix_socket_paths() {
+        let path = unix_socket_path("%2Fhome%2Fuser%2F.bitcoin%2Frpc.sock").unwrap();
+        assert_eq!(path, Path::new("/home/user/.bitcoin/rpc.sock"));
+        assert_eq!(unix_socket_path("%2frun%2Fa%20b").unwrap(), Path::new("/run/a b"));
+        assert!(unix_socket_path("%2").is_err());
+        assert!(unix_socket_path("%zz").is_err());
+    }
+}
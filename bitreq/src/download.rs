@@ -0,0 +1,329 @@
+//! Resumable file downloads.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use bitcoin_hashes::{sha256, Hash, HashEngine};
+
+use crate::{Error, HeaderMap, Request};
+
+/// How many bytes are buffered before being written to the file and
+/// reported to the progress callback.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The progress of a [`Download`], passed to its progress callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// How many bytes of the file have been downloaded, including bytes
+    /// downloaded before the download was resumed.
+    pub downloaded: u64,
+    /// The size of the file, if the server sent it.
+    pub total: Option<u64>,
+}
+
+/// Downloads the response to a request into a file, resuming the download
+/// if it is interrupted.
+///
+/// The response body is written to a `.part` file next to the destination,
+/// which is renamed to the destination once the download is complete (and
+/// its digest verified, if one was set). If the connection fails midway,
+/// the download is resumed with a `Range` request for the missing bytes,
+/// up to [`with_max_resumes`](Download::with_max_resumes) times. A download
+/// interrupted by the process exiting is resumed by the next `Download` to
+/// the same destination.
+///
+/// Downloads are only resumed if the server sent a strong `ETag` or a
+/// `Last-Modified` header, which is sent back in an `If-Range` header so
+/// the server sends the whole file again if it changed in the meantime.
+///
+/// Note that the request's [maximum body size](Request::with_max_body_size)
+/// applies to each response, so it may need to be raised for large files.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> Result<(), bitreq::Error> {
+/// let url = "https://bitcoincore.org/bin/bitcoin-core-29.0/bitcoin-29.0-x86_64-linux-gnu.tar.gz";
+/// bitreq::get(url)
+///     .with_timeout(60)
+///     .download("bitcoin-29.0.tar.gz")
+///     .with_progress(|progress| println!("{} of {:?} bytes", progress.downloaded, progress.total))
+///     .send()?;
+/// # Ok(()) }
+/// ```
+pub struct Download {
+    request: Request,
+    path: PathBuf,
+    max_resumes: usize,
+    sha256: Option<[u8; 32]>,
+    progress: Option<Box<dyn FnMut(Progress) + Send>>,
+}
+
+impl Download {
+    /// Creates a download of the response to `request` into the file at
+    /// `path`. See also [`Request::download`].
+    pub fn new<P: Into<PathBuf>>(request: Request, path: P) -> Download {
+        Download { request, path: path.into(), max_resumes: 5, sha256: None, progress: None }
+    }
+
+    /// Sets how many times an interrupted download is resumed before
+    /// giving up. 5 by default.
+    pub fn with_max_resumes(mut self, max_resumes: usize) -> Download {
+        self.max_resumes = max_resumes;
+        self
+    }
+
+    /// Sets the expected SHA-256 digest of the file. If the downloaded
+    /// file has a different digest, it is deleted and
+    /// [`DigestMismatch`](Error::DigestMismatch) is returned.
+    pub fn with_sha256(mut self, digest: [u8; 32]) -> Download {
+        self.sha256 = Some(digest);
+        self
+    }
+
+    /// Sets a callback called as the file is downloaded.
+    pub fn with_progress<F: FnMut(Progress) + Send + 'static>(mut self, progress: F) -> Download {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Downloads the file, returning its size.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt if the download could not be
+    /// completed, or
+    /// [`UnexpectedStatusCode`](Error::UnexpectedStatusCode) if the server
+    /// answered with a status code other than `200 OK` or
+    /// `206 Partial Content`.
+    pub fn send(mut self) -> Result<u64, Error> {
+        let part = sibling(&self.path, ".part");
+        let validator_path = sibling(&self.path, ".part-validator");
+        let mut state = State {
+            part: &part,
+            validator: fs::read_to_string(&validator_path).ok(),
+            offset: 0,
+            total: None,
+        };
+        if state.validator.is_some() {
+            state.offset = fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0);
+        }
+
+        let mut resumes = 0;
+        loop {
+            match self.attempt(&mut state, &validator_path) {
+                Ok(()) => break,
+                Err(Error::IoError(e)) if resumes < self.max_resumes && is_resumable(&e) => {
+                    #[cfg(feature = "log")]
+                    log::debug!("Resuming download at byte {}: {}", state.offset, e);
+                    resumes += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let Some(expected) = self.sha256 {
+            if file_sha256(&part)? != expected {
+                let _ = fs::remove_file(&part);
+                let _ = fs::remove_file(&validator_path);
+                return Err(Error::DigestMismatch);
+            }
+        }
+        fs::rename(&part, &self.path)?;
+        let _ = fs::remove_file(&validator_path);
+        Ok(state.offset)
+    }
+
+    /// Sends one request, appending the body to the part file.
+    fn attempt(&mut self, state: &mut State, validator_path: &Path) -> Result<(), Error> {
+        let mut request = self.request.clone();
+        if state.offset > 0 {
+            if let Some(validator) = &state.validator {
+                request = request
+                    .with_header("Range", format!("bytes={}-", state.offset))
+                    .with_header("If-Range", validator.as_str());
+            }
+        }
+        let mut response = request.send_lazy()?;
+
+        let mut file = match response.status_code {
+            206 if state.offset > 0 => {
+                let range = response.headers.get("content-range");
+                let (start, total) =
+                    range.and_then(parse_content_range).ok_or(Error::MalformedContentRange)?;
+                if start != state.offset {
+                    return Err(Error::MalformedContentRange);
+                }
+                state.total = total;
+                OpenOptions::new().append(true).open(state.part)?
+            }
+            416 if state.offset > 0 => {
+                // The part file may already be complete, otherwise start over.
+                let total = response.headers.get("content-range").and_then(unsatisfied_range_total);
+                if total == Some(state.offset) {
+                    state.total = total;
+                    return Ok(());
+                }
+                state.offset = 0;
+                state.validator = None;
+                let _ = fs::remove_file(validator_path);
+                return self.attempt(state, validator_path);
+            }
+            200 => {
+                state.offset = 0;
+                state.total = response.headers.content_length().map(|len| len as u64);
+                state.validator = validator(&response.headers).map(str::to_owned);
+                match &state.validator {
+                    Some(validator) => fs::write(validator_path, validator)?,
+                    None => {
+                        let _ = fs::remove_file(validator_path);
+                    }
+                }
+                File::create(state.part)?
+            }
+            status_code => return Err(Error::UnexpectedStatusCode(status_code)),
+        };
+
+        let mut buf = vec![0; CHUNK_SIZE];
+        let mut filled = 0;
+        let result = loop {
+            match response.read_body(&mut buf[filled..]) {
+                Ok(0) => break Ok(()),
+                Ok(read) => filled += read,
+                Err(e) => break Err(e),
+            }
+            if filled == buf.len() {
+                self.write_chunk(&mut file, state, &buf)?;
+                filled = 0;
+            }
+        };
+        // Keep the bytes received before an error, so they are not downloaded again.
+        self.write_chunk(&mut file, state, &buf[..filled])?;
+        file.sync_all()?;
+        result?;
+
+        match state.total {
+            Some(total) if total != state.offset => Err(Error::IoError(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the connection was closed before the whole file was downloaded",
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Appends `chunk` to the part file and reports the progress.
+    fn write_chunk(&mut self, file: &mut File, state: &mut State, chunk: &[u8]) -> io::Result<()> {
+        file.write_all(chunk)?;
+        state.offset += chunk.len() as u64;
+        if let Some(progress) = &mut self.progress {
+            progress(Progress { downloaded: state.offset, total: state.total });
+        }
+        Ok(())
+    }
+}
+
+/// The state of a download, kept across resumes.
+struct State<'a> {
+    part: &'a Path,
+    /// The `ETag` or `Last-Modified` value sent in `If-Range` headers.
+    validator: Option<String>,
+    /// How many bytes are in the part file.
+    offset: u64,
+    total: Option<u64>,
+}
+
+impl Request {
+    /// Creates a [`Download`] of the response to this request into the
+    /// file at `path`.
+    pub fn download<P: Into<PathBuf>>(self, path: P) -> Download { Download::new(self, path) }
+}
+
+/// Returns `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns the validator to send in `If-Range` headers: a strong `ETag`,
+/// or else the `Last-Modified` date.
+fn validator(headers: &HeaderMap) -> Option<&str> {
+    headers.etag().filter(|etag| !etag.starts_with("W/")).or_else(|| headers.get("last-modified"))
+}
+
+/// Parses a `Content-Range: bytes <start>-<end>/<total>` header, returning
+/// the start and total length (`None` if `*`).
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.parse::<u64>().ok()?, end.parse::<u64>().ok()?);
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse::<u64>().ok()?),
+    };
+    if end < start || total.is_some_and(|total| end >= total) {
+        return None;
+    }
+    Some((start, total))
+}
+
+/// Parses the `Content-Range: bytes */<total>` header of a
+/// `416 Range Not Satisfiable` response.
+fn unsatisfied_range_total(value: &str) -> Option<u64> {
+    value.trim().strip_prefix("bytes */")?.parse().ok()
+}
+
+/// Returns true for errors after which the download can be resumed.
+fn is_resumable(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::TimedOut
+    )
+}
+
+fn file_sha256(path: &Path) -> io::Result<[u8; 32]> {
+    let mut file = File::open(path)?;
+    let mut engine = sha256::HashEngine::default();
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        match file.read(&mut buf)? {
+            0 => return Ok(sha256::Hash::from_engine(engine).to_byte_array()),
+            n => engine.input(&buf[..n]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_content_range, unsatisfied_range_total, validator};
+    use crate::HeaderMap;
+
+    #[test]
+    fn content_range() {
+        assert_eq!(parse_content_range("bytes 10-19/20"), Some((10, Some(20))));
+        assert_eq!(parse_content_range("bytes 0-0/*"), Some((0, None)));
+        assert_eq!(parse_content_range("bytes 10-19/19"), None);
+        assert_eq!(parse_content_range("bytes 10-9/20"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+        assert_eq!(unsatisfied_range_total("bytes */20"), Some(20));
+        assert_eq!(unsatisfied_range_total("bytes 0-1/2"), None);
+    }
+
+    #[test]
+    fn if_range_validator() {
+        let headers: HeaderMap =
+            [("ETag", "W/\"1\""), ("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT")]
+                .into_iter()
+                .collect();
+        assert_eq!(validator(&headers), Some("Sun, 06 Nov 1994 08:49:37 GMT"));
+        let headers: HeaderMap = [("ETag", "\"1\"")].into_iter().collect();
+        assert_eq!(validator(&headers), Some("\"1\""));
+    }
+}
@@ -0,0 +1,310 @@
+//! HTTP header map and typed header accessors.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A collection of HTTP header fields.
+///
+/// Header names are compared case-insensitively and a name may have
+/// multiple values (eg. several `Set-Cookie` headers). Fields are kept in the
+/// order they were added or received.
+///
+/// # Example
+///
+/// ```
+/// let mut headers = bitreq::HeaderMap::new();
+/// headers.append("Set-Cookie", "a=1");
+/// headers.append("set-cookie", "b=2");
+/// headers.insert("Content-Type", "text/plain");
+///
+/// assert_eq!(headers.get("content-type"), Some("text/plain"));
+/// assert_eq!(headers.get_all("SET-COOKIE").collect::<Vec<_>>(), ["a=1", "b=2"]);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct HeaderMap {
+    fields: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    /// Creates an empty `HeaderMap`.
+    pub fn new() -> HeaderMap { HeaderMap { fields: Vec::new() } }
+
+    /// Returns the number of fields, counting each value of a repeated
+    /// header separately.
+    pub fn len(&self) -> usize { self.fields.len() }
+
+    /// Returns `true` if there are no fields.
+    pub fn is_empty(&self) -> bool { self.fields.is_empty() }
+
+    /// Returns the first value of the header `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let (_, value) = self.fields.iter().find(|(field, _)| field.eq_ignore_ascii_case(name))?;
+        Some(value)
+    }
+
+    /// Returns all values of the header `name`, in order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields
+            .iter()
+            .filter(move |(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns `true` if the header `name` has at least one value.
+    pub fn contains_key(&self, name: &str) -> bool { self.get(name).is_some() }
+
+    /// Sets the header `name` to `value`, replacing all of its previous
+    /// values. Returns the first previous value, if any.
+    pub fn insert<K: Into<String>, V: Into<String>>(
+        &mut self,
+        name: K,
+        value: V,
+    ) -> Option<String> {
+        let name = name.into();
+        let previous = self.remove(&name);
+        self.fields.push((name, value.into()));
+        previous
+    }
+
+    /// Adds `value` to the values of the header `name`, keeping the
+    /// previous ones.
+    pub fn append<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        self.fields.push((name.into(), value.into()));
+    }
+
+    /// Removes all values of the header `name`, returning the first one.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let mut removed = None;
+        self.fields.retain_mut(|(field, value)| {
+            if field.eq_ignore_ascii_case(name) {
+                if removed.is_none() {
+                    removed = Some(core::mem::take(value));
+                }
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+
+    /// Returns an iterator over all `(name, value)` fields, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the `Content-Type` header.
+    pub fn content_type(&self) -> Option<&str> { self.get("content-type") }
+
+    /// Returns the `Content-Length` header, if it is present and a valid
+    /// length.
+    pub fn content_length(&self) -> Option<usize> {
+        self.get("content-length")?.trim().parse().ok()
+    }
+
+    /// Returns the `Location` header.
+    pub fn location(&self) -> Option<&str> { self.get("location") }
+
+    /// Returns the `ETag` header.
+    pub fn etag(&self) -> Option<&str> { self.get("etag") }
+
+    /// Returns the `Retry-After` header, if it is present and valid.
+    pub fn retry_after(&self) -> Option<RetryAfter> { RetryAfter::parse(self.get("retry-after")?) }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for HeaderMap {
+    /// Inserts all fields, replacing previous values of the same headers.
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for HeaderMap {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.extend(iter);
+        headers
+    }
+}
+
+impl IntoIterator for HeaderMap {
+    type Item = (String, String);
+    type IntoIter = alloc::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter { self.fields.into_iter() }
+}
+
+/// The value of a `Retry-After` header.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum RetryAfter {
+    /// The number of seconds to wait before retrying.
+    Delay(Duration),
+    /// The time after which the request can be retried. Requires the `std`
+    /// feature.
+    #[cfg(feature = "std")]
+    Date(SystemTime),
+}
+
+impl RetryAfter {
+    fn parse(value: &str) -> Option<RetryAfter> {
+        let value = value.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(RetryAfter::Delay(Duration::from_secs(seconds)));
+        }
+        #[cfg(feature = "std")]
+        if let Some(date) = parse_http_date(value) {
+            return Some(RetryAfter::Date(date));
+        }
+        None
+    }
+
+    /// Returns how long to wait from `now` before retrying.
+    #[cfg(feature = "std")]
+    pub fn delay_from(&self, now: SystemTime) -> Duration {
+        match self {
+            RetryAfter::Delay(delay) => *delay,
+            RetryAfter::Date(date) => date.duration_since(now).unwrap_or(Duration::ZERO),
+        }
+    }
+}
+
+/// Parses an HTTP-date in any of the three formats recipients must accept
+/// ([RFC 9110 section 5.6.7](https://datatracker.ietf.org/doc/html/rfc9110#section-5.6.7)):
+///
+/// - `Sun, 06 Nov 1994 08:49:37 GMT` (IMF-fixdate)
+/// - `Sunday, 06-Nov-94 08:49:37 GMT` (RFC 850)
+/// - `Sun Nov  6 08:49:37 1994` (asctime)
+#[cfg(feature = "std")]
+pub(crate) fn parse_http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] =
+        ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let mut parts = value.split([' ', ',', '-']).filter(|p| !p.is_empty());
+    let _weekday = parts.next()?;
+    let first = parts.next()?;
+    let (day, month, year, time) = if let Some(month) = MONTHS.iter().position(|m| *m == first) {
+        // asctime: month day time year
+        let day = parts.next()?;
+        let time = parts.next()?;
+        (day, month, parts.next()?, time)
+    } else {
+        let month = parts.next()?;
+        let month = MONTHS.iter().position(|m| *m == month)?;
+        (first, month, parts.next()?, parts.next()?)
+    };
+    match parts.next() {
+        Some("GMT") | None => {}
+        Some(_) => return None,
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let day: u64 = day.parse().ok()?;
+    let mut year: u64 = year.parse().ok()?;
+    if year < 100 {
+        // RFC 850 two digit years are interpreted relative to 1970.
+        year += if year < 70 { 2000 } else { 1900 };
+    }
+    let mut time = time.split(':').map(|t| t.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if time.next().is_some()
+        || !(1..=31).contains(&day)
+        || year < 1970
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    // Days since the epoch of the given civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let month = month as u64 + 1;
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y / 400;
+    let year_of_era = y % 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).checked_sub(719_468)?;
+
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    #[cfg(feature = "std")]
+    use std::time::UNIX_EPOCH;
+
+    use super::{HeaderMap, RetryAfter};
+
+    #[test]
+    fn case_insensitive_multi_value() {
+        let mut headers = HeaderMap::new();
+        headers.append("Set-Cookie", "a=1");
+        headers.append("set-cookie", "b=2");
+        headers.append("Accept", "*/*");
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers.get("SET-COOKIE"), Some("a=1"));
+        assert_eq!(headers.get_all("Set-cookie").collect::<Vec<_>>(), ["a=1", "b=2"]);
+
+        assert_eq!(headers.insert("SET-COOKIE", "c=3"), Some("a=1".to_string()));
+        assert_eq!(headers.get_all("set-cookie").collect::<Vec<_>>(), ["c=3"]);
+        assert_eq!(headers.remove("accept"), Some("*/*".to_string()));
+        assert!(!headers.contains_key("Accept"));
+        assert_eq!(headers.iter().collect::<Vec<_>>(), [("SET-COOKIE", "c=3")]);
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let headers: HeaderMap = [
+            ("content-type", "application/json"),
+            ("content-length", " 42 "),
+            ("location", "/a"),
+            ("etag", "\"abc\""),
+            ("retry-after", "120"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(headers.content_type(), Some("application/json"));
+        assert_eq!(headers.content_length(), Some(42));
+        assert_eq!(headers.location(), Some("/a"));
+        assert_eq!(headers.etag(), Some("\"abc\""));
+        assert_eq!(headers.retry_after(), Some(RetryAfter::Delay(Duration::from_secs(120))));
+
+        let headers: HeaderMap =
+            [("content-length", "nope"), ("retry-after", "soon")].into_iter().collect();
+        assert_eq!(headers.content_length(), None);
+        assert_eq!(headers.retry_after(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn retry_after_http_date() {
+        let expected = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        for date in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            let headers: HeaderMap = [("retry-after", date)].into_iter().collect();
+            assert_eq!(headers.retry_after(), Some(RetryAfter::Date(expected)), "{}", date);
+        }
+        let leap_day = super::parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT");
+        assert_eq!(leap_day, Some(UNIX_EPOCH + Duration::from_secs(1_709_164_800)));
+        assert_eq!(super::parse_http_date("Sun, 06 Nov 1994 25:49:37 GMT"), None);
+        assert_eq!(super::parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+
+        let retry = RetryAfter::Date(expected);
+        assert_eq!(retry.delay_from(expected - Duration::from_secs(5)), Duration::from_secs(5));
+        assert_eq!(retry.delay_from(expected + Duration::from_secs(5)), Duration::ZERO);
+    }
+}
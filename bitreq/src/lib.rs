@@ -38,6 +38,10 @@
 //! feature variants were added, and is now an alias for
 //! `https-rustls`.
 //!
+//! The TLS settings (additional root certificates, client certificates
+//! for mutual TLS, SNI override and certificate pinning) can be changed
+//! per request or per [`Client`] with a [`TlsConfig`](struct.TlsConfig.html).
+//!
 //! ## `https-rustls-probe`
 //!
 //! Like `https-rustls`, but uses the
@@ -59,7 +63,16 @@
 //! that return futures for non-blocking operation.
 //!
 //! It also enables [`Client`](struct.Client.html) to reuse TCP connections
-//! across requests.
+//! across requests. Without this feature, a `Client` can still be used to
+//! apply [`Middleware`] and a [`RetryPolicy`] to blocking requests.
+//!
+//! ## `async-core`
+//!
+//! The async support of the `async` feature without tokio's runtime, so
+//! async requests can be sent from any executor. Every async request then
+//! needs a `Runtime` providing connections and timers, set with
+//! `Request::with_runtime` or `Client::with_runtime`. The `async` feature
+//! enables this one and uses `TokioRuntime` by default.
 //!
 //! ## `async-https` or `async-https-rustls`
 //!
@@ -81,10 +94,32 @@
 //! (provided by the `tokio` team) to provide HTTPS support for async
 //! connections.
 //!
+//! ## `download`
+//!
+//! This feature enables resumable file downloads with [`Download`],
+//! optionally checking the SHA-256 digest of the downloaded file.
+//!
+//! ## `happy-eyeballs`
+//!
+//! Races blocking connection attempts to all addresses of a host as
+//! described in RFC 8305, using [`mio`](https://crates.io/crates/mio).
+//! Without this feature blocking requests try the addresses one after
+//! another. Async requests always race their connection attempts.
+//!
 //! ## `proxy`
 //!
 //! This feature enables HTTP proxy support.
 //!
+//! ## `wasm`
+//!
+//! When compiling to WebAssembly, this feature sends requests through
+//! functions imported from the host (eg. a `fetch` wrapper in JavaScript)
+//! instead of sockets. Blocking requests expect the host functions to
+//! block, while async requests let the host resume them later through the
+//! exported `bitreq_wake` function. Connections, proxies and connectors are
+//! not used: redirects and TLS are left to the host. The host ABI is
+//! documented in `src/wasm.rs`.
+//!
 //! # Examples
 //!
 //! ## Get
@@ -160,16 +195,21 @@
 //!
 //! Reading the headers sent by the servers is done via the
 //! [`headers`](struct.Response.html#structfield.headers) field of the
-//! [`Response`](struct.Response.html). Note: the header field names
-//! (that is, the *keys* of the `HashMap`) are all lowercase: this is
-//! because the names are case-insensitive according to the spec, and
-//! this unifies the casings for easier `get()`ing.
+//! [`Response`](struct.Response.html), a [`HeaderMap`]. Header names
+//! are case-insensitive according to the spec, so lookups ignore case,
+//! and a header sent multiple times keeps all of its values. Common
+//! headers also have typed accessors, eg.
+//! [`content_length()`](HeaderMap::content_length) or
+//! [`retry_after()`](HeaderMap::retry_after).
 //!
 //! ```
 //! # #[cfg(feature = "std")]
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! let response = bitreq::get("http://example.com").send()?;
-//! assert!(response.headers.get("content-type").unwrap().starts_with("text/html"));
+//! assert!(response.headers.get("Content-Type").unwrap().starts_with("text/html"));
+//! for cookie in response.headers.get_all("set-cookie") {
+//!     println!("{}", cookie);
+//! }
 //! # Ok(()) }
 //! # #[cfg(not(feature = "std"))]
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> { Ok(()) }
@@ -249,24 +289,86 @@
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+mod cache;
 #[cfg(feature = "std")]
 mod client;
 #[cfg(feature = "std")]
 mod connection;
+#[cfg(feature = "std")]
+mod connector;
+#[cfg(feature = "download")]
+mod download;
 mod error;
+mod header;
+#[cfg(feature = "std")]
+mod limit;
+#[cfg(feature = "std")]
+mod middleware;
 #[cfg(feature = "proxy")]
 mod proxy;
+#[cfg(feature = "std")]
+mod redirect;
 mod request;
+#[cfg(feature = "std")]
+mod resolver;
 mod response;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "async-core")]
+mod runtime;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "rustls")]
+mod tls;
+#[cfg(feature = "std")]
+mod trace;
 mod url;
+#[cfg(feature = "wasm")]
+mod wasm;
 
-#[cfg(feature = "async")]
-pub use client::{Client, RequestExt};
+#[cfg(feature = "std")]
+pub use cache::Cache;
+#[cfg(feature = "std")]
+pub use client::Client;
+#[cfg(feature = "async-core")]
+pub use client::RequestExt;
+#[cfg(all(feature = "std", unix))]
+pub use connector::UnixConnector;
+#[cfg(feature = "async-core")]
+pub use connector::{AsyncStream, ConnectFuture};
+#[cfg(feature = "std")]
+pub use connector::{Connector, Stream};
+#[cfg(feature = "download")]
+pub use download::{Download, Progress};
 pub use error::*;
+pub use header::{HeaderMap, RetryAfter};
+#[cfg(feature = "std")]
+pub use middleware::{Exchange, Middleware};
 #[cfg(feature = "proxy")]
 pub use proxy::*;
+#[cfg(feature = "std")]
+pub use redirect::{MethodRewrite, RedirectAction, RedirectAttempt, RedirectPolicy};
 pub use request::*;
+#[cfg(feature = "std")]
+pub use resolver::{Resolve, StaticResolver, SystemResolver};
+#[cfg(feature = "async-core")]
+pub use response::AsyncResponseLazy;
 pub use response::Response;
 #[cfg(feature = "std")]
 pub use response::ResponseLazy;
+#[cfg(feature = "std")]
+pub use retry::RetryPolicy;
+#[cfg(feature = "async")]
+pub use runtime::TokioRuntime;
+#[cfg(feature = "async-core")]
+pub use runtime::{AsyncIo, Runtime, RuntimeFuture};
+#[cfg(feature = "json-using-serde")]
+pub use stream::JsonLines;
+#[cfg(feature = "std")]
+pub use stream::{Event, Events, Lines};
+#[cfg(feature = "rustls")]
+pub use tls::TlsConfig;
+#[cfg(feature = "std")]
+pub use trace::{TraceEvent, TraceKind, Tracer};
 pub use url::{ParseError as UrlParseError, Url};
@@ -0,0 +1,416 @@
+//! Concurrency and bandwidth limits for [`Client`](crate::Client).
+
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(feature = "async-core")]
+use core::future::poll_fn;
+#[cfg(feature = "async-core")]
+use core::pin::Pin;
+#[cfg(feature = "async-core")]
+use core::task::{ready, Context, Poll, Waker};
+use core::time::Duration;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Instant;
+
+#[cfg(feature = "async-core")]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[cfg(feature = "async-core")]
+use crate::runtime::{RuntimeFuture, RuntimeHandle};
+use crate::{Request, Url};
+
+/// The most bytes a rate limited read or write waits for before it starts,
+/// so that large buffers are not only read once the limiter saved up for
+/// all of them.
+const MAX_CHUNK: usize = 16 * 1024;
+
+/// A counting semaphore which can be waited on by both blocking and async
+/// requests. Permits are handed out in no particular order.
+pub(crate) struct Semaphore {
+    state: Mutex<SemaphoreState>,
+    released: Condvar,
+}
+
+struct SemaphoreState {
+    available: usize,
+    #[cfg(feature = "async-core")]
+    wakers: Vec<Waker>,
+}
+
+impl Semaphore {
+    pub(crate) fn new(permits: usize) -> Arc<Semaphore> {
+        Arc::new(Semaphore {
+            state: Mutex::new(SemaphoreState {
+                available: permits,
+                #[cfg(feature = "async-core")]
+                wakers: Vec::new(),
+            }),
+            released: Condvar::new(),
+        })
+    }
+
+    /// Blocks until a permit is available.
+    pub(crate) fn acquire_blocking(self: &Arc<Self>) -> Permit {
+        let mut state = self.state.lock().unwrap();
+        while state.available == 0 {
+            state = self.released.wait(state).unwrap();
+        }
+        state.available -= 1;
+        Permit(Arc::clone(self))
+    }
+
+    /// Waits until a permit is available.
+    #[cfg(feature = "async-core")]
+    pub(crate) async fn acquire(self: &Arc<Self>) -> Permit {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                return Poll::Ready(Permit(Arc::clone(self)));
+            }
+            if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                state.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// A permit of a [`Semaphore`], released when dropped.
+pub(crate) struct Permit(Arc<Semaphore>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.available += 1;
+        // Every waiting task is woken as some may have been cancelled since
+        // they started waiting. Those which lose the race wait again.
+        #[cfg(feature = "async-core")]
+        state.wakers.drain(..).for_each(Waker::wake);
+        drop(state);
+        self.0.released.notify_one();
+    }
+}
+
+/// Limits the number of requests in flight to each host.
+pub(crate) struct HostLimits {
+    max: usize,
+    hosts: Mutex<HashMap<(String, u16), Arc<Semaphore>>>,
+}
+
+impl HostLimits {
+    pub(crate) fn new(max: usize) -> HostLimits {
+        HostLimits { max, hosts: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the semaphore for the host of `request`, or `None` if its
+    /// URL is invalid (in which case sending it fails anyway).
+    pub(crate) fn semaphore(&self, request: &Request) -> Option<Arc<Semaphore>> {
+        let url = Url::parse(request.url()).ok()?;
+        let key = (url.base_url().to_ascii_lowercase(), url.port());
+        let mut hosts = self.hosts.lock().unwrap();
+        if !hosts.contains_key(&key) {
+            // Forget idle hosts, so the map doesn't grow with every host
+            // ever requested.
+            hosts.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+        Some(Arc::clone(hosts.entry(key).or_insert_with(|| Semaphore::new(self.max))))
+    }
+}
+
+/// Limits the rate at which bytes are read or written, shared by every
+/// request of a [`Client`](crate::Client).
+///
+/// This is a token bucket holding up to a second's worth of bytes, so
+/// short bursts after an idle period are not slowed down.
+#[derive(Clone)]
+pub(crate) struct RateLimiter(Arc<Mutex<Bucket>>);
+
+struct Bucket {
+    bytes_per_second: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(bytes_per_second: u64) -> RateLimiter {
+        let bytes_per_second = bytes_per_second.max(1) as f64;
+        RateLimiter(Arc::new(Mutex::new(Bucket {
+            bytes_per_second,
+            available: bytes_per_second,
+            updated: Instant::now(),
+        })))
+    }
+
+    /// Takes up to `wanted` bytes from the bucket, or returns how long to
+    /// wait until enough bytes are available.
+    fn reserve(&self, wanted: usize) -> Result<usize, Duration> {
+        if wanted == 0 {
+            return Ok(0);
+        }
+        let mut bucket = self.0.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * bucket.bytes_per_second;
+        bucket.available = (bucket.available + refill).min(bucket.bytes_per_second);
+        bucket.updated = now;
+
+        // Wait for a reasonably sized chunk rather than reading or writing
+        // a few bytes at a time.
+        let chunk = (bucket.bytes_per_second as usize / 8).clamp(1, MAX_CHUNK);
+        let needed = wanted.clamp(1, chunk) as f64;
+        if bucket.available < needed {
+            let missing = needed - bucket.available;
+            return Err(Duration::from_secs_f64(missing / bucket.bytes_per_second));
+        }
+        let granted = wanted.min(bucket.available as usize);
+        bucket.available -= granted as f64;
+        Ok(granted)
+    }
+
+    /// Puts back bytes which were reserved but not read or written.
+    fn refund(&self, bytes: usize) {
+        if bytes > 0 {
+            let mut bucket = self.0.lock().unwrap();
+            bucket.available = (bucket.available + bytes as f64).min(bucket.bytes_per_second);
+        }
+    }
+
+    fn addr(&self) -> *const () { Arc::as_ptr(&self.0) as *const () }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RateLimiter").field(&self.addr()).finish()
+    }
+}
+
+impl PartialEq for RateLimiter {
+    fn eq(&self, other: &Self) -> bool { self.addr() == other.addr() }
+}
+
+impl Eq for RateLimiter {}
+
+/// A stream whose reads or writes are slowed down by a [`RateLimiter`],
+/// if any.
+pub(crate) struct Throttled<S> {
+    inner: S,
+    limiter: Option<RateLimiter>,
+    #[cfg(feature = "async-core")]
+    runtime: Option<RuntimeHandle>,
+    #[cfg(feature = "async-core")]
+    delay: Option<RuntimeFuture<'static, ()>>,
+}
+
+impl<S> Throttled<S> {
+    /// Wraps a blocking stream.
+    pub(crate) fn new(inner: S, limiter: Option<RateLimiter>) -> Throttled<S> {
+        Throttled {
+            inner,
+            limiter,
+            #[cfg(feature = "async-core")]
+            runtime: None,
+            #[cfg(feature = "async-core")]
+            delay: None,
+        }
+    }
+
+    /// Wraps an async stream, which waits with `runtime`.
+    #[cfg(feature = "async-core")]
+    pub(crate) fn new_async(
+        inner: S,
+        limiter: Option<RateLimiter>,
+        runtime: &RuntimeHandle,
+    ) -> Throttled<S> {
+        Throttled { inner, limiter, runtime: Some(runtime.clone()), delay: None }
+    }
+
+    /// Polls the current delay, starting a new one if `limiter` can't
+    /// grant any bytes yet.
+    #[cfg(feature = "async-core")]
+    fn poll_reserve(&mut self, cx: &mut Context<'_>, wanted: usize) -> Poll<usize> {
+        let (Some(limiter), Some(runtime)) = (&self.limiter, &self.runtime) else {
+            return Poll::Ready(wanted);
+        };
+        loop {
+            if let Some(delay) = &mut self.delay {
+                ready!(delay.as_mut().poll(cx));
+                self.delay = None;
+            }
+            match limiter.reserve(wanted) {
+                Ok(granted) => return Poll::Ready(granted),
+                Err(wait) => self.delay = Some(runtime.0.sleep(wait)),
+            }
+        }
+    }
+
+    fn refund(&self, bytes: usize) {
+        if let Some(limiter) = &self.limiter {
+            limiter.refund(bytes);
+        }
+    }
+}
+
+/// Blocks until `limiter` grants some of `wanted` bytes.
+fn reserve_blocking(limiter: &Option<RateLimiter>, wanted: usize) -> usize {
+    let Some(limiter) = limiter else { return wanted };
+    loop {
+        match limiter.reserve(wanted) {
+            Ok(granted) => return granted,
+            Err(wait) => thread::sleep(wait),
+        }
+    }
+}
+
+impl<S: Read> Read for Throttled<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let granted = reserve_blocking(&self.limiter, buf.len());
+        let result = self.inner.read(&mut buf[..granted]);
+        self.refund(granted - *result.as_ref().unwrap_or(&0));
+        result
+    }
+}
+
+impl<S: Write> Write for Throttled<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let granted = reserve_blocking(&self.limiter, buf.len());
+        let result = self.inner.write(&buf[..granted]);
+        self.refund(granted - *result.as_ref().unwrap_or(&0));
+        result
+    }
+
+    fn flush(&mut self) -> io::Result<()> { self.inner.flush() }
+}
+
+#[cfg(feature = "async-core")]
+impl<S: AsyncRead + Unpin> AsyncRead for Throttled<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.limiter.is_none() {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        }
+        let granted = ready!(this.poll_reserve(cx, buf.remaining()));
+        let mut limited = ReadBuf::new(buf.initialize_unfilled_to(granted));
+        let result = Pin::new(&mut this.inner).poll_read(cx, &mut limited);
+        let read = limited.filled().len();
+        buf.advance(read);
+        this.refund(granted - read);
+        result
+    }
+}
+
+#[cfg(feature = "async-core")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for Throttled<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let granted = ready!(this.poll_reserve(cx, buf.len()));
+        let result = Pin::new(&mut this.inner).poll_write(cx, &buf[..granted]);
+        let written = match &result {
+            Poll::Ready(Ok(written)) => *written,
+            _ => 0,
+        };
+        this.refund(granted - written);
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{HostLimits, RateLimiter, Semaphore};
+
+    #[test]
+    fn semaphore() {
+        let semaphore = Semaphore::new(2);
+        let (active, max_active) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let (semaphore, active, max_active) =
+                    (semaphore.clone(), active.clone(), max_active.clone());
+                thread::spawn(move || {
+                    let _permit = semaphore.acquire_blocking();
+                    let now_active = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(now_active, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|thread| thread.join().unwrap());
+        assert_eq!(max_active.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(feature = "async-core")]
+    #[test]
+    fn semaphore_async() {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll, Waker};
+        use std::task::Wake;
+
+        struct Noop;
+
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let semaphore = Semaphore::new(1);
+        let permit = semaphore.acquire_blocking();
+        let mut acquire = pin!(semaphore.acquire());
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        assert!(acquire.as_mut().poll(&mut cx).is_pending());
+        drop(permit);
+        assert!(matches!(acquire.as_mut().poll(&mut cx), Poll::Ready(_)));
+    }
+
+    #[test]
+    fn host_limits() {
+        let limits = HostLimits::new(1);
+        let a = limits.semaphore(&crate::get("http://Example.com/a")).unwrap();
+        let b = limits.semaphore(&crate::get("http://example.com:80/b")).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        let other = limits.semaphore(&crate::get("http://example.com:8080/")).unwrap();
+        assert!(!Arc::ptr_eq(&a, &other));
+        drop((a, b));
+        // The idle host is forgotten when a new one is added.
+        limits.semaphore(&crate::get("http://example.org/")).unwrap();
+        assert_eq!(limits.hosts.lock().unwrap().len(), 2);
+        assert!(limits.semaphore(&crate::get("not a url")).is_none());
+    }
+
+    #[test]
+    fn rate_limiter() {
+        let limiter = RateLimiter::new(1000);
+        assert_eq!(limiter.reserve(600), Ok(600));
+        assert_eq!(limiter.reserve(600), Ok(400));
+        // Waits for a chunk of an eighth of the rate.
+        let wait = limiter.reserve(600).unwrap_err();
+        assert!(wait > Duration::from_millis(120) && wait <= Duration::from_millis(125));
+        limiter.refund(200);
+        assert_eq!(limiter.reserve(100), Ok(100));
+    }
+}
@@ -0,0 +1,79 @@
+//! Request and response hooks for [`Client`](crate::Client).
+
+use core::time::Duration;
+
+use crate::{Error, Method, Request, Response};
+
+/// Hooks run by a [`Client`](crate::Client) around every request it sends.
+///
+/// Middleware registered with
+/// [`Client::with_middleware`](crate::Client::with_middleware) see requests
+/// in the order they were added, and responses in the reverse order, so
+/// the first middleware added is the outermost one.
+///
+/// Both methods have default implementations which do nothing, so
+/// middleware only need to implement the hooks they use.
+///
+/// # Example
+///
+/// ```
+/// use bitreq::{Error, Exchange, Middleware, Request};
+///
+/// struct Tracing;
+///
+/// impl Middleware for Tracing {
+///     fn on_request(&self, request: Request) -> Result<Request, Error> {
+///         Ok(request.with_header("traceparent", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"))
+///     }
+///
+///     fn on_response(&self, exchange: &Exchange) {
+///         println!("{} {} took {:?}", exchange.method(), exchange.url(), exchange.elapsed());
+///     }
+/// }
+///
+/// let client = bitreq::Client::new(10).with_middleware(Tracing);
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Called before `request` is sent, returning the request to send.
+    ///
+    /// This is called again for every retry, with the request as it was
+    /// before any middleware ran, so headers such as signatures or nonces
+    /// are computed anew for each attempt.
+    ///
+    /// Returning an error aborts the request: it is returned to the
+    /// caller, and no further middleware are run.
+    fn on_request(&self, request: Request) -> Result<Request, Error> { Ok(request) }
+
+    /// Called once the request completed, successfully or not.
+    ///
+    /// This is called once per request, after all redirects and retries.
+    fn on_response(&self, _exchange: &Exchange) {}
+}
+
+/// A completed request, passed to [`Middleware::on_response`].
+#[derive(Debug)]
+pub struct Exchange<'a> {
+    pub(crate) method: &'a Method,
+    pub(crate) url: &'a str,
+    pub(crate) elapsed: Duration,
+    pub(crate) result: Result<&'a Response, &'a Error>,
+}
+
+impl<'a> Exchange<'a> {
+    /// The method of the request, as returned by the last middleware's
+    /// [`on_request`](Middleware::on_request).
+    pub fn method(&self) -> &'a Method { self.method }
+
+    /// The URL of the request, as returned by the last middleware's
+    /// [`on_request`](Middleware::on_request). See
+    /// [`Response::url`](crate::Response::url) for the URL of the
+    /// response.
+    pub fn url(&self) -> &'a str { self.url }
+
+    /// How long the request took, from when it was sent until the whole
+    /// response was received, including any redirects and retries.
+    pub fn elapsed(&self) -> Duration { self.elapsed }
+
+    /// The response, or the error which made the request fail.
+    pub fn result(&self) -> Result<&'a Response, &'a Error> { self.result }
+}
@@ -0,0 +1,265 @@
+//! Redirect handling.
+//!
+//! By default bitreq follows up to
+//! [`max_redirects`](crate::Request::with_max_redirects) redirects. A
+//! [`RedirectPolicy`] can be set per request with
+//! [`Request::with_redirect_policy`](crate::Request::with_redirect_policy)
+//! to stop following redirects, decide on each hop with a callback, or
+//! change how the request is rewritten between hops.
+
+use alloc::sync::Arc;
+use core::fmt;
+
+use crate::{Method, Url};
+
+/// Decides whether redirects are followed and how the request is rewritten
+/// when they are.
+///
+/// # Example
+///
+/// ```
+/// use bitreq::{RedirectAction, RedirectPolicy};
+///
+/// // Only follow redirects that stay on the same host.
+/// let policy = RedirectPolicy::custom(|attempt| {
+///     if attempt.url().base_url() == attempt.previous_url().base_url() {
+///         RedirectAction::Follow
+///     } else {
+///         RedirectAction::Stop
+///     }
+/// });
+/// let request = bitreq::get("http://example.com").with_redirect_policy(policy);
+/// ```
+#[derive(Clone)]
+pub struct RedirectPolicy {
+    decision: Decision,
+    strip_authorization: bool,
+    method_rewrite: MethodRewrite,
+}
+
+#[derive(Clone)]
+enum Decision {
+    Follow,
+    Never,
+    Custom(Arc<dyn Fn(&RedirectAttempt) -> RedirectAction + Send + Sync>),
+}
+
+/// The result of a [`RedirectPolicy::custom`] callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedirectAction {
+    /// Send the request to the new location.
+    Follow,
+    /// Return the redirect response as the final response.
+    Stop,
+}
+
+/// How the request method is changed when following a redirect.
+///
+/// `307 Temporary Redirect` and `308 Permanent Redirect` never change the
+/// method, as required by
+/// [RFC 9110 section 15.4](https://datatracker.ietf.org/doc/html/rfc9110#section-15.4).
+/// When the method is changed the body is dropped, along with the
+/// `Content-Length`, `Content-Type` and `Transfer-Encoding` headers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MethodRewrite {
+    /// `303 See Other` turns `POST`, `PUT` and `DELETE` requests into `GET`
+    /// requests. `301` and `302` keep the method.
+    #[default]
+    SeeOther,
+    /// Follow what browsers do: `301` and `302` turn `POST` requests into
+    /// `GET` requests, and `303` turns every method except `HEAD` into `GET`.
+    Browser,
+    /// Never change the method.
+    Preserve,
+}
+
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+impl MethodRewrite {
+    fn rewrite(self, status_code: i32, method: &Method) -> Option<Method> {
+        let rewrite = match (self, status_code) {
+            (MethodRewrite::SeeOther, 303) =>
+                matches!(method, Method::Post | Method::Put | Method::Delete),
+            (MethodRewrite::Browser, 301 | 302) => *method == Method::Post,
+            (MethodRewrite::Browser, 303) => !matches!(method, Method::Get | Method::Head),
+            _ => false,
+        };
+        if rewrite {
+            Some(Method::Get)
+        } else {
+            None
+        }
+    }
+}
+
+/// A redirect bitreq is about to follow, passed to
+/// [`RedirectPolicy::custom`] callbacks.
+#[derive(Debug)]
+pub struct RedirectAttempt<'a> {
+    pub(crate) status_code: i32,
+    pub(crate) method: &'a Method,
+    pub(crate) url: &'a Url,
+    pub(crate) previous_url: &'a Url,
+    pub(crate) history: &'a [Url],
+}
+
+impl<'a> RedirectAttempt<'a> {
+    /// The status code of the redirect response, eg. 301.
+    pub fn status_code(&self) -> i32 { self.status_code }
+
+    /// The method of the request that was redirected.
+    pub fn method(&self) -> &'a Method { self.method }
+
+    /// The URL the response redirects to.
+    pub fn url(&self) -> &'a Url { self.url }
+
+    /// The URL of the request that was redirected.
+    pub fn previous_url(&self) -> &'a Url { self.previous_url }
+
+    /// The URLs of the requests before `previous_url`, starting with the
+    /// original request. Empty on the first redirect.
+    pub fn history(&self) -> &'a [Url] { self.history }
+}
+
+impl RedirectPolicy {
+    /// Follows redirects, up to the request's
+    /// [`max_redirects`](crate::Request::with_max_redirects). This is the
+    /// default.
+    pub fn follow() -> RedirectPolicy {
+        RedirectPolicy {
+            decision: Decision::Follow,
+            strip_authorization: true,
+            method_rewrite: MethodRewrite::default(),
+        }
+    }
+
+    /// Never follows redirects, returning the redirect response instead.
+    pub fn never() -> RedirectPolicy {
+        RedirectPolicy { decision: Decision::Never, ..RedirectPolicy::follow() }
+    }
+
+    /// Calls `decide` for each redirect to decide whether to follow it.
+    ///
+    /// The request's [`max_redirects`](crate::Request::with_max_redirects)
+    /// and redirect loop detection still apply to followed redirects.
+    pub fn custom<F>(decide: F) -> RedirectPolicy
+    where
+        F: Fn(&RedirectAttempt) -> RedirectAction + Send + Sync + 'static,
+    {
+        RedirectPolicy { decision: Decision::Custom(Arc::new(decide)), ..RedirectPolicy::follow() }
+    }
+
+    /// Sets whether the `Authorization` header is removed when a redirect
+    /// leads to a different origin (scheme, host or port). Enabled by
+    /// default, so credentials meant for one server are not sent to
+    /// another.
+    pub fn with_strip_authorization(mut self, strip: bool) -> RedirectPolicy {
+        self.strip_authorization = strip;
+        self
+    }
+
+    /// Sets how the request method is changed when following a redirect.
+    pub fn with_method_rewrite(mut self, method_rewrite: MethodRewrite) -> RedirectPolicy {
+        self.method_rewrite = method_rewrite;
+        self
+    }
+}
+
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+impl RedirectPolicy {
+    pub(crate) fn follows_redirects(&self) -> bool { !matches!(self.decision, Decision::Never) }
+
+    pub(crate) fn should_follow(&self, attempt: &RedirectAttempt) -> bool {
+        match &self.decision {
+            Decision::Follow => true,
+            Decision::Never => false,
+            Decision::Custom(decide) => decide(attempt) == RedirectAction::Follow,
+        }
+    }
+
+    pub(crate) fn strip_authorization(&self, from: &Url, to: &Url) -> bool {
+        self.strip_authorization
+            && (from.scheme() != to.scheme()
+                || !from.base_url().eq_ignore_ascii_case(to.base_url())
+                || from.port() != to.port())
+    }
+
+    pub(crate) fn rewrite_method(&self, status_code: i32, method: &Method) -> Option<Method> {
+        self.method_rewrite.rewrite(status_code, method)
+    }
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self { RedirectPolicy::follow() }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decision = match self.decision {
+            Decision::Follow => "Follow",
+            Decision::Never => "Never",
+            Decision::Custom(_) => "Custom",
+        };
+        f.debug_struct("RedirectPolicy")
+            .field("decision", &decision)
+            .field("strip_authorization", &self.strip_authorization)
+            .field("method_rewrite", &self.method_rewrite)
+            .finish()
+    }
+}
+
+impl PartialEq for RedirectPolicy {
+    /// Custom policies are only equal if they share the same callback.
+    fn eq(&self, other: &Self) -> bool {
+        let same_decision = match (&self.decision, &other.decision) {
+            (Decision::Follow, Decision::Follow) | (Decision::Never, Decision::Never) => true,
+            (Decision::Custom(a), Decision::Custom(b)) =>
+                Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const (),
+            _ => false,
+        };
+        same_decision
+            && self.strip_authorization == other.strip_authorization
+            && self.method_rewrite == other.method_rewrite
+    }
+}
+
+impl Eq for RedirectPolicy {}
+
+#[cfg(test)]
+mod tests {
+    use super::{MethodRewrite, RedirectPolicy};
+    use crate::{Method, Url};
+
+    #[test]
+    fn method_rewriting() {
+        let see_other = MethodRewrite::SeeOther;
+        assert_eq!(see_other.rewrite(301, &Method::Post), None);
+        assert_eq!(see_other.rewrite(303, &Method::Post), Some(Method::Get));
+        assert_eq!(see_other.rewrite(303, &Method::Patch), None);
+
+        let browser = MethodRewrite::Browser;
+        assert_eq!(browser.rewrite(302, &Method::Post), Some(Method::Get));
+        assert_eq!(browser.rewrite(302, &Method::Put), None);
+        assert_eq!(browser.rewrite(303, &Method::Patch), Some(Method::Get));
+        assert_eq!(browser.rewrite(303, &Method::Head), None);
+
+        for rewrite in [MethodRewrite::SeeOther, MethodRewrite::Browser, MethodRewrite::Preserve] {
+            assert_eq!(rewrite.rewrite(307, &Method::Post), None);
+            assert_eq!(rewrite.rewrite(308, &Method::Post), None);
+        }
+        assert_eq!(MethodRewrite::Preserve.rewrite(303, &Method::Post), None);
+    }
+
+    #[test]
+    fn authorization_stripped_cross_origin() {
+        let url = |s| Url::parse(s).unwrap();
+        let policy = RedirectPolicy::follow();
+        let from = url("http://example.com/a");
+        assert!(!policy.strip_authorization(&from, &url("http://EXAMPLE.com:80/b")));
+        assert!(policy.strip_authorization(&from, &url("https://example.com/a")));
+        assert!(policy.strip_authorization(&from, &url("http://example.org/a")));
+        assert!(policy.strip_authorization(&from, &url("http://example.com:8080/a")));
+
+        let policy = policy.with_strip_authorization(false);
+        assert!(!policy.strip_authorization(&from, &url("http://example.org/a")));
+    }
+}
@@ -1,21 +1,52 @@
-use alloc::collections::BTreeMap;
 use core::fmt;
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 use core::fmt::Write;
 use core::time::Duration;
 #[cfg(feature = "std")]
 use std::env;
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 use std::time::Instant;
 
-#[cfg(feature = "async")]
-use crate::connection::AsyncConnection;
 #[cfg(feature = "std")]
+use crate::cache::{self, Cache};
+#[cfg(feature = "async-core")]
+use crate::connection::AsyncConnection;
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_family = "wasm"))))]
 use crate::connection::Connection;
+#[cfg(feature = "std")]
+use crate::connector::{Connector, ConnectorHandle};
+#[cfg(feature = "std")]
+use crate::limit::RateLimiter;
 #[cfg(feature = "proxy")]
 use crate::proxy::Proxy;
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
+use crate::redirect::RedirectAttempt;
+#[cfg(feature = "std")]
+use crate::redirect::RedirectPolicy;
+#[cfg(feature = "std")]
+use crate::resolver::{Resolve, ResolverHandle};
+#[cfg(feature = "std")]
+use crate::retry::{self, RetryPolicy};
+#[cfg(feature = "async-core")]
+use crate::runtime::{Runtime, RuntimeHandle};
+#[cfg(feature = "rustls")]
+use crate::tls::TlsConfig;
+#[cfg(feature = "std")]
+use crate::trace::{Tracer, TracerHandle};
 #[cfg(feature = "std")]
 use crate::url::Url;
+#[cfg(feature = "async-core")]
+use crate::AsyncResponseLazy;
+use crate::HeaderMap;
 #[cfg(feature = "std")]
 use crate::{Error, Response, ResponseLazy};
 
@@ -86,8 +117,8 @@ impl fmt::Display for Method {
 pub struct Request {
     pub(crate) method: Method,
     url: URL,
-    params: Vec<(String, String)>,
-    headers: BTreeMap<String, String>,
+    pub(crate) params: Vec<(String, String)>,
+    headers: HeaderMap,
     body: Option<Vec<u8>>,
     timeout: Option<u64>,
     pub(crate) pipelining: bool,
@@ -95,8 +126,28 @@ pub struct Request {
     pub(crate) max_status_line_len: Option<usize>,
     pub(crate) max_body_size: Option<usize>,
     max_redirects: usize,
+    #[cfg(feature = "std")]
+    redirect_policy: RedirectPolicy,
+    #[cfg(feature = "std")]
+    pub(crate) retry_policy: Option<RetryPolicy>,
     #[cfg(feature = "proxy")]
     pub(crate) proxy: Option<Proxy>,
+    #[cfg(feature = "std")]
+    pub(crate) resolver: Option<ResolverHandle>,
+    #[cfg(feature = "std")]
+    pub(crate) connector: Option<ConnectorHandle>,
+    #[cfg(feature = "async-core")]
+    pub(crate) runtime: Option<RuntimeHandle>,
+    #[cfg(feature = "std")]
+    pub(crate) cache: Option<Cache>,
+    #[cfg(feature = "rustls")]
+    pub(crate) tls: Option<TlsConfig>,
+    #[cfg(feature = "std")]
+    pub(crate) tracer: Option<TracerHandle>,
+    #[cfg(feature = "std")]
+    pub(crate) read_limit: Option<RateLimiter>,
+    #[cfg(feature = "std")]
+    pub(crate) write_limit: Option<RateLimiter>,
 }
 
 impl Request {
@@ -112,7 +163,7 @@ impl Request {
             method,
             url: url.into(),
             params: Vec::new(),
-            headers: BTreeMap::new(),
+            headers: HeaderMap::new(),
             body: None,
             timeout: None,
             pipelining: false,
@@ -125,31 +176,85 @@ impl Request {
             // Picked somewhat randomly
             max_body_size: Some(1024 * 1024 * 1024),
             max_redirects: 100,
+            #[cfg(feature = "std")]
+            redirect_policy: RedirectPolicy::follow(),
+            #[cfg(feature = "std")]
+            retry_policy: None,
             #[cfg(feature = "proxy")]
             proxy: None,
+            #[cfg(feature = "std")]
+            resolver: None,
+            #[cfg(feature = "std")]
+            connector: None,
+            #[cfg(feature = "async-core")]
+            runtime: None,
+            #[cfg(feature = "std")]
+            cache: None,
+            #[cfg(feature = "rustls")]
+            tls: None,
+            #[cfg(feature = "std")]
+            tracer: None,
+            #[cfg(feature = "std")]
+            read_limit: None,
+            #[cfg(feature = "std")]
+            write_limit: None,
         }
     }
 
     /// Add headers to the request this is called on. Use this
     /// function to add headers to your requests.
+    ///
+    /// Each header replaces any previous value of the same header
+    /// (compared case-insensitively), see [`HeaderMap::insert`].
     pub fn with_headers<T, K, V>(mut self, headers: T) -> Request
     where
         T: IntoIterator<Item = (K, V)>,
         K: Into<String>,
         V: Into<String>,
     {
-        let headers = headers.into_iter().map(|(k, v)| (k.into(), v.into()));
         self.headers.extend(headers);
         self
     }
 
     /// Adds a header to the request this is called on. Use this
     /// function to add headers to your requests.
+    ///
+    /// This replaces any previous value of the same header (compared
+    /// case-insensitively). To send a header multiple times, use
+    /// [`with_appended_header`](Request::with_appended_header).
     pub fn with_header<T: Into<String>, U: Into<String>>(mut self, key: T, value: U) -> Request {
-        self.headers.insert(key.into(), value.into());
+        self.headers.insert(key, value);
         self
     }
 
+    /// Adds a header to the request this is called on, keeping any
+    /// previous values of the same header.
+    pub fn with_appended_header<T: Into<String>, U: Into<String>>(
+        mut self,
+        key: T,
+        value: U,
+    ) -> Request {
+        self.headers.append(key, value);
+        self
+    }
+
+    /// Returns the method of this request.
+    pub fn method(&self) -> &Method { &self.method }
+
+    /// Returns the URL of this request, without the parameters added with
+    /// [`with_param`](Request::with_param).
+    pub fn url(&self) -> &str { &self.url }
+
+    /// Returns the body of this request, if it has one.
+    pub fn body(&self) -> Option<&[u8]> { self.body.as_deref() }
+
+    /// Returns the headers that will be sent with this request.
+    pub fn headers(&self) -> &HeaderMap { &self.headers }
+
+    /// Returns a mutable reference to the headers that will be sent with
+    /// this request.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap { &mut self.headers }
+
     /// Sets the request body.
     pub fn with_body<T: Into<Vec<u8>>>(mut self, body: T) -> Request {
         let body = body.into();
@@ -177,8 +282,7 @@ impl Request {
     /// string.
     #[cfg(feature = "json-using-serde")]
     pub fn with_json<T: serde::ser::Serialize>(mut self, body: &T) -> Result<Request, Error> {
-        self.headers
-            .insert("Content-Type".to_string(), "application/json; charset=UTF-8".to_string());
+        self.headers.insert("Content-Type", "application/json; charset=UTF-8");
         match serde_json::to_vec(&body) {
             Ok(json) => Ok(self.with_body(json)),
             Err(err) => Err(Error::SerdeJsonError(err)),
@@ -203,6 +307,20 @@ impl Request {
         self
     }
 
+    /// Sets the policy deciding which redirects are followed, and how
+    /// the request is rewritten when following them. Defaults to
+    /// [`RedirectPolicy::follow`].
+    ///
+    /// Note that by default `308 Permanent Redirect` responses are followed
+    /// like `307 Temporary Redirect` ones, and the `Authorization` header is
+    /// removed when a redirect leads to a different origin. Use
+    /// [`RedirectPolicy::with_strip_authorization`] to keep it.
+    #[cfg(feature = "std")]
+    pub fn with_redirect_policy(mut self, redirect_policy: RedirectPolicy) -> Request {
+        self.redirect_policy = redirect_policy;
+        self
+    }
+
     /// Sets the maximum size of all the headers this request will
     /// accept.
     ///
@@ -261,6 +379,31 @@ impl Request {
         self
     }
 
+    /// Sets the policy used to retry this request if it fails with a
+    /// connection error or a `429`/`503` response. Requests are not
+    /// retried by default.
+    ///
+    /// The number of attempts made is reported in
+    /// [`Response::attempts`](crate::Response::attempts).
+    #[cfg(feature = "std")]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Request {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Sets the [`Cache`](crate::Cache) used to store the response to this
+    /// request if it is a `GET` request, and to answer it from if a fresh
+    /// response is already stored.
+    ///
+    /// Only [`send`](Request::send) and
+    /// [`send_async`](Request::send_async) use the cache, lazily loaded
+    /// responses are always fetched from the server.
+    #[cfg(feature = "std")]
+    pub fn with_cache(mut self, cache: Cache) -> Request {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Sets the proxy to use.
     #[cfg(feature = "proxy")]
     pub fn with_proxy(mut self, proxy: Proxy) -> Request {
@@ -268,6 +411,67 @@ impl Request {
         self
     }
 
+    /// Sets the resolver used to look up the host (or the proxy's host)
+    /// of this request.
+    ///
+    /// If the host resolves to multiple addresses, connection attempts are
+    /// raced as described in [RFC 8305](https://datatracker.ietf.org/doc/html/rfc8305)
+    /// ("happy eyeballs"), so a single unreachable address does not use up
+    /// the whole timeout of the request. Blocking requests only race with the
+    /// `happy-eyeballs` feature, and otherwise try each address in turn.
+    ///
+    /// Defaults to the [`SystemResolver`](crate::SystemResolver).
+    #[cfg(feature = "std")]
+    pub fn with_resolver<R: Resolve + 'static>(mut self, resolver: R) -> Request {
+        self.resolver = Some(ResolverHandle(alloc::sync::Arc::new(resolver)));
+        self
+    }
+
+    /// Sets the connector used to open the stream this request is sent
+    /// over, instead of connecting to the host over TCP. See
+    /// [`Connector`](crate::Connector).
+    ///
+    /// Requests with different connectors never share a connection when
+    /// sent with a [`Client`](crate::Client).
+    #[cfg(feature = "std")]
+    pub fn with_connector<C: Connector + 'static>(mut self, connector: C) -> Request {
+        self.connector = Some(ConnectorHandle(alloc::sync::Arc::new(connector)));
+        self
+    }
+
+    /// Sets the runtime used to connect and to enforce timeouts when this
+    /// request is sent asynchronously. See [`Runtime`](crate::Runtime).
+    ///
+    /// Defaults to the [`TokioRuntime`](crate::TokioRuntime) with the
+    /// `async` feature. Requests with different runtimes never share a
+    /// connection when sent with a [`Client`](crate::Client).
+    #[cfg(feature = "async-core")]
+    pub fn with_runtime<R: Runtime + 'static>(mut self, runtime: R) -> Request {
+        self.runtime = Some(RuntimeHandle(alloc::sync::Arc::new(runtime)));
+        self
+    }
+
+    /// Sets the TLS settings used if this is an HTTPS request.
+    ///
+    /// Requests with different TLS settings never share a connection when
+    /// sent with a [`Client`](crate::Client).
+    #[cfg(feature = "rustls")]
+    pub fn with_tls_config(mut self, tls: TlsConfig) -> Request {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Traces the bytes of this request and its response to `tracer`, with
+    /// credentials redacted. See [`Tracer`] for details.
+    ///
+    /// Requests sent through the `wasm` backend are not traced, as they are
+    /// not written by bitreq.
+    #[cfg(feature = "std")]
+    pub fn with_tracer<T: Tracer + 'static>(mut self, tracer: T) -> Request {
+        self.tracer = Some(TracerHandle(alloc::sync::Arc::new(tracer)));
+        self
+    }
+
     /// Enables HTTP request pipelining for this request.
     ///
     /// Note that because pipelined requests may be replayed in case of failure, you should only
@@ -277,7 +481,7 @@ impl Request {
     /// the same server with the same proxy exists.
     ///
     /// [`Client`]: crate::Client
-    #[cfg(feature = "async")]
+    #[cfg(feature = "async-core")]
     pub fn with_pipelining(mut self) -> Request {
         self.pipelining = true;
         self
@@ -294,13 +498,24 @@ impl Request {
     /// [`InvalidUtf8InBody`](enum.Error.html#variant.InvalidUtf8InBody).
     #[cfg(feature = "std")]
     pub fn send(self) -> Result<Response, Error> {
+        cache::send(self, |request| retry::send(request, Request::send_once))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn send_once(self) -> Result<Response, Error> {
         let parsed_request = ParsedRequest::new(self)?;
-        let is_head = parsed_request.config.method == Method::Head;
-        let max_body_size = parsed_request.config.max_body_size;
-        let connection =
-            Connection::new(parsed_request.connection_params(), parsed_request.timeout_at)?;
-        let response = connection.send(parsed_request)?;
-        Response::create(response, is_head, max_body_size)
+        #[cfg(all(feature = "wasm", target_family = "wasm"))]
+        return crate::wasm::send(&crate::wasm::ImportedHost, parsed_request);
+        #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+        {
+            let is_head = parsed_request.config.method == Method::Head;
+            let max_body_size = parsed_request.config.max_body_size;
+            let url = parsed_request.url.clone();
+            Connection::new(parsed_request.connection_params(), parsed_request.timeout_at)
+                .and_then(|connection| connection.send(parsed_request))
+                .and_then(|response| Response::create(response, is_head, max_body_size))
+                .map_err(|e| e.with_url(&url))
+        }
     }
 
     /// Sends this request to the host, loaded lazily.
@@ -310,9 +525,22 @@ impl Request {
     /// See [`send`](struct.Request.html#method.send).
     #[cfg(feature = "std")]
     pub fn send_lazy(self) -> Result<ResponseLazy, Error> {
+        retry::send(self, Request::send_lazy_once)
+    }
+
+    #[cfg(feature = "std")]
+    fn send_lazy_once(self) -> Result<ResponseLazy, Error> {
         let parsed_request = ParsedRequest::new(self)?;
-        Connection::new(parsed_request.connection_params(), parsed_request.timeout_at)?
-            .send(parsed_request)
+        #[cfg(all(feature = "wasm", target_family = "wasm"))]
+        return crate::wasm::send(&crate::wasm::ImportedHost, parsed_request)
+            .map(ResponseLazy::dummy_from_response);
+        #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+        {
+            let url = parsed_request.url.clone();
+            Connection::new(parsed_request.connection_params(), parsed_request.timeout_at)
+                .and_then(|connection| connection.send(parsed_request))
+                .map_err(|e| e.with_url(&url))
+        }
     }
 
     /// Sends this request to the host asynchronously.
@@ -324,15 +552,30 @@ impl Request {
     /// is described in the `Err`, and it can be any
     /// [`bitreq::Error`](enum.Error.html) except
     /// [`InvalidUtf8InBody`](enum.Error.html#variant.InvalidUtf8InBody).
-    #[cfg(feature = "async")]
+    #[cfg(feature = "async-core")]
     pub async fn send_async(self) -> Result<Response, Error> {
-        let parsed_request = ParsedRequest::new(self)?;
-        AsyncConnection::new(parsed_request.connection_params(), parsed_request.timeout_at)
-            .await?
-            .send(parsed_request)
+        cache::send_async(self, |request| retry::send_async(request, Request::send_async_once))
             .await
     }
 
+    #[cfg(feature = "async-core")]
+    async fn send_async_once(self) -> Result<Response, Error> {
+        let parsed_request = ParsedRequest::new(self)?;
+        #[cfg(all(feature = "wasm", target_family = "wasm"))]
+        return crate::wasm::send_async(&crate::wasm::ImportedHost, parsed_request).await;
+        #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
+        {
+            let url = parsed_request.url.clone();
+            let result = async {
+                AsyncConnection::new(parsed_request.connection_params(), parsed_request.timeout_at)
+                    .await?
+                    .send(parsed_request)
+                    .await
+            };
+            result.await.map_err(|e| e.with_url(&url))
+        }
+    }
+
     /// Sends this request to the host asynchronously, "loaded lazily".
     ///
     /// Note that due to API limitations the response is not actually loaded lazily - it is loaded
@@ -344,19 +587,46 @@ impl Request {
     /// # Errors
     ///
     /// See [`send_async`](struct.Request.html#method.send_async).
-    #[cfg(feature = "async")]
+    #[cfg(feature = "async-core")]
     pub async fn send_lazy_async(self) -> Result<ResponseLazy, Error> {
         let response = self.send_async().await?;
         Ok(ResponseLazy::dummy_from_response(response))
     }
+
+    /// Sends this request to the host asynchronously, returning once the
+    /// response headers have been received so the body can be read as it
+    /// arrives.
+    ///
+    /// The request is sent on a new connection which is closed once the
+    /// returned [`AsyncResponseLazy`] is dropped. The request's timeout
+    /// also applies to reading the body.
+    ///
+    /// # Errors
+    ///
+    /// See [`send_async`](struct.Request.html#method.send_async).
+    #[cfg(feature = "async-core")]
+    pub async fn send_stream_async(self) -> Result<AsyncResponseLazy, Error> {
+        retry::send_async(self, |request| async move {
+            let parsed_request = ParsedRequest::new(request)?;
+            let url = parsed_request.url.clone();
+            AsyncConnection::send_lazy(parsed_request).await.map_err(|e| e.with_url(&url))
+        })
+        .await
+    }
 }
 
 #[cfg(feature = "std")]
 pub(crate) struct ParsedRequest {
     pub(crate) url: Url,
+    #[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
     pub(crate) redirects: Vec<Url>,
     pub(crate) config: Request,
+    #[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
     pub(crate) timeout_at: Option<Instant>,
+    /// The timeout handed to the WebAssembly host. `timeout_at` is left
+    /// unset there, as `Instant::now` panics on `wasm32-unknown-unknown`.
+    #[cfg(feature = "wasm")]
+    pub(crate) timeout: Option<Duration>,
 }
 
 #[cfg(feature = "std")]
@@ -410,12 +680,36 @@ impl ParsedRequest {
             Ok(t) => t.parse::<u64>().ok(),
             Err(_) => None,
         });
+        #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
         let timeout_at = timeout.map(|t| Instant::now() + Duration::from_secs(t));
-
-        Ok(ParsedRequest { url, redirects: Vec::new(), config, timeout_at })
+        #[cfg(all(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
+        let timeout_at = None;
+
+        Ok(ParsedRequest {
+            url,
+            #[cfg(any(
+                feature = "async-core",
+                not(all(feature = "wasm", target_family = "wasm"))
+            ))]
+            redirects: Vec::new(),
+            config,
+            #[cfg(any(
+                feature = "async-core",
+                not(all(feature = "wasm", target_family = "wasm"))
+            ))]
+            timeout_at,
+            #[cfg(feature = "wasm")]
+            timeout: timeout.map(Duration::from_secs),
+        })
     }
+}
 
-    fn get_http_head(&self) -> String {
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
+impl ParsedRequest {
+    pub(crate) fn get_http_head(&self) -> String {
         let mut http = String::with_capacity(32);
 
         // NOTE: As of 2.10.0, the fragment is intentionally left out of the request, based on:
@@ -442,7 +736,7 @@ impl ParsedRequest {
         http += "\r\n";
 
         // Add other headers
-        for (k, v) in &self.config.headers {
+        for (k, v) in self.config.headers.iter() {
             write!(http, "{}: {}\r\n", k, v).unwrap();
         }
 
@@ -450,11 +744,9 @@ impl ParsedRequest {
             || self.config.method == Method::Put
             || self.config.method == Method::Patch
         {
-            let not_length = |key: &String| {
-                let key = key.to_lowercase();
-                key != "content-length" && key != "transfer-encoding"
-            };
-            if self.config.headers.keys().all(not_length) {
+            let headers = &self.config.headers;
+            if !headers.contains_key("content-length") && !headers.contains_key("transfer-encoding")
+            {
                 // A user agent SHOULD send a Content-Length in a request message when no Transfer-Encoding
                 // is sent and the request method defines a meaning for an enclosed payload body.
                 // refer: https://tools.ietf.org/html/rfc7230#section-3.3.2
@@ -481,54 +773,87 @@ impl ParsedRequest {
         head
     }
 
-    /// Returns the redirected version of this Request, unless an
-    /// infinite redirection loop was detected, or the redirection
-    /// limit was reached.
-    pub(crate) fn redirect_to(&mut self, url: &str) -> Result<(), Error> {
-        if url.contains("://") {
-            let mut new_url = Url::parse(url).map_err(|_| {
+    /// Redirects this request to `location` if the redirect policy
+    /// allows it, returning whether it did. Fails if an infinite
+    /// redirection loop was detected, or the redirection limit was
+    /// reached.
+    pub(crate) fn redirect_to(&mut self, status_code: i32, location: &str) -> Result<bool, Error> {
+        let mut new_url = if location.contains("://") {
+            Url::parse(location).map_err(|_| {
                 // TODO: Uncomment this for 3.0
                 // Error::InvalidProtocolInRedirect
-                #[cfg(feature = "std")]
-                {
-                    Error::IoError(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "was redirected to an absolute url with an invalid protocol",
-                    ))
-                }
-                #[cfg(not(feature = "std"))]
-                {
-                    Error::Other("invalid protocol in redirect")
-                }
-            })?;
-            // Preserve fragment from original URL if new URL doesn't have one (RFC 7231 section 7.1.2)
-            new_url.preserve_fragment_from(&self.url);
-            std::mem::swap(&mut new_url, &mut self.url);
-            self.redirects.push(new_url);
+                Error::IoError(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "was redirected to an absolute url with an invalid protocol",
+                ))
+            })?
         } else {
             // The url does not have the protocol part, assuming it's
             // a relative resource.
             let mut absolute_url = String::new();
             self.url.write_base_url_to(&mut absolute_url).unwrap();
-            absolute_url.push_str(url);
-            let mut new_url = Url::parse(&absolute_url).map_err(|e| {
+            absolute_url.push_str(location);
+            Url::parse(&absolute_url).map_err(|e| {
                 Error::IoError(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))
-            })?;
-            // Preserve fragment from original URL if new URL doesn't have one (RFC 7231 section 7.1.2)
-            new_url.preserve_fragment_from(&self.url);
-            std::mem::swap(&mut new_url, &mut self.url);
-            self.redirects.push(new_url);
+            })?
+        };
+        // Preserve fragment from original URL if new URL doesn't have one (RFC 7231 section 7.1.2)
+        new_url.preserve_fragment_from(&self.url);
+
+        let policy = &self.config.redirect_policy;
+        let attempt = RedirectAttempt {
+            status_code,
+            method: &self.config.method,
+            url: &new_url,
+            previous_url: &self.url,
+            history: &self.redirects,
+        };
+        if !policy.should_follow(&attempt) {
+            return Ok(false);
+        }
+        let strip_authorization = policy.strip_authorization(&self.url, &new_url);
+        if let Some(method) = policy.rewrite_method(status_code, &self.config.method) {
+            // The body was meant for the original method, so it isn't sent along.
+            self.config.method = method;
+            self.config.body = None;
+            for header in ["content-length", "content-type", "transfer-encoding"] {
+                self.config.headers.remove(header);
+            }
+        }
+        if strip_authorization {
+            self.config.headers.remove("authorization");
         }
 
+        std::mem::swap(&mut new_url, &mut self.url);
+        self.redirects.push(new_url);
+
         if self.redirects.len() > self.config.max_redirects {
             Err(Error::TooManyRedirections)
         } else if self.redirects.iter().any(|redirect_url| redirect_url == &self.url) {
             Err(Error::InfiniteRedirectionLoop)
         } else {
-            Ok(())
+            Ok(true)
         }
     }
 
+    pub(crate) fn follows_redirects(&self) -> bool {
+        self.config.redirect_policy.follows_redirects()
+    }
+
+    /// Returns the URLs this request was redirected from, formatted
+    /// like [`Response::url`].
+    pub(crate) fn redirect_history(&self) -> Vec<String> {
+        self.redirects
+            .iter()
+            .map(|url| {
+                let mut formatted = String::new();
+                url.write_base_url_to(&mut formatted).unwrap();
+                url.write_resource_to(&mut formatted).unwrap();
+                formatted
+            })
+            .collect()
+    }
+
     pub(crate) fn connection_params(&self) -> ConnectionParams<'_> {
         ConnectionParams::from_request(self)
     }
@@ -536,43 +861,90 @@ impl ParsedRequest {
 
 /// A key which determines whether an existing connection can be reused
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 pub(crate) struct ConnectionParams<'a> {
     pub(crate) https: bool,
+    pub(crate) unix: bool,
     pub(crate) host: &'a str,
     pub(crate) port: u16,
     #[cfg(feature = "proxy")]
     pub(crate) proxy: Option<&'a Proxy>,
+    pub(crate) resolver: Option<&'a ResolverHandle>,
+    pub(crate) connector: Option<&'a ConnectorHandle>,
+    #[cfg(feature = "async-core")]
+    pub(crate) runtime: Option<&'a RuntimeHandle>,
+    #[cfg(feature = "rustls")]
+    pub(crate) tls: Option<&'a TlsConfig>,
 }
 
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 impl<'a> ConnectionParams<'a> {
     fn from_request(request: &'a ParsedRequest) -> Self {
         Self {
             https: request.url.is_https(),
+            unix: request.url.is_unix(),
             host: request.url.base_url(),
             port: request.url.port(),
             #[cfg(feature = "proxy")]
             proxy: request.config.proxy.as_ref(),
+            resolver: request.config.resolver.as_ref(),
+            connector: request.config.connector.as_ref(),
+            #[cfg(feature = "async-core")]
+            runtime: request.config.runtime.as_ref(),
+            #[cfg(feature = "rustls")]
+            tls: request.config.tls.as_ref(),
         }
     }
 }
 
 /// A [`ConnectionParams`] without references.
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub(crate) struct OwnedConnectionParams {
     pub(crate) https: bool,
+    pub(crate) unix: bool,
     pub(crate) host: String,
     pub(crate) port: u16,
     #[cfg(feature = "proxy")]
     pub(crate) proxy: Option<Proxy>,
+    pub(crate) resolver: Option<ResolverHandle>,
+    pub(crate) connector: Option<ConnectorHandle>,
+    #[cfg(feature = "async-core")]
+    pub(crate) runtime: Option<RuntimeHandle>,
+    #[cfg(feature = "rustls")]
+    pub(crate) tls: Option<TlsConfig>,
 }
 
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 impl PartialEq<ConnectionParams<'_>> for OwnedConnectionParams {
     fn eq(&self, other: &ConnectionParams<'_>) -> bool {
-        if self.https != other.https || self.host != other.host || self.port != other.port {
+        if self.https != other.https
+            || self.unix != other.unix
+            || self.host != other.host
+            || self.port != other.port
+            || self.resolver.as_ref() != other.resolver
+            || self.connector.as_ref() != other.connector
+        {
+            return false;
+        }
+        #[cfg(feature = "async-core")]
+        if self.runtime.as_ref() != other.runtime {
+            return false;
+        }
+        #[cfg(feature = "rustls")]
+        if self.tls.as_ref() != other.tls {
             return false;
         }
         #[cfg(feature = "proxy")]
@@ -586,15 +958,25 @@ impl PartialEq<ConnectionParams<'_>> for OwnedConnectionParams {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 impl From<ConnectionParams<'_>> for OwnedConnectionParams {
     fn from(other: ConnectionParams<'_>) -> Self {
         Self {
             https: other.https,
+            unix: other.unix,
             host: other.host.to_owned(),
             port: other.port,
             #[cfg(feature = "proxy")]
             proxy: other.proxy.cloned(),
+            resolver: other.resolver.cloned(),
+            connector: other.connector.cloned(),
+            #[cfg(feature = "async-core")]
+            runtime: other.runtime.cloned(),
+            #[cfg(feature = "rustls")]
+            tls: other.tls.cloned(),
         }
     }
 }
@@ -639,19 +1021,18 @@ pub fn patch<T: Into<URL>>(url: T) -> Request { Request::new(Method::Patch, url)
 #[cfg(feature = "std")]
 mod parsing_tests {
 
-    use alloc::collections::BTreeMap;
-
     use super::{get, ParsedRequest};
 
     #[test]
     fn test_headers() {
-        let mut headers = BTreeMap::new();
-        headers.insert("foo".to_string(), "bar".to_string());
-        headers.insert("foo".to_string(), "baz".to_string());
-
-        let req = get("http://www.example.org/test/res").with_headers(headers.clone());
+        let req = get("http://www.example.org/test/res")
+            .with_headers([("foo", "bar"), ("Foo", "baz")])
+            .with_appended_header("accept", "text/plain")
+            .with_appended_header("Accept", "text/html");
 
-        assert_eq!(req.headers, headers);
+        assert_eq!(req.headers.get("FOO"), Some("baz"));
+        assert_eq!(req.headers.get_all("accept").collect::<Vec<_>>(), ["text/plain", "text/html"]);
+        assert_eq!(req.headers.len(), 3);
     }
 
     #[test]
@@ -0,0 +1,220 @@
+//! Host name resolution.
+//!
+//! By default bitreq resolves hosts using the system resolver (via
+//! [`ToSocketAddrs`], run with the request's runtime for async requests). A
+//! [`Resolve`] implementation can be set per request with
+//! [`Request::with_resolver`](crate::Request::with_resolver) to override this.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+/// Resolves a host name to the socket addresses bitreq should try to connect to.
+///
+/// Implementations may block. When used for async requests they are run
+/// with the request's `Runtime::spawn_blocking` (tokio's blocking thread
+/// pool by default).
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+/// use std::net::{Ipv4Addr, SocketAddr};
+///
+/// struct Localhost;
+///
+/// impl bitreq::Resolve for Localhost {
+///     fn resolve(&self, _host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+///         Ok(vec![SocketAddr::from((Ipv4Addr::LOCALHOST, port))])
+///     }
+/// }
+///
+/// let request = bitreq::get("http://bitcoind.internal:8332").with_resolver(Localhost);
+/// ```
+pub trait Resolve: Send + Sync {
+    /// Returns the addresses for `host`, in the order they should be preferred.
+    ///
+    /// Returning an empty list results in an
+    /// [`AddressNotFound`](crate::Error::AddressNotFound) error.
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolves hosts using the operating system's resolver.
+///
+/// This is what bitreq uses when no resolver is set on a request.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok((host, port).to_socket_addrs()?.collect())
+    }
+}
+
+/// Resolves a fixed set of hosts to static addresses, falling back to
+/// another resolver (the [`SystemResolver`] by default) for all other hosts.
+///
+/// Host names are matched case-insensitively.
+///
+/// # Example
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// let resolver = bitreq::StaticResolver::new()
+///     .with_override("bitcoind.internal", [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))]);
+/// let request = bitreq::get("http://bitcoind.internal:8332").with_resolver(resolver);
+/// ```
+pub struct StaticResolver {
+    overrides: BTreeMap<String, Vec<IpAddr>>,
+    fallback: Option<Box<dyn Resolve>>,
+}
+
+impl StaticResolver {
+    /// Creates a new `StaticResolver` without any overrides.
+    pub fn new() -> StaticResolver {
+        StaticResolver { overrides: BTreeMap::new(), fallback: Some(Box::new(SystemResolver)) }
+    }
+
+    /// Resolves `host` to `addrs` (using the port from the request URL)
+    /// instead of asking the fallback resolver.
+    pub fn with_override<H, I>(mut self, host: H, addrs: I) -> StaticResolver
+    where
+        H: Into<String>,
+        I: IntoIterator<Item = IpAddr>,
+    {
+        let mut host = host.into();
+        host.make_ascii_lowercase();
+        self.overrides.insert(host, addrs.into_iter().collect());
+        self
+    }
+
+    /// Sets the resolver used for hosts without an override.
+    pub fn with_fallback<R: Resolve + 'static>(mut self, fallback: R) -> StaticResolver {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// Fails to resolve any host without an override, rather than falling
+    /// back to another resolver.
+    pub fn without_fallback(mut self) -> StaticResolver {
+        self.fallback = None;
+        self
+    }
+}
+
+impl Default for StaticResolver {
+    fn default() -> Self { StaticResolver::new() }
+}
+
+impl fmt::Debug for StaticResolver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StaticResolver")
+            .field("overrides", &self.overrides)
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl Resolve for StaticResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let key = host.to_ascii_lowercase();
+        if let Some(addrs) = self.overrides.get(&key) {
+            return Ok(addrs.iter().map(|ip| SocketAddr::new(*ip, port)).collect());
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.resolve(host, port),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl<F> Resolve for F
+where
+    F: Fn(&str, u16) -> io::Result<Vec<SocketAddr>> + Send + Sync,
+{
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> { self(host, port) }
+}
+
+/// A shared [`Resolve`] implementation stored in a [`Request`](crate::Request).
+///
+/// Two handles are only equal if they point to the same resolver, so requests
+/// with different resolvers never share a pooled connection.
+#[derive(Clone)]
+pub(crate) struct ResolverHandle(pub(crate) Arc<dyn Resolve>);
+
+impl ResolverHandle {
+    fn addr(&self) -> *const () { Arc::as_ptr(&self.0) as *const () }
+}
+
+impl fmt::Debug for ResolverHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ResolverHandle").field(&self.addr()).finish()
+    }
+}
+
+impl PartialEq for ResolverHandle {
+    fn eq(&self, other: &Self) -> bool { self.addr() == other.addr() }
+}
+
+impl Eq for ResolverHandle {}
+
+impl Hash for ResolverHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.addr().hash(state) }
+}
+
+/// Orders `addrs` for connection racing as described in
+/// [RFC 8305 section 4](https://datatracker.ietf.org/doc/html/rfc8305#section-4):
+/// address families are interleaved, starting with IPv6 if there is any IPv6
+/// address. The relative order within each family is preserved.
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+pub(crate) fn interleave_address_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+    let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+    let mut v6 = v6.into_iter();
+    let mut v4 = v4.into_iter();
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    use super::{interleave_address_families, Resolve, StaticResolver};
+
+    fn v4(last: u8) -> SocketAddr { SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, last)), 80) }
+
+    fn v6(last: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, last)), 80)
+    }
+
+    #[test]
+    fn interleave_starts_with_ipv6() {
+        let ordered = interleave_address_families(vec![v4(1), v4(2), v4(3), v6(1), v6(2)]);
+        assert_eq!(ordered, vec![v6(1), v4(1), v6(2), v4(2), v4(3)]);
+    }
+
+    #[test]
+    fn interleave_single_family() {
+        let ordered = interleave_address_families(vec![v4(2), v4(1)]);
+        assert_eq!(ordered, vec![v4(2), v4(1)]);
+    }
+
+    #[test]
+    fn static_resolver_overrides_case_insensitively() {
+        let resolver = StaticResolver::new()
+            .with_override("Node.Internal", [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))])
+            .without_fallback();
+        assert_eq!(resolver.resolve("node.INTERNAL", 80).unwrap(), vec![v4(1)]);
+        assert!(resolver.resolve("other.internal", 80).unwrap().is_empty());
+    }
+}
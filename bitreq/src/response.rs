@@ -1,18 +1,30 @@
-use alloc::collections::BTreeMap;
 use core::str;
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use std::future::Future;
 #[cfg(feature = "std")]
-use std::io::{self, BufReader, Bytes, Read};
+use std::io::{self, BufReader, Read};
+#[cfg(feature = "async-core")]
+use std::time::Instant;
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 use tokio::io::{AsyncRead, AsyncReadExt};
 
+#[cfg(feature = "async-core")]
+use crate::connection::AsyncHttpStream;
 #[cfg(feature = "std")]
 use crate::connection::HttpStream;
-use crate::Error;
-
 #[cfg(feature = "std")]
+use crate::limit::Throttled;
+#[cfg(feature = "async-core")]
+use crate::runtime::RuntimeHandle;
+#[cfg(feature = "std")]
+use crate::trace::TraceReader;
+use crate::{Error, HeaderMap};
+
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 const BACKING_READ_BUFFER_LENGTH: usize = 16 * 1024;
 #[cfg(feature = "std")]
 const MAX_CONTENT_LENGTH: usize = 16 * 1024;
@@ -38,20 +50,28 @@ pub struct Response {
     pub status_code: i32,
     /// The reason phrase of the response, eg. "Not Found".
     pub reason_phrase: String,
-    /// The headers of the response. The header field names (the
-    /// keys) are all lowercase.
-    pub headers: BTreeMap<String, String>,
+    /// The headers of the response. Header names are looked up
+    /// case-insensitively, see [`HeaderMap`].
+    pub headers: HeaderMap,
     /// The URL of the resource returned in this response. May differ from the
     /// request URL if it was redirected or typo corrections were applied (e.g.
     /// <http://example.com?foo=bar> would be corrected to
     /// <http://example.com/?foo=bar>).
     pub url: String,
-
-    body: Vec<u8>,
+    /// The URLs of the requests that were redirected before reaching
+    /// [`url`](Self::url), starting with the original request URL. Empty
+    /// if no redirects were followed.
+    pub redirects: Vec<String>,
+    /// How many times the request was sent before this response was
+    /// received. Only more than 1 if the request was retried by its
+    /// [`RetryPolicy`](crate::RetryPolicy).
+    pub attempts: usize,
+
+    pub(crate) body: Vec<u8>,
 }
 
 impl Response {
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_family = "wasm"))))]
     pub(crate) fn create(
         mut parent: ResponseLazy,
         is_head: bool,
@@ -69,12 +89,13 @@ impl Response {
             }
         }
 
-        let ResponseLazy { status_code, reason_phrase, headers, url, .. } = parent;
+        let ResponseLazy { status_code, reason_phrase, headers, url, redirects, attempts, .. } =
+            parent;
 
-        Ok(Response { status_code, reason_phrase, headers, url, body })
+        Ok(Response { status_code, reason_phrase, headers, url, redirects, attempts, body })
     }
 
-    #[cfg(feature = "async")]
+    #[cfg(feature = "async-core")]
     /// Fully read a [`Response`] from an async stream.
     ///
     /// When this crate was originally made "async", it actually just spawned sync requests on
@@ -150,7 +171,15 @@ impl Response {
             }
         }
 
-        Ok(Response { status_code, reason_phrase, headers, url: String::new(), body })
+        Ok(Response {
+            status_code,
+            reason_phrase,
+            headers,
+            url: String::new(),
+            redirects: Vec::new(),
+            attempts: 1,
+            body,
+        })
     }
 
     /// Returns the body as an `&str`.
@@ -299,14 +328,22 @@ pub struct ResponseLazy {
     pub status_code: i32,
     /// The reason phrase of the response, eg. "Not Found".
     pub reason_phrase: String,
-    /// The headers of the response. The header field names (the
-    /// keys) are all lowercase.
-    pub headers: BTreeMap<String, String>,
+    /// The headers of the response. Header names are looked up
+    /// case-insensitively, see [`HeaderMap`].
+    pub headers: HeaderMap,
     /// The URL of the resource returned in this response. May differ from the
     /// request URL if it was redirected or typo corrections were applied (e.g.
     /// <http://example.com?foo=bar> would be corrected to
     /// <http://example.com/?foo=bar>).
     pub url: String,
+    /// The URLs of the requests that were redirected before reaching
+    /// [`url`](Self::url), starting with the original request URL. Empty
+    /// if no redirects were followed.
+    pub redirects: Vec<String>,
+    /// How many times the request was sent before this response was
+    /// received. Only more than 1 if the request was retried by its
+    /// [`RetryPolicy`](crate::RetryPolicy).
+    pub attempts: usize,
 
     stream: HttpStreamBytes,
     state: HttpStreamState,
@@ -315,18 +352,39 @@ pub struct ResponseLazy {
     bytes_read: usize,
 }
 
+/// The stream of a [`ResponseLazy`], iterated over a byte at a time while
+/// parsing the response, and read from directly when copying the body.
 #[cfg(feature = "std")]
-type HttpStreamBytes = Bytes<BufReader<HttpStream>>;
+struct HttpStreamBytes(BufReader<TraceReader<Throttled<HttpStream>>>);
+
+#[cfg(feature = "std")]
+impl Iterator for HttpStreamBytes {
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut byte = 0;
+        loop {
+            return match self.0.read(core::slice::from_mut(&mut byte)) {
+                Ok(0) => None,
+                Ok(_) => Some(Ok(byte)),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Some(Err(e)),
+            };
+        }
+    }
+}
 
 #[cfg(feature = "std")]
 impl ResponseLazy {
+    #[cfg(not(all(feature = "wasm", target_family = "wasm")))]
     pub(crate) fn from_stream(
-        stream: HttpStream,
+        stream: TraceReader<Throttled<HttpStream>>,
         max_headers_size: Option<usize>,
         max_status_line_len: Option<usize>,
         max_body_size: Option<usize>,
     ) -> Result<ResponseLazy, Error> {
-        let mut stream = BufReader::with_capacity(BACKING_READ_BUFFER_LENGTH, stream).bytes();
+        let mut stream =
+            HttpStreamBytes(BufReader::with_capacity(BACKING_READ_BUFFER_LENGTH, stream));
         let ResponseMetadata {
             status_code,
             reason_phrase,
@@ -340,6 +398,8 @@ impl ResponseLazy {
             reason_phrase,
             headers,
             url: String::new(),
+            redirects: Vec::new(),
+            attempts: 1,
             stream,
             state,
             max_trailing_headers_size,
@@ -348,15 +408,18 @@ impl ResponseLazy {
         })
     }
 
-    #[cfg(feature = "async")]
+    #[cfg(any(feature = "async-core", all(feature = "wasm", target_family = "wasm")))]
     pub(crate) fn dummy_from_response(response: Response) -> ResponseLazy {
-        let http_stream = HttpStream::create_buffer(response.body);
+        let buffer = Throttled::new(HttpStream::create_buffer(response.body), None);
+        let http_stream = TraceReader::new(buffer, None);
         ResponseLazy {
             status_code: response.status_code,
             reason_phrase: response.reason_phrase,
             headers: response.headers,
             url: response.url,
-            stream: BufReader::with_capacity(1, http_stream).bytes(),
+            redirects: response.redirects,
+            attempts: response.attempts,
+            stream: HttpStreamBytes(BufReader::with_capacity(1, http_stream)),
             state: HttpStreamState::EndOnClose,
             max_trailing_headers_size: None,
             // Body was already fully loaded and size-checked by send_async
@@ -364,6 +427,48 @@ impl ResponseLazy {
             bytes_read: 0,
         }
     }
+
+    /// Reads the next bytes of the body into `buf`, returning how many were
+    /// read, or 0 at the end of the body.
+    ///
+    /// Bodies with a `Content-Length`, or which end when the connection is
+    /// closed, are copied from the stream in bulk. Chunked bodies are read a
+    /// byte at a time, as the chunk framing is parsed along the way.
+    pub(crate) fn read_body(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = match self.state {
+            HttpStreamState::EndOnClose => buf.len(),
+            HttpStreamState::ContentLength(length) => buf.len().min(length),
+            HttpStreamState::Chunked(..) => {
+                let mut index = 0;
+                while index < buf.len() {
+                    match self.next() {
+                        Some(byte) => buf[index] = byte?.0,
+                        None => break,
+                    }
+                    index += 1;
+                }
+                return Ok(index);
+            }
+        };
+        // An empty read would still block on the stream to fill its buffer.
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = loop {
+            match self.stream.0.read(&mut buf[..len]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        if let HttpStreamState::ContentLength(ref mut length) = self.state {
+            *length -= read;
+        }
+        self.bytes_read += read;
+        if self.max_body_size.is_some_and(|max| self.bytes_read > max) {
+            return Err(Error::BodyOverflow);
+        }
+        Ok(read)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -401,30 +506,154 @@ impl Iterator for ResponseLazy {
 #[cfg(feature = "std")]
 impl Read for ResponseLazy {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let mut index = 0;
-        for res in self {
-            // there is no use for the estimated length in the read implementation
-            // so it is ignored.
-            let (byte, _) = res.map_err(|e| match e {
-                Error::IoError(e) => e,
-                _ => io::Error::new(io::ErrorKind::Other, e),
-            })?;
-
-            buf[index] = byte;
-            index += 1;
-
-            // if the buffer is full, it should stop reading
-            if index >= buf.len() {
-                break;
+        self.read_body(buf).map_err(|e| match e {
+            Error::IoError(e) => e,
+            _ => io::Error::new(io::ErrorKind::Other, e),
+        })
+    }
+}
+
+/// An HTTP response whose body is read asynchronously as it arrives.
+///
+/// Returned by [`send_stream_async()`](crate::Request::send_stream_async).
+/// Unlike [`send_lazy_async()`](crate::Request::send_lazy_async), the
+/// request is sent on its own connection and only the status line and
+/// headers are read before returning, so this can be used for responses
+/// which never end, such as Server-Sent Events feeds.
+///
+/// The bytes are read with [`next_byte`](Self::next_byte), which works
+/// like the [`Iterator`] implementation of [`ResponseLazy`], or through
+/// the [`lines`](Self::lines) and [`events`](Self::events) readers.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn stream() -> Result<(), bitreq::Error> {
+/// let mut response = bitreq::get("http://example.com").send_stream_async().await?;
+/// let mut body = Vec::new();
+/// while let Some(byte) = response.next_byte().await {
+///     body.push(byte?.0);
+/// }
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "async-core")]
+pub struct AsyncResponseLazy {
+    /// The status code of the response, eg. 404.
+    pub status_code: i32,
+    /// The reason phrase of the response, eg. "Not Found".
+    pub reason_phrase: String,
+    /// The headers of the response. Header names are looked up
+    /// case-insensitively, see [`HeaderMap`].
+    pub headers: HeaderMap,
+    /// The URL of the resource returned in this response. May differ from the
+    /// request URL if it was redirected or typo corrections were applied.
+    pub url: String,
+    /// The URLs of the requests that were redirected before reaching
+    /// [`url`](Self::url), starting with the original request URL. Empty
+    /// if no redirects were followed.
+    pub redirects: Vec<String>,
+    /// How many times the request was sent before this response was
+    /// received. Only more than 1 if the request was retried by its
+    /// [`RetryPolicy`](crate::RetryPolicy).
+    pub attempts: usize,
+
+    stream: tokio::io::BufReader<TraceReader<Throttled<AsyncHttpStream>>>,
+    state: HttpStreamState,
+    max_trailing_headers_size: Option<usize>,
+    max_body_size: Option<usize>,
+    bytes_read: usize,
+    timeout_at: Option<Instant>,
+    runtime: RuntimeHandle,
+}
+
+#[cfg(feature = "async-core")]
+impl AsyncResponseLazy {
+    pub(crate) async fn from_stream(
+        stream: TraceReader<Throttled<AsyncHttpStream>>,
+        max_headers_size: Option<usize>,
+        max_status_line_len: Option<usize>,
+        max_body_size: Option<usize>,
+        timeout_at: Option<Instant>,
+        runtime: RuntimeHandle,
+    ) -> Result<AsyncResponseLazy, Error> {
+        let mut stream = tokio::io::BufReader::with_capacity(BACKING_READ_BUFFER_LENGTH, stream);
+        let metadata = read_metadata_async(&mut stream, max_headers_size, max_status_line_len);
+        let ResponseMetadata {
+            status_code,
+            reason_phrase,
+            headers,
+            state,
+            max_trailing_headers_size,
+        } = runtime.timeout_at(timeout_at, metadata).await??;
+
+        Ok(AsyncResponseLazy {
+            status_code,
+            reason_phrase,
+            headers,
+            url: String::new(),
+            redirects: Vec::new(),
+            attempts: 1,
+            stream,
+            state,
+            max_trailing_headers_size,
+            max_body_size,
+            bytes_read: 0,
+            timeout_at,
+            runtime,
+        })
+    }
+
+    /// Reads the next byte of the body, along with how many bytes are
+    /// expected to follow (see [`ResponseLazy`]). Returns `None` once the
+    /// whole body has been read.
+    pub async fn next_byte(&mut self) -> Option<Result<(u8, usize), Error>> {
+        let (timeout_at, runtime) = (self.timeout_at, self.runtime.clone());
+        let result =
+            runtime.timeout_at(timeout_at, self.read_byte()).await.unwrap_or_else(|e| Some(Err(e)));
+
+        // Check body size limit before returning the byte
+        if let Some(Ok((_, expected_length))) = &result {
+            if self.max_body_size.is_some_and(|max| self.bytes_read + expected_length > max) {
+                return Some(Err(Error::BodyOverflow));
             }
+            self.bytes_read += 1;
         }
 
-        // index of the next byte is the number of bytes thats have been read
-        Ok(index)
+        result
+    }
+
+    async fn read_byte(&mut self) -> Option<Result<(u8, usize), Error>> {
+        use HttpStreamState::*;
+        match self.state {
+            EndOnClose => match read_until_closed_async(&mut self.stream).await {
+                // Reading a byte asynchronously fails at the end of the stream
+                Some(Err(Error::IoError(err))) if err.kind() == io::ErrorKind::UnexpectedEof =>
+                    None,
+                result => result,
+            },
+            ContentLength(ref mut length) =>
+                read_with_content_length_async(&mut self.stream, length).await,
+            Chunked(ref mut expecting_chunks, ref mut length, ref mut content_length) =>
+                read_chunked_async(
+                    &mut self.stream,
+                    &mut self.headers,
+                    expecting_chunks,
+                    length,
+                    content_length,
+                    self.max_trailing_headers_size,
+                )
+                .await,
+        }
     }
 }
 
 #[cfg(feature = "std")]
+// Without async-core, a `ResponseLazy` on WebAssembly is only ever made from
+// a loaded `Response`, which is read until the end of its buffer.
+#[cfg_attr(
+    all(feature = "wasm", target_family = "wasm", not(feature = "async-core")),
+    allow(dead_code)
+)]
 enum HttpStreamState {
     // No Content-Length, and Transfer-Encoding != chunked, so we just
     // read unti lthe server closes the connection (this should be the
@@ -444,11 +673,14 @@ enum HttpStreamState {
 // constructors, but not in their structs, for api-cleanliness
 // reasons. (Eg. response.status_code is much cleaner than
 // response.meta.status_code or similar.)
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 struct ResponseMetadata {
     status_code: i32,
     reason_phrase: String,
-    headers: BTreeMap<String, String>,
+    headers: HeaderMap,
     state: HttpStreamState,
     max_trailing_headers_size: Option<usize>,
 }
@@ -462,19 +694,19 @@ macro_rules! maybe_await {
     };
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 /// We need to mungle [`AsyncRead`] to look like an iterator, which we do here.
 trait AsyncIteratorReadExt {
     fn next(&mut self) -> impl Future<Output = Option<Result<u8, io::Error>>>;
 }
 
-#[cfg(feature = "async")]
+#[cfg(feature = "async-core")]
 impl<T: AsyncReadExt + Unpin> AsyncIteratorReadExt for T {
     async fn next(&mut self) -> Option<Result<u8, io::Error>> { Some(self.read_u8().await) }
 }
 
 macro_rules! define_read_methods {
-    (($read_until_closed: ident, $read_with_content_length: ident, $read_trailers: ident, $read_chunked: ident, $read_metadata: ident, $read_line: ident)<$($arg: ident : $($argty: path $(|)?)*),*>, $stream_type: ident $(, $async: tt, $await: tt)?) => {
+    (($read_until_closed: ident, $read_with_content_length: ident, $read_trailers: ident, $read_chunked: ident, $(#[$read_metadata_attr: meta])* $read_metadata: ident, $read_line: ident)<$($arg: ident : $($argty: path $(|)?)*),*>, $stream_type: ident $(, $async: tt, $await: tt)?) => {
         $($async)? fn $read_until_closed<$($arg: $($argty +)*),*>(
             bytes: &mut $stream_type,
         ) -> Option<<ResponseLazy as Iterator>::Item> {
@@ -508,7 +740,7 @@ macro_rules! define_read_methods {
 
         $($async)? fn $read_trailers<$($arg: $($argty +)*),*>(
             bytes: &mut $stream_type,
-            headers: &mut BTreeMap<String, String>,
+            headers: &mut HeaderMap,
             mut max_headers_size: Option<usize>,
         ) -> Result<(), Error> {
             loop {
@@ -517,7 +749,7 @@ macro_rules! define_read_methods {
                     *max_headers_size -= trailer_line.len() + 2;
                 }
                 if let Some((header, value)) = parse_header(trailer_line) {
-                    headers.insert(header, value);
+                    headers.append(header, value);
                 } else {
                     break;
                 }
@@ -527,7 +759,7 @@ macro_rules! define_read_methods {
 
         $($async)? fn $read_chunked<$($arg: $($argty +)*),*>(
             bytes: &mut $stream_type,
-            headers: &mut BTreeMap<String, String>,
+            headers: &mut HeaderMap,
             expecting_more_chunks: &mut bool,
             chunk_length: &mut usize,
             content_length: &mut usize,
@@ -571,7 +803,7 @@ macro_rules! define_read_methods {
                     }
 
                     *expecting_more_chunks = false;
-                    headers.insert("content-length".to_string(), (*content_length).to_string());
+                    headers.insert("content-length", (*content_length).to_string());
                     headers.remove("transfer-encoding");
                     return None;
                 }
@@ -607,7 +839,7 @@ macro_rules! define_read_methods {
             None
         }
 
-        #[cfg(feature = "std")]
+        $(#[$read_metadata_attr])*
         $($async)? fn $read_metadata<$($arg: $($argty +)*),*>(
             stream: &mut $stream_type,
             mut max_headers_size: Option<usize>,
@@ -616,7 +848,7 @@ macro_rules! define_read_methods {
             let line = maybe_await!($read_line(stream, max_status_line_len, Error::StatusLineOverflow), $($await)?)?;
             let (status_code, reason_phrase) = parse_status_line(&line);
 
-            let mut headers = BTreeMap::new();
+            let mut headers = HeaderMap::new();
             loop {
                 let line = maybe_await!($read_line(stream, max_headers_size, Error::HeadersOverflow), $($await)?)?;
                 if line.is_empty() {
@@ -626,14 +858,14 @@ macro_rules! define_read_methods {
                 if let Some(ref mut max_headers_size) = max_headers_size {
                     *max_headers_size -= line.len() + 2;
                 }
-                if let Some(header) = parse_header(line) {
-                    headers.insert(header.0, header.1);
+                if let Some((header, value)) = parse_header(line) {
+                    headers.append(header, value);
                 }
             }
 
             let mut chunked = false;
             let mut content_length = None;
-            for (header, value) in &headers {
+            for (header, value) in headers.iter() {
                 // Handle the Transfer-Encoding header
                 if header.to_lowercase().trim() == "transfer-encoding"
                     && value.to_lowercase().trim() == "chunked"
@@ -700,11 +932,14 @@ macro_rules! define_read_methods {
 }
 
 #[cfg(feature = "std")]
-define_read_methods!((read_until_closed, read_with_content_length, read_trailers, read_chunked, read_metadata, read_line)<>, HttpStreamBytes);
-#[cfg(feature = "async")]
+define_read_methods!((read_until_closed, read_with_content_length, read_trailers, read_chunked, #[cfg(not(all(feature = "wasm", target_family = "wasm")))] read_metadata, read_line)<>, HttpStreamBytes);
+#[cfg(feature = "async-core")]
 define_read_methods!((read_until_closed_async, read_with_content_length_async, read_trailers_async, read_chunked_async, read_metadata_async, read_line_async)<R: AsyncRead | Unpin>, R, async, await);
 
-#[cfg(feature = "std")]
+#[cfg(all(
+    feature = "std",
+    any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+))]
 fn parse_status_line(line: &str) -> (i32, String) {
     // sample status line format
     // HTTP/1.1 200 OK
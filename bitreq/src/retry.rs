@@ -0,0 +1,311 @@
+//! Automatic retries.
+//!
+//! Requests are sent once by default. A [`RetryPolicy`] can be set with
+//! [`Request::with_retry_policy`](crate::Request::with_retry_policy) or
+//! [`Client::with_retry_policy`](crate::Client::with_retry_policy) to retry
+//! requests that failed with a connection error or were answered with a
+//! `429 Too Many Requests` or `503 Service Unavailable` response.
+
+use core::time::Duration;
+#[cfg(feature = "async-core")]
+use std::future::Future;
+use std::io;
+use std::time::SystemTime;
+
+#[cfg(feature = "async-core")]
+use crate::runtime::RuntimeHandle;
+use crate::{Error, HeaderMap, Method, Request, Response, ResponseLazy};
+
+/// Decides which failed requests are retried, and how long to wait before
+/// each retry.
+///
+/// Between attempts the policy waits for an exponentially increasing delay
+/// (the initial backoff, doubled on each retry, capped at the maximum
+/// backoff), unless the response has a `Retry-After` header, in which case
+/// that delay is used instead.
+///
+/// By default only requests with an idempotent method (`GET`, `HEAD`,
+/// `PUT`, `DELETE`, `OPTIONS` and `TRACE`) are retried, as other requests
+/// may have taken effect on the server even if the response was an error.
+///
+/// The request's [timeout](crate::Request::with_timeout) applies to each
+/// attempt separately.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let policy = bitreq::RetryPolicy::new(3)
+///     .with_backoff(Duration::from_millis(200), Duration::from_secs(5));
+/// let request = bitreq::get("http://example.com").with_retry_policy(policy);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_retry_after: Duration,
+    status_codes: Vec<i32>,
+    retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying a failed request up to `max_retries`
+    /// times, so the request is sent at most `max_retries + 1` times.
+    ///
+    /// The backoff starts at 500 milliseconds and is capped at 30 seconds.
+    pub fn new(max_retries: usize) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(60),
+            status_codes: vec![429, 503],
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// Sets the delay before the first retry, and the maximum delay
+    /// between retries.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the longest `Retry-After` delay the policy will wait for. If
+    /// a response asks for a longer delay, it is returned instead of being
+    /// retried. 60 seconds by default.
+    pub fn with_max_retry_after(mut self, max_retry_after: Duration) -> RetryPolicy {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Sets the response status codes which are retried. `429` and `503`
+    /// by default.
+    pub fn with_status_codes<I: IntoIterator<Item = i32>>(
+        mut self,
+        status_codes: I,
+    ) -> RetryPolicy {
+        self.status_codes = status_codes.into_iter().collect();
+        self
+    }
+
+    /// Also retries requests with a non-idempotent method, such as `POST`.
+    ///
+    /// Only enable this if the server handles duplicate requests safely,
+    /// eg. JSON-RPC calls that don't change any state.
+    pub fn with_non_idempotent_retries(mut self) -> RetryPolicy {
+        self.retry_non_idempotent = true;
+        self
+    }
+
+    /// Returns how long to wait before retrying a request which got
+    /// `outcome` on its `attempt`th try, or `None` if it should not be
+    /// retried.
+    fn retry_delay(
+        &self,
+        method: &Method,
+        attempt: usize,
+        outcome: Result<(i32, &HeaderMap), &Error>,
+    ) -> Option<Duration> {
+        if attempt > self.max_retries || !(self.retry_non_idempotent || is_idempotent(method)) {
+            return None;
+        }
+        match outcome {
+            Ok((status_code, headers)) if self.status_codes.contains(&status_code) =>
+                match headers.retry_after() {
+                    Some(retry_after) => {
+                        let delay = retry_after.delay_from(SystemTime::now());
+                        Some(delay).filter(|delay| *delay <= self.max_retry_after)
+                    }
+                    None => Some(self.backoff(attempt)),
+                },
+            Ok(_) => None,
+            Err(err) if is_retryable_error(err) => Some(self.backoff(attempt)),
+            Err(_) => None,
+        }
+    }
+
+    fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1) as u32).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        method,
+        Method::Get | Method::Head | Method::Put | Method::Delete | Method::Options | Method::Trace
+    )
+}
+
+/// Returns true for errors where the server likely never processed the
+/// request, or the connection broke before the response was read.
+fn is_retryable_error(err: &Error) -> bool {
+    match err {
+        Error::IoError(err) => matches!(
+            err.kind(),
+            io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::UnexpectedEof
+        ),
+        _ => false,
+    }
+}
+
+/// A response which reports how many attempts it took.
+pub(crate) trait Attempted {
+    fn status_code(&self) -> i32;
+    fn headers(&self) -> &HeaderMap;
+    fn set_attempts(&mut self, attempts: usize);
+}
+
+impl Attempted for Response {
+    fn status_code(&self) -> i32 { self.status_code }
+    fn headers(&self) -> &HeaderMap { &self.headers }
+    fn set_attempts(&mut self, attempts: usize) { self.attempts = attempts; }
+}
+
+impl Attempted for ResponseLazy {
+    fn status_code(&self) -> i32 { self.status_code }
+    fn headers(&self) -> &HeaderMap { &self.headers }
+    fn set_attempts(&mut self, attempts: usize) { self.attempts = attempts; }
+}
+
+#[cfg(feature = "async-core")]
+impl Attempted for crate::AsyncResponseLazy {
+    fn status_code(&self) -> i32 { self.status_code }
+    fn headers(&self) -> &HeaderMap { &self.headers }
+    fn set_attempts(&mut self, attempts: usize) { self.attempts = attempts; }
+}
+
+fn outcome<R: Attempted>(result: &Result<R, Error>) -> Result<(i32, &HeaderMap), &Error> {
+    result.as_ref().map(|response| (response.status_code(), response.headers()))
+}
+
+/// Sends `request` with `send`, retrying as configured by its
+/// [`RetryPolicy`].
+pub(crate) fn send<R, F>(request: Request, mut send: F) -> Result<R, Error>
+where
+    R: Attempted,
+    F: FnMut(Request) -> Result<R, Error>,
+{
+    let policy = match &request.retry_policy {
+        Some(policy) => policy.clone(),
+        None => return send(request),
+    };
+    let mut attempt = 1;
+    loop {
+        if attempt > policy.max_retries {
+            return with_attempts(send(request), attempt);
+        }
+        let result = send(request.clone());
+        match policy.retry_delay(&request.method, attempt, outcome(&result)) {
+            Some(delay) => {
+                #[cfg(feature = "log")]
+                log::debug!("Retrying request in {:?} (attempt {})", delay, attempt);
+                drop(result);
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            None => return with_attempts(result, attempt),
+        }
+    }
+}
+
+/// Async version of [`send`].
+#[cfg(feature = "async-core")]
+pub(crate) async fn send_async<R, F, Fut>(request: Request, mut send: F) -> Result<R, Error>
+where
+    R: Attempted,
+    F: FnMut(Request) -> Fut,
+    Fut: Future<Output = Result<R, Error>>,
+{
+    let policy = match &request.retry_policy {
+        Some(policy) => policy.clone(),
+        None => return send(request).await,
+    };
+    let mut attempt = 1;
+    loop {
+        if attempt > policy.max_retries {
+            return with_attempts(send(request).await, attempt);
+        }
+        let result = send(request.clone()).await;
+        match policy.retry_delay(&request.method, attempt, outcome(&result)) {
+            Some(delay) => {
+                #[cfg(feature = "log")]
+                log::debug!("Retrying request in {:?} (attempt {})", delay, attempt);
+                drop(result);
+                RuntimeHandle::or_default(request.runtime.as_ref())?.0.sleep(delay).await;
+                attempt += 1;
+            }
+            None => return with_attempts(result, attempt),
+        }
+    }
+}
+
+fn with_attempts<R: Attempted>(result: Result<R, Error>, attempts: usize) -> Result<R, Error> {
+    result.map(|mut response| {
+        response.set_attempts(attempts);
+        response
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::io;
+
+    use super::RetryPolicy;
+    use crate::{Error, HeaderMap, Method};
+
+    #[test]
+    fn exponential_backoff() {
+        let policy =
+            RetryPolicy::new(10).with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+        let headers = HeaderMap::new();
+        let delays: Vec<_> = (1..=6)
+            .map(|attempt| policy.retry_delay(&Method::Get, attempt, Ok((503, &headers))).unwrap())
+            .collect();
+        let expected = [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis);
+        assert_eq!(delays, expected);
+        assert_eq!(policy.backoff(usize::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn retry_decisions() {
+        let policy = RetryPolicy::new(2);
+        let headers = HeaderMap::new();
+        assert!(policy.retry_delay(&Method::Get, 1, Ok((429, &headers))).is_some());
+        assert!(policy.retry_delay(&Method::Get, 2, Ok((503, &headers))).is_some());
+        assert!(policy.retry_delay(&Method::Get, 3, Ok((503, &headers))).is_none());
+        assert!(policy.retry_delay(&Method::Get, 1, Ok((500, &headers))).is_none());
+        assert!(policy.retry_delay(&Method::Post, 1, Ok((503, &headers))).is_none());
+        let policy = policy.with_non_idempotent_retries();
+        assert!(policy.retry_delay(&Method::Post, 1, Ok((503, &headers))).is_some());
+
+        let reset = Error::IoError(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert!(policy.retry_delay(&Method::Get, 1, Err(&reset)).is_some());
+        assert!(policy.retry_delay(&Method::Get, 1, Err(&Error::AddressNotFound)).is_none());
+    }
+
+    #[test]
+    fn retry_after_header() {
+        let policy = RetryPolicy::new(1).with_max_retry_after(Duration::from_secs(10));
+        let headers: HeaderMap = [("Retry-After", "3")].into_iter().collect();
+        let delay = policy.retry_delay(&Method::Get, 1, Ok((429, &headers)));
+        assert_eq!(delay, Some(Duration::from_secs(3)));
+
+        let headers: HeaderMap = [("Retry-After", "11")].into_iter().collect();
+        assert_eq!(policy.retry_delay(&Method::Get, 1, Ok((429, &headers))), None);
+
+        let past: HeaderMap =
+            [("Retry-After", "Sun, 06 Nov 1994 08:49:37 GMT")].into_iter().collect();
+        let delay = policy.retry_delay(&Method::Get, 1, Ok((503, &past)));
+        assert_eq!(delay, Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,331 @@
+//! Async runtimes.
+//!
+//! Async requests are not tied to a particular executor: the few
+//! operations which need one (opening connections, timers, and running
+//! blocking host lookups) go through a [`Runtime`]. With the `async`
+//! feature, [`TokioRuntime`] is used unless another runtime is set with
+//! [`Request::with_runtime`](crate::Request::with_runtime) or
+//! [`Client::with_runtime`](crate::Client::with_runtime). With only the
+//! `async-core` feature, every async request needs a runtime set.
+
+use alloc::sync::Arc;
+use core::fmt;
+use core::future::{poll_fn, Future};
+use core::hash::{Hash, Hasher};
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll};
+use core::time::Duration;
+use std::io;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(feature = "async")]
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::connection::timeout_err;
+use crate::Error;
+
+/// The futures returned by a [`Runtime`].
+pub type RuntimeFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A byte stream opened by a [`Runtime`].
+///
+/// This mirrors the read and write traits of the async ecosystem, so
+/// streams of any runtime can be wrapped in a type implementing it by
+/// forwarding each method.
+pub trait AsyncIo: Send + Unpin {
+    /// Attempts to read bytes into `buf`, returning how many were read, 0
+    /// meaning the end of the stream.
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+
+    /// Attempts to write bytes from `buf`, returning how many were written.
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+
+    /// Attempts to flush buffered writes.
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+
+    /// Attempts to shut down the write side of the stream.
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/// The executor-specific operations async requests need.
+///
+/// # Example
+///
+/// A runtime for [smol](https://docs.rs/smol), wrapping its streams to
+/// implement [`AsyncIo`]:
+///
+/// ```ignore
+/// use std::io;
+/// use std::net::SocketAddr;
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll};
+/// use std::time::Duration;
+///
+/// use bitreq::{AsyncIo, RuntimeFuture};
+/// use futures_lite::{AsyncRead, AsyncWrite};
+///
+/// struct Smol;
+///
+/// struct SmolIo(smol::net::TcpStream);
+///
+/// impl AsyncIo for SmolIo {
+///     fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+///         Pin::new(&mut self.0).poll_read(cx, buf)
+///     }
+///     fn poll_write(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+///         Pin::new(&mut self.0).poll_write(cx, buf)
+///     }
+///     fn poll_flush(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+///         Pin::new(&mut self.0).poll_flush(cx)
+///     }
+///     fn poll_close(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+///         Pin::new(&mut self.0).poll_close(cx)
+///     }
+/// }
+///
+/// impl bitreq::Runtime for Smol {
+///     fn connect(&self, addr: SocketAddr) -> RuntimeFuture<'_, io::Result<Box<dyn AsyncIo>>> {
+///         Box::pin(async move {
+///             let stream: Box<dyn AsyncIo> = Box::new(SmolIo(smol::net::TcpStream::connect(addr).await?));
+///             Ok(stream)
+///         })
+///     }
+///
+///     fn sleep(&self, duration: Duration) -> RuntimeFuture<'static, ()> {
+///         Box::pin(async move {
+///             smol::Timer::after(duration).await;
+///         })
+///     }
+///
+///     fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send>) { smol::unblock(f).detach() }
+/// }
+///
+/// let response = smol::block_on(bitreq::get("http://127.0.0.1:8332").with_runtime(Smol).send_async());
+/// ```
+pub trait Runtime: Send + Sync {
+    /// Opens a TCP connection to `addr`.
+    fn connect(&self, addr: SocketAddr) -> RuntimeFuture<'_, io::Result<Box<dyn AsyncIo>>>;
+
+    /// Connects to the Unix domain socket at `path`, for `http+unix://`
+    /// URLs.
+    ///
+    /// The default implementation fails with
+    /// [`Unsupported`](io::ErrorKind::Unsupported).
+    #[cfg(unix)]
+    fn connect_unix<'a>(
+        &'a self,
+        _path: &'a Path,
+    ) -> RuntimeFuture<'a, io::Result<Box<dyn AsyncIo>>> {
+        Box::pin(async {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "this runtime does not support Unix domain sockets",
+            ))
+        })
+    }
+
+    /// Returns a future completing after `duration`.
+    fn sleep(&self, duration: Duration) -> RuntimeFuture<'static, ()>;
+
+    /// Runs `f`, which may block, without blocking the executor. This is
+    /// used to resolve host names.
+    ///
+    /// The default implementation runs `f` on a new thread.
+    fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send>) { std::thread::spawn(f); }
+}
+
+/// The [`Runtime`] for [tokio](https://docs.rs/tokio), used by default with
+/// the `async` feature.
+///
+/// Requests using it must be sent from within a tokio runtime.
+#[cfg(feature = "async")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioRuntime;
+
+#[cfg(feature = "async")]
+impl Runtime for TokioRuntime {
+    fn connect(&self, addr: SocketAddr) -> RuntimeFuture<'_, io::Result<Box<dyn AsyncIo>>> {
+        Box::pin(async move {
+            let stream: Box<dyn AsyncIo> =
+                Box::new(TokioIo(tokio::net::TcpStream::connect(addr).await?));
+            Ok(stream)
+        })
+    }
+
+    #[cfg(unix)]
+    fn connect_unix<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> RuntimeFuture<'a, io::Result<Box<dyn AsyncIo>>> {
+        Box::pin(async move {
+            let stream: Box<dyn AsyncIo> =
+                Box::new(TokioIo(tokio::net::UnixStream::connect(path).await?));
+            Ok(stream)
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> RuntimeFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send>) { tokio::task::spawn_blocking(f); }
+}
+
+/// Implements [`AsyncIo`] for tokio's streams.
+#[cfg(feature = "async")]
+struct TokioIo<T>(T);
+
+#[cfg(feature = "async")]
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncIo for TokioIo<T> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut buf = ReadBuf::new(buf);
+        Pin::new(&mut self.0).poll_read(cx, &mut buf).map_ok(|()| buf.filled().len())
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+/// A stream opened by a [`Runtime`], implementing the IO traits the rest
+/// of bitreq (and TLS) is built on.
+pub(crate) struct IoStream(pub(crate) Box<dyn AsyncIo>);
+
+impl AsyncRead for IoStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let read = match self.0.poll_read(cx, buf.initialize_unfilled()) {
+            Poll::Ready(Ok(read)) => read,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for IoStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.0.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.0.poll_close(cx)
+    }
+}
+
+/// A shared [`Runtime`] stored in a [`Request`](crate::Request).
+///
+/// Two handles are only equal if they point to the same runtime, so
+/// requests with different runtimes never share a pooled connection.
+#[derive(Clone)]
+pub(crate) struct RuntimeHandle(pub(crate) Arc<dyn Runtime>);
+
+impl RuntimeHandle {
+    fn addr(&self) -> *const () { Arc::as_ptr(&self.0) as *const () }
+
+    /// Returns the runtime set on a request, or the default one.
+    pub(crate) fn or_default(runtime: Option<&RuntimeHandle>) -> Result<RuntimeHandle, Error> {
+        if let Some(runtime) = runtime {
+            return Ok(runtime.clone());
+        }
+        #[cfg(feature = "async")]
+        {
+            static TOKIO: OnceLock<RuntimeHandle> = OnceLock::new();
+            Ok(TOKIO.get_or_init(|| RuntimeHandle(Arc::new(TokioRuntime))).clone())
+        }
+        #[cfg(not(feature = "async"))]
+        Err(Error::IoError(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "async requests need a runtime without the async feature",
+        )))
+    }
+
+    /// Awaits `future`, failing with a timeout error once `timeout_at` is
+    /// reached.
+    pub(crate) async fn timeout_at<F: Future>(
+        &self,
+        timeout_at: Option<Instant>,
+        future: F,
+    ) -> Result<F::Output, Error> {
+        let timeout_at = match timeout_at {
+            Some(timeout_at) => timeout_at,
+            None => return Ok(future.await),
+        };
+        let mut sleep = self.0.sleep(timeout_at.saturating_duration_since(Instant::now()));
+        let mut future = pin!(future);
+        poll_fn(|cx| {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+            sleep.as_mut().poll(cx).map(|()| Err(Error::IoError(timeout_err())))
+        })
+        .await
+    }
+
+    /// Runs `f` with [`Runtime::spawn_blocking`], returning its result.
+    pub(crate) async fn spawn_blocking<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.0.spawn_blocking(Box::new(move || {
+            let _ = sender.send(f());
+        }));
+        receiver.await.map_err(|_| Error::Other("blocking task panicked"))
+    }
+}
+
+impl fmt::Debug for RuntimeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RuntimeHandle").field(&self.addr()).finish()
+    }
+}
+
+impl PartialEq for RuntimeHandle {
+    fn eq(&self, other: &Self) -> bool { self.addr() == other.addr() }
+}
+
+impl Eq for RuntimeHandle {}
+
+impl Hash for RuntimeHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.addr().hash(state) }
+}
+
+/// Yields to the executor once, letting other tasks make progress.
+pub(crate) async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
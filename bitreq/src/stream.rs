@@ -0,0 +1,349 @@
+//! Line, NDJSON and Server-Sent Events readers for streamed responses.
+//!
+//! These read the body of a [`ResponseLazy`] (or an
+//! [`AsyncResponseLazy`](crate::AsyncResponseLazy)) as it arrives, so they
+//! go through the same transfer decoding and
+//! [body size limit](crate::Request::with_max_body_size) as reading it byte
+//! by byte. Note that the body size limit applies to the whole stream, so
+//! it may need to be raised (or disabled) for long-lived streams.
+
+#[cfg(feature = "json-using-serde")]
+use core::marker::PhantomData;
+use core::time::Duration;
+
+#[cfg(feature = "async-core")]
+use crate::AsyncResponseLazy;
+use crate::{Error, ResponseLazy};
+
+/// A Server-Sent Event, as defined by the
+/// [HTML Standard](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The event type, `"message"` unless the server set an `event` field.
+    pub event: String,
+    /// The data of the event. Multiple `data` fields are joined with `\n`.
+    pub data: String,
+    /// The last event ID set by the server, which can be sent back in a
+    /// `Last-Event-ID` header when reconnecting.
+    pub id: Option<String>,
+    /// The reconnection time most recently requested by the server with
+    /// a `retry` field.
+    pub retry: Option<Duration>,
+}
+
+/// Reads the lines of a response body.
+///
+/// Lines are separated by `\n` or `\r\n`, which are not included in the
+/// returned lines. A final line without a line ending is returned too.
+///
+/// Created by [`ResponseLazy::lines`] and
+/// [`AsyncResponseLazy::lines`](crate::AsyncResponseLazy::lines).
+pub struct Lines<R> {
+    response: R,
+    line: Vec<u8>,
+    done: bool,
+}
+
+impl<R> Lines<R> {
+    fn new(response: R) -> Lines<R> { Lines { response, line: Vec::new(), done: false } }
+
+    /// Returns the response the lines are read from.
+    pub fn into_inner(self) -> R { self.response }
+
+    /// Handles the next byte of the body, returning a line if it is
+    /// complete.
+    fn push(&mut self, byte: Option<Result<(u8, usize), Error>>) -> Option<Result<String, Error>> {
+        match byte {
+            Some(Ok((b'\n', _))) => {
+                if self.line.last() == Some(&b'\r') {
+                    self.line.pop();
+                }
+                Some(self.take_line())
+            }
+            Some(Ok((byte, _))) => {
+                self.line.push(byte);
+                None
+            }
+            Some(Err(err)) => {
+                self.done = true;
+                Some(Err(err))
+            }
+            None => {
+                self.done = true;
+                if self.line.is_empty() {
+                    None
+                } else {
+                    Some(self.take_line())
+                }
+            }
+        }
+    }
+
+    fn take_line(&mut self) -> Result<String, Error> {
+        String::from_utf8(core::mem::take(&mut self.line))
+            .map_err(|err| Error::InvalidUtf8InBody(err.utf8_error()))
+    }
+}
+
+impl Iterator for Lines<ResponseLazy> {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let byte = self.response.next();
+            let eof = byte.is_none();
+            if let Some(line) = self.push(byte) {
+                return Some(line);
+            }
+            if eof {
+                break;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "async-core")]
+impl Lines<AsyncResponseLazy> {
+    /// Reads the next line, or returns `None` at the end of the body.
+    pub async fn next_line(&mut self) -> Option<Result<String, Error>> {
+        while !self.done {
+            let byte = self.response.next_byte().await;
+            let eof = byte.is_none();
+            if let Some(line) = self.push(byte) {
+                return Some(line);
+            }
+            if eof {
+                break;
+            }
+        }
+        None
+    }
+}
+
+/// Reads newline-delimited JSON values ([NDJSON](https://github.com/ndjson/ndjson-spec))
+/// from a response body. Blank lines are skipped.
+///
+/// Created by [`ResponseLazy::json_lines`] and
+/// [`AsyncResponseLazy::json_lines`](crate::AsyncResponseLazy::json_lines).
+#[cfg(feature = "json-using-serde")]
+pub struct JsonLines<R, T> {
+    lines: Lines<R>,
+    _value: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "json-using-serde")]
+impl<R, T: serde::de::DeserializeOwned> JsonLines<R, T> {
+    fn parse(line: Result<String, Error>) -> Option<Result<T, Error>> {
+        match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(serde_json::from_str(&line).map_err(Error::SerdeJsonError)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(feature = "json-using-serde")]
+impl<T: serde::de::DeserializeOwned> Iterator for JsonLines<ResponseLazy, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> { self.lines.by_ref().find_map(Self::parse) }
+}
+
+#[cfg(all(feature = "json-using-serde", feature = "async-core"))]
+impl<T: serde::de::DeserializeOwned> JsonLines<AsyncResponseLazy, T> {
+    /// Reads the next value, or returns `None` at the end of the body.
+    pub async fn next_value(&mut self) -> Option<Result<T, Error>> {
+        while let Some(line) = self.lines.next_line().await {
+            if let Some(value) = Self::parse(line) {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+/// Reads [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html)
+/// from a `text/event-stream` response body.
+///
+/// Created by [`ResponseLazy::events`] and
+/// [`AsyncResponseLazy::events`](crate::AsyncResponseLazy::events).
+pub struct Events<R> {
+    lines: Lines<R>,
+    parser: EventParser,
+}
+
+impl Iterator for Events<ResponseLazy> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in self.lines.by_ref() {
+            match line {
+                Ok(line) =>
+                    if let Some(event) = self.parser.feed(&line) {
+                        return Some(Ok(event));
+                    },
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "async-core")]
+impl Events<AsyncResponseLazy> {
+    /// Reads the next event, or returns `None` at the end of the body.
+    pub async fn next_event(&mut self) -> Option<Result<Event, Error>> {
+        while let Some(line) = self.lines.next_line().await {
+            match line {
+                Ok(line) =>
+                    if let Some(event) = self.parser.feed(&line) {
+                        return Some(Ok(event));
+                    },
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+/// The event stream interpretation state machine, fed one line at a time.
+#[derive(Default)]
+struct EventParser {
+    started: bool,
+    event: String,
+    data: String,
+    has_data: bool,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl EventParser {
+    fn feed(&mut self, mut line: &str) -> Option<Event> {
+        if !self.started {
+            self.started = true;
+            line = line.strip_prefix('\u{feff}').unwrap_or(line);
+        }
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "" => {} // A comment
+            "event" => self.event = value.to_string(),
+            "data" => {
+                if self.has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) =>
+                self.retry = value.parse().ok().map(Duration::from_millis),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = core::mem::take(&mut self.event);
+        if !core::mem::take(&mut self.has_data) {
+            return None;
+        }
+        Some(Event {
+            event: if event.is_empty() { "message".to_string() } else { event },
+            data: core::mem::take(&mut self.data),
+            id: self.last_event_id.clone(),
+            retry: self.retry,
+        })
+    }
+}
+
+macro_rules! reader_methods {
+    () => {
+        /// Reads the body line by line, see [`Lines`].
+        pub fn lines(self) -> Lines<Self> { Lines::new(self) }
+
+        /// Reads the body as newline-delimited JSON values, see
+        /// [`JsonLines`].
+        #[cfg(feature = "json-using-serde")]
+        pub fn json_lines<T: serde::de::DeserializeOwned>(self) -> JsonLines<Self, T> {
+            JsonLines { lines: Lines::new(self), _value: PhantomData }
+        }
+
+        /// Reads the body as a stream of Server-Sent Events, see
+        /// [`Events`].
+        pub fn events(self) -> Events<Self> {
+            Events { lines: Lines::new(self), parser: EventParser::default() }
+        }
+    };
+}
+
+impl ResponseLazy {
+    reader_methods!();
+}
+
+#[cfg(feature = "async-core")]
+impl AsyncResponseLazy {
+    reader_methods!();
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::{Event, EventParser, Lines};
+
+    fn parse_events(stream: &str) -> Vec<Event> {
+        let mut parser = EventParser::default();
+        stream.split('\n').filter_map(|line| parser.feed(line)).collect()
+    }
+
+    #[test]
+    fn event_stream() {
+        let events = parse_events(
+            "\u{feff}: comment\ndata: first\ndata:second\n\nevent: block\nid: 7\nretry: 1500\ndata\n\n",
+        );
+        assert_eq!(
+            events,
+            [
+                Event {
+                    event: "message".to_string(),
+                    data: "first\nsecond".to_string(),
+                    id: None,
+                    retry: None,
+                },
+                Event {
+                    event: "block".to_string(),
+                    data: String::new(),
+                    id: Some("7".to_string()),
+                    retry: Some(Duration::from_millis(1500)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn event_without_data_is_not_dispatched() {
+        let events = parse_events("event: ping\n\nid: 1\ndata: x\n\ndata: unterminated");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "message");
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn line_endings() {
+        let mut lines = Lines::new(());
+        let mut out = Vec::new();
+        for byte in b"a\r\nb\n\nc".iter() {
+            out.extend(lines.push(Some(Ok((*byte, 1)))));
+        }
+        out.extend(lines.push(None));
+        let out: Vec<_> = out.into_iter().map(Result::unwrap).collect();
+        assert_eq!(out, ["a", "b", "", "c"]);
+    }
+}
@@ -0,0 +1,310 @@
+//! Wire-level tracing of requests and responses.
+//!
+//! A [`Tracer`] set with [`Request::with_tracer`](crate::Request::with_tracer)
+//! or [`Client::with_tracer`](crate::Client::with_tracer) is passed the bytes
+//! of each request as they are written to the connection, and the bytes of
+//! its response as they are read from it, before any parsing. This is meant
+//! for debugging servers which respond in unexpected ways.
+//!
+//! The values of the `Authorization`, `Proxy-Authorization`, `Cookie` and
+//! `Set-Cookie` headers are replaced with `[redacted]`, everything else is
+//! passed on as is. Responses are traced as they are read, so the body of a
+//! [`ResponseLazy`](crate::ResponseLazy) is only traced as far as it is read.
+
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(feature = "async-core")]
+use core::pin::Pin;
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "async-core")]
+use core::task::{ready, Context, Poll};
+use std::io::{self, Read};
+
+#[cfg(feature = "async-core")]
+use tokio::io::{AsyncRead, ReadBuf};
+
+#[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+use crate::request::ParsedRequest;
+
+/// The headers whose values are not traced.
+const REDACTED_HEADERS: [&str; 4] =
+    ["authorization", "proxy-authorization", "cookie", "set-cookie"];
+
+/// Receives the bytes of traced requests and responses.
+///
+/// Implemented for closures taking a [`TraceEvent`].
+///
+/// # Example
+///
+/// ```
+/// use bitreq::TraceEvent;
+///
+/// let request = bitreq::get("http://127.0.0.1:8332").with_tracer(|event: &TraceEvent| {
+///     eprintln!("#{} {:?}: {}", event.id(), event.kind(), String::from_utf8_lossy(event.data()));
+/// });
+/// ```
+pub trait Tracer: Send + Sync {
+    /// Records bytes written to or read from a connection.
+    ///
+    /// This is called while the request is being sent, so it should not
+    /// block for long.
+    fn record(&self, event: &TraceEvent<'_>);
+}
+
+impl<F> Tracer for F
+where
+    F: Fn(&TraceEvent<'_>) + Send + Sync,
+{
+    fn record(&self, event: &TraceEvent<'_>) { self(event) }
+}
+
+/// The part of an exchange a [`TraceEvent`] holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TraceKind {
+    /// The request line and headers, including the blank line ending
+    /// them.
+    RequestHead,
+    /// The request body.
+    RequestBody,
+    /// The status line and headers of the response, including the blank
+    /// line ending them. If the connection fails before the end of the
+    /// headers, this holds the bytes read until then.
+    ResponseHead,
+    /// Bytes of the response body as they were read from the connection,
+    /// so still chunk-encoded if the response is. The body of a response
+    /// may be split across any number of events.
+    ResponseBody,
+}
+
+/// Bytes written to or read from a connection, passed to a [`Tracer`].
+#[derive(Debug)]
+pub struct TraceEvent<'a> {
+    id: u64,
+    url: &'a str,
+    kind: TraceKind,
+    data: &'a [u8],
+}
+
+impl<'a> TraceEvent<'a> {
+    /// Identifies the exchange, which is every event for one request and
+    /// its response. Requests sent again on redirects or retries are
+    /// separate exchanges.
+    pub fn id(&self) -> u64 { self.id }
+
+    /// The URL of the request, without credentials or fragment.
+    pub fn url(&self) -> &'a str { self.url }
+
+    /// What part of the exchange [`data`](Self::data) is.
+    pub fn kind(&self) -> TraceKind { self.kind }
+
+    /// The traced bytes, with sensitive header values redacted.
+    pub fn data(&self) -> &'a [u8] { self.data }
+}
+
+/// A shared [`Tracer`] stored in a [`Request`](crate::Request).
+#[derive(Clone)]
+pub(crate) struct TracerHandle(pub(crate) Arc<dyn Tracer>);
+
+impl TracerHandle {
+    fn addr(&self) -> *const () { Arc::as_ptr(&self.0) as *const () }
+}
+
+impl fmt::Debug for TracerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TracerHandle").field(&self.addr()).finish()
+    }
+}
+
+impl PartialEq for TracerHandle {
+    fn eq(&self, other: &Self) -> bool { self.addr() == other.addr() }
+}
+
+impl Eq for TracerHandle {}
+
+/// The trace of one exchange.
+pub(crate) struct WireTrace {
+    tracer: TracerHandle,
+    id: u64,
+    url: String,
+}
+
+impl WireTrace {
+    /// Records `request` if it has a tracer, returning the trace its
+    /// response should be recorded to.
+    #[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+    pub(crate) fn start(request: &ParsedRequest) -> Option<WireTrace> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let tracer = request.config.tracer.clone()?;
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let trace = WireTrace { tracer, id, url: request.url.redacted() };
+        trace.record(TraceKind::RequestHead, &redact_head(request.get_http_head().as_bytes()));
+        match request.config.body() {
+            Some(body) if !body.is_empty() => trace.record(TraceKind::RequestBody, body),
+            _ => {}
+        }
+        Some(trace)
+    }
+
+    fn record(&self, kind: TraceKind, data: &[u8]) {
+        let event = TraceEvent { id: self.id, url: &self.url, kind, data };
+        self.tracer.0.record(&event);
+    }
+}
+
+/// Records the bytes read from a response stream to a [`WireTrace`].
+///
+/// This must wrap the stream below any buffering, so that only the bytes
+/// actually read from the connection are traced.
+pub(crate) struct TraceReader<R> {
+    inner: R,
+    trace: Option<WireTrace>,
+    /// The response head read so far, until it is complete.
+    head: Option<Vec<u8>>,
+}
+
+impl<R> TraceReader<R> {
+    pub(crate) fn new(inner: R, trace: Option<WireTrace>) -> TraceReader<R> {
+        TraceReader { inner, trace, head: Some(Vec::new()) }
+    }
+
+    fn record(&mut self, data: &[u8]) {
+        let TraceReader { trace: Some(trace), head, .. } = self else { return };
+        let body = match head {
+            Some(buffered) => {
+                let from = buffered.len();
+                buffered.extend_from_slice(data);
+                let Some(end) = head_end(buffered, from) else { return };
+                trace.record(TraceKind::ResponseHead, &redact_head(&buffered[..end]));
+                *head = None;
+                &data[end - from..]
+            }
+            None => data,
+        };
+        if !body.is_empty() {
+            trace.record(TraceKind::ResponseBody, body);
+        }
+    }
+}
+
+impl<R> Drop for TraceReader<R> {
+    fn drop(&mut self) {
+        if let (Some(trace), Some(head)) = (&self.trace, &self.head) {
+            if !head.is_empty() {
+                trace.record(TraceKind::ResponseHead, &redact_head(head));
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for TraceReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.record(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async-core")]
+impl<R: AsyncRead + Unpin> AsyncRead for TraceReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        this.record(&buf.filled()[filled..]);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Returns the length of the message head in `buf` if the blank line
+/// ending it has been read, searching from `from`.
+fn head_end(buf: &[u8], from: usize) -> Option<usize> {
+    (from..buf.len())
+        .find(|&i| buf[i] == b'\n' && (buf[..i].ends_with(b"\n") || buf[..i].ends_with(b"\n\r")))
+        .map(|i| i + 1)
+}
+
+/// Replaces the values of the [`REDACTED_HEADERS`] in a message head.
+fn redact_head(head: &[u8]) -> Vec<u8> {
+    let mut redacted = Vec::with_capacity(head.len());
+    for line in head.split_inclusive(|&b| b == b'\n') {
+        let name = line.iter().position(|&b| b == b':').map(|colon| &line[..colon]);
+        match name {
+            Some(name)
+                if REDACTED_HEADERS.iter().any(|h| name.eq_ignore_ascii_case(h.as_bytes())) =>
+            {
+                redacted.extend_from_slice(name);
+                redacted.extend_from_slice(b": [redacted]");
+                let line_ending = line.iter().rev().take_while(|&&b| b == b'\r' || b == b'\n');
+                redacted.extend_from_slice(&line[line.len() - line_ending.count()..]);
+            }
+            _ => redacted.extend_from_slice(line),
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    use super::{head_end, redact_head, TraceKind, TraceReader, TracerHandle, WireTrace};
+    use crate::TraceEvent;
+
+    #[test]
+    fn redaction() {
+        let head = b"GET / HTTP/1.1\r\nHost: a\r\nauthorization: Basic dTpw\r\nCookie: a=b\r\n\r\n";
+        assert_eq!(
+            redact_head(head),
+            b"GET / HTTP/1.1\r\nHost: a\r\nauthorization: [redacted]\r\nCookie: [redacted]\r\n\r\n"
+        );
+        let head = b"HTTP/1.1 200 OK\nSet-Cookie: id=1; Secure\nX-Cookie: 1";
+        assert_eq!(redact_head(head), b"HTTP/1.1 200 OK\nSet-Cookie: [redacted]\nX-Cookie: 1");
+    }
+
+    #[test]
+    fn end_of_head() {
+        assert_eq!(head_end(b"HTTP/1.1 200 OK\r\nA: b\r\n\r\nbody", 0), Some(25));
+        assert_eq!(head_end(b"HTTP/1.1 200 OK\n\nbody", 0), Some(17));
+        assert_eq!(head_end(b"HTTP/1.1 200 OK\r\nA: b\r\n", 0), None);
+        assert_eq!(head_end(b"HTTP/1.1 200 OK\r\n\r\n", 18), Some(19));
+    }
+
+    #[test]
+    fn reader() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let tracer = TracerHandle(Arc::new(move |event: &TraceEvent| {
+            sink.lock().unwrap().push((event.kind(), event.data().to_vec()));
+        }));
+        let trace = WireTrace { tracer, id: 0, url: String::new() };
+
+        let response: &[u8] = b"HTTP/1.1 200 OK\r\nSet-Cookie: a\r\n\r\nbody";
+        let mut reader = TraceReader::new(response, Some(trace));
+        let mut buf = [0; 7];
+        while reader.read(&mut buf).unwrap() > 0 {}
+        drop(reader);
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            events[0],
+            (
+                TraceKind::ResponseHead,
+                b"HTTP/1.1 200 OK\r\nSet-Cookie: [redacted]\r\n\r\n".to_vec()
+            )
+        );
+        let body: Vec<u8> = events[1..]
+            .iter()
+            .inspect(|(kind, _)| assert_eq!(*kind, TraceKind::ResponseBody))
+            .flat_map(|(_, data)| data.clone())
+            .collect();
+        assert_eq!(body, b"body");
+    }
+}
@@ -5,7 +5,7 @@ use std::ops::Range;
 /// Returns the default port for known schemes, or `None` for unknown schemes.
 fn default_port_for_scheme(scheme: &str) -> Option<u16> {
     match scheme {
-        "http" | "ws" => Some(80),
+        "http" | "http+unix" | "ws" => Some(80),
         "https" | "wss" => Some(443),
         "ftp" => Some(21),
         _ => None,
@@ -378,8 +378,18 @@ impl Url {
     pub fn as_str(&self) -> &str { &self.serialization }
 
     /// Returns `true` if the URL scheme is "https" or "wss".
+    #[cfg(any(
+        feature = "async-core",
+        feature = "proxy",
+        not(all(feature = "wasm", target_family = "wasm"))
+    ))]
     pub(crate) fn is_https(&self) -> bool { matches!(self.scheme(), "https" | "wss") }
 
+    /// Returns `true` if the URL scheme is "http+unix", whose host is the
+    /// percent-encoded path of a Unix domain socket.
+    #[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
+    pub(crate) fn is_unix(&self) -> bool { self.scheme() == "http+unix" }
+
     /// Returns `true` if a non-default port was explicitly specified in the URL.
     ///
     /// This is useful for serialization purposes: ports that are the default for
@@ -396,6 +406,7 @@ impl Url {
     ///
     /// The returned string includes the leading `/` (if present) and the `?`
     /// separator (if there's a query string). Returns "/" if the path is empty.
+    #[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
     pub(crate) fn path_and_query(&self) -> String {
         let path = self.path();
         let path = if path.is_empty() { "/" } else { path };
@@ -436,6 +447,7 @@ impl Url {
     ///
     /// This implements RFC 7231 section 7.1.2 behavior for preserving fragments
     /// across redirects.
+    #[cfg(any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm"))))]
     pub(crate) fn preserve_fragment_from(&mut self, other: &Url) {
         if self.fragment.is_some() {
             return;
@@ -458,8 +470,24 @@ impl Url {
         Ok(())
     }
 
+    /// Returns the URL without its credentials or fragment, for error
+    /// messages and traces.
+    #[cfg(all(
+        feature = "std",
+        any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+    ))]
+    pub(crate) fn redacted(&self) -> String {
+        let mut redacted = String::new();
+        let _ = self.write_base_url_to(&mut redacted);
+        redacted.push_str(&self.path_and_query());
+        redacted
+    }
+
     /// Writes the `path [ "?" query ] [ "#" fragment ]` part to the destination.
-    #[cfg(feature = "std")]
+    #[cfg(all(
+        feature = "std",
+        any(feature = "async-core", not(all(feature = "wasm", target_family = "wasm")))
+    ))]
     pub(crate) fn write_resource_to<W: std::fmt::Write>(&self, dst: &mut W) -> std::fmt::Result {
         let path = self.path();
         let path = if path.is_empty() { "/" } else { path };
@@ -1,210 +1,549 @@
-//! WASM-specific HTTP implementation using extern C functions
+//! WebAssembly backend, delegating requests to the host environment.
 //!
-//! This module provides HTTP functionality for WebAssembly environments
-//! by delegating the actual network operations to JavaScript through
-//! extern C function calls.
+//! When compiled to WebAssembly with the `wasm` feature, requests are not
+//! sent over sockets but handed to the host (eg. a JavaScript `fetch`
+//! wrapper, or a wasmtime embedder) through the functions imported from
+//! the `bitreq` module, described on [`Host`]. The response head and body
+//! are then read back in chunks, so responses are only limited by the
+//! request's [`max_headers_size`](crate::Request::with_max_headers_size)
+//! and [`max_body_size`](crate::Request::with_max_body_size).
+//!
+//! Blocking requests expect the host functions to block until they can
+//! make progress. Async requests let them return [`PENDING`], in which
+//! case the host must later call the exported `bitreq_wake` function with
+//! the waker ID it was given, so the request's future is polled again.
+
+// Outside of WebAssembly the backend is only driven by the tests' mock host.
+#![cfg_attr(not(target_family = "wasm"), allow(dead_code))]
+
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::task::{Poll, Waker};
+use std::io;
+use std::sync::Mutex;
 
-use crate::{Error, Response};
 use crate::request::ParsedRequest;
-use alloc::string::String;
-use alloc::vec;
-
-/// Maximum size for HTTP response bodies in WASM
-const MAX_RESPONSE_SIZE: usize = 10 * 1024 * 1024; // 10MB
-
-// Extern C functions that must be implemented by the JavaScript host environment
-extern "C" {
-    /// Performs an HTTP request and returns the response.
-    ///
-    /// # Parameters
-    ///
-    /// - method: pointer to null-terminated method string (GET, POST, etc.)
-    /// - url: pointer to null-terminated URL string
-    /// - headers: pointer to null-terminated headers string (key:value\nkey:value format)
-    /// - body: pointer to request body bytes
-    /// - body_len: length of request body
-    /// - response_buf: buffer to write response into
-    /// - response_buf_len: size of response buffer
-    ///
-    /// # Returns
-    ///
-    /// - Positive value: actual response length written to buffer
-    /// - Negative value: error code
-    /// - 0: empty response
-    fn bitreq_wasm_http_request(
-        method: *const u8,
-        url: *const u8,
-        headers: *const u8,
-        body: *const u8,
-        body_len: usize,
-        response_buf: *mut u8,
-        response_buf_len: usize,
-    ) -> i32;
-
-    /// Gets the HTTP status code from the last request.
-    fn bitreq_wasm_get_status_code() -> i32;
-
-    /// Gets the response headers from the last request
-    ///
-    /// # Parameters
-    ///
-    /// - headers_buf: buffer to write headers into (key:value\nkey:value format)
-    /// - headers_buf_len: size of headers buffer
-    ///
-    /// # Returns
-    ///
-    /// - Positive value: actual headers length written to buffer
-    /// - Negative value: error code
-    /// - 0: no headers
-    fn bitreq_wasm_get_response_headers(
-        headers_buf: *mut u8,
-        headers_buf_len: usize,
-    ) -> i32;
+use crate::{Error, HeaderMap, Response};
+
+/// Returned by a host function which can't make progress yet.
+pub(crate) const PENDING: i32 = -1;
+/// Returned by a host function if the server could not be reached.
+pub(crate) const ERR_CONNECT: i32 = -2;
+/// Returned by a host function if the request timed out.
+pub(crate) const ERR_TIMEOUT: i32 = -3;
+
+/// How many bytes of the body are read from the host at a time.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// The functions the host provides, imported from the `bitreq` module.
+///
+/// Requests are identified by the non-negative handle returned by
+/// [`start`](Host::start). Functions return a negative error code on
+/// failure: [`PENDING`], [`ERR_CONNECT`], [`ERR_TIMEOUT`], or any other
+/// negative value for other errors. `waker` is the ID to pass to
+/// `bitreq_wake` after returning [`PENDING`].
+pub(crate) trait Host {
+    /// Starts sending a request. `headers` contains one `name: value` line
+    /// per header field. Imported as `bitreq_request_start`, taking a
+    /// pointer and length for each argument.
+    fn start(&self, method: &str, url: &str, headers: &str, body: &[u8], timeout_ms: i64) -> i32;
+
+    /// Returns 0 once the response's status and headers are available.
+    /// Imported as `bitreq_request_poll`.
+    fn poll(&self, handle: i32, waker: u32) -> i32;
+
+    /// Returns the status code of the response. Imported as
+    /// `bitreq_response_status`.
+    fn status(&self, handle: i32) -> i32;
+
+    /// Writes the response's header fields, one `name: value` line each,
+    /// into `buf` and returns their length. If they don't fit, nothing is
+    /// written and the required length is returned. Imported as
+    /// `bitreq_response_headers`.
+    fn headers(&self, handle: i32, buf: &mut [u8]) -> i32;
+
+    /// Reads the next bytes of the body into `buf`, returning how many
+    /// were read, or 0 at the end of the body. Imported as
+    /// `bitreq_response_read`.
+    fn read(&self, handle: i32, buf: &mut [u8], waker: u32) -> i32;
+
+    /// Releases the request, aborting it if it is still in progress.
+    /// Imported as `bitreq_request_close`.
+    fn close(&self, handle: i32);
 }
 
-/// Sends an HTTP request using WASM extern functions.
-pub(crate) fn send_request(parsed_request: ParsedRequest) -> Result<Response, Error> {
-    // Convert method to C string
-    let method_str = format!("{}\0", parsed_request.config.method);
-    let method_ptr = method_str.as_ptr();
-
-    // Build full URL
-    let full_url = build_full_url(&parsed_request);
-    let url_str = format!("{}\0", full_url);
-    let url_ptr = url_str.as_ptr();
-
-    // Convert headers to string format
-    let headers_str = build_headers_string(&parsed_request);
-    let headers_ptr = headers_str.as_ptr();
-
-    // Get request body
-    let body = parsed_request.get_body().map(|b| b.as_slice()).unwrap_or(&[]);
-    let body_ptr = body.as_ptr();
-    let body_len = body.len();
-
-    // Prepare response buffer
-    let mut response_buf = vec![0u8; MAX_RESPONSE_SIZE];
-    let response_buf_ptr = response_buf.as_mut_ptr();
-    let response_buf_len = response_buf.len();
-
-    // Make the extern C call
-    let result = unsafe {
-        bitreq_wasm_http_request(
-            method_ptr,
-            url_ptr,
-            headers_ptr,
-            body_ptr,
-            body_len,
-            response_buf_ptr,
-            response_buf_len,
-        )
-    };
+/// The [`Host`] backed by the functions imported from the `bitreq` module.
+#[cfg(target_family = "wasm")]
+pub(crate) struct ImportedHost;
+
+#[cfg(target_family = "wasm")]
+mod imports {
+    #[link(wasm_import_module = "bitreq")]
+    extern "C" {
+        #[allow(clippy::too_many_arguments)]
+        pub(super) fn bitreq_request_start(
+            method: *const u8,
+            method_len: usize,
+            url: *const u8,
+            url_len: usize,
+            headers: *const u8,
+            headers_len: usize,
+            body: *const u8,
+            body_len: usize,
+            timeout_ms: i64,
+        ) -> i32;
+        pub(super) fn bitreq_request_poll(handle: i32, waker: u32) -> i32;
+        pub(super) fn bitreq_response_status(handle: i32) -> i32;
+        pub(super) fn bitreq_response_headers(handle: i32, buf: *mut u8, buf_len: usize) -> i32;
+        pub(super) fn bitreq_response_read(
+            handle: i32,
+            buf: *mut u8,
+            buf_len: usize,
+            waker: u32,
+        ) -> i32;
+        pub(super) fn bitreq_request_close(handle: i32);
+    }
+}
+
+#[cfg(target_family = "wasm")]
+impl Host for ImportedHost {
+    fn start(&self, method: &str, url: &str, headers: &str, body: &[u8], timeout_ms: i64) -> i32 {
+        // SAFETY: every pointer is valid for reads of the given length for the whole call.
+        unsafe {
+            imports::bitreq_request_start(
+                method.as_ptr(),
+                method.len(),
+                url.as_ptr(),
+                url.len(),
+                headers.as_ptr(),
+                headers.len(),
+                body.as_ptr(),
+                body.len(),
+                timeout_ms,
+            )
+        }
+    }
+
+    fn poll(&self, handle: i32, waker: u32) -> i32 {
+        unsafe { imports::bitreq_request_poll(handle, waker) }
+    }
+
+    fn status(&self, handle: i32) -> i32 { unsafe { imports::bitreq_response_status(handle) } }
 
-    // Handle the result
-    if result < 0 {
-        return Err(Error::Other("WASM HTTP request failed"));
+    fn headers(&self, handle: i32, buf: &mut [u8]) -> i32 {
+        // SAFETY: `buf` is valid for writes of its length for the whole call.
+        unsafe { imports::bitreq_response_headers(handle, buf.as_mut_ptr(), buf.len()) }
     }
 
-    let response_len = result as usize;
-    response_buf.truncate(response_len);
+    fn read(&self, handle: i32, buf: &mut [u8], waker: u32) -> i32 {
+        // SAFETY: `buf` is valid for writes of its length for the whole call.
+        unsafe { imports::bitreq_response_read(handle, buf.as_mut_ptr(), buf.len(), waker) }
+    }
+
+    fn close(&self, handle: i32) { unsafe { imports::bitreq_request_close(handle) } }
+}
 
-    // Get status code
-    let status_code = unsafe { bitreq_wasm_get_status_code() };
+/// Wakes the request waiting on `waker`, after a host function returned
+/// [`PENDING`] for it.
+#[cfg(target_family = "wasm")]
+#[no_mangle]
+pub extern "C" fn bitreq_wake(waker: u32) { wake(waker) }
 
-    // Get response headers
-    let response_headers = get_response_headers()?;
+/// The wakers of requests waiting for the host, by ID.
+static WAKERS: Mutex<BTreeMap<u32, Waker>> = Mutex::new(BTreeMap::new());
+static NEXT_WAKER_ID: AtomicU32 = AtomicU32::new(0);
 
-    // Build and return Response
-    Ok(Response::new(
-        status_code,
-        get_reason_phrase(status_code),
-        response_headers,
-        full_url,
-        response_buf,
-    ))
+pub(crate) fn wake(waker: u32) {
+    if let Some(waker) = WAKERS.lock().unwrap().remove(&waker) {
+        waker.wake();
+    }
 }
 
-/// Builds the full URL including path and query parameters.
-fn build_full_url(parsed_request: &ParsedRequest) -> String {
-    parsed_request.url.as_str().to_string()
+/// Calls `f` with the waker ID `id`, registering `waker` under it first so
+/// the host may wake it before `f` returns.
+fn call(id: u32, waker: Option<&Waker>, f: impl FnOnce(u32) -> i32) -> i32 {
+    if let Some(waker) = waker {
+        WAKERS.lock().unwrap().insert(id, waker.clone());
+    }
+    let result = f(id);
+    if result != PENDING {
+        WAKERS.lock().unwrap().remove(&id);
+    }
+    result
 }
 
-/// Builds headers string in `key:value\nkey:value` format.
-fn build_headers_string(parsed_request: &ParsedRequest) -> String {
-    let mut headers_str = String::new();
+fn host_error(code: i32) -> Error {
+    let (kind, msg) = match code {
+        PENDING => (io::ErrorKind::WouldBlock, "the host can't block for a blocking request"),
+        ERR_CONNECT => (io::ErrorKind::ConnectionRefused, "the host could not connect"),
+        ERR_TIMEOUT => (io::ErrorKind::TimedOut, "the timeout of the request was reached"),
+        _ => (io::ErrorKind::Other, "the host failed to send the request"),
+    };
+    Error::IoError(io::Error::new(kind, msg))
+}
 
-    for (key, value) in parsed_request.get_headers() {
-        if !headers_str.is_empty() {
-            headers_str.push('\n');
+/// A request handed to the host, closed when dropped.
+struct Exchange<'a, H: Host> {
+    host: &'a H,
+    handle: i32,
+    waker: u32,
+    url: String,
+    max_headers_size: Option<usize>,
+    max_body_size: Option<usize>,
+    response: Option<Response>,
+    buf: Vec<u8>,
+}
+
+impl<'a, H: Host> Exchange<'a, H> {
+    fn start(host: &'a H, request: &ParsedRequest) -> Result<Exchange<'a, H>, Error> {
+        let config = &request.config;
+        let url = request.url.as_str().split('#').next().unwrap_or_default().to_string();
+        let mut headers = String::new();
+        for (name, value) in config.headers().iter() {
+            headers.push_str(name);
+            headers.push_str(": ");
+            headers.push_str(value);
+            headers.push('\n');
+        }
+        let body = config.body().unwrap_or_default();
+        let timeout_ms = match request.timeout {
+            Some(timeout) => timeout.as_millis().try_into().unwrap_or(i64::MAX),
+            None => -1,
+        };
+        let method = config.method().to_string();
+        let handle = host.start(&method, &url, &headers, body, timeout_ms);
+        if handle < 0 {
+            return Err(host_error(handle));
         }
-        headers_str.push_str(key);
-        headers_str.push(':');
-        headers_str.push_str(value);
+        Ok(Exchange {
+            host,
+            handle,
+            waker: NEXT_WAKER_ID.fetch_add(1, Ordering::Relaxed),
+            url,
+            max_headers_size: config.max_headers_size,
+            max_body_size: config.max_body_size,
+            response: None,
+            buf: vec![0; CHUNK_SIZE],
+        })
     }
 
-    headers_str.push('\0'); // Null terminate
-    headers_str
+    /// Makes as much progress as possible, returning `Poll::Pending` if
+    /// the host returned [`PENDING`]. Without a waker, [`PENDING`] is an
+    /// error.
+    fn advance(&mut self, waker: Option<&Waker>) -> Poll<Result<Response, Error>> {
+        let (host, handle, id) = (self.host, self.handle, self.waker);
+        loop {
+            if self.response.is_none() {
+                match call(id, waker, |id| host.poll(handle, id)) {
+                    PENDING if waker.is_some() => return Poll::Pending,
+                    code if code < 0 => return Poll::Ready(Err(host_error(code))),
+                    _ => {}
+                }
+                self.response = Some(match self.read_head() {
+                    Ok(response) => response,
+                    Err(e) => return Poll::Ready(Err(e)),
+                });
+            }
+            let response = self.response.as_mut().unwrap();
+            let buf = &mut self.buf;
+            match call(id, waker, |id| host.read(handle, buf, id)) {
+                PENDING if waker.is_some() => return Poll::Pending,
+                code if code < 0 => return Poll::Ready(Err(host_error(code))),
+                0 => return Poll::Ready(Ok(self.response.take().unwrap())),
+                read => {
+                    let read = (read as usize).min(buf.len());
+                    if self
+                        .max_body_size
+                        .is_some_and(|max| response.body.len().saturating_add(read) > max)
+                    {
+                        return Poll::Ready(Err(Error::BodyOverflow));
+                    }
+                    response.body.extend_from_slice(&buf[..read]);
+                }
+            }
+        }
+    }
+
+    fn read_head(&mut self) -> Result<Response, Error> {
+        let status_code = self.host.status(self.handle);
+        if status_code < 0 {
+            return Err(host_error(status_code));
+        }
+        let mut buf = Vec::new();
+        loop {
+            let len = self.host.headers(self.handle, &mut buf);
+            if len < 0 {
+                return Err(host_error(len));
+            }
+            let len = len as usize;
+            if self.max_headers_size.is_some_and(|max| len > max) {
+                return Err(Error::HeadersOverflow);
+            }
+            if len <= buf.len() {
+                buf.truncate(len);
+                break;
+            }
+            buf.resize(len, 0);
+        }
+        let fields = String::from_utf8(buf).map_err(|_| Error::InvalidUtf8InResponse)?;
+        let mut headers = HeaderMap::new();
+        for line in fields.lines() {
+            if let Some((name, value)) = line.split_once(':') {
+                headers.append(name.trim().to_ascii_lowercase(), value.trim());
+            }
+        }
+        Ok(Response {
+            status_code,
+            reason_phrase: reason_phrase(status_code).to_string(),
+            headers,
+            url: self.url.clone(),
+            redirects: Vec::new(),
+            attempts: 1,
+            body: Vec::new(),
+        })
+    }
 }
 
-/// Retrieves response headers from the WASM environment.
-fn get_response_headers() -> Result<crate::alloc::collections::BTreeMap<String, String>, Error> {
-    use alloc::collections::BTreeMap;
+impl<H: Host> Drop for Exchange<'_, H> {
+    fn drop(&mut self) {
+        WAKERS.lock().unwrap().remove(&self.waker);
+        self.host.close(self.handle);
+    }
+}
 
-    let mut headers_buf = vec![0u8; 8192]; // 8KB for headers
-    let headers_buf_ptr = headers_buf.as_mut_ptr();
-    let headers_buf_len = headers_buf.len();
+/// Sends `request` through `host`, blocking until the whole response has
+/// been read.
+pub(crate) fn send<H: Host>(host: &H, request: ParsedRequest) -> Result<Response, Error> {
+    match Exchange::start(host, &request)?.advance(None) {
+        Poll::Ready(result) => result,
+        Poll::Pending => Err(host_error(PENDING)),
+    }
+}
 
-    let result = unsafe {
-        bitreq_wasm_get_response_headers(headers_buf_ptr, headers_buf_len)
-    };
+/// Sends `request` through `host`, returning once the whole response has
+/// been read.
+#[cfg(feature = "async-core")]
+pub(crate) async fn send_async<H: Host>(
+    host: &H,
+    request: ParsedRequest,
+) -> Result<Response, Error> {
+    let mut exchange = Exchange::start(host, &request)?;
+    core::future::poll_fn(|cx| exchange.advance(Some(cx.waker()))).await
+}
 
-    if result < 0 {
-        return Err(Error::Other("Failed to get response headers"));
+/// Returns the standard reason phrase for a status code, as hosts like
+/// `fetch` don't reliably expose the one sent by the server.
+fn reason_phrase(status_code: i32) -> &'static str {
+    match status_code {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
     }
+}
 
-    let headers_len = result as usize;
-    headers_buf.truncate(headers_len);
+#[cfg(test)]
+mod tests {
+    use alloc::collections::VecDeque;
+    use alloc::sync::Arc;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use core::task::{Context, Poll};
+    use std::io;
+    use std::sync::Mutex;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
 
-    // Parse headers string
-    let headers_str = String::from_utf8(headers_buf)
-        .map_err(|_| Error::Other("Invalid UTF-8 in response headers"))?;
+    use super::{send, send_async, wake, Host, ERR_CONNECT, PENDING};
+    use crate::request::ParsedRequest;
+    use crate::Error;
 
-    let mut headers = BTreeMap::new();
+    /// A host serving a canned response, standing in for the imported functions.
+    #[derive(Default)]
+    struct MockHost {
+        start_result: i32,
+        headers: &'static str,
+        chunks: Mutex<VecDeque<&'static [u8]>>,
+        /// Return `PENDING` every other call, waking the request from another thread.
+        pending: bool,
+        pended: AtomicBool,
+        wakes: AtomicUsize,
+        started: Mutex<Option<Started>>,
+        closed: AtomicBool,
+    }
 
-    for line in headers_str.lines() {
-        if let Some(colon_pos) = line.find(':') {
-            let key = line[..colon_pos].trim().to_lowercase();
-            let value = line[colon_pos + 1..].trim().to_string();
-            headers.insert(key, value);
+    /// The arguments `start` was called with.
+    struct Started {
+        method: String,
+        url: String,
+        headers: String,
+        body: Vec<u8>,
+    }
+
+    impl MockHost {
+        fn new(headers: &'static str, chunks: &[&'static [u8]]) -> MockHost {
+            MockHost {
+                headers,
+                chunks: Mutex::new(chunks.iter().copied().collect()),
+                ..Default::default()
+            }
+        }
+
+        fn pend(&self, waker: u32) -> bool {
+            if !self.pending || self.pended.swap(false, Ordering::SeqCst) {
+                return false;
+            }
+            self.pended.store(true, Ordering::SeqCst);
+            self.wakes.fetch_add(1, Ordering::SeqCst);
+            thread::spawn(move || wake(waker));
+            true
         }
     }
 
-    Ok(headers)
-}
+    impl Host for MockHost {
+        fn start(&self, method: &str, url: &str, headers: &str, body: &[u8], _: i64) -> i32 {
+            *self.started.lock().unwrap() = Some(Started {
+                method: method.to_string(),
+                url: url.to_string(),
+                headers: headers.to_string(),
+                body: body.to_vec(),
+            });
+            self.start_result
+        }
 
-/// Gets a reason phrase for the given status code.
-fn get_reason_phrase(status_code: i32) -> String {
-    match status_code {
-        200 => "OK".to_string(),
-        201 => "Created".to_string(),
-        202 => "Accepted".to_string(),
-        204 => "No Content".to_string(),
-        301 => "Moved Permanently".to_string(),
-        302 => "Found".to_string(),
-        303 => "See Other".to_string(),
-        304 => "Not Modified".to_string(),
-        307 => "Temporary Redirect".to_string(),
-        308 => "Permanent Redirect".to_string(),
-        400 => "Bad Request".to_string(),
-        401 => "Unauthorized".to_string(),
-        403 => "Forbidden".to_string(),
-        404 => "Not Found".to_string(),
-        405 => "Method Not Allowed".to_string(),
-        429 => "Too Many Requests".to_string(),
-        500 => "Internal Server Error".to_string(),
-        502 => "Bad Gateway".to_string(),
-        503 => "Service Unavailable".to_string(),
-        504 => "Gateway Timeout".to_string(),
-        _ => "Unknown".to_string(),
+        fn poll(&self, _: i32, waker: u32) -> i32 {
+            if self.pend(waker) {
+                PENDING
+            } else {
+                0
+            }
+        }
+
+        fn status(&self, _: i32) -> i32 { 200 }
+
+        fn headers(&self, _: i32, buf: &mut [u8]) -> i32 {
+            if let Some(buf) = buf.get_mut(..self.headers.len()) {
+                buf.copy_from_slice(self.headers.as_bytes());
+            }
+            self.headers.len() as i32
+        }
+
+        fn read(&self, _: i32, buf: &mut [u8], waker: u32) -> i32 {
+            if self.pend(waker) {
+                return PENDING;
+            }
+            match self.chunks.lock().unwrap().pop_front() {
+                Some(chunk) => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    chunk.len() as i32
+                }
+                None => 0,
+            }
+        }
+
+        fn close(&self, _: i32) { self.closed.store(true, Ordering::SeqCst) }
+    }
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) { self.0.unpark() }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    fn parse(request: crate::Request) -> ParsedRequest { ParsedRequest::new(request).unwrap() }
+
+    #[test]
+    fn blocking_request() {
+        let host = MockHost::new("Content-Type: text/plain\nX-Two: 2\n", &[b"Hello, ", b"world!"]);
+        let request = crate::post("http://example.com/path?q=1#fragment")
+            .with_header("Authorization", "Basic dXNlcjpwYXNz")
+            .with_body("body");
+        let response = send(&host, parse(request)).unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.reason_phrase, "OK");
+        assert_eq!(response.headers.get("content-type"), Some("text/plain"));
+        assert_eq!(response.headers.get("x-two"), Some("2"));
+        assert_eq!(response.as_str().unwrap(), "Hello, world!");
+
+        let Started { method, url, headers, body } = host.started.lock().unwrap().take().unwrap();
+        assert_eq!(method, "POST");
+        assert_eq!(url, "http://example.com/path?q=1");
+        assert!(headers.contains("Authorization: Basic dXNlcjpwYXNz\n"));
+        assert_eq!(body, b"body");
+        assert!(host.closed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn async_request() {
+        let mut host = MockHost::new("ETag: \"1\"\n", &[b"a", b"b", b"c"]);
+        host.pending = true;
+        let response =
+            block_on(send_async(&host, parse(crate::get("http://example.com")))).unwrap();
+        assert_eq!(response.headers.etag(), Some("\"1\""));
+        assert_eq!(response.as_bytes(), b"abc");
+        // The head and each chunk, and the end of the body.
+        assert_eq!(host.wakes.load(Ordering::SeqCst), 5);
+        assert!(host.closed.load(Ordering::SeqCst));
+
+        // A blocking request can't wait for a pending host.
+        let host = MockHost { pending: true, ..MockHost::new("", &[]) };
+        match send(&host, parse(crate::get("http://example.com"))) {
+            Err(Error::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::WouldBlock),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn size_limits() {
+        let host = MockHost::new("X-Long: 0123456789\n", &[]);
+        let request = crate::get("http://example.com").with_max_headers_size(10);
+        assert!(matches!(send(&host, parse(request)), Err(Error::HeadersOverflow)));
+
+        let host = MockHost::new("", &[b"0123", b"4567"]);
+        let request = crate::get("http://example.com").with_max_body_size(6);
+        assert!(matches!(send(&host, parse(request)), Err(Error::BodyOverflow)));
+        assert!(host.closed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn host_errors() {
+        let host = MockHost { start_result: ERR_CONNECT, ..MockHost::new("", &[]) };
+        match send(&host, parse(crate::get("http://example.com"))) {
+            Err(Error::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(!host.closed.load(Ordering::SeqCst));
     }
 }
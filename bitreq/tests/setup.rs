@@ -4,6 +4,7 @@ extern crate bitreq;
 extern crate tiny_http;
 use std::io::Read;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Once};
 use std::thread;
 use std::time::Duration;
@@ -11,6 +12,9 @@ use std::time::Duration;
 use self::tiny_http::{Header, Method, Response, Server, StatusCode};
 
 static INIT: Once = Once::new();
+/// How many full responses the `/cached` and `/revalidated` routes sent.
+static CACHED_RESPONSES: AtomicUsize = AtomicUsize::new(0);
+static REVALIDATED_RESPONSES: AtomicUsize = AtomicUsize::new(0);
 
 pub fn setup() {
     INIT.call_once(|| {
@@ -69,6 +73,61 @@ pub fn setup() {
                         respond!(Response::from_string("No header!"));
                     }
 
+                    Method::Get if url == "/multi_header" => {
+                        let response = Response::from_string("")
+                            .with_header(Header::from_str("Set-Cookie: a=1").unwrap())
+                            .with_header(Header::from_str("Set-Cookie: b=2").unwrap())
+                            .with_header(Header::from_str("ETag: \"v1\"").unwrap());
+                        respond!(response);
+                    }
+
+                    Method::Get if url == "/cached" => {
+                        let count = CACHED_RESPONSES.fetch_add(1, Ordering::SeqCst) + 1;
+                        let response = Response::from_string(format!("{}", count))
+                            .with_header(Header::from_str("Cache-Control: max-age=60").unwrap())
+                            .with_header(Header::from_str("ETag: \"v1\"").unwrap());
+                        respond!(response);
+                    }
+
+                    Method::Get if url == "/revalidated" => {
+                        let cache_control = Header::from_str("Cache-Control: no-cache").unwrap();
+                        let etag = Header::from_str("ETag: \"v1\"").unwrap();
+                        if headers
+                            .iter()
+                            .any(|h| h.field.equiv("If-None-Match") && h.value == "\"v1\"")
+                        {
+                            respond!(Response::empty(304)
+                                .with_header(cache_control)
+                                .with_header(etag));
+                        } else {
+                            let count = REVALIDATED_RESPONSES.fetch_add(1, Ordering::SeqCst) + 1;
+                            let response = Response::from_string(format!("{}", count))
+                                .with_header(cache_control)
+                                .with_header(etag);
+                            respond!(response);
+                        }
+                    }
+
+                    _ if url == "/retry-after" => {
+                        let response = Response::from_string("busy")
+                            .with_status_code(StatusCode(503))
+                            .with_header(Header::from_str("Retry-After: 0").unwrap());
+                        respond!(response);
+                    }
+
+                    Method::Get if url == "/sse" => {
+                        // No data length, so the body is sent chunked
+                        let body = ": hello\ndata: {\"a\":1}\n\nevent: b\ndata: {\"a\":2}\r\n\r\n";
+                        let response = Response::new(
+                            StatusCode(200),
+                            vec![Header::from_str("Content-Type: text/event-stream").unwrap()],
+                            std::io::Cursor::new(body),
+                            None,
+                            None,
+                        );
+                        request.respond(response).unwrap();
+                    }
+
                     Method::Get if url == "/slow_a" => {
                         thread::sleep(Duration::from_secs(2));
                         let response = Response::from_string(format!("j: {}", content));
@@ -128,6 +187,49 @@ pub fn setup() {
                         respond!(response);
                     }
 
+                    Method::Post if url == "/redirect-307" => {
+                        let response = Response::empty(307).with_header(
+                            Header::from_str("Location: http://localhost:35562/echo").unwrap(),
+                        );
+                        respond!(response);
+                    }
+                    Method::Post
+                        if url == "/redirect-301"
+                            || url == "/redirect-302"
+                            || url == "/redirect-303" =>
+                    {
+                        let status: u16 = url["/redirect-".len()..].parse().unwrap();
+                        let response = Response::empty(status).with_header(
+                            Header::from_str("Location: http://localhost:35562/method_pong")
+                                .unwrap(),
+                        );
+                        respond!(response);
+                    }
+                    _ if url == "/method_pong" => {
+                        let content_type = headers
+                            .iter()
+                            .find(|header| header.field.equiv("Content-Type"))
+                            .map(|header| format!(" ({})", header.value))
+                            .unwrap_or_default();
+                        let body = format!("{}: {}{}", request.method(), content, content_type);
+                        respond!(Response::from_string(body));
+                    }
+                    Method::Get if url == "/redirect-cross-origin" => {
+                        let response = Response::empty(302).with_header(
+                            Header::from_str("Location: http://127.0.0.1:35562/auth_pong").unwrap(),
+                        );
+                        respond!(response);
+                    }
+                    Method::Get if url == "/auth_pong" => {
+                        for header in headers {
+                            if header.field.equiv("Authorization") {
+                                respond!(Response::from_string(format!("{}", header.value)));
+                                return;
+                            }
+                        }
+                        respond!(Response::from_string("No header!"));
+                    }
+
                     Method::Get if url == "/infiniteredirect" => {
                         let response = Response::empty(301).with_header(
                             Header::from_bytes(
@@ -8,7 +8,7 @@
 FEATURES_WITH_STD=""
 
 # Test all these features without "std" enabled.
-FEATURES_WITHOUT_STD="client-sync"
+FEATURES_WITHOUT_STD="client-sync client-async rpcauth-random"
 
 # Run these examples.
 EXAMPLES=""
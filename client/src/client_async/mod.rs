@@ -0,0 +1,411 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Async JSON-RPC clients for testing against specific versions of Bitcoin Core.
+//!
+//! Each `client_async::vNN::Client` has the same methods as the blocking
+//! `client_sync::vNN::Client`, returning futures instead. Requests are sent using an async
+//! [`bitreq::Client`] which keeps connections to the server open, by default this requires a tokio
+//! runtime.
+
+pub mod v17;
+pub mod v18;
+pub mod v19;
+pub mod v20;
+pub mod v21;
+pub mod v22;
+pub mod v23;
+pub mod v24;
+pub mod v25;
+pub mod v26;
+pub mod v27;
+pub mod v28;
+pub mod v29;
+pub mod v30;
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use jsonrpc::bitreq_http::{self, HttpError};
+use serde_json::value::RawValue;
+
+use crate::client_sync::transport::{
+    basic_auth, is_unauthorized, wallet_url, Credentials, UserPass,
+};
+pub use crate::client_sync::{options, Auth, Error, Result};
+
+/// The number of connections to the server kept open.
+const CONNECTION_CAPACITY: usize = 4;
+
+/// Defines an async `Client` using `bitreq`.
+#[macro_export]
+macro_rules! define_jsonrpc_bitreq_async_client {
+    ($version:literal) => {
+        use std::fmt;
+
+        use $crate::client_async::{Auth, Result, Transport};
+        use $crate::client_sync::transport::Credentials;
+        use $crate::client_sync::{log_response, Error};
+
+        /// Client implements an async JSON-RPC client for the Bitcoin Core daemon or compatible APIs.
+        pub struct Client {
+            inner: Transport,
+            /// The wallet this client is scoped to, if it is a wallet handle.
+            wallet: Option<String>,
+        }
+
+        impl fmt::Debug for Client {
+            fn fmt(&self, f: &mut fmt::Formatter) -> core::fmt::Result {
+                write!(
+                    f,
+                    "corepc_client::client_async::{}::Client({:?})", $version, self.inner
+                )
+            }
+        }
+
+        impl Client {
+            /// The major version of Bitcoin Core this client is for.
+            pub const CORE_VERSION: usize = $crate::client_sync::major_version($version);
+
+            /// Creates a client to a bitcoind JSON-RPC server without authentication.
+            pub fn new(url: &str) -> Self {
+                Self { inner: Transport::new(url, Credentials::none()), wallet: None }
+            }
+
+            /// Creates a client to a bitcoind JSON-RPC server with authentication.
+            ///
+            /// With [`Auth::CookieFile`] the cookie file is read again if the server rejects the
+            /// credentials, e.g. after `bitcoind` restarted, and the request retried.
+            pub fn new_with_auth(url: &str, auth: Auth) -> Result<Self> {
+                if matches!(auth, Auth::None) {
+                    return Err(Error::MissingUserPassword);
+                }
+                Ok(Self { inner: Transport::new(url, Credentials::new(auth)?), wallet: None })
+            }
+
+            /// Creates a client for the node with data directory `datadir`.
+            ///
+            /// The URL and credentials are read from the config file as `bitcoin-cli` does, see
+            /// [`RpcConfig::from_datadir`]($crate::client_sync::config::RpcConfig::from_datadir).
+            pub fn from_datadir(
+                datadir: &std::path::Path,
+                conf: Option<&std::path::Path>,
+                network: Option<bitcoin::Network>,
+            ) -> Result<Self> {
+                let config =
+                    $crate::client_sync::config::RpcConfig::from_datadir(datadir, conf, network)?;
+                Self::new_with_auth(&config.url, config.auth)
+            }
+
+            /// Sets a function called with the path of the cookie file after new credentials were
+            /// read from it.
+            ///
+            /// Applies to this client and all wallet handles created from it.
+            pub fn on_cookie_reload<F>(&self, f: F)
+            where
+                F: Fn(&std::path::Path) + std::marker::Send + std::marker::Sync + 'static,
+            {
+                self.inner.credentials.set_on_reload(Box::new(f));
+            }
+
+            /// Returns a handle for calling wallet methods on the wallet `name`.
+            ///
+            /// The handle shares the connections and credentials of this client, creating one is
+            /// cheap. If the wallet is not loaded when a method is called on the handle, the
+            /// wallet is loaded and the call retried.
+            pub fn wallet(&self, name: &str) -> Self {
+                Self { inner: self.inner.for_wallet(name), wallet: Some(name.to_owned()) }
+            }
+
+            /// Call an RPC `method` with given `args` list.
+            pub async fn call<T: for<'a> serde::de::Deserialize<'a>>(
+                &self,
+                method: &str,
+                args: &[serde_json::Value],
+            ) -> Result<T> {
+                let raw = serde_json::value::to_raw_value(args)?;
+                self.call_with_params(method, &raw).await
+            }
+
+            /// Call an RPC `method` with given named `args`.
+            ///
+            /// Arguments left out use their default value, so any optional argument can be set
+            /// without also setting the ones before it.
+            pub async fn call_named<T: for<'a> serde::de::Deserialize<'a>>(
+                &self,
+                method: &str,
+                args: &serde_json::Map<String, serde_json::Value>,
+            ) -> Result<T> {
+                let raw = serde_json::value::to_raw_value(args)?;
+                self.call_with_params(method, &raw).await
+            }
+
+            async fn call_with_params<T: for<'a> serde::de::Deserialize<'a>>(
+                &self,
+                method: &str,
+                params: &serde_json::value::RawValue,
+            ) -> Result<T> {
+                let resp = self.send_request(method, params).await?;
+                if let Some(wallet) = self.wallet_to_load(&resp) {
+                    let load_params = serde_json::value::to_raw_value(&[wallet])?;
+                    // Retry even if loading failed, the wallet may have been loaded concurrently.
+                    let _ = self.send_request("loadwallet", &load_params).await;
+                    return Ok(self.send_request(method, params).await?.result()?);
+                }
+                Ok(resp.result()?)
+            }
+
+            async fn send_request(
+                &self,
+                method: &str,
+                params: &serde_json::value::RawValue,
+            ) -> Result<jsonrpc::Response> {
+                if log::log_enabled!(log::Level::Debug) {
+                    log::debug!(target: "corepc", "request: {} {}", method, params);
+                }
+
+                let resp = self.inner.send_request(method, params).await.map_err(Error::from);
+                log_response(method, &resp);
+                resp
+            }
+        }
+
+        $crate::impl_client_wallet_handle!(async);
+    }
+}
+
+/// An HTTP transport sending JSON-RPC requests with an async [`bitreq::Client`].
+///
+/// Errors are reported the same way as by [`bitreq_http::BitreqHttpTransport`].
+struct Transport {
+    client: bitreq::Client,
+    /// URL of the RPC server.
+    server_url: String,
+    /// URL requests are sent to, the server URL or the endpoint of a wallet.
+    url: String,
+    /// The credentials, shared with the wallet handles.
+    credentials: Arc<Credentials>,
+    timeout: Duration,
+    nonce: AtomicUsize,
+}
+
+impl Transport {
+    fn new(url: &str, credentials: Credentials) -> Self {
+        Transport {
+            client: bitreq::Client::new(CONNECTION_CAPACITY),
+            server_url: url.to_owned(),
+            url: url.to_owned(),
+            credentials: Arc::new(credentials),
+            timeout: Duration::from_secs(60),
+            nonce: AtomicUsize::new(1),
+        }
+    }
+
+    /// Returns a transport for the wallet endpoint of wallet `name`, sharing this transport's
+    /// connections.
+    fn for_wallet(&self, name: &str) -> Self {
+        Transport {
+            client: self.client.clone(),
+            server_url: self.server_url.clone(),
+            url: wallet_url(&self.server_url, name),
+            credentials: Arc::clone(&self.credentials),
+            timeout: self.timeout,
+            nonce: AtomicUsize::new(1),
+        }
+    }
+
+    /// Sends a request for `method` and returns the response, without checking it for errors.
+    ///
+    /// If the server responds with `401 Unauthorized` the cookie file, if any, is read again and
+    /// the request is retried with the new credentials.
+    async fn send_request(
+        &self,
+        method: &str,
+        params: &RawValue,
+    ) -> std::result::Result<jsonrpc::Response, jsonrpc::Error> {
+        let sent = self.credentials.user_pass();
+        match self.send_request_once(method, params, sent.as_ref()).await {
+            Err(ref e) if is_unauthorized(e) && self.credentials.reload(&sent) =>
+                self.send_request_once(method, params, self.credentials.user_pass().as_ref()).await,
+            result => result,
+        }
+    }
+
+    /// Sends a request for `method`, authenticated with `user_pass`.
+    async fn send_request_once(
+        &self,
+        method: &str,
+        params: &RawValue,
+        user_pass: Option<&UserPass>,
+    ) -> std::result::Result<jsonrpc::Response, jsonrpc::Error> {
+        let nonce = self.nonce.fetch_add(1, Ordering::Relaxed);
+        let request = jsonrpc::Request {
+            method,
+            params: Some(params),
+            id: serde_json::Value::from(nonce),
+            jsonrpc: Some("2.0"),
+        };
+
+        let mut req = bitreq::post(&self.url)
+            .with_timeout(self.timeout.as_secs())
+            .with_json(&request)
+            .map_err(bitreq_http::Error::Bitreq)?;
+        if let Some(user_pass) = user_pass {
+            req = req.with_header("Authorization", basic_auth(user_pass));
+        }
+
+        // As for the blocking transport, if the response is an error that does not contain valid
+        // JSON in its body return the raw HTTP error so users can match against it.
+        let resp = self.client.send_async(req).await.map_err(bitreq_http::Error::Bitreq)?;
+        match resp.json() {
+            Ok(json) => Ok(json),
+            Err(bitreq_err) => {
+                let err = if resp.status_code != 200 {
+                    bitreq_http::Error::Http(HttpError {
+                        status_code: resp.status_code,
+                        body: resp.as_str().unwrap_or("").to_string(),
+                    })
+                } else {
+                    bitreq_http::Error::Bitreq(bitreq_err)
+                };
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transport").field("url", &self.url).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Barrier;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::client_async::v29::Client;
+    use crate::client_sync::mock::{basic_auth, response, Cookie, MockServer};
+
+    fn methods(server: &MockServer) -> Vec<(String, String)> {
+        server
+            .requests()
+            .into_iter()
+            .map(|r| (r.path, r.body["method"].as_str().unwrap_or_default().to_owned()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn call() {
+        let server = MockServer::start(|request| (200, response(&request.body, json!(101))));
+        let client = Client::new(server.url());
+
+        assert_eq!(client.get_block_count().await.unwrap().0, 101);
+        assert_eq!(client.call::<u64>("getblockcount", &[]).await.unwrap(), 101);
+        assert_eq!(methods(&server), vec![("/".to_owned(), "getblockcount".to_owned()); 2]);
+        assert_eq!(server.requests()[0].body["params"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn http_error_without_json_body() {
+        let server = MockServer::start(|_| (503, json!("<html>Service Unavailable</html>")));
+        let client = Client::new(server.url());
+
+        let err = client.get_block_count().await.unwrap_err();
+        let http_error = match err {
+            Error::JsonRpc(jsonrpc::Error::Transport(ref e)) =>
+                e.downcast_ref::<bitreq_http::Error>(),
+            _ => None,
+        };
+        match http_error {
+            Some(bitreq_http::Error::Http(HttpError { status_code, body })) => {
+                assert_eq!(*status_code, 503);
+                assert_eq!(body, "<html>Service Unavailable</html>");
+            }
+            _ => panic!("expected an HTTP error, got {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_with_rewritten_cookie() {
+        let cookie = Cookie::new("async-rewritten", "__cookie__:old");
+        let expected = basic_auth("__cookie__:new");
+        let server = MockServer::start(move |request| {
+            if request.authorization.as_ref() != Some(&expected) {
+                return (401, Value::Null);
+            }
+            (200, response(&request.body, json!(101)))
+        });
+        let client =
+            Client::new_with_auth(server.url(), Auth::CookieFile(cookie.0.clone())).unwrap();
+        let reloaded = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&reloaded);
+        client.on_cookie_reload(move |_| flag.store(true, Ordering::Relaxed));
+
+        // The node restarted and wrote a new cookie file.
+        fs::write(&cookie.0, "__cookie__:new").unwrap();
+        assert_eq!(client.get_block_count().await.unwrap().0, 101);
+
+        let auths = server.requests().into_iter().map(|r| r.authorization).collect::<Vec<_>>();
+        assert_eq!(auths, [Some(basic_auth("__cookie__:old")), Some(basic_auth("__cookie__:new"))]);
+        assert!(reloaded.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn concurrent_rejected_requests_all_retry() {
+        let cookie = Cookie::new("async-concurrent", "__cookie__:old");
+        let expected = basic_auth("__cookie__:new");
+        // Both requests are rejected before either reads the cookie file again.
+        let rejected = Barrier::new(2);
+        let server = MockServer::start(move |request| {
+            if request.authorization.as_ref() != Some(&expected) {
+                rejected.wait();
+                return (401, Value::Null);
+            }
+            (200, response(&request.body, json!(101)))
+        });
+        let client =
+            Client::new_with_auth(server.url(), Auth::CookieFile(cookie.0.clone())).unwrap();
+
+        fs::write(&cookie.0, "__cookie__:new").unwrap();
+        let alice = client.wallet("alice");
+        let (a, b) = tokio::join!(client.get_block_count(), alice.get_block_count());
+        assert_eq!((a.unwrap().0, b.unwrap().0), (101, 101));
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn wallet_handle_loads_unloaded_wallet() {
+        let loaded = AtomicBool::new(false);
+        let server = MockServer::start(move |request| {
+            let body = &request.body;
+            match body["method"].as_str() {
+                Some("loadwallet") => {
+                    loaded.store(true, Ordering::Relaxed);
+                    (200, response(body, json!({ "name": "alice", "warning": "" })))
+                }
+                Some("getbalance") if !loaded.load(Ordering::Relaxed) => {
+                    let error = json!({ "code": -18, "message": "Requested wallet does not exist or is not loaded" });
+                    (500, json!({ "id": body["id"], "result": null, "error": error }))
+                }
+                Some("getbalance") => (200, response(body, json!(1.5))),
+                _ => (500, Value::Null),
+            }
+        });
+        let alice = Client::new(server.url()).wallet("alice");
+
+        assert_eq!(alice.get_balance().await.unwrap().0, 1.5);
+        let expected = [
+            ("/wallet/alice", "getbalance"),
+            ("/wallet/alice", "loadwallet"),
+            ("/wallet/alice", "getbalance"),
+        ];
+        assert_eq!(methods(&server), expected.map(|(p, m)| (p.to_owned(), m.to_owned())));
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! An async JSON-RPC client for testing against Bitcoin Core `v0.17`.
+//!
+//! We ignore option arguments unless they effect the shape of the returned JSON data. A few methods
+//! with many optional arguments also have a variant taking one of the structs in
+//! [`crate::client_sync::options`].
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bitcoin::address::{Address, NetworkChecked};
+use bitcoin::{sign_message, Amount, Block, BlockHash, PublicKey, Txid};
+
+use crate::client_sync::into_json;
+pub use crate::client_sync::v17::{
+    AddNodeCommand, AddressType, ImportMultiRequest, ImportMultiScriptPubKey, ImportMultiTimestamp,
+    Input, Output, SetBanCommand, TemplateRequest, TemplateRules, WalletCreateFundedPsbtInput,
+};
+use crate::types::v17::*;
+
+crate::define_jsonrpc_bitreq_async_client!("v17");
+crate::impl_client_check_expected_server_version!({ [170200] }, async);
+
+// == Blockchain ==
+crate::impl_client_v17__get_best_block_hash!(async);
+crate::impl_client_v17__get_block!(async);
+crate::impl_client_v17__get_blockchain_info!(async);
+crate::impl_client_v17__get_block_count!(async);
+crate::impl_client_v17__get_block_hash!(async);
+crate::impl_client_v17__get_block_header!(async);
+crate::impl_client_v17__get_block_stats!(async);
+crate::impl_client_v17__get_chain_tips!(async);
+crate::impl_client_v17__get_chain_tx_stats!(async);
+crate::impl_client_v17__get_difficulty!(async);
+crate::impl_client_v17__get_mempool_ancestors!(async);
+crate::impl_client_v17__get_mempool_descendants!(async);
+crate::impl_client_v17__get_mempool_entry!(async);
+crate::impl_client_v17__get_mempool_info!(async);
+crate::impl_client_v17__get_raw_mempool!(async);
+crate::impl_client_v17__get_tx_out!(async);
+crate::impl_client_v17__get_tx_out_proof!(async);
+crate::impl_client_v17__get_tx_out_set_info!(async);
+crate::impl_client_v17__precious_block!(async);
+crate::impl_client_v17__prune_blockchain!(async);
+crate::impl_client_v17__save_mempool!(async);
+crate::impl_client_v17__scan_tx_out_set!(async);
+crate::impl_client_v17__verify_chain!(async);
+crate::impl_client_v17__verify_tx_out_proof!(async);
+
+// == Control ==
+crate::impl_client_v17__get_memory_info!(async);
+crate::impl_client_v17__help!(async);
+crate::impl_client_v17__logging!(async);
+crate::impl_client_v17__stop!(async);
+crate::impl_client_v17__uptime!(async);
+
+// == Generating ==
+crate::impl_client_v17__generate_to_address!(async);
+crate::impl_client_v17__generate!(async);
+crate::impl_client_v17__invalidate_block!(async);
+
+// == Hidden ==
+crate::impl_client_v17__estimate_raw_fee!(async);
+crate::impl_client_v17__wait_for_block!(async);
+crate::impl_client_v17__wait_for_block_height!(async);
+crate::impl_client_v17__wait_for_new_block!(async);
+crate::impl_client_v17__sync_with_validation_interface_queue!(async);
+crate::impl_client_v17__reconsider_block!(async);
+
+// == Mining ==
+crate::impl_client_v17__get_block_template!(async);
+crate::impl_client_v17__get_mining_info!(async);
+crate::impl_client_v17__get_network_hashes_per_second!(async);
+crate::impl_client_v17__prioritise_transaction!(async);
+crate::impl_client_v17__submit_block!(async);
+
+// == Network ==
+crate::impl_client_v17__add_node!(async);
+crate::impl_client_v17__clear_banned!(async);
+crate::impl_client_v17__disconnect_node!(async);
+crate::impl_client_v17__get_added_node_info!(async);
+crate::impl_client_v17__get_connection_count!(async);
+crate::impl_client_v17__get_net_totals!(async);
+crate::impl_client_v17__get_network_info!(async);
+crate::impl_client_v17__get_peer_info!(async);
+crate::impl_client_v17__list_banned!(async);
+crate::impl_client_v17__ping!(async);
+crate::impl_client_v17__set_ban!(async);
+crate::impl_client_v17__set_network_active!(async);
+
+// == Rawtransactions ==
+crate::impl_client_v17__combine_psbt!(async);
+crate::impl_client_v17__combine_raw_transaction!(async);
+crate::impl_client_v17__convert_to_psbt!(async);
+crate::impl_client_v17__create_psbt!(async);
+crate::impl_client_v17__create_raw_transaction!(async);
+crate::impl_client_v17__decode_psbt!(async);
+crate::impl_client_v17__decode_raw_transaction!(async);
+crate::impl_client_v17__decode_script!(async);
+crate::impl_client_v17__finalize_psbt!(async);
+crate::impl_client_v17__fund_raw_transaction!(async);
+crate::impl_client_v17__get_raw_transaction!(async);
+crate::impl_client_v17__send_raw_transaction!(async);
+crate::impl_client_v17__sign_raw_transaction!(async);
+crate::impl_client_v17__sign_raw_transaction_with_key!(async);
+crate::impl_client_v17__test_mempool_accept!(async);
+
+// == Util ==
+crate::impl_client_v17__create_multisig!(async);
+crate::impl_client_v17__estimate_smart_fee!(async);
+crate::impl_client_v17__sign_message_with_priv_key!(async);
+crate::impl_client_v17__validate_address!(async);
+crate::impl_client_v17__verify_message!(async);
+
+// == Wallet ==
+crate::impl_client_v17__abandon_transaction!(async);
+crate::impl_client_v17__abort_rescan!(async);
+crate::impl_client_v17__add_multisig_address!(async);
+crate::impl_client_v17__backup_wallet!(async);
+crate::impl_client_v17__bump_fee!(async);
+crate::impl_client_v17__create_wallet!(async);
+crate::impl_client_v17__dump_priv_key!(async);
+crate::impl_client_v17__dump_wallet!(async);
+crate::impl_client_v17__encrypt_wallet!(async);
+crate::impl_client_v17__get_addresses_by_label!(async);
+crate::impl_client_v17__get_address_info!(async);
+crate::impl_client_v17__get_balance!(async);
+crate::impl_client_v17__get_new_address!(async);
+crate::impl_client_v17__get_raw_change_address!(async);
+crate::impl_client_v17__get_received_by_address!(async);
+crate::impl_client_v17__get_transaction!(async);
+crate::impl_client_v17__get_unconfirmed_balance!(async);
+crate::impl_client_v17__get_wallet_info!(async);
+crate::impl_client_v17__import_address!(async);
+crate::impl_client_v17__import_multi!(async);
+crate::impl_client_v17__import_privkey!(async);
+crate::impl_client_v17__import_pruned_funds!(async);
+crate::impl_client_v17__import_pubkey!(async);
+crate::impl_client_v17__import_wallet!(async);
+crate::impl_client_v17__key_pool_refill!(async);
+crate::impl_client_v17__list_address_groupings!(async);
+crate::impl_client_v17__list_labels!(async);
+crate::impl_client_v17__list_lock_unspent!(async);
+crate::impl_client_v17__list_received_by_address!(async);
+crate::impl_client_v17__list_since_block!(async);
+crate::impl_client_v17__list_transactions!(async);
+crate::impl_client_v17__list_unspent!(async);
+crate::impl_client_v17__list_wallets!(async);
+crate::impl_client_v17__load_wallet!(async);
+crate::impl_client_v17__lock_unspent!(async);
+crate::impl_client_v17__remove_pruned_funds!(async);
+crate::impl_client_v17__rescan_blockchain!(async);
+crate::impl_client_v17__send_many!(async);
+crate::impl_client_v17__send_to_address!(async);
+crate::impl_client_v17__set_hd_seed!(async);
+crate::impl_client_v17__set_tx_fee!(async);
+crate::impl_client_v17__sign_message!(async);
+crate::impl_client_v17__sign_raw_transaction_with_wallet!(async);
+crate::impl_client_v17__unload_wallet!(async);
+crate::impl_client_v17__wallet_create_funded_psbt!(async);
+crate::impl_client_v17__wallet_lock!(async);
+crate::impl_client_v17__wallet_passphrase!(async);
+crate::impl_client_v17__wallet_passphrase_change!(async);
+crate::impl_client_v17__wallet_process_psbt!(async);
+
+// == Zmq ==
+crate::impl_client_v17__get_zmq_notifications!(async);
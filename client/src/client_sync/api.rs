@@ -0,0 +1,404 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Traits for the sections of the Bitcoin Core JSON-RPC API.
+//!
+//! Each trait has the methods of one section of the API docs which are available in every
+//! supported version of Core, returning the version nonspecific [`model`] types where there is
+//! one. The traits are implemented by the version specific clients and by [`auto::Client`], so
+//! code can be written once for any of them, and tested against a mock implementation.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bitcoin::address::{Address, NetworkChecked};
+use bitcoin::{Amount, Block, BlockHash, Txid};
+
+use crate::client_sync::auto::{self, IntoModelResult};
+use crate::client_sync::{
+    v17, v18, v19, v20, v21, v22, v23, v24, v25, v26, v27, v28, v29, v30, Result,
+    WalletCreateFundedPsbtInput,
+};
+use crate::types::{self, model};
+
+/// Defines an RPC trait and implements it for all the clients.
+///
+/// Methods returning a `model` type are marked with `=> into_model`, the version specific clients
+/// convert their results with `into_model()`.
+macro_rules! define_rpc_trait {
+    (
+        $(#[$attr:meta])*
+        pub trait $trait:ident $methods:tt
+    ) => {
+        define_rpc_trait!(@trait $(#[$attr])* $trait $methods);
+        define_rpc_trait!(
+            @impls $trait $methods;
+            v17, v18, v19, v20, v21, v22, v23, v24, v25, v26, v27, v28, v29, v30, auto
+        );
+    };
+    (
+        @trait $(#[$attr:meta])* $trait:ident {
+            $(
+                $(#[$method_attr:meta])*
+                fn $method:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> Result<$ret:ty>
+                    $(=> $convert:ident)?;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        pub trait $trait {
+            $(
+                $(#[$method_attr])*
+                fn $method(&self $(, $arg: $ty)*) -> Result<$ret>;
+            )*
+        }
+    };
+    (@impls $trait:ident $methods:tt; $($client:ident),*) => {
+        $(define_rpc_trait!(@impl $client $trait $methods);)*
+    };
+    (
+        @impl auto $trait:ident {
+            $(
+                $(#[$method_attr:meta])*
+                fn $method:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> Result<$ret:ty>
+                    $(=> $convert:ident)?;
+            )*
+        }
+    ) => {
+        impl $trait for auto::Client {
+            $(
+                fn $method(&self $(, $arg: $ty)*) -> Result<$ret> { self.$method($($arg),*) }
+            )*
+        }
+    };
+    (
+        @impl $version:ident $trait:ident {
+            $(
+                $(#[$method_attr:meta])*
+                fn $method:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> Result<$ret:ty>
+                    $(=> $convert:ident)?;
+            )*
+        }
+    ) => {
+        impl $trait for $version::Client {
+            $(
+                fn $method(&self $(, $arg: $ty)*) -> Result<$ret> {
+                    define_rpc_trait!(@call self.$method($($arg),*) $(=> $convert)?)
+                }
+            )*
+        }
+    };
+    (@call $call:expr) => {
+        $call
+    };
+    (@call $call:expr => into_model) => {
+        $call?.into_model().into_model_result()
+    };
+}
+
+define_rpc_trait! {
+    /// Methods found under the `== Blockchain ==` section of the API docs of Bitcoin Core.
+    pub trait BlockchainRpc {
+        /// Gets the blockhash of the current chain tip.
+        fn best_block_hash(&self) -> Result<bitcoin::BlockHash>;
+        fn get_best_block_hash(&self) -> Result<model::GetBestBlockHash> => into_model;
+        /// Gets a block by blockhash. Kept for compatibility; uses verbose set to 0.
+        fn get_block(&self, hash: BlockHash) -> Result<Block>;
+        /// Gets a block by blockhash with verbose set to 0.
+        fn get_block_verbose_zero(
+            &self,
+            hash: BlockHash,
+        ) -> Result<model::GetBlockVerboseZero> => into_model;
+        /// Gets a block by blockhash with verbose set to 1.
+        fn get_block_verbose_one(
+            &self,
+            hash: BlockHash,
+        ) -> Result<model::GetBlockVerboseOne> => into_model;
+        fn get_blockchain_info(&self) -> Result<model::GetBlockchainInfo> => into_model;
+        fn get_block_count(&self) -> Result<model::GetBlockCount> => into_model;
+        fn get_block_hash(&self, height: u64) -> Result<model::GetBlockHash> => into_model;
+        fn get_block_header(&self, hash: &BlockHash) -> Result<model::GetBlockHeader> => into_model;
+        fn get_block_header_verbose(
+            &self,
+            hash: &BlockHash,
+        ) -> Result<model::GetBlockHeaderVerbose> => into_model;
+        fn get_block_stats_by_height(
+            &self,
+            height: u32,
+        ) -> Result<model::GetBlockStats> => into_model;
+        fn get_block_stats_by_block_hash(
+            &self,
+            hash: &BlockHash,
+        ) -> Result<model::GetBlockStats> => into_model;
+        fn get_chain_tips(&self) -> Result<model::GetChainTips> => into_model;
+        fn get_chain_tx_stats(&self) -> Result<model::GetChainTxStats> => into_model;
+        fn get_difficulty(&self) -> Result<model::GetDifficulty> => into_model;
+        fn get_mempool_ancestors(
+            &self,
+            txid: Txid,
+        ) -> Result<model::GetMempoolAncestors> => into_model;
+        fn get_mempool_ancestors_verbose(
+            &self,
+            txid: Txid,
+        ) -> Result<model::GetMempoolAncestorsVerbose> => into_model;
+        fn get_mempool_descendants(
+            &self,
+            txid: Txid,
+        ) -> Result<model::GetMempoolDescendants> => into_model;
+        fn get_mempool_descendants_verbose(
+            &self,
+            txid: Txid,
+        ) -> Result<model::GetMempoolDescendantsVerbose> => into_model;
+        fn get_mempool_entry(&self, txid: Txid) -> Result<model::GetMempoolEntry> => into_model;
+        fn get_mempool_info(&self) -> Result<model::GetMempoolInfo> => into_model;
+        fn get_raw_mempool(&self) -> Result<model::GetRawMempool> => into_model;
+        fn get_raw_mempool_verbose(&self) -> Result<model::GetRawMempoolVerbose> => into_model;
+        fn get_tx_out(&self, txid: Txid, vout: u64) -> Result<model::GetTxOut> => into_model;
+        fn get_tx_out_proof(&self, txids: &[Txid]) -> Result<String>;
+        fn get_tx_out_set_info(&self) -> Result<model::GetTxOutSetInfo> => into_model;
+        fn precious_block(&self, hash: BlockHash) -> Result<()>;
+        /// Instructs the node to prune the blockchain up to a specified height or timestamp.
+        fn prune_blockchain(&self, target: u64) -> Result<types::v17::PruneBlockchain>;
+        /// Aborts an ongoing `scantxoutset` scan.
+        fn scan_tx_out_set_abort(&self) -> Result<types::v17::ScanTxOutSetAbort>;
+        /// Starts a scan of the UTXO set for specified descriptors.
+        fn scan_tx_out_set_start(
+            &self,
+            scan_objects: &[&str],
+        ) -> Result<model::ScanTxOutSetStart> => into_model;
+        fn verify_chain(&self) -> Result<types::v17::VerifyChain>;
+        fn verify_tx_out_proof(&self, proof: &str) -> Result<model::VerifyTxOutProof> => into_model;
+    }
+}
+
+define_rpc_trait! {
+    /// Methods found under the `== Mining ==` section of the API docs of Bitcoin Core.
+    pub trait MiningRpc {
+        fn get_mining_info(&self) -> Result<model::GetMiningInfo> => into_model;
+        fn get_network_hash_ps(&self) -> Result<f64>;
+        fn prioritise_transaction(
+            &self,
+            txid: &Txid,
+            fee_delta: bitcoin::SignedAmount,
+        ) -> Result<bool>;
+        fn submit_block(&self, block: &Block) -> Result<()>;
+    }
+}
+
+define_rpc_trait! {
+    /// Methods found under the `== Network ==` section of the API docs of Bitcoin Core.
+    pub trait NetworkRpc {
+        fn add_node(&self, node: &str, command: v17::AddNodeCommand) -> Result<()>;
+        fn clear_banned(&self) -> Result<()>;
+        fn disconnect_node(&self, address: &str) -> Result<()>;
+        fn get_added_node_info(&self) -> Result<types::v17::GetAddedNodeInfo>;
+        fn get_connection_count(&self) -> Result<types::v17::GetConnectionCount>;
+        fn get_net_totals(&self) -> Result<types::v17::GetNetTotals>;
+        fn get_network_info(&self) -> Result<model::GetNetworkInfo> => into_model;
+        fn ping(&self) -> Result<()>;
+        fn set_ban(&self, subnet: &str, command: v17::SetBanCommand) -> Result<()>;
+        fn set_network_active(&self, state: bool) -> Result<types::v17::SetNetworkActive>;
+    }
+}
+
+define_rpc_trait! {
+    /// Methods found under the `== Rawtransactions ==` section of the API docs of Bitcoin Core.
+    pub trait RawTransactionsRpc {
+        fn combine_psbt(&self, txs: &[bitcoin::Psbt]) -> Result<model::CombinePsbt> => into_model;
+        fn combine_raw_transaction(
+            &self,
+            txs: &[bitcoin::Transaction],
+        ) -> Result<model::CombineRawTransaction> => into_model;
+        fn convert_to_psbt(
+            &self,
+            tx: &bitcoin::Transaction,
+        ) -> Result<model::ConvertToPsbt> => into_model;
+        fn create_psbt(
+            &self,
+            inputs: &[v17::Input],
+            outputs: &[v17::Output],
+        ) -> Result<model::CreatePsbt> => into_model;
+        fn create_raw_transaction(
+            &self,
+            inputs: &[v17::Input],
+            outputs: &[v17::Output],
+        ) -> Result<model::CreateRawTransaction> => into_model;
+        fn decode_psbt(&self, psbt: &str) -> Result<model::DecodePsbt> => into_model;
+        fn decode_raw_transaction(
+            &self,
+            tx: &bitcoin::Transaction,
+        ) -> Result<model::DecodeRawTransaction> => into_model;
+        fn decode_script(&self, script: &str) -> Result<model::DecodeScript> => into_model;
+        fn finalize_psbt(&self, psbt: &bitcoin::Psbt) -> Result<model::FinalizePsbt> => into_model;
+        fn fund_raw_transaction(
+            &self,
+            tx: &bitcoin::Transaction,
+        ) -> Result<model::FundRawTransaction> => into_model;
+        fn get_raw_transaction(
+            &self,
+            txid: bitcoin::Txid,
+        ) -> Result<model::GetRawTransaction> => into_model;
+        fn get_raw_transaction_verbose(
+            &self,
+            txid: Txid,
+        ) -> Result<model::GetRawTransactionVerbose> => into_model;
+        fn send_raw_transaction(
+            &self,
+            tx: &bitcoin::Transaction,
+        ) -> Result<model::SendRawTransaction> => into_model;
+        fn sign_raw_transaction(
+            &self,
+            tx: &bitcoin::Transaction,
+        ) -> Result<model::SignRawTransaction> => into_model;
+        fn sign_raw_transaction_with_key(
+            &self,
+            tx: &bitcoin::Transaction,
+            keys: &[bitcoin::PrivateKey],
+        ) -> Result<model::SignRawTransaction> => into_model;
+        fn test_mempool_accept(
+            &self,
+            txs: &[bitcoin::Transaction],
+        ) -> Result<model::TestMempoolAccept> => into_model;
+    }
+}
+
+define_rpc_trait! {
+    /// Methods found under the `== Wallet ==` section of the API docs of Bitcoin Core.
+    pub trait WalletRpc {
+        fn abandon_transaction(&self, txid: Txid) -> Result<()>;
+        fn abort_rescan(&self) -> Result<types::v17::AbortRescan>;
+        fn backup_wallet(&self, destination: &Path) -> Result<()>;
+        fn bump_fee(&self, txid: Txid) -> Result<model::BumpFee> => into_model;
+        /// Calls `createwallet` with `wallet` as the only argument.
+        ///
+        /// In v23 and later this creates a descriptor wallet. Use `create_legacy_wallet` to create
+        /// a legacy wallet.
+        fn create_wallet(&self, wallet: &str) -> Result<model::CreateWallet> => into_model;
+        fn encrypt_wallet(&self, passphrase: &str) -> Result<types::v17::EncryptWallet>;
+        fn get_addresses_by_label(
+            &self,
+            label: &str,
+        ) -> Result<model::GetAddressesByLabel> => into_model;
+        fn get_address_info(
+            &self,
+            address: &Address,
+        ) -> Result<model::GetAddressInfo> => into_model;
+        fn get_balance(&self) -> Result<model::GetBalance> => into_model;
+        /// Gets a new address from `bitcoind` and parses it assuming its correct.
+        fn new_address(&self) -> Result<bitcoin::Address>;
+        fn new_address_with_label(
+            &self,
+            label: &str,
+        ) -> Result<bitcoin::Address<bitcoin::address::NetworkUnchecked>>;
+        fn get_raw_change_address(&self) -> Result<model::GetRawChangeAddress> => into_model;
+        fn get_received_by_address(
+            &self,
+            address: &Address<NetworkChecked>,
+        ) -> Result<model::GetReceivedByAddress> => into_model;
+        fn get_transaction(&self, txid: Txid) -> Result<model::GetTransaction> => into_model;
+        fn get_wallet_info(&self) -> Result<model::GetWalletInfo> => into_model;
+        fn import_pruned_funds(&self, raw_transaction: &str, tx_out_proof: &str) -> Result<()>;
+        fn key_pool_refill(&self) -> Result<()>;
+        fn list_address_groupings(&self) -> Result<model::ListAddressGroupings> => into_model;
+        fn list_labels(&self) -> Result<types::v17::ListLabels>;
+        fn list_lock_unspent(&self) -> Result<model::ListLockUnspent> => into_model;
+        fn list_received_by_address(&self) -> Result<model::ListReceivedByAddress> => into_model;
+        fn list_since_block(&self) -> Result<model::ListSinceBlock> => into_model;
+        fn list_transactions(&self) -> Result<model::ListTransactions> => into_model;
+        fn list_unspent(&self) -> Result<model::ListUnspent> => into_model;
+        fn list_wallets(&self) -> Result<model::ListWallets> => into_model;
+        fn load_wallet(&self, wallet: &str) -> Result<model::LoadWallet> => into_model;
+        /// Lock the given list of transaction outputs. Returns true on success.
+        ///
+        /// This wraps Core RPC: `lockunspent false [{"txid":"..","vout":n},...]`.
+        fn lock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<types::v17::LockUnspent>;
+        /// Unlock the given list of transaction outputs. Returns true on success.
+        ///
+        /// This wraps Core RPC: `lockunspent true [{"txid":"..","vout":n},...]`.
+        fn unlock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<types::v17::LockUnspent>;
+        fn remove_pruned_funds(&self, txid: Txid) -> Result<()>;
+        fn rescan_blockchain(&self) -> Result<model::RescanBlockchain> => into_model;
+        fn send_many(
+            &self,
+            amounts: BTreeMap<Address, Amount>,
+        ) -> Result<model::SendMany> => into_model;
+        fn send_to_address(
+            &self,
+            address: &Address<NetworkChecked>,
+            amount: Amount,
+        ) -> Result<model::SendToAddress> => into_model;
+        fn send_to_address_rbf(
+            &self,
+            address: &Address<NetworkChecked>,
+            amount: Amount,
+        ) -> Result<model::SendToAddress> => into_model;
+        fn set_tx_fee(&self, fee_rate: bitcoin::FeeRate) -> Result<types::v17::SetTxFee>;
+        fn sign_message(
+            &self,
+            address: &Address,
+            message: &str,
+        ) -> Result<model::SignMessage> => into_model;
+        fn sign_raw_transaction_with_wallet(
+            &self,
+            tx: &bitcoin::Transaction,
+        ) -> Result<model::SignRawTransaction> => into_model;
+        fn wallet_create_funded_psbt(
+            &self,
+            inputs: Vec<WalletCreateFundedPsbtInput>,
+            outputs: Vec<BTreeMap<Address, Amount>>,
+        ) -> Result<model::WalletCreateFundedPsbt> => into_model;
+        fn wallet_lock(&self) -> Result<()>;
+        fn wallet_passphrase(&self, passphrase: &str, timeout: u64) -> Result<()>;
+        fn wallet_passphrase_change(
+            &self,
+            old_passphrase: &str,
+            new_passphrase: &str,
+        ) -> Result<()>;
+        fn wallet_process_psbt(
+            &self,
+            psbt: &bitcoin::Psbt,
+        ) -> Result<model::WalletProcessPsbt> => into_model;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::client_sync::mock::{response, MockServer};
+
+    /// Code written once against the trait, for any client.
+    fn block_count<T: BlockchainRpc>(client: &T) -> Result<model::GetBlockCount> {
+        client.get_block_count()
+    }
+
+    fn server() -> MockServer {
+        MockServer::start(|request| {
+            let result = match request.body["method"].as_str() {
+                Some("getnetworkinfo") => json!({ "version": 290000 }),
+                Some("getblockcount") => json!(101),
+                _ => return (500, serde_json::Value::Null),
+            };
+            (200, response(&request.body, result))
+        })
+    }
+
+    #[test]
+    fn generic_blockchain_rpc_versioned_client() {
+        let server = server();
+        let client = v29::Client::new(server.url());
+
+        assert_eq!(block_count(&client).unwrap(), model::GetBlockCount(101));
+    }
+
+    #[test]
+    fn generic_blockchain_rpc_auto_client() {
+        let server = server();
+        let client = auto::Client::new(server.url()).unwrap();
+
+        assert_eq!(block_count(&client).unwrap(), model::GetBlockCount(101));
+        let methods =
+            server.requests().iter().map(|r| r.body["method"].clone()).collect::<Vec<_>>();
+        assert_eq!(methods, ["getnetworkinfo", "getblockcount"]);
+    }
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! The arguments of JSON-RPC methods.
+//!
+//! Shared by the methods on the clients and the queueing methods on a [`Batch`], so a call sends
+//! the same arguments whether or not it is batched.
+//!
+//! [`Batch`]: crate::client_sync::batch::Batch
+
+use bitcoin::{BlockHash, Txid};
+use serde_json::Value;
+
+/// Arguments of `getblockhash`.
+pub fn get_block_hash(height: u64) -> Vec<Value> { vec![height.into()] }
+
+/// Arguments of `getblockheader` returning the hex encoded header.
+pub fn get_block_header(hash: &BlockHash) -> Vec<Value> {
+    vec![hash.to_string().into(), false.into()]
+}
+
+/// Arguments of `getblockheader` returning the header as a JSON object.
+pub fn get_block_header_verbose(hash: &BlockHash) -> Vec<Value> { vec![hash.to_string().into()] }
+
+/// Arguments of `getblock` with verbosity 0.
+pub fn get_block_verbose_zero(hash: BlockHash) -> Vec<Value> {
+    vec![hash.to_string().into(), 0.into()]
+}
+
+/// Arguments of `getblock` with verbosity 1.
+pub fn get_block_verbose_one(hash: BlockHash) -> Vec<Value> {
+    vec![hash.to_string().into(), 1.into()]
+}
+
+/// Arguments of `getblockstats` for the block at `height`.
+pub fn get_block_stats_by_height(height: u32) -> Vec<Value> { vec![height.into()] }
+
+/// Arguments of `getblockstats` for the block with hash `hash`.
+pub fn get_block_stats_by_block_hash(hash: &BlockHash) -> Vec<Value> {
+    vec![hash.to_string().into()]
+}
+
+/// Arguments of `getmempoolentry`.
+pub fn get_mempool_entry(txid: Txid) -> Vec<Value> { vec![txid.to_string().into()] }
+
+/// Arguments of `gettxout`.
+pub fn get_tx_out(txid: Txid, vout: u64) -> Vec<Value> {
+    vec![txid.to_string().into(), vout.into()]
+}
+
+/// Arguments of `getrawtransaction` returning the hex encoded transaction.
+pub fn get_raw_transaction(txid: Txid) -> Vec<Value> { vec![txid.to_string().into(), false.into()] }
+
+/// Arguments of `getrawtransaction` returning the transaction as a JSON object.
+pub fn get_raw_transaction_verbose(txid: Txid) -> Vec<Value> {
+    vec![txid.to_string().into(), true.into()]
+}
+
+/// Arguments of `gettransaction`.
+pub fn get_transaction(txid: Txid) -> Vec<Value> { vec![txid.to_string().into()] }
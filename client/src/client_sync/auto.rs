@@ -0,0 +1,1611 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! A JSON-RPC client for any supported version of Bitcoin Core, detected at runtime.
+//!
+//! The version specific clients require picking the Core version at compile time and return the
+//! version specific JSON types. The [`Client`] in this module asks the server for its version when
+//! it is created, then uses the matching version specific client for every call and converts the
+//! results into the version nonspecific [`model`] types.
+//!
+//! Results with no `model` type are returned as the version specific type if it is the same in every
+//! version supporting the method. Methods whose arguments or results otherwise differ between Core
+//! versions are not provided, these can still be called with [`Client::call`]:
+//!
+//! - `getdescriptorinfo`, `getnodeaddresses`, `getpeerinfo`, `getrawaddrman`, `getrpcinfo`,
+//!   `getzmqnotifications`, `listbanned`, `listdescriptors`, `listwalletdir` and `logging` return
+//!   a different type in some versions and have no `model` type.
+//! - `importmulti` returns a different type from `v18` and is removed in `v30`.
+//! - `savemempool` returns nothing before `v23` and the file name since.
+//! - `addconnection` is a hidden, test only method whose arguments change in `v27`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use bitcoin::address::{Address, NetworkChecked};
+use bitcoin::{sign_message, Amount, Block, BlockHash, PublicKey, Txid};
+use serde::Deserialize;
+
+use crate::client_sync::config::RpcConfig;
+use crate::client_sync::error::UnexpectedServerVersionError;
+use crate::client_sync::options::{
+    FundRawTransactionOptions, GetBalanceOptions, ListTransactionsOptions, ListUnspentQuery,
+    SendManyOptions, SendToAddressOptions, WalletCreateFundedPsbtOptions,
+};
+use crate::client_sync::transport::{Connection, Credentials};
+use crate::client_sync::{
+    into_json, v17, v18, v19, v20, v21, v22, v23, v24, v25, v26, v27, v28, v29, v30, AddressType,
+    Auth, Error, Result, WalletCreateFundedPsbtInput,
+};
+use crate::types::{self, model};
+
+/// Calls a method on the version specific client of the detected server version.
+///
+/// Pass `into_model` to convert the result into a `model` type. Methods not available in every
+/// supported version need the versions implementing them listed after a `;`.
+macro_rules! dispatch {
+    ($self:ident, $method:ident $args:tt) => {
+        dispatch!(@all $self, $method $args, raw)
+    };
+    ($self:ident, $method:ident $args:tt, into_model) => {
+        dispatch!(@all $self, $method $args, into_model)
+    };
+    ($self:ident, $method:ident $args:tt; $($version:ident),+) => {
+        dispatch!(@match $self, $method $args, raw; $($version),+)
+    };
+    ($self:ident, $method:ident $args:tt, into_model; $($version:ident),+) => {
+        dispatch!(@match $self, $method $args, into_model; $($version),+)
+    };
+    (@all $self:ident, $method:ident $args:tt, $convert:ident) => {
+        dispatch!(
+            @match $self, $method $args, $convert;
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    };
+    (@match $self:ident, $method:ident $args:tt, $convert:ident; $($version:ident),+) => {
+        match $self.inner {
+            $(Inner::$version(ref client) => dispatch!(@$convert client.$method $args?),)+
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::UnsupportedMethod {
+                method: stringify!($method),
+                version: $self.version,
+            }),
+        }
+    };
+    (@raw $json:expr) => {
+        Ok($json)
+    };
+    (@into_model $json:expr) => {
+        IntoModelResult::into_model_result($json.into_model())
+    };
+}
+
+/// The version specific client for the server version.
+enum Inner {
+    V17(v17::Client),
+    V18(v18::Client),
+    V19(v19::Client),
+    V20(v20::Client),
+    V21(v21::Client),
+    V22(v22::Client),
+    V23(v23::Client),
+    V24(v24::Client),
+    V25(v25::Client),
+    V26(v26::Client),
+    V27(v27::Client),
+    V28(v28::Client),
+    V29(v29::Client),
+    V30(v30::Client),
+}
+
+/// Client implements a JSON-RPC client for any supported version of the Bitcoin Core daemon.
+///
+/// The server version is detected when the client is created. Servers newer than the most recent
+/// supported version, i.e. `v30` or later, are used with the `v30` client. Methods or results
+/// changed by such a version may fail even though the client was created.
+pub struct Client {
+    inner: Inner,
+    /// The server version, as returned by `getnetworkinfo`.
+    version: usize,
+    /// The wallet this client is scoped to, if it is a wallet handle.
+    wallet: Option<String>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> core::fmt::Result {
+        write!(f, "corepc_client::client_sync::auto::Client(version: {})", self.version)
+    }
+}
+
+impl Client {
+    /// Creates a client to a bitcoind JSON-RPC server without authentication.
+    ///
+    /// Calls `getnetworkinfo` to detect the server version.
+    pub fn new(url: &str) -> Result<Self> { Self::detect(url, Auth::None) }
+
+    /// Creates a client to a bitcoind JSON-RPC server with authentication.
+    ///
+    /// Calls `getnetworkinfo` to detect the server version.
+    pub fn new_with_auth(url: &str, auth: Auth) -> Result<Self> {
+        if matches!(auth, Auth::None) {
+            return Err(Error::MissingUserPassword);
+        }
+        Self::detect(url, auth)
+    }
+
+    /// Creates a client for the node with data directory `datadir`.
+    ///
+    /// The URL and credentials are read from the config file as `bitcoin-cli` does, see
+    /// [`RpcConfig::from_datadir`]. Calls `getnetworkinfo` to detect the server version.
+    pub fn from_datadir(
+        datadir: &Path,
+        conf: Option<&Path>,
+        network: Option<bitcoin::Network>,
+    ) -> Result<Self> {
+        let config = RpcConfig::from_datadir(datadir, conf, network)?;
+        Self::detect(&config.url, config.auth)
+    }
+
+    fn detect(url: &str, auth: Auth) -> Result<Self> {
+        /// The part of `getnetworkinfo` which is the same in every version.
+        #[derive(Deserialize)]
+        struct NetworkInfo {
+            version: usize,
+        }
+
+        // The probe and the version specific client share one connection, and so the credentials.
+        let connection = Arc::new(Connection::new(url, Credentials::new(auth)?));
+        let probe = v17::Client::from_connection(Arc::clone(&connection));
+        let version = probe.call::<NetworkInfo>("getnetworkinfo", &[])?.version;
+
+        let inner = match version / 10_000 {
+            17 => Inner::V17(probe),
+            18 => Inner::V18(v18::Client::from_connection(connection)),
+            19 => Inner::V19(v19::Client::from_connection(connection)),
+            20 => Inner::V20(v20::Client::from_connection(connection)),
+            21 => Inner::V21(v21::Client::from_connection(connection)),
+            22 => Inner::V22(v22::Client::from_connection(connection)),
+            23 => Inner::V23(v23::Client::from_connection(connection)),
+            24 => Inner::V24(v24::Client::from_connection(connection)),
+            25 => Inner::V25(v25::Client::from_connection(connection)),
+            26 => Inner::V26(v26::Client::from_connection(connection)),
+            27 => Inner::V27(v27::Client::from_connection(connection)),
+            28 => Inner::V28(v28::Client::from_connection(connection)),
+            29 => Inner::V29(v29::Client::from_connection(connection)),
+            30.. => Inner::V30(v30::Client::from_connection(connection)),
+            _ => {
+                let expected = (17..=30).map(|major| major * 10_000).collect();
+                return Err(UnexpectedServerVersionError { got: version, expected }.into());
+            }
+        };
+        Ok(Client { inner, version, wallet: None })
+    }
+
+    /// Returns the server version detected when the client was created, e.g. `290000`.
+    pub fn server_version(&self) -> usize { self.version }
+
+    /// Call an RPC `method` with given `args` list.
+    pub fn call<T: for<'a> serde::de::Deserialize<'a>>(
+        &self,
+        method: &str,
+        args: &[serde_json::Value],
+    ) -> Result<T> {
+        dispatch!(self, call(method, args))
+    }
+
+    /// Call an RPC `method` with given named `args`.
+    pub fn call_named<T: for<'a> serde::de::Deserialize<'a>>(
+        &self,
+        method: &str,
+        args: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<T> {
+        dispatch!(self, call_named(method, args))
+    }
+
+    /// Returns a handle for calling wallet methods on the wallet `name`.
+    ///
+    /// The handle uses the same server and credentials as this client, creating one is cheap. If
+    /// the wallet is not loaded when a method is called on the handle, the wallet is loaded and the
+    /// call retried.
+    pub fn wallet(&self, name: &str) -> Self {
+        macro_rules! wallet {
+            ($($version:ident),+) => {
+                match self.inner {
+                    $(Inner::$version(ref client) => Inner::$version(client.wallet(name)),)+
+                }
+            };
+        }
+        let inner = wallet!(V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30);
+        Client { inner, version: self.version, wallet: Some(name.to_owned()) }
+    }
+
+    /// Sets a function called with the path of the cookie file after new credentials were read
+    /// from it.
+    ///
+    /// Applies to this client and all wallet handles created from it.
+    pub fn on_cookie_reload<F>(&self, f: F)
+    where
+        F: Fn(&Path) + Send + Sync + 'static,
+    {
+        macro_rules! on_cookie_reload {
+            ($($version:ident),+) => {
+                match self.inner {
+                    $(Inner::$version(ref client) => client.on_cookie_reload(f),)+
+                }
+            };
+        }
+        on_cookie_reload!(V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Returns the name of the wallet this client is scoped to, if it is a wallet handle.
+    pub fn wallet_name(&self) -> Option<&str> { self.wallet.as_deref() }
+
+    /// Returns the names of the currently loaded wallets.
+    pub fn loaded_wallets(&self) -> Result<Vec<String>> { dispatch!(self, loaded_wallets()) }
+
+    /// Loads the wallet `name`, unless it is already loaded, and returns a handle for it.
+    pub fn open_wallet(&self, name: &str) -> Result<Self> {
+        if !self.loaded_wallets()?.iter().any(|w| w == name) {
+            let _: serde_json::Value = self.call("loadwallet", &[name.into()])?;
+        }
+        Ok(self.wallet(name))
+    }
+
+    /// Creates the wallet `name`, with default options, and returns a handle for it.
+    pub fn new_wallet(&self, name: &str) -> Result<Self> {
+        let _: serde_json::Value = self.call("createwallet", &[name.into()])?;
+        Ok(self.wallet(name))
+    }
+
+    // == Blockchain ==
+    /// Gets the blockhash of the current chain tip.
+    pub fn best_block_hash(&self) -> Result<bitcoin::BlockHash> {
+        dispatch!(self, best_block_hash())
+    }
+
+    pub fn get_best_block_hash(&self) -> Result<model::GetBestBlockHash> {
+        dispatch!(self, get_best_block_hash(), into_model)
+    }
+
+    /// Gets a block by blockhash. Kept for compatibility; uses verbose set to 0.
+    pub fn get_block(&self, hash: BlockHash) -> Result<Block> { dispatch!(self, get_block(hash)) }
+
+    /// Gets a block by blockhash with verbose set to 0.
+    pub fn get_block_verbose_zero(&self, hash: BlockHash) -> Result<model::GetBlockVerboseZero> {
+        dispatch!(self, get_block_verbose_zero(hash), into_model)
+    }
+
+    /// Gets a block by blockhash with verbose set to 1.
+    pub fn get_block_verbose_one(&self, hash: BlockHash) -> Result<model::GetBlockVerboseOne> {
+        dispatch!(self, get_block_verbose_one(hash), into_model)
+    }
+
+    pub fn get_blockchain_info(&self) -> Result<model::GetBlockchainInfo> {
+        dispatch!(self, get_blockchain_info(), into_model)
+    }
+
+    pub fn get_block_count(&self) -> Result<model::GetBlockCount> {
+        dispatch!(self, get_block_count(), into_model)
+    }
+
+    pub fn get_block_hash(&self, height: u64) -> Result<model::GetBlockHash> {
+        dispatch!(self, get_block_hash(height), into_model)
+    }
+
+    pub fn get_block_header(&self, hash: &BlockHash) -> Result<model::GetBlockHeader> {
+        dispatch!(self, get_block_header(hash), into_model)
+    }
+
+    pub fn get_block_header_verbose(
+        &self,
+        hash: &BlockHash,
+    ) -> Result<model::GetBlockHeaderVerbose> {
+        dispatch!(self, get_block_header_verbose(hash), into_model)
+    }
+
+    pub fn get_block_stats_by_height(&self, height: u32) -> Result<model::GetBlockStats> {
+        dispatch!(self, get_block_stats_by_height(height), into_model)
+    }
+
+    pub fn get_block_stats_by_block_hash(&self, hash: &BlockHash) -> Result<model::GetBlockStats> {
+        dispatch!(self, get_block_stats_by_block_hash(hash), into_model)
+    }
+
+    pub fn get_chain_tips(&self) -> Result<model::GetChainTips> {
+        dispatch!(self, get_chain_tips(), into_model)
+    }
+
+    pub fn get_chain_tx_stats(&self) -> Result<model::GetChainTxStats> {
+        dispatch!(self, get_chain_tx_stats(), into_model)
+    }
+
+    pub fn get_difficulty(&self) -> Result<model::GetDifficulty> {
+        dispatch!(self, get_difficulty(), into_model)
+    }
+
+    pub fn get_mempool_ancestors(&self, txid: Txid) -> Result<model::GetMempoolAncestors> {
+        dispatch!(self, get_mempool_ancestors(txid), into_model)
+    }
+
+    pub fn get_mempool_ancestors_verbose(
+        &self,
+        txid: Txid,
+    ) -> Result<model::GetMempoolAncestorsVerbose> {
+        dispatch!(self, get_mempool_ancestors_verbose(txid), into_model)
+    }
+
+    pub fn get_mempool_descendants(&self, txid: Txid) -> Result<model::GetMempoolDescendants> {
+        dispatch!(self, get_mempool_descendants(txid), into_model)
+    }
+
+    pub fn get_mempool_descendants_verbose(
+        &self,
+        txid: Txid,
+    ) -> Result<model::GetMempoolDescendantsVerbose> {
+        dispatch!(self, get_mempool_descendants_verbose(txid), into_model)
+    }
+
+    pub fn get_mempool_entry(&self, txid: Txid) -> Result<model::GetMempoolEntry> {
+        dispatch!(self, get_mempool_entry(txid), into_model)
+    }
+
+    pub fn get_mempool_info(&self) -> Result<model::GetMempoolInfo> {
+        dispatch!(self, get_mempool_info(), into_model)
+    }
+
+    pub fn get_raw_mempool(&self) -> Result<model::GetRawMempool> {
+        dispatch!(self, get_raw_mempool(), into_model)
+    }
+
+    pub fn get_raw_mempool_verbose(&self) -> Result<model::GetRawMempoolVerbose> {
+        dispatch!(self, get_raw_mempool_verbose(), into_model)
+    }
+
+    pub fn get_tx_out(&self, txid: Txid, vout: u64) -> Result<model::GetTxOut> {
+        dispatch!(self, get_tx_out(txid, vout), into_model)
+    }
+
+    pub fn get_tx_out_proof(&self, txids: &[Txid]) -> Result<String> {
+        dispatch!(self, get_tx_out_proof(txids))
+    }
+
+    pub fn get_tx_out_set_info(&self) -> Result<model::GetTxOutSetInfo> {
+        dispatch!(self, get_tx_out_set_info(), into_model)
+    }
+
+    pub fn precious_block(&self, hash: BlockHash) -> Result<()> {
+        dispatch!(self, precious_block(hash))
+    }
+
+    /// Instructs the node to prune the blockchain up to a specified height or timestamp.
+    pub fn prune_blockchain(&self, target: u64) -> Result<types::v17::PruneBlockchain> {
+        dispatch!(self, prune_blockchain(target))
+    }
+
+    /// Aborts an ongoing `scantxoutset` scan.
+    pub fn scan_tx_out_set_abort(&self) -> Result<types::v17::ScanTxOutSetAbort> {
+        dispatch!(self, scan_tx_out_set_abort())
+    }
+
+    /// Starts a scan of the UTXO set for specified descriptors.
+    pub fn scan_tx_out_set_start(&self, scan_objects: &[&str]) -> Result<model::ScanTxOutSetStart> {
+        dispatch!(self, scan_tx_out_set_start(scan_objects), into_model)
+    }
+
+    pub fn scan_tx_out_set_status(&self) -> Result<Option<types::v17::ScanTxOutSetStatus>> {
+        dispatch!(self, scan_tx_out_set_status())
+    }
+
+    pub fn verify_chain(&self) -> Result<types::v17::VerifyChain> {
+        dispatch!(self, verify_chain())
+    }
+
+    pub fn verify_tx_out_proof(&self, proof: &str) -> Result<model::VerifyTxOutProof> {
+        dispatch!(self, verify_tx_out_proof(proof), into_model)
+    }
+
+    /// Only supported by Core `v19` and later.
+    pub fn get_block_filter(&self, block: BlockHash) -> Result<model::GetBlockFilter> {
+        dispatch!(
+            self, get_block_filter(block), into_model;
+            V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn get_raw_mempool_sequence(&self) -> Result<model::GetRawMempoolSequence> {
+        dispatch!(
+            self, get_raw_mempool_sequence(), into_model;
+            V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v23` and later.
+    pub fn get_block_from_peer(&self, blockhash: BlockHash, peer_id: u32) -> Result<()> {
+        dispatch!(
+            self, get_block_from_peer(blockhash, peer_id);
+            V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v23` and later.
+    pub fn get_deployment_info(&self, blockhash: &BlockHash) -> Result<model::GetDeploymentInfo> {
+        dispatch!(
+            self, get_deployment_info(blockhash), into_model;
+            V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v24` and later.
+    pub fn get_tx_spending_prevout(
+        &self,
+        outputs: &[bitcoin::OutPoint],
+    ) -> Result<model::GetTxSpendingPrevout> {
+        dispatch!(
+            self, get_tx_spending_prevout(outputs), into_model;
+            V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Aborts an ongoing `scanblocks` scan.
+    ///
+    /// Only supported by Core `v25` and later.
+    pub fn scan_blocks_abort(&self) -> Result<types::v25::ScanBlocksAbort> {
+        dispatch!(self, scan_blocks_abort(); V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Starts a scan of blocks for specified descriptors.
+    ///
+    /// Only supported by Core `v25` and later.
+    pub fn scan_blocks_start(&self, scan_objects: &[&str]) -> Result<model::ScanBlocksStart> {
+        dispatch!(self, scan_blocks_start(scan_objects), into_model; V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v25` and later.
+    pub fn scan_blocks_status(&self) -> Result<Option<types::v25::ScanBlocksStatus>> {
+        dispatch!(self, scan_blocks_status(); V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v26` and later.
+    pub fn get_chain_states(&self) -> Result<model::GetChainStates> {
+        dispatch!(self, get_chain_states(), into_model; V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v26` and later.
+    pub fn import_mempool(&self, filepath: &str) -> Result<()> {
+        dispatch!(self, import_mempool(filepath); V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v26` to `v26`.
+    pub fn load_tx_out_set(&self, path: &str) -> Result<model::LoadTxOutSet> {
+        dispatch!(self, load_tx_out_set(path), into_model; V26)
+    }
+
+    /// Writes the UTXO set at the chain tip to `path`.
+    ///
+    /// Only supported by Core `v26` and later.
+    pub fn dump_tx_out_set(&self, path: &str) -> Result<model::DumpTxOutSet> {
+        match self.inner {
+            Inner::V29(_) | Inner::V30(_) =>
+                dispatch!(self, dump_tx_out_set(path, "latest"), into_model; V29, V30),
+            _ => dispatch!(self, dump_tx_out_set(path), into_model; V26, V27, V28),
+        }
+    }
+
+    /// Gets a block by blockhash with verbose set to 2.
+    ///
+    /// Only supported by Core `v29` and later.
+    pub fn get_block_verbose_two(&self, hash: BlockHash) -> Result<model::GetBlockVerboseTwo> {
+        dispatch!(self, get_block_verbose_two(hash), into_model; V29, V30)
+    }
+
+    /// Gets a block by blockhash with verbose set to 3.
+    ///
+    /// Only supported by Core `v29` and later.
+    pub fn get_block_verbose_three(&self, hash: BlockHash) -> Result<model::GetBlockVerboseThree> {
+        dispatch!(self, get_block_verbose_three(hash), into_model; V29, V30)
+    }
+
+    /// Only supported by Core `v29` and later.
+    pub fn get_descriptor_activity(&self) -> Result<model::GetDescriptorActivity> {
+        dispatch!(self, get_descriptor_activity(), into_model; V29, V30)
+    }
+
+    // == Control ==
+    pub fn get_memory_info(&self) -> Result<types::v17::GetMemoryInfoStats> {
+        dispatch!(self, get_memory_info())
+    }
+
+    pub fn help(&self) -> Result<String> { dispatch!(self, help()) }
+
+    pub fn stop(&self) -> Result<String> { dispatch!(self, stop()) }
+
+    pub fn uptime(&self) -> Result<u32> { dispatch!(self, uptime()) }
+
+    // == Generating ==
+    pub fn generate_to_address(
+        &self,
+        nblocks: usize,
+        address: &bitcoin::Address,
+    ) -> Result<model::GenerateToAddress> {
+        dispatch!(self, generate_to_address(nblocks, address), into_model)
+    }
+
+    /// Only supported by Core `v17` to `v18`.
+    pub fn generate(&self, nblocks: usize) -> Result<model::Generate> {
+        dispatch!(self, generate(nblocks), into_model; V17, V18)
+    }
+
+    /// Mines a block with `transactions` paying the coinbase to `output`, and submits it.
+    ///
+    /// Only supported by Core `v21` and later.
+    pub fn generate_block(
+        &self,
+        output: &str,
+        transactions: &[String],
+    ) -> Result<model::GenerateBlock> {
+        match self.inner {
+            Inner::V21(_) | Inner::V22(_) | Inner::V23(_) | Inner::V24(_) =>
+                dispatch!(self, generate_block(output, transactions), into_model; V21, V22, V23, V24),
+            _ => dispatch!(
+                self, generate_block(output, transactions, true), into_model;
+                V25, V26, V27, V28, V29, V30
+            ),
+        }
+    }
+
+    pub fn invalidate_block(&self, hash: BlockHash) -> Result<()> {
+        dispatch!(self, invalidate_block(hash))
+    }
+
+    /// Only supported by Core `v20` and later.
+    pub fn generate_to_descriptor(
+        &self,
+        nblocks: usize,
+        descriptor: &str,
+    ) -> Result<model::GenerateToDescriptor> {
+        dispatch!(
+            self, generate_to_descriptor(nblocks, descriptor), into_model;
+            V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    // == Hidden ==
+    /// # Panics
+    ///
+    /// * Panics if `conf_target` is outside the range [1, 1008].
+    pub fn estimate_raw_fee(&self, conf_target: u32) -> Result<model::EstimateRawFee> {
+        dispatch!(self, estimate_raw_fee(conf_target), into_model)
+    }
+
+    pub fn wait_for_block(&self, hash: &bitcoin::BlockHash) -> Result<model::WaitForBlock> {
+        dispatch!(self, wait_for_block(hash), into_model)
+    }
+
+    pub fn wait_for_block_height(&self, height: u64) -> Result<model::WaitForBlockHeight> {
+        dispatch!(self, wait_for_block_height(height), into_model)
+    }
+
+    pub fn wait_for_new_block(&self) -> Result<model::WaitForNewBlock> {
+        dispatch!(self, wait_for_new_block(), into_model)
+    }
+
+    pub fn sync_with_validation_interface_queue(&self) -> Result<()> {
+        dispatch!(self, sync_with_validation_interface_queue())
+    }
+
+    pub fn reconsider_block(&self, blockhash: bitcoin::BlockHash) -> Result<()> {
+        dispatch!(self, reconsider_block(blockhash))
+    }
+
+    /// Only supported by Core `v20` and later.
+    pub fn mock_scheduler(&self, delta_time: u64) -> Result<()> {
+        dispatch!(
+            self, mock_scheduler(delta_time);
+            V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn add_peer_address(&self, address: &str, port: u16) -> Result<types::v21::AddPeerAddress> {
+        dispatch!(
+            self, add_peer_address(address, port);
+            V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v29` and later.
+    pub fn get_orphan_txs(&self) -> Result<model::GetOrphanTxs> {
+        dispatch!(self, get_orphan_txs(), into_model; V29, V30)
+    }
+
+    /// Only supported by Core `v29` and later.
+    pub fn get_orphan_txs_verbosity_1(&self) -> Result<model::GetOrphanTxsVerboseOne> {
+        dispatch!(self, get_orphan_txs_verbosity_1(), into_model; V29, V30)
+    }
+
+    /// Only supported by Core `v29` and later.
+    pub fn get_orphan_txs_verbosity_2(&self) -> Result<model::GetOrphanTxsVerboseTwo> {
+        dispatch!(self, get_orphan_txs_verbosity_2(), into_model; V29, V30)
+    }
+
+    // == Mining ==
+    pub fn get_mining_info(&self) -> Result<model::GetMiningInfo> {
+        dispatch!(self, get_mining_info(), into_model)
+    }
+
+    /// Takes the `v29` request type for every version, older versions accept the same JSON object.
+    pub fn get_block_template(
+        &self,
+        request: &v29::TemplateRequest,
+    ) -> Result<model::GetBlockTemplate> {
+        let json: types::v17::GetBlockTemplate =
+            self.call("getblocktemplate", &[into_json(request)?])?;
+        IntoModelResult::into_model_result(json.into_model())
+    }
+
+    pub fn get_network_hash_ps(&self) -> Result<f64> { dispatch!(self, get_network_hash_ps()) }
+
+    pub fn prioritise_transaction(
+        &self,
+        txid: &Txid,
+        fee_delta: bitcoin::SignedAmount,
+    ) -> Result<bool> {
+        dispatch!(self, prioritise_transaction(txid, fee_delta))
+    }
+
+    pub fn submit_block(&self, block: &Block) -> Result<()> { dispatch!(self, submit_block(block)) }
+
+    /// Only supported by Core `v18` and later.
+    pub fn submit_header(&self, header: &bitcoin::block::Header) -> Result<()> {
+        dispatch!(
+            self, submit_header(header);
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v26` and later.
+    pub fn get_prioritised_transactions(&self) -> Result<model::GetPrioritisedTransactions> {
+        dispatch!(self, get_prioritised_transactions(), into_model; V26, V27, V28, V29, V30)
+    }
+
+    // == Network ==
+    pub fn add_node(&self, node: &str, command: v17::AddNodeCommand) -> Result<()> {
+        dispatch!(self, add_node(node, command))
+    }
+
+    pub fn clear_banned(&self) -> Result<()> { dispatch!(self, clear_banned()) }
+
+    pub fn disconnect_node(&self, address: &str) -> Result<()> {
+        dispatch!(self, disconnect_node(address))
+    }
+
+    pub fn get_added_node_info(&self) -> Result<types::v17::GetAddedNodeInfo> {
+        dispatch!(self, get_added_node_info())
+    }
+
+    pub fn get_connection_count(&self) -> Result<types::v17::GetConnectionCount> {
+        dispatch!(self, get_connection_count())
+    }
+
+    pub fn get_net_totals(&self) -> Result<types::v17::GetNetTotals> {
+        dispatch!(self, get_net_totals())
+    }
+
+    pub fn get_network_info(&self) -> Result<model::GetNetworkInfo> {
+        dispatch!(self, get_network_info(), into_model)
+    }
+
+    pub fn ping(&self) -> Result<()> { dispatch!(self, ping()) }
+
+    pub fn set_ban(&self, subnet: &str, command: v17::SetBanCommand) -> Result<()> {
+        dispatch!(self, set_ban(subnet, command))
+    }
+
+    pub fn set_network_active(&self, state: bool) -> Result<types::v17::SetNetworkActive> {
+        dispatch!(self, set_network_active(state))
+    }
+
+    /// Only supported by Core `v26` and later.
+    pub fn get_addr_man_info(&self) -> Result<types::v26::GetAddrManInfo> {
+        dispatch!(self, get_addr_man_info(); V26, V27, V28, V29, V30)
+    }
+
+    // == Rawtransactions ==
+    pub fn combine_psbt(&self, txs: &[bitcoin::Psbt]) -> Result<model::CombinePsbt> {
+        dispatch!(self, combine_psbt(txs), into_model)
+    }
+
+    pub fn combine_raw_transaction(
+        &self,
+        txs: &[bitcoin::Transaction],
+    ) -> Result<model::CombineRawTransaction> {
+        dispatch!(self, combine_raw_transaction(txs), into_model)
+    }
+
+    pub fn convert_to_psbt(&self, tx: &bitcoin::Transaction) -> Result<model::ConvertToPsbt> {
+        dispatch!(self, convert_to_psbt(tx), into_model)
+    }
+
+    pub fn create_psbt(
+        &self,
+        inputs: &[v17::Input],
+        outputs: &[v17::Output],
+    ) -> Result<model::CreatePsbt> {
+        dispatch!(self, create_psbt(inputs, outputs), into_model)
+    }
+
+    pub fn create_raw_transaction(
+        &self,
+        inputs: &[v17::Input],
+        outputs: &[v17::Output],
+    ) -> Result<model::CreateRawTransaction> {
+        dispatch!(self, create_raw_transaction(inputs, outputs), into_model)
+    }
+
+    pub fn decode_psbt(&self, psbt: &str) -> Result<model::DecodePsbt> {
+        dispatch!(self, decode_psbt(psbt), into_model)
+    }
+
+    pub fn decode_raw_transaction(
+        &self,
+        tx: &bitcoin::Transaction,
+    ) -> Result<model::DecodeRawTransaction> {
+        dispatch!(self, decode_raw_transaction(tx), into_model)
+    }
+
+    pub fn decode_script(&self, script: &str) -> Result<model::DecodeScript> {
+        dispatch!(self, decode_script(script), into_model)
+    }
+
+    pub fn finalize_psbt(&self, psbt: &bitcoin::Psbt) -> Result<model::FinalizePsbt> {
+        dispatch!(self, finalize_psbt(psbt), into_model)
+    }
+
+    pub fn fund_raw_transaction(
+        &self,
+        tx: &bitcoin::Transaction,
+    ) -> Result<model::FundRawTransaction> {
+        dispatch!(self, fund_raw_transaction(tx), into_model)
+    }
+
+    pub fn fund_raw_transaction_with_options(
+        &self,
+        tx: &bitcoin::Transaction,
+        options: &FundRawTransactionOptions,
+    ) -> Result<model::FundRawTransaction> {
+        dispatch!(self, fund_raw_transaction_with_options(tx, options), into_model)
+    }
+
+    pub fn get_raw_transaction(&self, txid: bitcoin::Txid) -> Result<model::GetRawTransaction> {
+        dispatch!(self, get_raw_transaction(txid), into_model)
+    }
+
+    pub fn get_raw_transaction_verbose(
+        &self,
+        txid: Txid,
+    ) -> Result<model::GetRawTransactionVerbose> {
+        dispatch!(self, get_raw_transaction_verbose(txid), into_model)
+    }
+
+    pub fn send_raw_transaction(
+        &self,
+        tx: &bitcoin::Transaction,
+    ) -> Result<model::SendRawTransaction> {
+        dispatch!(self, send_raw_transaction(tx), into_model)
+    }
+
+    pub fn sign_raw_transaction(
+        &self,
+        tx: &bitcoin::Transaction,
+    ) -> Result<model::SignRawTransaction> {
+        dispatch!(self, sign_raw_transaction(tx), into_model)
+    }
+
+    pub fn sign_raw_transaction_with_key(
+        &self,
+        tx: &bitcoin::Transaction,
+        keys: &[bitcoin::PrivateKey],
+    ) -> Result<model::SignRawTransaction> {
+        dispatch!(self, sign_raw_transaction_with_key(tx, keys), into_model)
+    }
+
+    pub fn test_mempool_accept(
+        &self,
+        txs: &[bitcoin::Transaction],
+    ) -> Result<model::TestMempoolAccept> {
+        dispatch!(self, test_mempool_accept(txs), into_model)
+    }
+
+    /// Only supported by Core `v26` and later.
+    pub fn submit_package(&self, package: &[bitcoin::Transaction]) -> Result<model::SubmitPackage> {
+        match self.inner {
+            Inner::V26(_) | Inner::V27(_) =>
+                dispatch!(self, submit_package(package), into_model; V26, V27),
+            _ => dispatch!(
+                self, submit_package(package, None, None), into_model;
+                V28, V29, V30
+            ),
+        }
+    }
+
+    /// Only supported by Core `v18` and later.
+    pub fn analyze_psbt(&self, psbt: &bitcoin::Psbt) -> Result<model::AnalyzePsbt> {
+        dispatch!(
+            self, analyze_psbt(psbt), into_model;
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v18` and later.
+    pub fn join_psbts(&self, psbts: &[bitcoin::Psbt]) -> Result<model::JoinPsbts> {
+        dispatch!(
+            self, join_psbts(psbts), into_model;
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v18` and later.
+    pub fn utxo_update_psbt(&self, psbt: &bitcoin::Psbt) -> Result<model::UtxoUpdatePsbt> {
+        dispatch!(
+            self, utxo_update_psbt(psbt), into_model;
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    // == Util ==
+    pub fn create_multisig(
+        &self,
+        nrequired: u32,
+        keys: Vec<PublicKey>,
+    ) -> Result<model::CreateMultisig> {
+        dispatch!(self, create_multisig(nrequired, keys), into_model)
+    }
+
+    pub fn estimate_smart_fee(&self, blocks: u32) -> Result<model::EstimateSmartFee> {
+        dispatch!(self, estimate_smart_fee(blocks), into_model)
+    }
+
+    pub fn sign_message_with_privkey(
+        &self,
+        privkey: &bitcoin::PrivateKey,
+        message: &str,
+    ) -> Result<model::SignMessageWithPrivKey> {
+        dispatch!(self, sign_message_with_privkey(privkey, message), into_model)
+    }
+
+    pub fn validate_address(
+        &self,
+        address: &Address<NetworkChecked>,
+    ) -> Result<model::ValidateAddress> {
+        dispatch!(self, validate_address(address), into_model)
+    }
+
+    pub fn verify_message(
+        &self,
+        address: &Address<NetworkChecked>,
+        signature: &sign_message::MessageSignature,
+        message: &str,
+    ) -> Result<types::v17::VerifyMessage> {
+        dispatch!(self, verify_message(address, signature, message))
+    }
+
+    /// Only supported by Core `v18` and later.
+    pub fn derive_addresses(&self, descriptor: &str) -> Result<model::DeriveAddresses> {
+        dispatch!(
+            self, derive_addresses(descriptor), into_model;
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn get_index_info(&self) -> Result<types::v21::GetIndexInfo> {
+        dispatch!(self, get_index_info(); V21, V22, V23, V24, V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v29` and later.
+    pub fn derive_addresses_multipath(
+        &self,
+        descriptor: &str,
+        range: (u32, u32),
+    ) -> Result<model::DeriveAddressesMultipath> {
+        dispatch!(self, derive_addresses_multipath(descriptor, range), into_model; V29, V30)
+    }
+
+    // == Wallet ==
+    pub fn abandon_transaction(&self, txid: Txid) -> Result<()> {
+        dispatch!(self, abandon_transaction(txid))
+    }
+
+    pub fn abort_rescan(&self) -> Result<types::v17::AbortRescan> {
+        dispatch!(self, abort_rescan())
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn add_multisig_address_with_keys(
+        &self,
+        nrequired: u32,
+        keys: Vec<PublicKey>,
+    ) -> Result<model::AddMultisigAddress> {
+        dispatch!(
+            self, add_multisig_address_with_keys(nrequired, keys), into_model;
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn add_multisig_address_with_addresses(
+        &self,
+        nrequired: u32,
+        keys: Vec<Address>,
+    ) -> Result<model::AddMultisigAddress> {
+        dispatch!(
+            self, add_multisig_address_with_addresses(nrequired, keys), into_model;
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    pub fn backup_wallet(&self, destination: &Path) -> Result<()> {
+        dispatch!(self, backup_wallet(destination))
+    }
+
+    pub fn bump_fee(&self, txid: Txid) -> Result<model::BumpFee> {
+        dispatch!(self, bump_fee(txid), into_model)
+    }
+
+    /// Calls `createwallet` with `wallet` as the only argument.
+    ///
+    /// In v23 and later this creates a descriptor wallet. Use `create_legacy_wallet` to create
+    /// a legacy wallet.
+    pub fn create_wallet(&self, wallet: &str) -> Result<model::CreateWallet> {
+        dispatch!(self, create_wallet(wallet), into_model)
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn dump_priv_key(&self, address: &Address) -> Result<model::DumpPrivKey> {
+        dispatch!(
+            self, dump_priv_key(address), into_model;
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn dump_wallet(&self, filename: &Path) -> Result<types::v17::DumpWallet> {
+        dispatch!(
+            self, dump_wallet(filename);
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    pub fn encrypt_wallet(&self, passphrase: &str) -> Result<types::v17::EncryptWallet> {
+        dispatch!(self, encrypt_wallet(passphrase))
+    }
+
+    pub fn get_addresses_by_label(&self, label: &str) -> Result<model::GetAddressesByLabel> {
+        dispatch!(self, get_addresses_by_label(label), into_model)
+    }
+
+    pub fn get_address_info(&self, address: &Address) -> Result<model::GetAddressInfo> {
+        dispatch!(self, get_address_info(address), into_model)
+    }
+
+    pub fn get_balance(&self) -> Result<model::GetBalance> {
+        dispatch!(self, get_balance(), into_model)
+    }
+
+    pub fn get_balance_with_options(
+        &self,
+        options: &GetBalanceOptions,
+    ) -> Result<model::GetBalance> {
+        dispatch!(self, get_balance_with_options(options), into_model)
+    }
+
+    /// Gets a new address from `bitcoind` and parses it assuming its correct.
+    pub fn new_address(&self) -> Result<bitcoin::Address> { dispatch!(self, new_address()) }
+
+    pub fn new_address_with_label(
+        &self,
+        label: &str,
+    ) -> Result<bitcoin::Address<bitcoin::address::NetworkUnchecked>> {
+        dispatch!(self, new_address_with_label(label))
+    }
+
+    /// Gets a new address of type `ty` from `bitcoind` and parses it assuming its correct.
+    pub fn new_address_with_type(&self, ty: AddressType) -> Result<bitcoin::Address> {
+        Ok(self.get_new_address(None, Some(ty))?.0.assume_checked())
+    }
+
+    /// Gets a new address - low level RPC call.
+    ///
+    /// `AddressType::Bech32m` is only supported by Core `v23` and later.
+    pub fn get_new_address(
+        &self,
+        label: Option<&str>,
+        ty: Option<AddressType>,
+    ) -> Result<model::GetNewAddress> {
+        match self.inner {
+            Inner::V17(_)
+            | Inner::V18(_)
+            | Inner::V19(_)
+            | Inner::V20(_)
+            | Inner::V21(_)
+            | Inner::V22(_) => {
+                let ty = ty.map(|ty| self.v17_address_type(ty)).transpose()?;
+                dispatch!(
+                    self, get_new_address(label, ty), into_model;
+                    V17, V18, V19, V20, V21, V22
+                )
+            }
+            _ => dispatch!(
+                self, get_new_address(label, ty), into_model;
+                V23, V24, V25, V26, V27, V28, V29, V30
+            ),
+        }
+    }
+
+    pub fn get_raw_change_address(&self) -> Result<model::GetRawChangeAddress> {
+        dispatch!(self, get_raw_change_address(), into_model)
+    }
+
+    pub fn get_received_by_address(
+        &self,
+        address: &Address<NetworkChecked>,
+    ) -> Result<model::GetReceivedByAddress> {
+        dispatch!(self, get_received_by_address(address), into_model)
+    }
+
+    pub fn get_transaction(&self, txid: Txid) -> Result<model::GetTransaction> {
+        dispatch!(self, get_transaction(txid), into_model)
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn get_unconfirmed_balance(&self) -> Result<model::GetUnconfirmedBalance> {
+        dispatch!(
+            self, get_unconfirmed_balance(), into_model;
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    pub fn get_wallet_info(&self) -> Result<model::GetWalletInfo> {
+        dispatch!(self, get_wallet_info(), into_model)
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn import_address(&self, address: &Address) -> Result<()> {
+        dispatch!(
+            self, import_address(address);
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn import_privkey(&self, privkey: &bitcoin::PrivateKey) -> Result<()> {
+        dispatch!(
+            self, import_privkey(privkey);
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    pub fn import_pruned_funds(&self, raw_transaction: &str, tx_out_proof: &str) -> Result<()> {
+        dispatch!(self, import_pruned_funds(raw_transaction, tx_out_proof))
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn import_pubkey(&self, pubkey: &bitcoin::PublicKey) -> Result<()> {
+        dispatch!(
+            self, import_pubkey(pubkey);
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn import_wallet(&self, filename: &Path) -> Result<()> {
+        dispatch!(
+            self, import_wallet(filename);
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    pub fn key_pool_refill(&self) -> Result<()> { dispatch!(self, key_pool_refill()) }
+
+    pub fn list_address_groupings(&self) -> Result<model::ListAddressGroupings> {
+        dispatch!(self, list_address_groupings(), into_model)
+    }
+
+    pub fn list_labels(&self) -> Result<types::v17::ListLabels> { dispatch!(self, list_labels()) }
+
+    pub fn list_lock_unspent(&self) -> Result<model::ListLockUnspent> {
+        dispatch!(self, list_lock_unspent(), into_model)
+    }
+
+    pub fn list_received_by_address(&self) -> Result<model::ListReceivedByAddress> {
+        dispatch!(self, list_received_by_address(), into_model)
+    }
+
+    pub fn list_since_block(&self) -> Result<model::ListSinceBlock> {
+        dispatch!(self, list_since_block(), into_model)
+    }
+
+    pub fn list_transactions(&self) -> Result<model::ListTransactions> {
+        dispatch!(self, list_transactions(), into_model)
+    }
+
+    pub fn list_transactions_with_options(
+        &self,
+        options: &ListTransactionsOptions,
+    ) -> Result<model::ListTransactions> {
+        dispatch!(self, list_transactions_with_options(options), into_model)
+    }
+
+    pub fn list_unspent(&self) -> Result<model::ListUnspent> {
+        dispatch!(self, list_unspent(), into_model)
+    }
+
+    pub fn list_unspent_with_query(&self, query: &ListUnspentQuery) -> Result<model::ListUnspent> {
+        dispatch!(self, list_unspent_with_query(query), into_model)
+    }
+
+    pub fn list_wallets(&self) -> Result<model::ListWallets> {
+        dispatch!(self, list_wallets(), into_model)
+    }
+
+    pub fn load_wallet(&self, wallet: &str) -> Result<model::LoadWallet> {
+        dispatch!(self, load_wallet(wallet), into_model)
+    }
+
+    /// Lock the given list of transaction outputs. Returns true on success.
+    ///
+    /// This wraps Core RPC: `lockunspent false [{"txid":"..","vout":n},...]`.
+    pub fn lock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<types::v17::LockUnspent> {
+        dispatch!(self, lock_unspent(outputs))
+    }
+
+    /// Unlock the given list of transaction outputs. Returns true on success.
+    ///
+    /// This wraps Core RPC: `lockunspent true [{"txid":"..","vout":n},...]`.
+    pub fn unlock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<types::v17::LockUnspent> {
+        dispatch!(self, unlock_unspent(outputs))
+    }
+
+    pub fn remove_pruned_funds(&self, txid: Txid) -> Result<()> {
+        dispatch!(self, remove_pruned_funds(txid))
+    }
+
+    pub fn rescan_blockchain(&self) -> Result<model::RescanBlockchain> {
+        dispatch!(self, rescan_blockchain(), into_model)
+    }
+
+    pub fn send_many(&self, amounts: BTreeMap<Address, Amount>) -> Result<model::SendMany> {
+        dispatch!(self, send_many(amounts), into_model)
+    }
+
+    pub fn send_many_with_options(
+        &self,
+        amounts: &BTreeMap<Address, Amount>,
+        options: &SendManyOptions,
+    ) -> Result<model::SendMany> {
+        dispatch!(self, send_many_with_options(amounts, options), into_model)
+    }
+
+    pub fn send_to_address(
+        &self,
+        address: &Address<NetworkChecked>,
+        amount: Amount,
+    ) -> Result<model::SendToAddress> {
+        dispatch!(self, send_to_address(address, amount), into_model)
+    }
+
+    pub fn send_to_address_rbf(
+        &self,
+        address: &Address<NetworkChecked>,
+        amount: Amount,
+    ) -> Result<model::SendToAddress> {
+        dispatch!(self, send_to_address_rbf(address, amount), into_model)
+    }
+
+    pub fn send_to_address_with_options(
+        &self,
+        address: &Address<NetworkChecked>,
+        amount: Amount,
+        options: &SendToAddressOptions,
+    ) -> Result<model::SendToAddress> {
+        dispatch!(self, send_to_address_with_options(address, amount, options), into_model)
+    }
+
+    /// Only supported by Core `v17` to `v29`.
+    pub fn set_hd_seed(&self) -> Result<()> {
+        dispatch!(
+            self, set_hd_seed();
+            V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29
+        )
+    }
+
+    pub fn set_tx_fee(&self, fee_rate: bitcoin::FeeRate) -> Result<types::v17::SetTxFee> {
+        dispatch!(self, set_tx_fee(fee_rate))
+    }
+
+    pub fn sign_message(&self, address: &Address, message: &str) -> Result<model::SignMessage> {
+        dispatch!(self, sign_message(address, message), into_model)
+    }
+
+    pub fn sign_raw_transaction_with_wallet(
+        &self,
+        tx: &bitcoin::Transaction,
+    ) -> Result<model::SignRawTransaction> {
+        dispatch!(self, sign_raw_transaction_with_wallet(tx), into_model)
+    }
+
+    /// Unloads the wallet `name`.
+    ///
+    /// Core versions before `v21` return no warnings.
+    pub fn unload_wallet(&self, name: &str) -> Result<model::UnloadWallet> {
+        match self.inner {
+            Inner::V17(_) | Inner::V18(_) | Inner::V19(_) | Inner::V20(_) => {
+                dispatch!(self, unload_wallet(name); V17, V18, V19, V20)?;
+                Ok(model::UnloadWallet { warnings: vec![] })
+            }
+            _ => dispatch!(
+                self, unload_wallet(name), into_model;
+                V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+            ),
+        }
+    }
+
+    pub fn wallet_create_funded_psbt(
+        &self,
+        inputs: Vec<WalletCreateFundedPsbtInput>,
+        outputs: Vec<BTreeMap<Address, Amount>>,
+    ) -> Result<model::WalletCreateFundedPsbt> {
+        dispatch!(self, wallet_create_funded_psbt(inputs, outputs), into_model)
+    }
+
+    pub fn wallet_create_funded_psbt_with_options(
+        &self,
+        inputs: Vec<WalletCreateFundedPsbtInput>,
+        outputs: Vec<BTreeMap<Address, Amount>>,
+        options: &WalletCreateFundedPsbtOptions,
+    ) -> Result<model::WalletCreateFundedPsbt> {
+        dispatch!(
+            self,
+            wallet_create_funded_psbt_with_options(inputs, outputs, options),
+            into_model
+        )
+    }
+
+    pub fn wallet_lock(&self) -> Result<()> { dispatch!(self, wallet_lock()) }
+
+    pub fn wallet_passphrase(&self, passphrase: &str, timeout: u64) -> Result<()> {
+        dispatch!(self, wallet_passphrase(passphrase, timeout))
+    }
+
+    pub fn wallet_passphrase_change(
+        &self,
+        old_passphrase: &str,
+        new_passphrase: &str,
+    ) -> Result<()> {
+        dispatch!(self, wallet_passphrase_change(old_passphrase, new_passphrase))
+    }
+
+    pub fn wallet_process_psbt(&self, psbt: &bitcoin::Psbt) -> Result<model::WalletProcessPsbt> {
+        dispatch!(self, wallet_process_psbt(psbt), into_model)
+    }
+
+    /// Only supported by Core `v18` and later.
+    pub fn get_received_by_label(&self, label: &str) -> Result<model::GetReceivedByLabel> {
+        dispatch!(
+            self, get_received_by_label(label), into_model;
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v18` and later.
+    pub fn list_received_by_label(&self) -> Result<model::ListReceivedByLabel> {
+        dispatch!(
+            self, list_received_by_label(), into_model;
+            V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v19` and later.
+    pub fn get_balances(&self) -> Result<model::GetBalances> {
+        dispatch!(
+            self, get_balances(), into_model;
+            V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v19` and later.
+    pub fn set_wallet_flag(&self, flag: &str) -> Result<types::v19::SetWalletFlag> {
+        dispatch!(
+            self, set_wallet_flag(flag);
+            V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Creates a wallet with descriptors=true (descriptor wallet).
+    ///
+    /// > createwallet "wallet_name" ( disable_private_keys blank "passphrase" avoid_reuse descriptors load_on_startup )
+    /// >
+    /// > Creates and loads a new wallet.
+    /// >
+    /// > Arguments:
+    /// > 1. wallet_name             (string, required) The name for the new wallet. If this is a path, the wallet will be created at the path location.
+    /// > 2. disable_private_keys    (boolean, optional, default=false) Disable the possibility of private keys (only watchonlys are possible in this mode).
+    /// > 3. blank                   (boolean, optional, default=false) Create a blank wallet. A blank wallet has no keys or HD seed. One can be set using sethdseed.
+    /// > 4. passphrase              (string, optional) Encrypt the wallet with this passphrase.
+    /// > 5. avoid_reuse             (boolean, optional, default=false) Keep track of coin reuse, and treat dirty and clean coins differently with privacy considerations in mind.
+    /// > 6. descriptors             (boolean, optional, default=true) Create a native descriptor wallet. The wallet will use descriptors internally to handle address creation
+    /// > 7. load_on_startup         (boolean, optional) Save wallet name to persistent settings and load on startup. True to add wallet to startup list, false to remove, null to leave unchanged.
+    ///
+    /// Only supported by Core `v21` to `v22`.
+    pub fn create_descriptor_wallet(&self, wallet: &str) -> Result<model::CreateWallet> {
+        dispatch!(self, create_descriptor_wallet(wallet), into_model; V21, V22)
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn import_descriptors(
+        &self,
+        requests: &[v21::ImportDescriptorsRequest],
+    ) -> Result<types::v21::ImportDescriptors> {
+        dispatch!(
+            self, import_descriptors(requests);
+            V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn psbt_bump_fee(&self, txid: &bitcoin::Txid) -> Result<model::PsbtBumpFee> {
+        dispatch!(
+            self, psbt_bump_fee(txid), into_model;
+            V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn send(&self, outputs: &BTreeMap<String, f64>) -> Result<model::Send> {
+        dispatch!(self, send(outputs), into_model; V21, V22, V23, V24, V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v21` and later.
+    pub fn send_many_verbose(
+        &self,
+        amounts: BTreeMap<Address, Amount>,
+    ) -> Result<model::SendManyVerbose> {
+        dispatch!(
+            self, send_many_verbose(amounts), into_model;
+            V21, V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v21` to `v29`.
+    pub fn upgrade_wallet(&self) -> Result<types::v21::UpgradeWallet> {
+        dispatch!(self, upgrade_wallet(); V21, V22, V23, V24, V25, V26, V27, V28, V29)
+    }
+
+    /// Only supported by Core `v22` and later.
+    pub fn wallet_display_address(&self, address: &str) -> Result<model::WalletDisplayAddress> {
+        dispatch!(
+            self, wallet_display_address(address), into_model;
+            V22, V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Creates a legacy wallet (i.e not a native descriptor wallet).
+    ///
+    /// > createwallet "wallet_name" ( disable_private_keys blank "passphrase" avoid_reuse descriptors load_on_startup external_signer )
+    /// >
+    /// > Creates and loads a new wallet.
+    /// >
+    /// > Arguments:
+    /// > 1. wallet_name             (string, required) The name for the new wallet. If this is a path, the wallet will be created at the path location.
+    /// > 2. disable_private_keys    (boolean, optional, default=false) Disable the possibility of private keys (only watchonlys are possible in this mode).
+    /// > 3. blank                   (boolean, optional, default=false) Create a blank wallet. A blank wallet has no keys or HD seed. One can be set using sethdseed.
+    /// > 4. passphrase              (string, optional) Encrypt the wallet with this passphrase.
+    /// > 5. avoid_reuse             (boolean, optional, default=false) Keep track of coin reuse, and treat dirty and clean coins differently with privacy considerations in mind.
+    /// > 6. descriptors             (boolean, optional, default=true) Create a native descriptor wallet. The wallet will use descriptors internally to handle address creation
+    /// > 7. load_on_startup         (boolean, optional) Save wallet name to persistent settings and load on startup. True to add wallet to startup list, false to remove, null to leave unchanged.
+    /// > 8. external_signer         (boolean, optional, default=false) Use an external signer such as a hardware wallet. Requires -signer to be configured. Wallet creation will fail if keys cannot be fetched. Requires disable_private_keys and descriptors set to true.
+    ///
+    /// Only supported by Core `v23` and later.
+    pub fn create_legacy_wallet(&self, wallet: &str) -> Result<model::CreateWallet> {
+        dispatch!(
+            self, create_legacy_wallet(wallet), into_model;
+            V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Calls `newkeypool` for the loaded wallet.
+    ///
+    /// > newkeypool
+    /// >
+    /// > Entirely clears and refills the keypool.
+    /// > Requires wallet passphrase to be set if wallet is encrypted.
+    ///
+    /// Only supported by Core `v23` to `v29`.
+    pub fn new_keypool(&self) -> Result<()> {
+        dispatch!(self, new_keypool(); V23, V24, V25, V26, V27, V28, V29)
+    }
+
+    /// Calls `restorewallet` with required and optional arguments.
+    ///
+    /// > restorewallet "wallet_name" "backup_file" ( load_on_startup )
+    ///
+    /// Only supported by Core `v23` and later.
+    pub fn restore_wallet(
+        &self,
+        wallet_name: &str,
+        backup_file: &Path,
+    ) -> Result<types::v23::RestoreWallet> {
+        dispatch!(
+            self, restore_wallet(wallet_name, backup_file);
+            V23, V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v24` and later.
+    pub fn migrate_wallet(&self, wallet_name: &str) -> Result<types::v24::MigrateWallet> {
+        dispatch!(self, migrate_wallet(wallet_name); V24, V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v24` and later.
+    pub fn send_all(&self, recipients: &[Address]) -> Result<model::SendAll> {
+        dispatch!(self, send_all(recipients), into_model; V24, V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Only supported by Core `v24` and later.
+    pub fn simulate_raw_transaction(
+        &self,
+        rawtxs: &[String],
+    ) -> Result<model::SimulateRawTransaction> {
+        dispatch!(
+            self, simulate_raw_transaction(rawtxs), into_model;
+            V24, V25, V26, V27, V28, V29, V30
+        )
+    }
+
+    /// Only supported by Core `v28` and later.
+    pub fn create_wallet_descriptor(
+        &self,
+        address_type: &str,
+        hdkey: &str,
+    ) -> Result<types::v28::CreateWalletDescriptor> {
+        dispatch!(self, create_wallet_descriptor(address_type, hdkey); V28, V29, V30)
+    }
+
+    /// Only supported by Core `v28` and later.
+    pub fn get_hd_keys(&self) -> Result<model::GetHdKeys> {
+        dispatch!(self, get_hd_keys(), into_model; V28, V29, V30)
+    }
+
+    // == Signer ==
+    /// Only supported by Core `v22` and later.
+    pub fn enumerate_signers(&self) -> Result<types::v22::EnumerateSigners> {
+        dispatch!(self, enumerate_signers(); V22, V23, V24, V25, V26, V27, V28, V29, V30)
+    }
+
+    /// Converts `ty` into the address type taken by Core `v17` to `v22`.
+    fn v17_address_type(&self, ty: AddressType) -> Result<v17::AddressType> {
+        match ty {
+            AddressType::Legacy => Ok(v17::AddressType::Legacy),
+            AddressType::P2shSegwit => Ok(v17::AddressType::P2shSegwit),
+            AddressType::Bech32 => Ok(v17::AddressType::Bech32),
+            AddressType::Bech32m => Err(Error::UnsupportedArgument {
+                method: "getnewaddress",
+                argument: "address_type",
+                version: self.version / 10_000,
+            }),
+        }
+    }
+}
+
+/// Turns the value returned by a version specific type's `into_model` into a [`Result`].
+///
+/// Most conversions are fallible and return a `Result` with a version specific error type, the rest
+/// return the model type directly.
+pub(crate) trait IntoModelResult<T> {
+    fn into_model_result(self) -> Result<T>;
+}
+
+impl<T, E> IntoModelResult<T> for std::result::Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn into_model_result(self) -> Result<T> { self.map_err(|e| Error::Model(Box::new(e))) }
+}
+
+/// A `model` type which some version specific type converts into infallibly.
+pub(crate) trait InfallibleModel {}
+
+impl<T: InfallibleModel> IntoModelResult<T> for T {
+    fn into_model_result(self) -> Result<T> { Ok(self) }
+}
+
+impl InfallibleModel for model::CreateWallet {}
+impl InfallibleModel for model::GetBlockCount {}
+impl InfallibleModel for model::GetDifficulty {}
+impl InfallibleModel for model::GetMiningInfo {}
+impl InfallibleModel for model::GetOrphanTxs {}
+impl InfallibleModel for model::ListWallets {}
+impl InfallibleModel for model::LoadWallet {}
+impl InfallibleModel for model::UnloadWallet {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::client_sync::mock::{response, MockServer};
+
+    /// Returns a server reporting version `version`.
+    fn server(version: usize) -> MockServer {
+        MockServer::start(move |request| {
+            let result = match request.body["method"].as_str() {
+                Some("getnetworkinfo") => json!({ "version": version }),
+                Some("getblockcount") => json!(101),
+                Some("unloadwallet") => Value::Null,
+                _ => return (500, Value::Null),
+            };
+            (200, response(&request.body, result))
+        })
+    }
+
+    fn methods(server: &MockServer) -> Vec<Value> {
+        server.requests().iter().map(|r| r.body["method"].clone()).collect()
+    }
+
+    #[test]
+    fn detects_version() {
+        let server = server(170200);
+        let client = Client::new(server.url()).unwrap();
+
+        assert!(matches!(client.inner, Inner::V17(_)));
+        assert_eq!(client.server_version(), 170200);
+        assert_eq!(client.get_block_count().unwrap(), model::GetBlockCount(101));
+        // `v17` returns nothing, the model has no warnings.
+        assert_eq!(
+            client.unload_wallet("alice").unwrap(),
+            model::UnloadWallet { warnings: vec![] }
+        );
+        assert_eq!(methods(&server), ["getnetworkinfo", "getblockcount", "unloadwallet"]);
+    }
+
+    #[test]
+    fn newer_version_uses_latest_client() {
+        let server = server(310000);
+        let client = Client::new(server.url()).unwrap();
+
+        assert!(matches!(client.inner, Inner::V30(_)));
+        assert_eq!(client.server_version(), 310000);
+    }
+
+    #[test]
+    fn older_version_is_rejected() {
+        let server = server(160000);
+
+        match Client::new(server.url()) {
+            Err(Error::ServerVersion(e)) => {
+                assert_eq!(e.got, 160000);
+                assert_eq!(e.expected.first(), Some(&170000));
+                assert_eq!(e.expected.last(), Some(&300000));
+            }
+            result => panic!("expected a server version error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn unsupported_method() {
+        let server = server(170200);
+        let client = Client::new(server.url()).unwrap();
+
+        match client.get_chain_states() {
+            Err(Error::UnsupportedMethod { method, version }) => {
+                assert_eq!(method, "get_chain_states");
+                assert_eq!(version, 170200);
+            }
+            result => panic!("expected an unsupported method error, got {:?}", result),
+        }
+        // The method is not sent to the server.
+        assert_eq!(methods(&server), ["getnetworkinfo"]);
+    }
+}
@@ -0,0 +1,336 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Batches of JSON-RPC requests sent to the server in a single HTTP request.
+//!
+//! A [`Batch`] is created with `Client::batch`. Each call queued on it returns a [`Pending`]
+//! handle, typed with the same return type as the single call method on the client, that is used
+//! to get its result from the [`BatchResponse`] once the batch is executed.
+//!
+//! Batches are only available on the blocking, version specific clients. The async clients and
+//! [`auto::Client`] do not have a `batch` method.
+//!
+//! ```no_run
+//! # use corepc_client::client_sync::{v29::Client, Result};
+//! # fn main() -> Result<()> {
+//! let client = Client::new("http://127.0.0.1:8332");
+//!
+//! let mut batch = client.batch();
+//! let pending = (0..10_000).map(|height| batch.get_block_hash(height)).collect::<Vec<_>>();
+//! let response = batch.execute()?;
+//!
+//! for p in pending {
+//!     let hash = response.get(p)?.block_hash()?;
+//!     println!("{}", hash);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`auto::Client`]: crate::client_sync::auto::Client
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::client_sync::{Error, Result};
+
+/// A batch of requests, built with the queueing methods and sent with [`Batch::execute`].
+///
+/// `C` is the versioned client the batch was created from, the typed queueing methods are the
+/// ones available for that version.
+pub struct Batch<'a, C> {
+    client: &'a jsonrpc::client::Client,
+    /// Identifies the batch, so a [`Pending`] can only be used with its own batch's response.
+    id: u64,
+    calls: Vec<(String, Vec<Value>)>,
+    version: PhantomData<C>,
+}
+
+impl<'a, C> Batch<'a, C> {
+    pub(crate) fn new(client: &'a jsonrpc::client::Client) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Self { client, id, calls: vec![], version: PhantomData }
+    }
+
+    /// Queues a call of RPC `method` with given `args` list.
+    ///
+    /// The result is deserialized as `T` when read from the response.
+    pub fn queue<T: DeserializeOwned>(&mut self, method: &str, args: Vec<Value>) -> Pending<T> {
+        self.calls.push((method.to_owned(), args));
+        Pending { batch: self.id, index: self.calls.len() - 1, result: PhantomData }
+    }
+
+    /// Returns the number of calls queued.
+    pub fn len(&self) -> usize { self.calls.len() }
+
+    /// Returns true if no calls are queued.
+    pub fn is_empty(&self) -> bool { self.calls.is_empty() }
+
+    /// Sends all queued calls to the server in a single request.
+    ///
+    /// Returns an error if the request failed as a whole, errors returned for individual calls are
+    /// returned by [`BatchResponse::get`].
+    pub fn execute(self) -> Result<BatchResponse> {
+        if self.calls.is_empty() {
+            return Ok(BatchResponse { batch: self.id, responses: vec![] });
+        }
+
+        let params = self
+            .calls
+            .iter()
+            .map(|(_, args)| serde_json::value::to_raw_value(args))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let requests = self
+            .calls
+            .iter()
+            .zip(params.iter())
+            .map(|((method, _), params)| self.client.build_request(method, Some(params)))
+            .collect::<Vec<_>>();
+        if log::log_enabled!(log::Level::Debug) {
+            log::debug!(target: "corepc", "batch request: {} calls", requests.len());
+        }
+
+        let responses = self.client.send_batch(&requests)?;
+        Ok(BatchResponse { batch: self.id, responses })
+    }
+}
+
+impl<C> fmt::Debug for Batch<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Batch").field("calls", &self.calls).finish_non_exhaustive()
+    }
+}
+
+/// A call queued on a [`Batch`], used to get its result from the [`BatchResponse`].
+pub struct Pending<T> {
+    batch: u64,
+    index: usize,
+    result: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for Pending<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pending").field("batch", &self.batch).field("index", &self.index).finish()
+    }
+}
+
+/// The responses to an executed [`Batch`].
+#[derive(Debug)]
+pub struct BatchResponse {
+    batch: u64,
+    responses: Vec<Option<jsonrpc::Response>>,
+}
+
+impl BatchResponse {
+    /// Returns the result of the `pending` call.
+    ///
+    /// Errors if the server returned an error for this call or did not respond to it, or if
+    /// `pending` was queued on a different batch.
+    pub fn get<T: DeserializeOwned>(&self, pending: Pending<T>) -> Result<T> {
+        if pending.batch != self.batch {
+            return Err(Error::BatchMismatch);
+        }
+        match self.responses.get(pending.index) {
+            Some(Some(resp)) => Ok(resp.result()?),
+            _ => Err(Error::UnexpectedStructure),
+        }
+    }
+}
+
+/// Implements the typed queueing methods on `Batch` for the versioned clients.
+///
+/// Requires `Client` and the version specific types to be in scope.
+#[macro_export]
+macro_rules! impl_client_v17__batch {
+    () => {
+        impl $crate::client_sync::batch::Batch<'_, Client> {
+            pub fn get_block_hash(
+                &mut self,
+                height: u64,
+            ) -> $crate::client_sync::batch::Pending<GetBlockHash> {
+                self.queue("getblockhash", $crate::client_sync::args::get_block_hash(height))
+            }
+
+            pub fn get_block_header(
+                &mut self,
+                hash: &bitcoin::BlockHash,
+            ) -> $crate::client_sync::batch::Pending<GetBlockHeader> {
+                self.queue("getblockheader", $crate::client_sync::args::get_block_header(hash))
+            }
+
+            pub fn get_block_header_verbose(
+                &mut self,
+                hash: &bitcoin::BlockHash,
+            ) -> $crate::client_sync::batch::Pending<GetBlockHeaderVerbose> {
+                self.queue(
+                    "getblockheader",
+                    $crate::client_sync::args::get_block_header_verbose(hash),
+                )
+            }
+
+            pub fn get_block_verbose_zero(
+                &mut self,
+                hash: bitcoin::BlockHash,
+            ) -> $crate::client_sync::batch::Pending<GetBlockVerboseZero> {
+                self.queue("getblock", $crate::client_sync::args::get_block_verbose_zero(hash))
+            }
+
+            pub fn get_block_verbose_one(
+                &mut self,
+                hash: bitcoin::BlockHash,
+            ) -> $crate::client_sync::batch::Pending<GetBlockVerboseOne> {
+                self.queue("getblock", $crate::client_sync::args::get_block_verbose_one(hash))
+            }
+
+            pub fn get_block_stats_by_height(
+                &mut self,
+                height: u32,
+            ) -> $crate::client_sync::batch::Pending<GetBlockStats> {
+                self.queue(
+                    "getblockstats",
+                    $crate::client_sync::args::get_block_stats_by_height(height),
+                )
+            }
+
+            pub fn get_block_stats_by_block_hash(
+                &mut self,
+                hash: &bitcoin::BlockHash,
+            ) -> $crate::client_sync::batch::Pending<GetBlockStats> {
+                self.queue(
+                    "getblockstats",
+                    $crate::client_sync::args::get_block_stats_by_block_hash(hash),
+                )
+            }
+
+            pub fn get_mempool_entry(
+                &mut self,
+                txid: bitcoin::Txid,
+            ) -> $crate::client_sync::batch::Pending<GetMempoolEntry> {
+                self.queue("getmempoolentry", $crate::client_sync::args::get_mempool_entry(txid))
+            }
+
+            pub fn get_tx_out(
+                &mut self,
+                txid: bitcoin::Txid,
+                vout: u64,
+            ) -> $crate::client_sync::batch::Pending<GetTxOut> {
+                self.queue("gettxout", $crate::client_sync::args::get_tx_out(txid, vout))
+            }
+
+            pub fn get_raw_transaction(
+                &mut self,
+                txid: bitcoin::Txid,
+            ) -> $crate::client_sync::batch::Pending<GetRawTransaction> {
+                self.queue(
+                    "getrawtransaction",
+                    $crate::client_sync::args::get_raw_transaction(txid),
+                )
+            }
+
+            pub fn get_raw_transaction_verbose(
+                &mut self,
+                txid: bitcoin::Txid,
+            ) -> $crate::client_sync::batch::Pending<GetRawTransactionVerbose> {
+                self.queue(
+                    "getrawtransaction",
+                    $crate::client_sync::args::get_raw_transaction_verbose(txid),
+                )
+            }
+
+            pub fn get_transaction(
+                &mut self,
+                txid: bitcoin::Txid,
+            ) -> $crate::client_sync::batch::Pending<GetTransaction> {
+                self.queue("gettransaction", $crate::client_sync::args::get_transaction(txid))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::client_sync::mock::{response, MockServer};
+    use crate::client_sync::v29::Client;
+
+    /// Answers each `getblockhash` call in a batch with its height, leaving out height `skip`.
+    ///
+    /// The responses are sent in reverse order, as allowed by the JSON-RPC spec.
+    fn server(skip: Option<u64>) -> MockServer {
+        MockServer::start(move |request| {
+            let responses = request
+                .body
+                .as_array()
+                .expect("batch request")
+                .iter()
+                .rev()
+                .filter(|call| call["params"][0].as_u64() != skip)
+                .map(|call| response(call, call["params"][0].clone()))
+                .collect::<Vec<_>>();
+            (200, Value::Array(responses))
+        })
+    }
+
+    #[test]
+    fn results_follow_queue_order() {
+        let server = server(None);
+        let client = Client::new(server.url());
+
+        let mut batch = client.batch();
+        let pending = (0..5).map(|height| batch.queue::<u64>("getblockhash", vec![height.into()]));
+        let pending = pending.collect::<Vec<_>>();
+        let response = batch.execute().unwrap();
+
+        let results = pending.into_iter().map(|p| response.get(p).unwrap()).collect::<Vec<_>>();
+        assert_eq!(results, [0, 1, 2, 3, 4]);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn typed_method_sends_call() {
+        let server = server(None);
+        let client = Client::new(server.url());
+
+        let mut batch = client.batch();
+        let _ = batch.get_block_hash(7);
+        batch.execute().unwrap();
+
+        let request = &server.requests()[0].body[0];
+        assert_eq!(request["method"], "getblockhash");
+        assert_eq!(request["params"], json!([7]));
+    }
+
+    #[test]
+    fn missing_response_is_unexpected_structure() {
+        let server = server(Some(1));
+        let client = Client::new(server.url());
+
+        let mut batch = client.batch();
+        let first = batch.queue::<u64>("getblockhash", vec![0.into()]);
+        let missing = batch.queue::<u64>("getblockhash", vec![1.into()]);
+        let response = batch.execute().unwrap();
+
+        assert_eq!(response.get(first).unwrap(), 0);
+        assert!(matches!(response.get(missing), Err(Error::UnexpectedStructure)));
+    }
+
+    #[test]
+    fn pending_from_other_batch_is_rejected() {
+        let server = server(None);
+        let client = Client::new(server.url());
+
+        let mut batch = client.batch();
+        let _ = batch.queue::<u64>("getblockhash", vec![0.into()]);
+        let mut other = client.batch();
+        let foreign = other.queue::<u64>("getblockhash", vec![1.into()]);
+        let response = batch.execute().unwrap();
+
+        assert!(matches!(response.get(foreign), Err(Error::BatchMismatch)));
+    }
+}
@@ -0,0 +1,519 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Connection settings read from a Bitcoin Core data directory and `bitcoin.conf` file.
+//!
+//! The settings are found the same way `bitcoin-cli` finds them:
+//!
+//! - Values in the section of the selected network (`[main]`, `[test]`, `[testnet4]`, `[signet]`
+//!   or `[regtest]`), or prefixed with its name e.g., `regtest.rpcport`, take precedence over
+//!   values outside of any section.
+//! - `rpcport` outside of any section only applies to mainnet.
+//! - If a setting is given more than once the first value is used.
+//! - Files named by `includeconf` are read after the main config file.
+//! - Unless `rpcpassword` is set the cookie file is used, `rpccookiefile` or `.cookie` in the
+//!   network's data directory.
+
+use std::path::{Path, PathBuf};
+use std::{error, fmt, fs, io};
+
+use bitcoin::Network;
+
+use crate::client_sync::{Auth, Error, Result};
+
+/// The name of the config file in the data directory if none is given.
+const DEFAULT_CONF: &str = "bitcoin.conf";
+
+/// The name of the cookie file in the network's data directory if none is given.
+const DEFAULT_COOKIE: &str = ".cookie";
+
+/// The server's address if none is given.
+const DEFAULT_HOST: &str = "127.0.0.1";
+
+/// The URL and credentials used to connect to the JSON-RPC server of a Core node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcConfig {
+    /// The URL of the JSON-RPC server e.g., `http://127.0.0.1:18443`.
+    pub url: String,
+    /// The credentials, either a user and password or the cookie file.
+    pub auth: Auth,
+}
+
+impl RpcConfig {
+    /// Reads the settings from the node with data directory `datadir`.
+    ///
+    /// `conf` is the path of the config file, relative paths are relative to `datadir`. Without
+    /// one `bitcoin.conf` in `datadir` is read, if it exists. The network is read from the config
+    /// file if `network` is `None`, defaulting to mainnet.
+    pub fn from_datadir(
+        datadir: &Path,
+        conf: Option<&Path>,
+        network: Option<Network>,
+    ) -> Result<Self> {
+        let read = |path: &Path| fs::read_to_string(path);
+        let settings = match conf {
+            Some(conf) => Settings::read(datadir, &datadir.join(conf), read)?,
+            None => {
+                let path = datadir.join(DEFAULT_CONF);
+                if path.exists() {
+                    Settings::read(datadir, &path, read)?
+                } else {
+                    Settings::default()
+                }
+            }
+        };
+        Self::from_settings(datadir, &settings, network)
+    }
+
+    fn from_settings(
+        datadir: &Path,
+        settings: &Settings,
+        network: Option<Network>,
+    ) -> Result<Self> {
+        let chain = match network {
+            Some(network) => ChainParams::from_core_arg(network.to_core_arg())
+                .ok_or_else(|| ConfigError::UnknownChain(network.to_core_arg().to_owned()))?,
+            None => settings.chain()?,
+        };
+        let get = |key| settings.get(chain.section, key);
+
+        let (host, connect_port) = match get("rpcconnect") {
+            Some(connect) => split_host_port(connect)?,
+            None => (DEFAULT_HOST.to_owned(), None),
+        };
+        let port = match get("rpcport") {
+            Some(port) => parse_port(port)?,
+            None => connect_port.unwrap_or(chain.rpc_port),
+        };
+        let url = if host.contains(':') {
+            format!("http://[{}]:{}", host, port)
+        } else {
+            format!("http://{}:{}", host, port)
+        };
+
+        let auth = match get("rpcpassword") {
+            Some(pass) if !pass.value.is_empty() => Auth::UserPass(
+                get("rpcuser").map(|user| user.value.clone()).unwrap_or_default(),
+                pass.value.clone(),
+            ),
+            _ => {
+                let network_dir = datadir.join(chain.data_dir);
+                let cookie = get("rpccookiefile").map_or(DEFAULT_COOKIE, |c| c.value.as_str());
+                Auth::CookieFile(network_dir.join(cookie))
+            }
+        };
+
+        Ok(RpcConfig { url, auth })
+    }
+}
+
+/// An error in the settings of a config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// A line is not a section header, a setting or a comment.
+    Syntax {
+        /// The config file.
+        path: PathBuf,
+        /// The line number, starting at 1.
+        line: usize,
+    },
+    /// A setting has a value which is not valid for its key.
+    InvalidValue {
+        /// The config file.
+        path: PathBuf,
+        /// The line number, starting at 1.
+        line: usize,
+        /// The key of the setting e.g., `rpcport`.
+        key: String,
+        /// The value of the setting.
+        value: String,
+    },
+    /// The network is not one known to Core.
+    UnknownChain(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ConfigError::*;
+
+        match *self {
+            Syntax { ref path, line } =>
+                write!(f, "parse error on line {} of {}", line, path.display()),
+            InvalidValue { ref path, line, ref key, ref value } => write!(
+                f,
+                "invalid value {:?} for {} on line {} of {}",
+                value,
+                key,
+                line,
+                path.display()
+            ),
+            UnknownChain(ref chain) => write!(f, "unknown chain: {}", chain),
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self { Self::InvalidConfig(e) }
+}
+
+/// The settings of a network that differ between networks.
+struct ChainParams {
+    /// The name of the network's config file section.
+    section: &'static str,
+    /// The network's data directory, relative to the data directory.
+    data_dir: &'static str,
+    /// The default JSON-RPC port.
+    rpc_port: u16,
+}
+
+impl ChainParams {
+    /// Returns the settings of the network with name `arg`, as used by Core's `-chain` option.
+    fn from_core_arg(arg: &str) -> Option<Self> {
+        let (section, data_dir, rpc_port) = match arg {
+            "main" => ("main", "", 8332),
+            "test" => ("test", "testnet3", 18332),
+            "testnet4" => ("testnet4", "testnet4", 48332),
+            "signet" => ("signet", "signet", 38332),
+            "regtest" => ("regtest", "regtest", 18443),
+            _ => return None,
+        };
+        Some(ChainParams { section, data_dir, rpc_port })
+    }
+}
+
+/// A setting read from a config file.
+struct Setting {
+    /// The network section, empty if none.
+    section: String,
+    key: String,
+    value: String,
+    /// The config file.
+    path: PathBuf,
+    /// The line number, starting at 1.
+    line: usize,
+}
+
+impl Setting {
+    /// Returns the error for this setting having an invalid value.
+    fn invalid(&self) -> ConfigError {
+        ConfigError::InvalidValue {
+            path: self.path.clone(),
+            line: self.line,
+            key: self.key.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+/// The settings of a config file, in the order they were read.
+#[derive(Default)]
+struct Settings {
+    values: Vec<Setting>,
+}
+
+impl Settings {
+    /// Reads the config file at `path` and the files it includes, using `read` to read each file.
+    fn read<F>(datadir: &Path, path: &Path, read: F) -> Result<Self>
+    where
+        F: Fn(&Path) -> io::Result<String>,
+    {
+        let mut settings = Settings::default();
+        settings.parse(path, &read(path)?)?;
+
+        // Included files are read after the main file, so its settings take precedence. Includes
+        // in included files are ignored, as by Core.
+        let includes = settings
+            .values
+            .iter()
+            .filter(|setting| setting.key == "includeconf")
+            .map(|setting| datadir.join(&setting.value))
+            .collect::<Vec<PathBuf>>();
+        for include in includes {
+            let mut included = Settings::default();
+            included.parse(&include, &read(&include)?)?;
+            settings
+                .values
+                .extend(included.values.into_iter().filter(|setting| setting.key != "includeconf"));
+        }
+        Ok(settings)
+    }
+
+    /// Parses `contents`, the contents of the config file at `path`.
+    fn parse(&mut self, path: &Path, contents: &str) -> Result<()> {
+        let mut section = String::new();
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_owned();
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| ConfigError::Syntax { path: path.to_owned(), line: i + 1 })?;
+            let (key, value) = (key.trim(), value.trim());
+            let (section, key) = match key.split_once('.') {
+                Some((prefix, key)) if section.is_empty() => (prefix, key),
+                _ => (section.as_str(), key),
+            };
+            self.values.push(Setting {
+                section: section.to_owned(),
+                key: key.to_owned(),
+                value: value.to_owned(),
+                path: path.to_owned(),
+                line: i + 1,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the first setting of `key` for the network with config file section `network`.
+    fn get(&self, network: &str, key: &str) -> Option<&Setting> {
+        self.first(network, key).or_else(|| {
+            // Network only options outside of a section only apply to mainnet.
+            if key == "rpcport" && network != "main" {
+                None
+            } else {
+                self.first("", key)
+            }
+        })
+    }
+
+    fn first(&self, section: &str, key: &str) -> Option<&Setting> {
+        self.values.iter().find(|setting| setting.section == section && setting.key == key)
+    }
+
+    /// Returns the network selected by the config file, mainnet if none is.
+    fn chain(&self) -> Result<ChainParams> {
+        if let Some(chain) = self.first("", "chain") {
+            return Ok(ChainParams::from_core_arg(&chain.value).ok_or_else(|| chain.invalid())?);
+        }
+        for arg in ["regtest", "signet", "testnet4", "testnet"] {
+            if self.first("", arg).is_some_and(|setting| setting.value != "0") {
+                let arg = if arg == "testnet" { "test" } else { arg };
+                return Ok(ChainParams::from_core_arg(arg).expect("known network"));
+            }
+        }
+        Ok(ChainParams::from_core_arg("main").expect("known network"))
+    }
+}
+
+/// Splits `rpcconnect` into the host and the port, if it has one.
+fn split_host_port(connect: &Setting) -> Result<(String, Option<u16>)> {
+    let value = connect.value.as_str();
+    if let Some(rest) = value.strip_prefix('[') {
+        // An IPv6 address e.g., `[::1]:8332`.
+        let (host, rest) = rest.split_once(']').ok_or_else(|| connect.invalid())?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => Some(port.parse().map_err(|_| connect.invalid())?),
+            None => None,
+        };
+        return Ok((host.to_owned(), port));
+    }
+    match value.rsplit_once(':') {
+        // More than one colon is an IPv6 address without a port.
+        Some((host, port)) if !host.contains(':') =>
+            Ok((host.to_owned(), Some(port.parse().map_err(|_| connect.invalid())?))),
+        _ => Ok((value.to_owned(), None)),
+    }
+}
+
+fn parse_port(port: &Setting) -> Result<u16> { Ok(port.value.parse().map_err(|_| port.invalid())?) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATADIR: &str = "/data";
+
+    /// Returns the settings for the config files `files`, the name and contents of each file in
+    /// the data directory. The first file is the main config file.
+    fn config(files: &[(&str, &str)], network: Option<Network>) -> Result<RpcConfig> {
+        let datadir = Path::new(DATADIR);
+        let read = |path: &Path| {
+            files
+                .iter()
+                .find(|(name, _)| datadir.join(name) == path)
+                .map(|(_, contents)| contents.to_string())
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        };
+        let settings = Settings::read(datadir, &datadir.join(files[0].0), read)?;
+        RpcConfig::from_settings(datadir, &settings, network)
+    }
+
+    fn user_pass(user: &str, pass: &str) -> Auth {
+        Auth::UserPass(user.to_owned(), pass.to_owned())
+    }
+
+    fn cookie(path: &str) -> Auth { Auth::CookieFile(Path::new(DATADIR).join(path)) }
+
+    #[test]
+    fn defaults() {
+        let config = config(&[("bitcoin.conf", "")], None).unwrap();
+        assert_eq!(config.url, "http://127.0.0.1:8332");
+        assert_eq!(config.auth, cookie(".cookie"));
+    }
+
+    #[test]
+    fn section_takes_precedence() {
+        let conf = "rpcuser=alice\nrpcpassword=a\n[regtest]\nrpcuser=bob\n[main]\nrpcuser=carol\n";
+
+        let regtest = config(&[("bitcoin.conf", conf)], Some(Network::Regtest)).unwrap();
+        assert_eq!(regtest.auth, user_pass("bob", "a"));
+        let signet = config(&[("bitcoin.conf", conf)], Some(Network::Signet)).unwrap();
+        assert_eq!(signet.auth, user_pass("alice", "a"));
+        let main = config(&[("bitcoin.conf", conf)], Some(Network::Bitcoin)).unwrap();
+        assert_eq!(main.auth, user_pass("carol", "a"));
+    }
+
+    #[test]
+    fn network_prefixed_keys() {
+        let conf =
+            "regtest=1\nrpcuser=alice\nrpcpassword=a\nregtest.rpcuser=bob\nregtest.rpcport=1234\n";
+
+        let config = config(&[("bitcoin.conf", conf)], None).unwrap();
+        assert_eq!(config.url, "http://127.0.0.1:1234");
+        assert_eq!(config.auth, user_pass("bob", "a"));
+    }
+
+    #[test]
+    fn first_value_wins() {
+        let conf = "rpcuser=alice\nrpcpassword=a\nrpcuser=bob\nrpcpassword=b\n";
+
+        let config = config(&[("bitcoin.conf", conf)], None).unwrap();
+        assert_eq!(config.auth, user_pass("alice", "a"));
+    }
+
+    #[test]
+    fn rpcport_outside_section_is_mainnet_only() {
+        let conf = "rpcport=1234\n";
+
+        let main = config(&[("bitcoin.conf", conf)], None).unwrap();
+        assert_eq!(main.url, "http://127.0.0.1:1234");
+        let regtest = config(&[("bitcoin.conf", conf)], Some(Network::Regtest)).unwrap();
+        assert_eq!(regtest.url, "http://127.0.0.1:18443");
+    }
+
+    #[test]
+    fn chain_from_config() {
+        let files = [("bitcoin.conf", "chain=test\n")];
+        assert_eq!(config(&files, None).unwrap().url, "http://127.0.0.1:18332");
+        let files = [("bitcoin.conf", "testnet4=1\n")];
+        assert_eq!(config(&files, None).unwrap().url, "http://127.0.0.1:48332");
+        let files = [("bitcoin.conf", "regtest=0\n")];
+        assert_eq!(config(&files, None).unwrap().url, "http://127.0.0.1:8332");
+    }
+
+    #[test]
+    fn includeconf() {
+        let files = [
+            ("bitcoin.conf", "includeconf=rpc.conf\nrpcuser=alice\n"),
+            ("rpc.conf", "rpcuser=bob\nrpcpassword=b\nincludeconf=nested.conf\n"),
+            ("nested.conf", "rpcport=1234\n"),
+        ];
+
+        let config = config(&files, None).unwrap();
+        // The main file takes precedence, and includes in included files are ignored.
+        assert_eq!(config.auth, user_pass("alice", "b"));
+        assert_eq!(config.url, "http://127.0.0.1:8332");
+    }
+
+    #[test]
+    fn missing_includeconf() {
+        let files = [("bitcoin.conf", "includeconf=missing.conf\n")];
+        assert!(matches!(config(&files, None), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn cookie_per_network() {
+        let files = [("bitcoin.conf", "")];
+        let cases = [
+            (Network::Bitcoin, ".cookie"),
+            (Network::Testnet, "testnet3/.cookie"),
+            (Network::Signet, "signet/.cookie"),
+            (Network::Regtest, "regtest/.cookie"),
+        ];
+        for (network, path) in cases {
+            assert_eq!(config(&files, Some(network)).unwrap().auth, cookie(path));
+        }
+        // `Network::Testnet4` is only in newer `bitcoin` releases than the minimum supported.
+        let files = [("bitcoin.conf", "chain=testnet4\n")];
+        assert_eq!(config(&files, None).unwrap().auth, cookie("testnet4/.cookie"));
+    }
+
+    #[test]
+    fn rpccookiefile() {
+        let files =
+            [("bitcoin.conf", "[regtest]\nrpccookiefile=auth\n[signet]\nrpccookiefile=/a\n")];
+
+        let regtest = config(&files, Some(Network::Regtest)).unwrap();
+        assert_eq!(regtest.auth, cookie("regtest/auth"));
+        let signet = config(&files, Some(Network::Signet)).unwrap();
+        assert_eq!(signet.auth, Auth::CookieFile(PathBuf::from("/a")));
+    }
+
+    #[test]
+    fn empty_password_uses_cookie() {
+        let files = [("bitcoin.conf", "rpcuser=alice\nrpcpassword=\n")];
+        assert_eq!(config(&files, None).unwrap().auth, cookie(".cookie"));
+    }
+
+    #[test]
+    fn rpcconnect() {
+        let cases = [
+            ("rpcconnect=10.0.0.1", "http://10.0.0.1:8332"),
+            ("rpcconnect=10.0.0.1:1234", "http://10.0.0.1:1234"),
+            ("rpcconnect=10.0.0.1:1234\nrpcport=5678", "http://10.0.0.1:5678"),
+            ("rpcconnect=::1", "http://[::1]:8332"),
+            ("rpcconnect=[::1]:1234", "http://[::1]:1234"),
+        ];
+        for (conf, url) in cases {
+            assert_eq!(config(&[("bitcoin.conf", conf)], None).unwrap().url, url, "{}", conf);
+        }
+    }
+
+    #[test]
+    fn syntax_error() {
+        let files = [("bitcoin.conf", "# comment\n\nrpcuser\n")];
+        match config(&files, None) {
+            Err(Error::InvalidConfig(ConfigError::Syntax { path, line })) => {
+                assert_eq!(path, Path::new(DATADIR).join("bitcoin.conf"));
+                assert_eq!(line, 3);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn invalid_value() {
+        let files = [("bitcoin.conf", "includeconf=rpc.conf\n"), ("rpc.conf", "\nrpcport=port\n")];
+        match config(&files, None) {
+            Err(Error::InvalidConfig(ConfigError::InvalidValue { path, line, key, value })) => {
+                assert_eq!(path, Path::new(DATADIR).join("rpc.conf"));
+                assert_eq!(line, 2);
+                assert_eq!(key, "rpcport");
+                assert_eq!(value, "port");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn unknown_chain() {
+        let files = [("bitcoin.conf", "chain=bitcoin\n")];
+        assert!(matches!(
+            config(&files, None),
+            Err(Error::InvalidConfig(ConfigError::InvalidValue { line: 1, .. }))
+        ));
+    }
+}
@@ -4,6 +4,8 @@ use std::{error, fmt, io};
 
 use bitcoin::hex;
 
+use crate::client_sync::config::ConfigError;
+
 /// The error type for errors produced in this library.
 #[derive(Debug)]
 pub enum Error {
@@ -14,14 +16,33 @@ pub enum Error {
     BitcoinSerialization(bitcoin::consensus::encode::FromHexError),
     Io(io::Error),
     InvalidCookieFile,
+    /// The `rpcauth` value is not of the form `<user>:<salt>$<hash>`.
+    InvalidRpcAuth,
     /// The JSON result had an unexpected structure.
     UnexpectedStructure,
+    /// A pending call was used with the response of a different batch.
+    BatchMismatch,
     /// The daemon returned an error string.
     Returned(String),
     /// The server version did not match what was expected.
     ServerVersion(UnexpectedServerVersionError),
     /// Missing user/password.
     MissingUserPassword,
+    /// Converting the JSON result into a `model` type failed.
+    Model(Box<dyn error::Error + Send + Sync>),
+    /// The method is not available on the detected server version.
+    UnsupportedMethod {
+        method: &'static str,
+        version: usize,
+    },
+    /// The config file could not be parsed.
+    InvalidConfig(ConfigError),
+    /// An optional argument was set that is not available in the client's Core version.
+    UnsupportedArgument {
+        method: &'static str,
+        argument: &'static str,
+        version: usize,
+    },
 }
 
 impl From<jsonrpc::error::Error> for Error {
@@ -60,10 +81,21 @@ impl fmt::Display for Error {
             BitcoinSerialization(ref e) => write!(f, "Bitcoin serialization error: {}", e),
             Io(ref e) => write!(f, "I/O error: {}", e),
             InvalidCookieFile => write!(f, "invalid cookie file"),
+            InvalidRpcAuth => write!(f, "invalid rpcauth value"),
             UnexpectedStructure => write!(f, "the JSON result had an unexpected structure"),
+            BatchMismatch => write!(f, "the pending call is from a different batch"),
             Returned(ref s) => write!(f, "the daemon returned an error string: {}", s),
             ServerVersion(ref e) => write!(f, "server version: {}", e),
             MissingUserPassword => write!(f, "missing user and/or password"),
+            Model(ref e) => write!(f, "conversion into model type failed: {}", e),
+            UnsupportedMethod { method, version } =>
+                write!(f, "method {} is not supported by server version {}", method, version),
+            InvalidConfig(ref e) => write!(f, "invalid config file: {}", e),
+            UnsupportedArgument { method, argument, version } => write!(
+                f,
+                "argument {} of method {} is not supported by Core v{}",
+                argument, method, version
+            ),
         }
     }
 }
@@ -80,7 +112,16 @@ impl error::Error for Error {
             BitcoinSerialization(ref e) => Some(e),
             Io(ref e) => Some(e),
             ServerVersion(ref e) => Some(e),
-            InvalidCookieFile | UnexpectedStructure | Returned(_) | MissingUserPassword => None,
+            Model(ref e) => Some(&**e),
+            InvalidConfig(ref e) => Some(e),
+            InvalidCookieFile
+            | InvalidRpcAuth
+            | UnexpectedStructure
+            | BatchMismatch
+            | Returned(_)
+            | MissingUserPassword
+            | UnsupportedMethod { .. }
+            | UnsupportedArgument { .. } => None,
         }
     }
 }
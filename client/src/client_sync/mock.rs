@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! A JSON-RPC server for unit tests, answering requests without a running `bitcoind`.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::{env, fs, process, thread};
+
+use jsonrpc::base64::engine::general_purpose::STANDARD as BASE64;
+use jsonrpc::base64::Engine;
+use serde_json::{json, Value};
+
+/// A request received by a [`MockServer`].
+#[derive(Clone, Debug)]
+pub(crate) struct MockRequest {
+    /// The path of the request e.g., `/wallet/alice`.
+    pub(crate) path: String,
+    /// The value of the `Authorization` header, if any.
+    pub(crate) authorization: Option<String>,
+    /// The JSON-RPC request, or an array of requests if it is a batch.
+    pub(crate) body: Value,
+}
+
+/// A JSON-RPC server on localhost which answers every request with a user supplied function.
+pub(crate) struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// Starts a server answering each request with the HTTP status code and body returned by
+    /// `respond`.
+    ///
+    /// A `null` body is sent as an empty body and a string body is sent as is, any other body is
+    /// sent as JSON. Each connection is served on its own thread, so `respond` may be called
+    /// concurrently.
+    pub(crate) fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&MockRequest) -> (u16, Value) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind localhost");
+        let url = format!("http://{}", listener.local_addr().expect("local address"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let respond = Arc::new(respond);
+
+        let received = Arc::clone(&requests);
+        // The threads are left running when the server is dropped, they end with the test process.
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let received = Arc::clone(&received);
+                let respond = Arc::clone(&respond);
+                thread::spawn(move || {
+                    while let Some(request) = read_request(&stream) {
+                        received.lock().expect("lock poisoned").push(request.clone());
+                        let (status, body) = respond(&request);
+                        if write_response(&stream, status, &body).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        MockServer { url, requests }
+    }
+
+    /// Returns the URL of the server.
+    pub(crate) fn url(&self) -> &str { &self.url }
+
+    /// Returns the requests received so far, oldest first.
+    pub(crate) fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().expect("lock poisoned").clone()
+    }
+}
+
+/// Returns the JSON-RPC response to `request` with result `result`.
+pub(crate) fn response(request: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": request["id"], "result": result, "error": null })
+}
+
+/// Returns the value of the `Authorization` header for the credentials `user_pass`.
+pub(crate) fn basic_auth(user_pass: &str) -> String {
+    format!("Basic {}", BASE64.encode(user_pass))
+}
+
+/// A cookie file in the temporary directory, removed when dropped.
+pub(crate) struct Cookie(pub(crate) PathBuf);
+
+impl Cookie {
+    /// Writes the credentials `user_pass` to a new cookie file, `name` must be unique per test.
+    pub(crate) fn new(name: &str, user_pass: &str) -> Self {
+        let path = env::temp_dir().join(format!("corepc-{}-{}.cookie", process::id(), name));
+        fs::write(&path, user_pass).unwrap();
+        Cookie(path)
+    }
+}
+
+impl Drop for Cookie {
+    fn drop(&mut self) { let _ = fs::remove_file(&self.0); }
+}
+
+/// Reads an HTTP request from `stream`, returns `None` once the client closes the connection.
+fn read_request(stream: &TcpStream) -> Option<MockRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).ok()? == 0 {
+        return None;
+    }
+    // e.g., `POST /wallet/alice HTTP/1.1`.
+    let path = request_line.split_whitespace().nth(1)?.to_owned();
+    let mut content_length = 0;
+    let mut authorization = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().ok()?,
+                "authorization" => authorization = Some(value.trim().to_owned()),
+                _ => {}
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(MockRequest { path, authorization, body: serde_json::from_slice(&body).ok()? })
+}
+
+fn write_response(mut stream: &TcpStream, status: u16, body: &Value) -> std::io::Result<()> {
+    let body = match body {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        json => json.to_string(),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
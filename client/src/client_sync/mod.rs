@@ -2,7 +2,17 @@
 
 //! JSON-RPC clients for testing against specific versions of Bitcoin Core.
 
-mod error;
+pub mod api;
+pub mod args;
+pub mod auto;
+pub mod batch;
+pub mod config;
+pub(crate) mod error;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod options;
+pub mod rpcauth;
+pub(crate) mod transport;
 pub mod v17;
 pub mod v18;
 pub mod v19;
@@ -18,11 +28,16 @@ pub mod v28;
 pub mod v29;
 pub mod v30;
 
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
+use bitcoin::Txid;
+use serde::{Deserialize, Serialize};
+
 pub use crate::client_sync::error::Error;
+pub use crate::client_sync::rpcauth::RpcAuth;
 
 /// Crate-specific Result type.
 ///
@@ -31,18 +46,40 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 /// The different authentication methods for the client.
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[non_exhaustive]
 pub enum Auth {
     None,
     UserPass(String, String),
     CookieFile(PathBuf),
+    /// The credentials of an `-rpcauth` option and the password it was created with.
+    ///
+    /// The client authenticates with the `rpcauth` user and the password.
+    RpcAuth(RpcAuth, String),
 }
 
 impl Auth {
+    /// Creates `rpcauth` credentials for `user` and `password` with a random salt.
+    ///
+    /// Pass the [`Auth::rpcauth`] value to `bitcoind` using `-rpcauth` and this to the client.
+    #[cfg(feature = "rpcauth-random")]
+    pub fn new_rpcauth(user: &str, password: &str) -> Result<Self> {
+        Ok(Auth::RpcAuth(RpcAuth::new(user, password)?, password.to_owned()))
+    }
+
+    /// Returns the `rpcauth` credentials, if this is [`Auth::RpcAuth`].
+    pub fn rpcauth(&self) -> Option<&RpcAuth> {
+        match self {
+            Auth::RpcAuth(rpcauth, _) => Some(rpcauth),
+            _ => None,
+        }
+    }
+
     /// Convert into the arguments that jsonrpc::Client needs.
     pub fn get_user_pass(self) -> Result<(Option<String>, Option<String>)> {
         match self {
             Auth::None => Ok((None, None)),
             Auth::UserPass(u, p) => Ok((Some(u), Some(p))),
+            Auth::RpcAuth(rpcauth, p) => Ok((Some(rpcauth.user().to_owned()), Some(p))),
             Auth::CookieFile(path) => {
                 let line = BufReader::new(File::open(path)?)
                     .lines()
@@ -55,6 +92,48 @@ impl Auth {
     }
 }
 
+/// Argument to the `Client::get_new_address_with_type` function.
+///
+/// Used by the clients for Core v23 and onwards, which support [`AddressType::Bech32m`]. The
+/// clients for earlier versions use `v17::AddressType`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AddressType {
+    Legacy,
+    P2shSegwit,
+    Bech32,
+    /// Supported by Core v23 and later.
+    Bech32m,
+}
+
+impl fmt::Display for AddressType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AddressType::*;
+
+        let s = match *self {
+            Legacy => "legacy",
+            P2shSegwit => "p2sh-segwit",
+            Bech32 => "bech32",
+            Bech32m => "bech32m",
+        };
+        fmt::Display::fmt(s, f)
+    }
+}
+
+/// An element in the `inputs` argument of method `walletcreatefundedpsbt`.
+///
+/// The same in every version of Core.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct WalletCreateFundedPsbtInput {
+    txid: Txid,
+    vout: u32,
+}
+
+impl WalletCreateFundedPsbtInput {
+    /// Create a new walletcreatefundedpsbt input entry.
+    pub fn new(txid: Txid, vout: u32) -> Self { Self { txid, vout } }
+}
+
 /// Defines a `jsonrpc::Client` using `bitreq`.
 #[macro_export]
 macro_rules! define_jsonrpc_bitreq_client {
@@ -62,11 +141,16 @@ macro_rules! define_jsonrpc_bitreq_client {
         use std::fmt;
 
         use $crate::client_sync::{log_response, Auth, Result};
+        use $crate::client_sync::transport::{Connection, Credentials};
         use $crate::client_sync::error::Error;
 
         /// Client implements a JSON-RPC client for the Bitcoin Core daemon or compatible APIs.
         pub struct Client {
             inner: jsonrpc::client::Client,
+            /// The connection settings, shared with the wallet handles created from this client.
+            connection: std::sync::Arc<Connection>,
+            /// The wallet this client is scoped to, if it is a wallet handle.
+            wallet: Option<String>,
         }
 
         impl fmt::Debug for Client {
@@ -79,34 +163,77 @@ macro_rules! define_jsonrpc_bitreq_client {
         }
 
         impl Client {
+            /// The major version of Bitcoin Core this client is for.
+            pub const CORE_VERSION: usize = $crate::client_sync::major_version($version);
+
             /// Creates a client to a bitcoind JSON-RPC server without authentication.
             pub fn new(url: &str) -> Self {
-                let transport = jsonrpc::http::bitreq_http::Builder::new()
-                    .url(url)
-                    .expect("jsonrpc v0.19, this function does not error")
-                    .timeout(std::time::Duration::from_secs(60))
-                    .build();
-                let inner = jsonrpc::client::Client::with_transport(transport);
-
-                Self { inner }
+                Self::with_connection(Connection::new(url, Credentials::none()))
             }
 
             /// Creates a client to a bitcoind JSON-RPC server with authentication.
+            ///
+            /// With [`Auth::CookieFile`] the cookie file is read again if the server rejects the
+            /// credentials, e.g. after `bitcoind` restarted, and the request retried.
             pub fn new_with_auth(url: &str, auth: Auth) -> Result<Self> {
                 if matches!(auth, Auth::None) {
                     return Err(Error::MissingUserPassword);
                 }
-                let (user, pass) = auth.get_user_pass()?;
+                Ok(Self::with_connection(Connection::new(url, Credentials::new(auth)?)))
+            }
+
+            /// Creates a client for the node with data directory `datadir`.
+            ///
+            /// The URL and credentials are read from the config file as `bitcoin-cli` does, see
+            /// [`RpcConfig::from_datadir`]($crate::client_sync::config::RpcConfig::from_datadir).
+            pub fn from_datadir(
+                datadir: &std::path::Path,
+                conf: Option<&std::path::Path>,
+                network: Option<bitcoin::Network>,
+            ) -> Result<Self> {
+                let config =
+                    $crate::client_sync::config::RpcConfig::from_datadir(datadir, conf, network)?;
+                Self::new_with_auth(&config.url, config.auth)
+            }
 
-                let transport = jsonrpc::http::bitreq_http::Builder::new()
-                    .url(url)
-                    .expect("jsonrpc v0.19, this function does not error")
-                    .timeout(std::time::Duration::from_secs(60))
-                    .basic_auth(user.unwrap(), pass)
-                    .build();
-                let inner = jsonrpc::client::Client::with_transport(transport);
+            fn with_connection(connection: Connection) -> Self {
+                Self::from_connection(std::sync::Arc::new(connection))
+            }
 
-                Ok(Self { inner })
+            /// Creates a client using an existing connection, e.g. one shared with another client.
+            #[allow(dead_code)] // Only used by `auto::Client`, not in every crate using the macro.
+            pub(crate) fn from_connection(connection: std::sync::Arc<Connection>) -> Self {
+                let inner = Connection::jsonrpc_client(&connection, None);
+                Self { inner, connection, wallet: None }
+            }
+
+            /// Sets a function called with the path of the cookie file after new credentials were
+            /// read from it.
+            ///
+            /// Applies to this client and all wallet handles created from it.
+            pub fn on_cookie_reload<F>(&self, f: F)
+            where
+                F: Fn(&std::path::Path) + std::marker::Send + std::marker::Sync + 'static,
+            {
+                self.connection.credentials().set_on_reload(Box::new(f));
+            }
+
+            /// Returns a handle for calling wallet methods on the wallet `name`.
+            ///
+            /// The handle uses the same server and credentials as this client, creating one is
+            /// cheap. If the wallet is not loaded when a method is called on the handle, the
+            /// wallet is loaded and the call retried.
+            pub fn wallet(&self, name: &str) -> Self {
+                Self {
+                    inner: Connection::jsonrpc_client(&self.connection, Some(name)),
+                    connection: std::sync::Arc::clone(&self.connection),
+                    wallet: Some(name.to_owned()),
+                }
+            }
+
+            /// Returns a builder for a batch of calls sent to the server in a single request.
+            pub fn batch(&self) -> $crate::client_sync::batch::Batch<'_, Self> {
+                $crate::client_sync::batch::Batch::new(&self.inner)
             }
 
             /// Call an RPC `method` with given `args` list.
@@ -116,19 +243,116 @@ macro_rules! define_jsonrpc_bitreq_client {
                 args: &[serde_json::Value],
             ) -> Result<T> {
                 let raw = serde_json::value::to_raw_value(args)?;
-                let req = self.inner.build_request(&method, Some(&*raw));
+                self.call_with_params(method, &raw)
+            }
+
+            /// Call an RPC `method` with given named `args`.
+            ///
+            /// Arguments left out use their default value, so any optional argument can be set
+            /// without also setting the ones before it.
+            pub fn call_named<T: for<'a> serde::de::Deserialize<'a>>(
+                &self,
+                method: &str,
+                args: &serde_json::Map<String, serde_json::Value>,
+            ) -> Result<T> {
+                let raw = serde_json::value::to_raw_value(args)?;
+                self.call_with_params(method, &raw)
+            }
+
+            fn call_with_params<T: for<'a> serde::de::Deserialize<'a>>(
+                &self,
+                method: &str,
+                params: &serde_json::value::RawValue,
+            ) -> Result<T> {
+                let resp = self.send_request(method, params)?;
+                if let Some(wallet) = self.wallet_to_load(&resp) {
+                    let load_params = serde_json::value::to_raw_value(&[wallet])?;
+                    // Retry even if loading failed, the wallet may have been loaded concurrently.
+                    let _ = self.send_request("loadwallet", &load_params);
+                    return Ok(self.send_request(method, params)?.result()?);
+                }
+                Ok(resp.result()?)
+            }
+
+            fn send_request(
+                &self,
+                method: &str,
+                params: &serde_json::value::RawValue,
+            ) -> Result<jsonrpc::Response> {
+                let req = self.inner.build_request(&method, Some(params));
                 if log::log_enabled!(log::Level::Debug) {
-                    log::debug!(target: "corepc", "request: {} {}", method, serde_json::Value::from(args));
+                    log::debug!(target: "corepc", "request: {} {}", method, params);
                 }
 
                 let resp = self.inner.send_request(req).map_err(Error::from);
                 log_response(method, &resp);
-                Ok(resp?.result()?)
+                resp
             }
         }
+
+        $crate::impl_client_wallet_handle!();
     }
 }
 
+/// Implements the methods shared by the blocking and async clients for using wallet handles.
+///
+/// Requires `Client` to be in scope and have a `wallet: Option<String>` field, a `wallet()` method
+/// and a `call()` method.
+///
+/// # Parameters
+///
+/// - `async`: Optional, implements the methods as `async fn`s for the `client_async` clients.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_client_wallet_handle {
+    ($($async:tt)?) => {
+        impl Client {
+            /// Returns the name of the wallet this client is scoped to, if it is a wallet handle.
+            pub fn wallet_name(&self) -> Option<&str> { self.wallet.as_deref() }
+
+            /// Returns the names of the currently loaded wallets.
+            pub $($async)? fn loaded_wallets(&self) -> Result<Vec<String>> {
+                $crate::maybe_await!(self.call("listwallets", &[]), $($async)?)
+            }
+
+            /// Loads the wallet `name`, unless it is already loaded, and returns a handle for it.
+            pub $($async)? fn open_wallet(&self, name: &str) -> Result<Self> {
+                let loaded = $crate::maybe_await!(self.loaded_wallets(), $($async)?)?;
+                if !loaded.iter().any(|w| w == name) {
+                    let _: serde_json::Value = $crate::maybe_await!(
+                        self.call("loadwallet", &[name.into()]),
+                        $($async)?
+                    )?;
+                }
+                Ok(self.wallet(name))
+            }
+
+            /// Creates the wallet `name`, with default options, and returns a handle for it.
+            pub $($async)? fn new_wallet(&self, name: &str) -> Result<Self> {
+                let _: serde_json::Value =
+                    $crate::maybe_await!(self.call("createwallet", &[name.into()]), $($async)?)?;
+                Ok(self.wallet(name))
+            }
+
+            /// Unloads the wallet `name`.
+            pub $($async)? fn close_wallet(&self, name: &str) -> Result<()> {
+                let _: serde_json::Value =
+                    $crate::maybe_await!(self.call("unloadwallet", &[name.into()]), $($async)?)?;
+                Ok(())
+            }
+
+            /// Returns the wallet to load before retrying a call that returned `resp`.
+            fn wallet_to_load(&self, resp: &jsonrpc::Response) -> Option<&str> {
+                match resp.error {
+                    Some(ref e) if e.code == $crate::client_sync::RPC_WALLET_NOT_FOUND =>
+                        self.wallet.as_deref(),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
 /// Implements the `check_expected_server_version()` on `Client`.
 ///
 /// Requires `Client` to be in scope and implement `server_version()`.
@@ -137,13 +361,14 @@ macro_rules! define_jsonrpc_bitreq_client {
 /// # Parameters
 ///
 /// - `$expected_versions`: An vector of expected server versions e.g., `[230100, 230200]`.
+/// - `async`: Optional, implements the method as an `async fn` for the `client_async` clients.
 #[macro_export]
 macro_rules! impl_client_check_expected_server_version {
-    ($expected_versions:expr) => {
+    ($expected_versions:expr $(, $async:tt)?) => {
         impl Client {
             /// Checks that the JSON-RPC endpoint is for a `bitcoind` instance with the expected version.
-            pub fn check_expected_server_version(&self) -> Result<()> {
-                let server_version = self.server_version()?;
+            pub $($async)? fn check_expected_server_version(&self) -> Result<()> {
+                let server_version = $crate::maybe_await!(self.server_version(), $($async)?)?;
                 if !$expected_versions.contains(&server_version) {
                     return Err($crate::client_sync::error::UnexpectedServerVersionError {
                         got: server_version,
@@ -156,8 +381,36 @@ macro_rules! impl_client_check_expected_server_version {
     };
 }
 
+/// Awaits `$e` when passed `async`, used by the `impl_client_*` macros so that each method can be
+/// implemented for both the blocking and the async clients.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! maybe_await {
+    ($e:expr, async) => {
+        $e.await
+    };
+    ($e:expr,) => {
+        $e
+    };
+}
+
+/// Error code returned by Core if the requested wallet does not exist or is not loaded.
+pub(crate) const RPC_WALLET_NOT_FOUND: i32 = -18;
+
+/// Returns the major Core version from a client's version string e.g., 17 for `"v17"`.
+pub(crate) const fn major_version(version: &str) -> usize {
+    let bytes = version.as_bytes();
+    let mut major = 0;
+    let mut i = 1; // Skip the leading 'v'.
+    while i < bytes.len() {
+        major = major * 10 + (bytes[i] - b'0') as usize;
+        i += 1;
+    }
+    major
+}
+
 /// Shorthand for converting a variable into a `serde_json::Value`.
-fn into_json<T>(val: T) -> Result<serde_json::Value>
+pub(crate) fn into_json<T>(val: T) -> Result<serde_json::Value>
 where
     T: serde::ser::Serialize,
 {
@@ -165,7 +418,7 @@ where
 }
 
 /// Helper to log an RPC response.
-fn log_response(method: &str, resp: &Result<jsonrpc::Response>) {
+pub(crate) fn log_response(method: &str, resp: &Result<jsonrpc::Response>) {
     use log::Level::{Debug, Trace, Warn};
 
     if log::log_enabled!(Warn) || log::log_enabled!(Debug) || log::log_enabled!(Trace) {
@@ -188,3 +441,78 @@ fn log_response(method: &str, resp: &Result<jsonrpc::Response>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+
+    use crate::client_sync::mock::{response, MockServer};
+    use crate::client_sync::v29::Client;
+
+    /// Returns a server with the wallet `alice`, which is loaded if `loaded` is true.
+    fn server(loaded: bool) -> MockServer {
+        let loaded = Arc::new(AtomicBool::new(loaded));
+        MockServer::start(move |request| {
+            let body = &request.body;
+            match body["method"].as_str() {
+                Some("loadwallet") => {
+                    loaded.store(true, Ordering::Relaxed);
+                    (200, response(body, json!({ "name": "alice", "warning": "" })))
+                }
+                Some("getbalance") if request.path != "/wallet/alice" => {
+                    let error = json!({ "code": -19, "message": "Wallet file not specified" });
+                    (500, json!({ "id": body["id"], "result": null, "error": error }))
+                }
+                Some("getbalance") if !loaded.load(Ordering::Relaxed) => {
+                    let error = json!({ "code": -18, "message": "Requested wallet does not exist or is not loaded" });
+                    (500, json!({ "id": body["id"], "result": null, "error": error }))
+                }
+                Some("getbalance") => (200, response(body, json!(1.5))),
+                _ => (500, Value::Null),
+            }
+        })
+    }
+
+    fn methods(server: &MockServer) -> Vec<(String, String)> {
+        server
+            .requests()
+            .into_iter()
+            .map(|r| (r.path, r.body["method"].as_str().unwrap_or_default().to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn wallet_handle_loads_unloaded_wallet() {
+        let server = server(false);
+        let alice = Client::new(server.url()).wallet("alice");
+
+        assert_eq!(alice.get_balance().unwrap().0, 1.5);
+        let expected = [
+            ("/wallet/alice", "getbalance"),
+            ("/wallet/alice", "loadwallet"),
+            ("/wallet/alice", "getbalance"),
+        ];
+        assert_eq!(methods(&server), expected.map(|(p, m)| (p.to_owned(), m.to_owned())));
+    }
+
+    #[test]
+    fn wallet_handle_does_not_load_loaded_wallet() {
+        let server = server(true);
+        let alice = Client::new(server.url()).wallet("alice");
+
+        assert_eq!(alice.get_balance().unwrap().0, 1.5);
+        assert_eq!(methods(&server).len(), 1);
+    }
+
+    #[test]
+    fn client_without_wallet_does_not_load() {
+        let server = server(false);
+        let client = Client::new(server.url());
+
+        assert!(client.get_balance().is_err());
+        assert_eq!(methods(&server), [("/".to_owned(), "getbalance".to_owned())]);
+    }
+}
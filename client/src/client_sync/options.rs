@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Optional arguments for the JSON-RPC methods that have many of them.
+//!
+//! The plain client methods ignore optional arguments unless they effect the shape of the returned
+//! JSON data. The `*_with_options` methods take one of the structs in this module instead, every
+//! field left as `None` (or empty) is not sent and Core uses its default. The arguments are sent as
+//! named parameters, which every Core version supported by this crate accepts.
+//!
+//! Some arguments were added in later versions of Core, these are documented on the field. Setting
+//! one when calling a client for an older version returns [`Error::UnsupportedArgument`] without
+//! making a request.
+
+use std::collections::BTreeMap;
+
+use bitcoin::address::{Address, NetworkChecked};
+use bitcoin::{absolute, Amount, FeeRate};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::client_sync::{into_json, AddressType, Error, Result};
+
+/// Fee estimate mode used by the `estimate_mode` argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EstimateMode {
+    /// Let Core pick the mode.
+    Unset,
+    /// Use a lower fee estimate that responds faster to short term drops in fees.
+    Economical,
+    /// Use a more conservative fee estimate over a longer history.
+    Conservative,
+}
+
+/// Optional arguments of the `sendtoaddress` method.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SendToAddressOptions {
+    /// A comment used to store what the transaction is for, stored in the wallet only.
+    pub comment: Option<String>,
+    /// A comment to store the name of the person or organization being sent to.
+    pub comment_to: Option<String>,
+    /// Deduct the fee from the amount being sent.
+    pub subtract_fee_from_amount: Option<bool>,
+    /// Signal that this transaction can be replaced by a transaction with a higher fee (BIP-125).
+    pub replaceable: Option<bool>,
+    /// Confirmation target in blocks.
+    pub conf_target: Option<u32>,
+    /// The fee estimate mode.
+    pub estimate_mode: Option<EstimateMode>,
+    /// Avoid spending from dirty addresses (Core v0.19 and later).
+    pub avoid_reuse: Option<bool>,
+    /// Explicit fee rate, overrides `conf_target` and `estimate_mode` (Core v21 and later).
+    pub fee_rate: Option<FeeRate>,
+}
+
+impl SendToAddressOptions {
+    pub(crate) fn args(
+        &self,
+        address: &Address<NetworkChecked>,
+        amount: Amount,
+        version: usize,
+    ) -> Result<Args> {
+        Args::new("sendtoaddress", version)
+            .arg("address", address.to_string())?
+            .arg("amount", amount.to_btc())?
+            .opt("comment", self.comment.as_ref())?
+            .opt("comment_to", self.comment_to.as_ref())?
+            .opt("subtractfeefromamount", self.subtract_fee_from_amount)?
+            .opt("replaceable", self.replaceable)?
+            .opt("conf_target", self.conf_target)?
+            .opt("estimate_mode", self.estimate_mode)?
+            .opt_since("avoid_reuse", 19, self.avoid_reuse)?
+            .opt_since("fee_rate", 21, self.fee_rate.map(sat_per_vb))
+    }
+}
+
+/// Optional arguments of the `sendmany` method.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SendManyOptions {
+    /// Only use the balance confirmed at least this many times.
+    pub minconf: Option<u32>,
+    /// A comment, stored in the wallet only.
+    pub comment: Option<String>,
+    /// The fee is equally deducted from the amounts sent to these addresses.
+    pub subtract_fee_from: Vec<Address>,
+    /// Signal that this transaction can be replaced by a transaction with a higher fee (BIP-125).
+    pub replaceable: Option<bool>,
+    /// Confirmation target in blocks.
+    pub conf_target: Option<u32>,
+    /// The fee estimate mode.
+    pub estimate_mode: Option<EstimateMode>,
+    /// Explicit fee rate, overrides `conf_target` and `estimate_mode` (Core v21 and later).
+    pub fee_rate: Option<FeeRate>,
+}
+
+impl SendManyOptions {
+    pub(crate) fn args(&self, amounts: &BTreeMap<Address, Amount>, version: usize) -> Result<Args> {
+        let amounts: Map<String, Value> = amounts
+            .iter()
+            .map(|(addr, amount)| (addr.to_string(), amount.to_btc().into()))
+            .collect();
+        let subtract_fee_from = non_empty(addresses(&self.subtract_fee_from));
+
+        Args::new("sendmany", version)
+            .arg("dummy", "")? // Must be set to "" for backwards compatibility.
+            .arg("amounts", amounts)?
+            .opt("minconf", self.minconf)?
+            .opt("comment", self.comment.as_ref())?
+            .opt("subtractfeefrom", subtract_fee_from)?
+            .opt("replaceable", self.replaceable)?
+            .opt("conf_target", self.conf_target)?
+            .opt("estimate_mode", self.estimate_mode)?
+            .opt_since("fee_rate", 21, self.fee_rate.map(sat_per_vb))
+    }
+}
+
+/// The `options` argument of the `fundrawtransaction` method.
+///
+/// Also used for the options of `walletcreatefundedpsbt`, see [`WalletCreateFundedPsbtOptions`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FundRawTransactionOptions {
+    /// The address to receive the change.
+    pub change_address: Option<Address>,
+    /// The index of the change output.
+    pub change_position: Option<u32>,
+    /// The output type to use for the change, only used if `change_address` is not set.
+    ///
+    /// [`AddressType::Bech32m`] is supported by Core v23 and later.
+    pub change_type: Option<AddressType>,
+    /// Also select inputs which are watch only.
+    pub include_watching: Option<bool>,
+    /// Lock selected unspent outputs.
+    pub lock_unspents: Option<bool>,
+    /// Explicit fee rate, overrides `conf_target` and `estimate_mode`.
+    pub fee_rate: Option<FeeRate>,
+    /// The fee is equally deducted from the outputs with these indices.
+    pub subtract_fee_from_outputs: Vec<u32>,
+    /// Signal that this transaction can be replaced by a transaction with a higher fee (BIP-125).
+    pub replaceable: Option<bool>,
+    /// Confirmation target in blocks.
+    pub conf_target: Option<u32>,
+    /// The fee estimate mode.
+    pub estimate_mode: Option<EstimateMode>,
+}
+
+impl FundRawTransactionOptions {
+    /// Returns the options as a JSON object, `null` if no option is set.
+    fn to_value(&self, method: &'static str, version: usize) -> Result<Value> {
+        let subtract_fee_from_outputs = non_empty(self.subtract_fee_from_outputs.clone());
+        let change_type_since = match self.change_type {
+            Some(AddressType::Bech32m) => 23,
+            _ => 17,
+        };
+
+        Object::new(method, version)
+            .opt("changeAddress", self.change_address.as_ref().map(ToString::to_string))?
+            .opt("changePosition", self.change_position)?
+            .opt_since("change_type", change_type_since, self.change_type.as_ref())?
+            .opt("includeWatching", self.include_watching)?
+            .opt("lockUnspents", self.lock_unspents)?
+            .opt("feeRate", self.fee_rate.map(btc_per_kvb))?
+            .opt("subtractFeeFromOutputs", subtract_fee_from_outputs)?
+            .opt("replaceable", self.replaceable)?
+            .opt("conf_target", self.conf_target)?
+            .opt("estimate_mode", self.estimate_mode)
+            .map(Object::into_value)
+    }
+
+    pub(crate) fn args(&self, tx: &bitcoin::Transaction, version: usize) -> Result<Args> {
+        let method = "fundrawtransaction";
+        Args::new(method, version)
+            .arg("hexstring", bitcoin::consensus::encode::serialize_hex(tx))?
+            .opt("options", Some(self.to_value(method, version)?))
+    }
+}
+
+/// Optional arguments of the `walletcreatefundedpsbt` method.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WalletCreateFundedPsbtOptions {
+    /// Raw locktime, non-zero values also locktime-activate inputs.
+    pub locktime: Option<absolute::LockTime>,
+    /// Options used when funding the transaction.
+    pub fund: FundRawTransactionOptions,
+    /// Include BIP-32 derivation paths for public keys if we know them.
+    pub bip32derivs: Option<bool>,
+}
+
+impl WalletCreateFundedPsbtOptions {
+    pub(crate) fn args(&self, inputs: Value, outputs: Value, version: usize) -> Result<Args> {
+        let method = "walletcreatefundedpsbt";
+        Args::new(method, version)
+            .arg("inputs", inputs)?
+            .arg("outputs", outputs)?
+            .opt("locktime", self.locktime.map(|l| l.to_consensus_u32()))?
+            .opt("options", Some(self.fund.to_value(method, version)?))?
+            .opt("bip32derivs", self.bip32derivs)
+    }
+}
+
+/// Filters for the unspent outputs returned by the `listunspent` method.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListUnspentQuery {
+    /// The minimum confirmations to filter.
+    pub minconf: Option<u32>,
+    /// The maximum confirmations to filter.
+    pub maxconf: Option<u32>,
+    /// Only return outputs paying to one of these addresses.
+    pub addresses: Vec<Address>,
+    /// Include outputs that are not safe to spend.
+    pub include_unsafe: Option<bool>,
+    /// Minimum value of each output.
+    pub minimum_amount: Option<Amount>,
+    /// Maximum value of each output.
+    pub maximum_amount: Option<Amount>,
+    /// Maximum number of outputs.
+    pub maximum_count: Option<u32>,
+    /// Minimum sum value of all outputs.
+    pub minimum_sum_amount: Option<Amount>,
+    /// Include immature coinbase outputs (Core v24 and later).
+    pub include_immature_coinbase: Option<bool>,
+}
+
+impl ListUnspentQuery {
+    pub(crate) fn args(&self, version: usize) -> Result<Args> {
+        let method = "listunspent";
+        let query_options = Object::new(method, version)
+            .opt("minimumAmount", self.minimum_amount.map(Amount::to_btc))?
+            .opt("maximumAmount", self.maximum_amount.map(Amount::to_btc))?
+            .opt("maximumCount", self.maximum_count)?
+            .opt("minimumSumAmount", self.minimum_sum_amount.map(Amount::to_btc))?
+            .opt_since("include_immature_coinbase", 24, self.include_immature_coinbase)?
+            .into_value();
+
+        Args::new(method, version)
+            .opt("minconf", self.minconf)?
+            .opt("maxconf", self.maxconf)?
+            .opt("addresses", non_empty(addresses(&self.addresses)))?
+            .opt("include_unsafe", self.include_unsafe)?
+            .opt("query_options", Some(query_options))
+    }
+}
+
+/// Optional arguments of the `getbalance` method.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GetBalanceOptions {
+    /// Only include transactions confirmed at least this many times.
+    pub minconf: Option<u32>,
+    /// Also include balance in watch-only addresses.
+    pub include_watchonly: Option<bool>,
+    /// Do not include balance in dirty outputs (Core v0.19 and later).
+    pub avoid_reuse: Option<bool>,
+}
+
+impl GetBalanceOptions {
+    pub(crate) fn args(&self, version: usize) -> Result<Args> {
+        Args::new("getbalance", version)
+            .opt("minconf", self.minconf)?
+            .opt("include_watchonly", self.include_watchonly)?
+            .opt_since("avoid_reuse", 19, self.avoid_reuse)
+    }
+}
+
+/// Optional arguments of the `listtransactions` method.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListTransactionsOptions {
+    /// Only return transactions with this label, `"*"` for all.
+    pub label: Option<String>,
+    /// The number of transactions to return.
+    pub count: Option<u32>,
+    /// The number of transactions to skip.
+    pub skip: Option<u32>,
+    /// Include transactions to watch-only addresses.
+    pub include_watchonly: Option<bool>,
+}
+
+impl ListTransactionsOptions {
+    pub(crate) fn args(&self, version: usize) -> Result<Args> {
+        Args::new("listtransactions", version)
+            .opt("label", self.label.as_ref())?
+            .opt("count", self.count)?
+            .opt("skip", self.skip)?
+            .opt("include_watchonly", self.include_watchonly)
+    }
+}
+
+/// The named arguments of a method call built from one of the option structs.
+#[derive(Debug)]
+pub(crate) struct Args {
+    method: &'static str,
+    /// The major Core version of the client making the call.
+    version: usize,
+    args: Vec<(&'static str, Value)>,
+}
+
+impl Args {
+    fn new(method: &'static str, version: usize) -> Self { Self { method, version, args: vec![] } }
+
+    /// Adds a required argument.
+    fn arg<T: Serialize>(mut self, name: &'static str, value: T) -> Result<Self> {
+        self.args.push((name, into_json(value)?));
+        Ok(self)
+    }
+
+    /// Adds an optional argument supported by every Core version.
+    fn opt<T: Serialize>(self, name: &'static str, value: Option<T>) -> Result<Self> {
+        self.opt_since(name, 17, value)
+    }
+
+    /// Adds an optional argument first supported by Core version `since`.
+    fn opt_since<T: Serialize>(
+        mut self,
+        name: &'static str,
+        since: usize,
+        value: Option<T>,
+    ) -> Result<Self> {
+        let value = match value {
+            Some(value) => {
+                check_version(self.method, name, since, self.version)?;
+                into_json(value)?
+            }
+            None => Value::Null,
+        };
+        self.args.push((name, value));
+        Ok(self)
+    }
+
+    /// Returns the arguments as named parameters, leaving out unset optional arguments.
+    pub(crate) fn into_named(self) -> Map<String, Value> {
+        self.args
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect()
+    }
+}
+
+/// A JSON object argument built from one of the option structs, unset fields are left out.
+struct Object {
+    method: &'static str,
+    version: usize,
+    map: Map<String, Value>,
+}
+
+impl Object {
+    fn new(method: &'static str, version: usize) -> Self {
+        Self { method, version, map: Map::new() }
+    }
+
+    fn opt<T: Serialize>(self, name: &'static str, value: Option<T>) -> Result<Self> {
+        self.opt_since(name, 17, value)
+    }
+
+    fn opt_since<T: Serialize>(
+        mut self,
+        name: &'static str,
+        since: usize,
+        value: Option<T>,
+    ) -> Result<Self> {
+        if let Some(value) = value {
+            check_version(self.method, name, since, self.version)?;
+            self.map.insert(name.to_owned(), into_json(value)?);
+        }
+        Ok(self)
+    }
+
+    /// Returns the object, or `null` if it is empty.
+    fn into_value(self) -> Value {
+        if self.map.is_empty() {
+            Value::Null
+        } else {
+            Value::Object(self.map)
+        }
+    }
+}
+
+fn check_version(
+    method: &'static str,
+    argument: &'static str,
+    since: usize,
+    version: usize,
+) -> Result<()> {
+    if version < since {
+        return Err(Error::UnsupportedArgument { method, argument, version });
+    }
+    Ok(())
+}
+
+fn addresses(addresses: &[Address]) -> Vec<String> {
+    addresses.iter().map(ToString::to_string).collect()
+}
+
+fn non_empty<T>(v: Vec<T>) -> Option<Vec<T>> {
+    if v.is_empty() {
+        None
+    } else {
+        Some(v)
+    }
+}
+
+/// Converts `fee_rate` into sat/vB as used by the `fee_rate` arguments.
+fn sat_per_vb(fee_rate: FeeRate) -> f64 { fee_rate.to_sat_per_kwu() as f64 * 4.0 / 1000.0 }
+
+/// Converts `fee_rate` into BTC/kvB as used by the `feeRate` option.
+fn btc_per_kvb(fee_rate: FeeRate) -> f64 { fee_rate.to_sat_per_kwu() as f64 * 4.0 / 100_000_000.0 }
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash;
+    use bitcoin::Txid;
+    use serde_json::json;
+
+    use super::*;
+
+    fn address() -> Address {
+        "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"
+            .parse::<Address<_>>()
+            .unwrap()
+            .assume_checked()
+    }
+
+    fn is_unsupported(result: Result<Args>, expected: &'static str, at: usize) -> bool {
+        matches!(
+            result,
+            Err(Error::UnsupportedArgument { argument, version, .. })
+                if argument == expected && version == at
+        )
+    }
+
+    #[test]
+    fn send_to_address_leaves_out_unset_options() {
+        let args = SendToAddressOptions::default()
+            .args(&address(), Amount::from_sat(100_000), 17)
+            .unwrap()
+            .into_named();
+
+        let want = json!({ "address": address().to_string(), "amount": 0.001 });
+        assert_eq!(Value::Object(args), want);
+    }
+
+    #[test]
+    fn send_to_address_options() {
+        let options = SendToAddressOptions {
+            comment: Some("comment".to_owned()),
+            replaceable: Some(true),
+            estimate_mode: Some(EstimateMode::Economical),
+            avoid_reuse: Some(false),
+            fee_rate: Some(FeeRate::from_sat_per_kwu(500)),
+            ..Default::default()
+        };
+        let args = options.args(&address(), Amount::from_sat(100_000), 21).unwrap().into_named();
+
+        let want = json!({
+            "address": address().to_string(),
+            "amount": 0.001,
+            "comment": "comment",
+            "replaceable": true,
+            "estimate_mode": "ECONOMICAL",
+            "avoid_reuse": false,
+            "fee_rate": 2.0,
+        });
+        assert_eq!(Value::Object(args), want);
+    }
+
+    #[test]
+    fn send_to_address_checks_argument_versions() {
+        let amount = Amount::from_sat(100_000);
+        let options = SendToAddressOptions { avoid_reuse: Some(true), ..Default::default() };
+        assert!(is_unsupported(options.args(&address(), amount, 18), "avoid_reuse", 18));
+        assert!(options.args(&address(), amount, 19).is_ok());
+
+        let fee_rate = Some(FeeRate::from_sat_per_kwu(250));
+        let options = SendToAddressOptions { fee_rate, ..Default::default() };
+        assert!(is_unsupported(options.args(&address(), amount, 20), "fee_rate", 20));
+        assert!(options.args(&address(), amount, 21).is_ok());
+    }
+
+    #[test]
+    fn send_many_options() {
+        let amounts = BTreeMap::from([(address(), Amount::from_sat(50_000))]);
+        let options = SendManyOptions {
+            minconf: Some(0),
+            subtract_fee_from: vec![address()],
+            ..Default::default()
+        };
+        let args = options.args(&amounts, 17).unwrap().into_named();
+
+        let want = json!({
+            "dummy": "",
+            "amounts": { address().to_string(): 0.0005 },
+            "minconf": 0,
+            "subtractfeefrom": [address().to_string()],
+        });
+        assert_eq!(Value::Object(args), want);
+    }
+
+    #[test]
+    fn fund_raw_transaction_options_object() {
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        let options = FundRawTransactionOptions {
+            change_type: Some(AddressType::Bech32),
+            fee_rate: Some(FeeRate::from_sat_per_kwu(2_500)),
+            subtract_fee_from_outputs: vec![0],
+            ..Default::default()
+        };
+        let args = options.args(&tx, 17).unwrap().into_named();
+
+        let want = json!({
+            "hexstring": bitcoin::consensus::encode::serialize_hex(&tx),
+            "options": {
+                "change_type": "bech32",
+                "feeRate": 0.0001,
+                "subtractFeeFromOutputs": [0],
+            },
+        });
+        assert_eq!(Value::Object(args), want);
+
+        let args = FundRawTransactionOptions::default().args(&tx, 17).unwrap().into_named();
+        assert!(!args.contains_key("options"));
+    }
+
+    #[test]
+    fn bech32m_change_type_requires_v23() {
+        let fund = FundRawTransactionOptions {
+            change_type: Some(AddressType::Bech32m),
+            ..Default::default()
+        };
+        let options = WalletCreateFundedPsbtOptions { fund, ..Default::default() };
+        assert!(is_unsupported(options.args(json!([]), json!([]), 22), "change_type", 22));
+
+        let args = options.args(json!([]), json!([]), 23).unwrap().into_named();
+        assert_eq!(args["options"], json!({ "change_type": "bech32m" }));
+    }
+
+    #[test]
+    fn wallet_create_funded_psbt_sends_replaceable_as_option() {
+        let txid = Txid::from_byte_array([1; 32]);
+        let inputs = json!([{ "txid": txid, "vout": 0 }]);
+        let options = WalletCreateFundedPsbtOptions {
+            locktime: Some(absolute::LockTime::from_consensus(100)),
+            fund: FundRawTransactionOptions { replaceable: Some(true), ..Default::default() },
+            bip32derivs: Some(false),
+        };
+
+        for version in [17, 18, 30] {
+            let args = options.args(inputs.clone(), json!([]), version).unwrap().into_named();
+
+            let want = json!({
+                "inputs": inputs,
+                "outputs": [],
+                "locktime": 100,
+                "options": { "replaceable": true },
+                "bip32derivs": false,
+            });
+            assert_eq!(Value::Object(args), want);
+        }
+    }
+
+    #[test]
+    fn list_unspent_query_options() {
+        let query = ListUnspentQuery {
+            minconf: Some(1),
+            minimum_amount: Some(Amount::from_sat(1_000)),
+            maximum_count: Some(10),
+            ..Default::default()
+        };
+        let args = query.args(17).unwrap().into_named();
+
+        let want = json!({
+            "minconf": 1,
+            "query_options": { "minimumAmount": 0.00001, "maximumCount": 10 },
+        });
+        assert_eq!(Value::Object(args), want);
+
+        let query =
+            ListUnspentQuery { include_immature_coinbase: Some(true), ..Default::default() };
+        assert!(is_unsupported(query.args(23), "include_immature_coinbase", 23));
+        let args = query.args(24).unwrap().into_named();
+        assert_eq!(args["query_options"], json!({ "include_immature_coinbase": true }));
+    }
+
+    #[test]
+    fn get_balance_checks_avoid_reuse_version() {
+        let options = GetBalanceOptions { avoid_reuse: Some(true), ..Default::default() };
+        assert!(is_unsupported(options.args(18), "avoid_reuse", 18));
+        let args = options.args(19).unwrap().into_named();
+        assert_eq!(Value::Object(args), json!({ "avoid_reuse": true }));
+    }
+
+    #[test]
+    fn list_transactions_options() {
+        let options = ListTransactionsOptions {
+            label: Some("*".to_owned()),
+            skip: Some(5),
+            ..Default::default()
+        };
+        let args = options.args(17).unwrap().into_named();
+        assert_eq!(Value::Object(args), json!({ "label": "*", "skip": 5 }));
+    }
+}
@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Credentials for Core's `-rpcauth` option.
+//!
+//! An `rpcauth` value has the form `<user>:<salt>$<hash>`, where the hash is the HMAC-SHA256 of the
+//! password keyed with the hex encoded salt. This lets the node check the password without the
+//! password itself being stored in its config, see Core's `share/rpcauth/rpcauth.py`.
+//!
+//! [`Auth::RpcAuth`] pairs the `rpcauth` value passed to the node with the password the client
+//! authenticates with.
+//!
+//! ```
+//! # use corepc_client::client_sync::{Auth, RpcAuth};
+//! let rpcauth = RpcAuth::with_salt("alice", "hunter2", "cccd5d7fd36e55c1b8576b8077dc1b83");
+//! let auth = Auth::RpcAuth(rpcauth, "hunter2".to_owned());
+//! let arg = auth.rpcauth().unwrap().to_arg(); // Pass to `bitcoind`.
+//! ```
+//!
+//! [`Auth::RpcAuth`]: crate::client_sync::Auth::RpcAuth
+
+use std::fmt;
+use std::str::FromStr;
+
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use bitcoin::hex::DisplayHex;
+
+use crate::client_sync::Error;
+
+/// The number of random bytes in a salt, as used by `rpcauth.py`.
+#[cfg(feature = "rpcauth-random")]
+const SALT_LEN: usize = 16;
+
+/// The value of an `-rpcauth` option, the user and salted password hash.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct RpcAuth {
+    user: String,
+    salt: String,
+    hash: String,
+}
+
+impl RpcAuth {
+    /// Creates the `rpcauth` value for `user` and `password` with a random salt.
+    ///
+    /// Errors if the system's random number generator fails.
+    #[cfg(feature = "rpcauth-random")]
+    pub fn new(user: &str, password: &str) -> Result<Self, Error> {
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::getrandom(&mut salt).map_err(|e| Error::Io(e.into()))?;
+        Ok(Self::with_salt(user, password, &salt.to_lower_hex_string()))
+    }
+
+    /// Creates the `rpcauth` value for `user` and `password` with the given hex encoded `salt`.
+    pub fn with_salt(user: &str, password: &str, salt: &str) -> Self {
+        Self { user: user.to_owned(), salt: salt.to_owned(), hash: password_hmac(salt, password) }
+    }
+
+    /// Returns the user.
+    pub fn user(&self) -> &str { &self.user }
+
+    /// Returns the hex encoded salt.
+    pub fn salt(&self) -> &str { &self.salt }
+
+    /// Returns the hex encoded HMAC-SHA256 of the password.
+    pub fn hash(&self) -> &str { &self.hash }
+
+    /// Returns true if `password` is the password these credentials were created with.
+    pub fn verify(&self, password: &str) -> bool {
+        let hash = password_hmac(&self.salt, password);
+        // Compares every byte so the time taken does not depend on where the hashes differ.
+        hash.len() == self.hash.len()
+            && hash.bytes().zip(self.hash.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+
+    /// Returns the command line argument for `bitcoind` e.g., `-rpcauth=user:salt$hash`.
+    pub fn to_arg(&self) -> String { format!("-rpcauth={}", self) }
+}
+
+/// Formats as the value of the `-rpcauth` option, `<user>:<salt>$<hash>`.
+impl fmt::Display for RpcAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}${}", self.user, self.salt, self.hash)
+    }
+}
+
+impl FromStr for RpcAuth {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (user, rest) = s.split_once(':').ok_or(Error::InvalidRpcAuth)?;
+        let (salt, hash) = rest.split_once('$').ok_or(Error::InvalidRpcAuth)?;
+        // Core splits the value on both separators so neither may appear more than once.
+        if user.contains('$') || hash.contains(['$', ':']) || salt.contains(':') {
+            return Err(Error::InvalidRpcAuth);
+        }
+        Ok(Self { user: user.to_owned(), salt: salt.to_owned(), hash: hash.to_owned() })
+    }
+}
+
+/// Returns the hex encoded HMAC-SHA256 of `password` keyed with `salt`.
+fn password_hmac(salt: &str, password: &str) -> String {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(salt.as_bytes());
+    engine.input(password.as_bytes());
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_byte_array().to_lower_hex_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_sync::Auth;
+
+    /// Generated with `rpcauth.py bitcoind bitcoind`.
+    const VECTOR: &str = "bitcoind:cccd5d7fd36e55c1b8576b8077dc1b83$60b5676a09f8518dcb4574838fb86f37700cd690d99bd2fdc2ea2bf2ab80ead6";
+
+    #[test]
+    fn matches_rpcauth_py() {
+        let rpcauth =
+            RpcAuth::with_salt("bitcoind", "bitcoind", "cccd5d7fd36e55c1b8576b8077dc1b83");
+        assert_eq!(rpcauth.to_string(), VECTOR);
+        assert_eq!(rpcauth.to_arg(), format!("-rpcauth={}", VECTOR));
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        let rpcauth = VECTOR.parse::<RpcAuth>().unwrap();
+        assert_eq!(rpcauth.user(), "bitcoind");
+        assert_eq!(rpcauth.salt(), "cccd5d7fd36e55c1b8576b8077dc1b83");
+        assert_eq!(rpcauth.to_string(), VECTOR);
+    }
+
+    #[test]
+    fn verify_password() {
+        let rpcauth = VECTOR.parse::<RpcAuth>().unwrap();
+        assert!(rpcauth.verify("bitcoind"));
+        assert!(!rpcauth.verify("bitcoin"));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for s in ["bitcoind", "bitcoind:salt", "bit$coind:salt$hash", "bitcoind:salt$ha:sh"] {
+            assert!(matches!(s.parse::<RpcAuth>(), Err(Error::InvalidRpcAuth)), "{}", s);
+        }
+    }
+
+    #[test]
+    fn auth_uses_rpcauth_user() {
+        let auth = Auth::RpcAuth(VECTOR.parse().unwrap(), "bitcoind".to_owned());
+        assert_eq!(auth.rpcauth().map(ToString::to_string).as_deref(), Some(VECTOR));
+        let user_pass = auth.get_user_pass().unwrap();
+        assert_eq!(user_pass, (Some("bitcoind".to_owned()), Some("bitcoind".to_owned())));
+        assert_eq!(Auth::None.rpcauth(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "rpcauth-random")]
+    fn random_salt() {
+        let a = RpcAuth::new("bitcoind", "bitcoind").unwrap();
+        let b = RpcAuth::new("bitcoind", "bitcoind").unwrap();
+        assert_eq!(a.salt().len(), 2 * SALT_LEN);
+        assert_ne!(a.salt(), b.salt());
+        assert!(a.verify("bitcoind"));
+
+        let auth = Auth::new_rpcauth("bitcoind", "bitcoind").unwrap();
+        assert!(auth.rpcauth().unwrap().verify("bitcoind"));
+    }
+}
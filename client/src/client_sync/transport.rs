@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! The HTTP transport used by the blocking clients, and the credentials shared with the async ones.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use jsonrpc::bitreq_http::{self, BitreqHttpTransport, HttpError};
+use jsonrpc::{Request, Response};
+
+use crate::client_sync::{Auth, Result};
+
+/// Function called after the credentials were read again from the cookie file.
+type ReloadHook = Box<dyn Fn(&Path) + Send + Sync>;
+
+/// A user and optional password.
+pub(crate) type UserPass = (String, Option<String>);
+
+/// The credentials used to authenticate with the server, shared by a client and its wallet handles.
+///
+/// Credentials from a cookie file are read again when the server rejects them, since `bitcoind`
+/// writes a new cookie file every time it starts.
+pub(crate) struct Credentials {
+    user_pass: RwLock<Option<UserPass>>,
+    cookie_file: Option<PathBuf>,
+    on_reload: RwLock<Option<ReloadHook>>,
+}
+
+impl Credentials {
+    /// Returns credentials for `auth`, reading the cookie file if there is one.
+    pub(crate) fn new(auth: Auth) -> Result<Self> {
+        let cookie_file = match auth {
+            Auth::CookieFile(ref path) => Some(path.clone()),
+            _ => None,
+        };
+        let user_pass = match auth.get_user_pass()? {
+            (Some(user), pass) => Some((user, pass)),
+            (None, _) => None,
+        };
+        Ok(Self { user_pass: RwLock::new(user_pass), cookie_file, on_reload: RwLock::new(None) })
+    }
+
+    /// Returns credentials that do not authenticate.
+    pub(crate) fn none() -> Self {
+        Self { user_pass: RwLock::new(None), cookie_file: None, on_reload: RwLock::new(None) }
+    }
+
+    /// Returns the current user and password.
+    pub(crate) fn user_pass(&self) -> Option<UserPass> {
+        self.user_pass.read().expect("lock poisoned").clone()
+    }
+
+    /// Sets the function called after the credentials were read again from the cookie file.
+    pub(crate) fn set_on_reload(&self, hook: ReloadHook) {
+        *self.on_reload.write().expect("lock poisoned") = Some(hook);
+    }
+
+    /// Reads the cookie file again after a request sent with the credentials `sent` was rejected,
+    /// returns true if the current credentials differ from `sent` and the request should be
+    /// retried.
+    ///
+    /// The credentials may already have been read again because of another rejected request, in
+    /// which case the cookie file is not read.
+    pub(crate) fn reload(&self, sent: &Option<UserPass>) -> bool {
+        let path = match self.cookie_file {
+            Some(ref path) => path,
+            None => return false,
+        };
+        if self.user_pass() != *sent {
+            return true;
+        }
+        let user_pass = match Auth::CookieFile(path.clone()).get_user_pass() {
+            Ok((Some(user), pass)) => Some((user, pass)),
+            Ok((None, _)) => return false,
+            Err(e) => {
+                log::debug!(target: "corepc", "failed to read cookie file {}: {}", path.display(), e);
+                return false;
+            }
+        };
+
+        {
+            let mut current = self.user_pass.write().expect("lock poisoned");
+            if *current != *sent {
+                return true;
+            }
+            if *current == user_pass {
+                return false;
+            }
+            *current = user_pass;
+        }
+        log::debug!(target: "corepc", "read new credentials from cookie file {}", path.display());
+        if let Some(ref hook) = *self.on_reload.read().expect("lock poisoned") {
+            hook(path);
+        }
+        true
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("cookie_file", &self.cookie_file)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The settings used to connect to the server, shared by a client and its wallet handles.
+#[derive(Debug)]
+pub(crate) struct Connection {
+    url: String,
+    credentials: Credentials,
+}
+
+impl Connection {
+    pub(crate) fn new(url: &str, credentials: Credentials) -> Self {
+        Self { url: url.to_owned(), credentials }
+    }
+
+    /// Returns the credentials used for all requests on this connection.
+    pub(crate) fn credentials(&self) -> &Credentials { &self.credentials }
+
+    /// Returns a client for the server, or for the wallet `wallet` on the server.
+    pub(crate) fn jsonrpc_client(
+        connection: &Arc<Self>,
+        wallet: Option<&str>,
+    ) -> jsonrpc::client::Client {
+        let url = match wallet {
+            Some(name) => wallet_url(&connection.url, name),
+            None => connection.url.clone(),
+        };
+        jsonrpc::client::Client::with_transport(Transport {
+            connection: Arc::clone(connection),
+            url,
+        })
+    }
+}
+
+/// An HTTP transport using the current credentials of a [`Connection`].
+///
+/// If the server responds with `401 Unauthorized` the cookie file, if any, is read again and the
+/// request is retried with the new credentials.
+struct Transport {
+    connection: Arc<Connection>,
+    /// URL requests are sent to, the server URL or the endpoint of a wallet.
+    url: String,
+}
+
+impl Transport {
+    fn send<R>(
+        &self,
+        send: impl Fn(&BitreqHttpTransport) -> std::result::Result<R, jsonrpc::Error>,
+    ) -> std::result::Result<R, jsonrpc::Error> {
+        let credentials = &self.connection.credentials;
+        let sent = credentials.user_pass();
+        match send(&self.bitreq_transport(sent.clone())) {
+            Err(ref e) if is_unauthorized(e) && credentials.reload(&sent) =>
+                send(&self.bitreq_transport(credentials.user_pass())),
+            result => result,
+        }
+    }
+
+    fn bitreq_transport(&self, user_pass: Option<UserPass>) -> BitreqHttpTransport {
+        let mut builder = bitreq_http::Builder::new()
+            .url(&self.url)
+            .expect("jsonrpc v0.19, this function does not error")
+            .timeout(Duration::from_secs(60));
+        if let Some((user, pass)) = user_pass {
+            builder = builder.basic_auth(user, pass);
+        }
+        builder.build()
+    }
+}
+
+impl jsonrpc::Transport for Transport {
+    fn send_request(&self, req: Request) -> std::result::Result<Response, jsonrpc::Error> {
+        self.send(|transport| transport.send_request(req.clone()))
+    }
+
+    fn send_batch(&self, reqs: &[Request]) -> std::result::Result<Vec<Response>, jsonrpc::Error> {
+        self.send(|transport| transport.send_batch(reqs))
+    }
+
+    fn fmt_target(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.url) }
+}
+
+/// Returns true if `e` is an HTTP `401 Unauthorized` response.
+pub(crate) fn is_unauthorized(e: &jsonrpc::Error) -> bool {
+    match e {
+        jsonrpc::Error::Transport(e) => matches!(
+            e.downcast_ref::<bitreq_http::Error>(),
+            Some(bitreq_http::Error::Http(HttpError { status_code: 401, .. }))
+        ),
+        _ => false,
+    }
+}
+
+/// Returns the value of the `Authorization` HTTP header for the credentials `user_pass`.
+#[cfg(feature = "client-async")]
+pub(crate) fn basic_auth(user_pass: &UserPass) -> String {
+    use jsonrpc::base64::engine::general_purpose::STANDARD as BASE64;
+    use jsonrpc::base64::Engine;
+
+    let (user, pass) = user_pass;
+    let credentials = format!("{}:{}", user, pass.as_deref().unwrap_or_default());
+    format!("Basic {}", BASE64.encode(credentials.as_bytes()))
+}
+
+/// Returns the URL of the wallet endpoint for wallet `name` on the server at `url`.
+pub(crate) fn wallet_url(url: &str, name: &str) -> String {
+    let mut url = format!("{}/wallet/", url.trim_end_matches('/'));
+    for b in name.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            url.push(b as char);
+        } else {
+            url.push_str(&format!("%{:02X}", b));
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+    use std::{fs, thread};
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::client_sync::mock::{basic_auth, response, Cookie, MockServer};
+    use crate::client_sync::v29::Client;
+    use crate::client_sync::Error;
+
+    /// Returns a server which only accepts the credentials `user_pass`.
+    fn server(user_pass: &str) -> MockServer {
+        let expected = basic_auth(user_pass);
+        MockServer::start(move |request| {
+            if request.authorization.as_ref() != Some(&expected) {
+                return (401, Value::Null);
+            }
+            (200, response(&request.body, json!(101)))
+        })
+    }
+
+    #[test]
+    fn retries_with_rewritten_cookie() {
+        let cookie = Cookie::new("rewritten", "__cookie__:old");
+        let server = server("__cookie__:new");
+        let client =
+            Client::new_with_auth(server.url(), Auth::CookieFile(cookie.0.clone())).unwrap();
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reloads);
+        client.on_cookie_reload(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        // The node restarted and wrote a new cookie file.
+        fs::write(&cookie.0, "__cookie__:new").unwrap();
+        assert_eq!(client.get_block_count().unwrap().0, 101);
+
+        let auths = server.requests().into_iter().map(|r| r.authorization).collect::<Vec<_>>();
+        assert_eq!(auths, [Some(basic_auth("__cookie__:old")), Some(basic_auth("__cookie__:new"))]);
+        assert_eq!(reloads.load(Ordering::Relaxed), 1);
+
+        // Later calls use the new credentials straight away.
+        client.get_block_count().unwrap();
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn concurrent_rejected_requests_all_retry() {
+        let cookie = Cookie::new("concurrent", "__cookie__:old");
+        let expected = basic_auth("__cookie__:new");
+        // Both requests are rejected before either reads the cookie file again.
+        let rejected = Barrier::new(2);
+        let server = MockServer::start(move |request| {
+            if request.authorization.as_ref() != Some(&expected) {
+                rejected.wait();
+                return (401, Value::Null);
+            }
+            (200, response(&request.body, json!(101)))
+        });
+        let client =
+            Client::new_with_auth(server.url(), Auth::CookieFile(cookie.0.clone())).unwrap();
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reloads);
+        client.on_cookie_reload(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        fs::write(&cookie.0, "__cookie__:new").unwrap();
+        let alice = client.wallet("alice");
+        thread::scope(|s| {
+            let other = s.spawn(|| alice.get_block_count().unwrap().0);
+            assert_eq!(client.get_block_count().unwrap().0, 101);
+            assert_eq!(other.join().unwrap(), 101);
+        });
+
+        assert_eq!(server.requests().len(), 4);
+        assert_eq!(reloads.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn does_not_retry_with_unchanged_cookie() {
+        let cookie = Cookie::new("unchanged", "__cookie__:old");
+        let server = server("__cookie__:new");
+        let client =
+            Client::new_with_auth(server.url(), Auth::CookieFile(cookie.0.clone())).unwrap();
+
+        let err = client.get_block_count().unwrap_err();
+        assert!(matches!(err, Error::JsonRpc(ref e) if is_unauthorized(e)), "{:?}", err);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn wallet_url_percent_encodes_name() {
+        let url = "http://127.0.0.1:18443";
+        assert_eq!(wallet_url(url, "alice"), "http://127.0.0.1:18443/wallet/alice");
+        assert_eq!(wallet_url(url, "a-b.c_d~e"), "http://127.0.0.1:18443/wallet/a-b.c_d~e");
+        assert_eq!(wallet_url(url, "my wallet/1"), "http://127.0.0.1:18443/wallet/my%20wallet%2F1");
+        assert_eq!(wallet_url(url, ""), "http://127.0.0.1:18443/wallet/");
+        assert_eq!(wallet_url(url, "ü"), "http://127.0.0.1:18443/wallet/%C3%BC");
+        assert_eq!(wallet_url("http://127.0.0.1:18443/", "a"), "http://127.0.0.1:18443/wallet/a");
+    }
+}
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getblockchaininfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_blockchain_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_blockchain_info(&self) -> Result<GetBlockchainInfo> {
-                self.call("getblockchaininfo", &[])
+            pub $($async)? fn get_blockchain_info(&self) -> Result<GetBlockchainInfo> {
+                $crate::maybe_await!(self.call("getblockchaininfo", &[]), $($async)?)
             }
         }
     };
@@ -24,16 +24,16 @@ macro_rules! impl_client_v17__get_blockchain_info {
 /// Implements Bitcoin Core JSON-RPC API method `getbestblockhash`.
 #[macro_export]
 macro_rules! impl_client_v17__get_best_block_hash {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Gets the blockhash of the current chain tip.
-            pub fn best_block_hash(&self) -> Result<bitcoin::BlockHash> {
-                let json = self.get_best_block_hash()?;
+            pub $($async)? fn best_block_hash(&self) -> Result<bitcoin::BlockHash> {
+                let json = $crate::maybe_await!(self.get_best_block_hash(), $($async)?)?;
                 Ok(json.block_hash()?)
             }
 
-            pub fn get_best_block_hash(&self) -> Result<GetBestBlockHash> {
-                self.call("getbestblockhash", &[])
+            pub $($async)? fn get_best_block_hash(&self) -> Result<GetBestBlockHash> {
+                $crate::maybe_await!(self.call("getbestblockhash", &[]), $($async)?)
             }
         }
     };
@@ -42,22 +42,34 @@ macro_rules! impl_client_v17__get_best_block_hash {
 /// Implements Bitcoin Core JSON-RPC API method `getblock`.
 #[macro_export]
 macro_rules! impl_client_v17__get_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Gets a block by blockhash.
-            pub fn get_block(&self, hash: BlockHash) -> Result<Block> {
-                let json = self.get_block_verbose_zero(hash)?;
+            pub $($async)? fn get_block(&self, hash: BlockHash) -> Result<Block> {
+                let json = $crate::maybe_await!(self.get_block_verbose_zero(hash), $($async)?)?;
                 Ok(json.block()?)
             }
 
             /// Gets a block by blockhash with verbose set to 0.
-            pub fn get_block_verbose_zero(&self, hash: BlockHash) -> Result<GetBlockVerboseZero> {
-                self.call("getblock", &[into_json(hash)?, 0.into()])
+            pub $($async)? fn get_block_verbose_zero(
+                &self,
+                hash: BlockHash,
+            ) -> Result<GetBlockVerboseZero> {
+                $crate::maybe_await!(
+                    self.call("getblock", &$crate::client_sync::args::get_block_verbose_zero(hash)),
+                    $($async)?
+                )
             }
 
             /// Gets a block by blockhash with verbose set to 1.
-            pub fn get_block_verbose_one(&self, hash: BlockHash) -> Result<GetBlockVerboseOne> {
-                self.call("getblock", &[into_json(hash)?, 1.into()])
+            pub $($async)? fn get_block_verbose_one(
+                &self,
+                hash: BlockHash,
+            ) -> Result<GetBlockVerboseOne> {
+                $crate::maybe_await!(
+                    self.call("getblock", &$crate::client_sync::args::get_block_verbose_one(hash)),
+                    $($async)?
+                )
             }
         }
     };
@@ -66,10 +78,10 @@ macro_rules! impl_client_v17__get_block {
 /// Implements Bitcoin Core JSON-RPC API method `getblockcount`.
 #[macro_export]
 macro_rules! impl_client_v17__get_block_count {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_count(&self) -> Result<GetBlockCount> {
-                self.call("getblockcount", &[])
+            pub $($async)? fn get_block_count(&self) -> Result<GetBlockCount> {
+                $crate::maybe_await!(self.call("getblockcount", &[]), $($async)?)
             }
         }
     };
@@ -78,10 +90,10 @@ macro_rules! impl_client_v17__get_block_count {
 /// Implements Bitcoin Core JSON-RPC API method `getblockhash`.
 #[macro_export]
 macro_rules! impl_client_v17__get_block_hash {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_hash(&self, height: u64) -> Result<GetBlockHash> {
-                self.call("getblockhash", &[into_json(height)?])
+            pub $($async)? fn get_block_hash(&self, height: u64) -> Result<GetBlockHash> {
+                $crate::maybe_await!(self.call("getblockhash", &$crate::client_sync::args::get_block_hash(height)), $($async)?)
             }
         }
     };
@@ -90,18 +102,21 @@ macro_rules! impl_client_v17__get_block_hash {
 /// Implements Bitcoin Core JSON-RPC API method `getblockheader`.
 #[macro_export]
 macro_rules! impl_client_v17__get_block_header {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_header(&self, hash: &BlockHash) -> Result<GetBlockHeader> {
-                self.call("getblockheader", &[into_json(hash)?, into_json(false)?])
+            pub $($async)? fn get_block_header(&self, hash: &BlockHash) -> Result<GetBlockHeader> {
+                $crate::maybe_await!(
+                    self.call("getblockheader", &$crate::client_sync::args::get_block_header(hash)),
+                    $($async)?
+                )
             }
 
             // This is the same as calling getblockheader with verbose==true.
-            pub fn get_block_header_verbose(
+            pub $($async)? fn get_block_header_verbose(
                 &self,
                 hash: &BlockHash,
             ) -> Result<GetBlockHeaderVerbose> {
-                self.call("getblockheader", &[into_json(hash)?])
+                $crate::maybe_await!(self.call("getblockheader", &$crate::client_sync::args::get_block_header_verbose(hash)), $($async)?)
             }
         }
     };
@@ -110,14 +125,20 @@ macro_rules! impl_client_v17__get_block_header {
 /// Implements Bitcoin Core JSON-RPC API method `getblockstats`.
 #[macro_export]
 macro_rules! impl_client_v17__get_block_stats {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_stats_by_height(&self, height: u32) -> Result<GetBlockStats> {
-                self.call("getblockstats", &[into_json(height)?])
+            pub $($async)? fn get_block_stats_by_height(
+                &self,
+                height: u32,
+            ) -> Result<GetBlockStats> {
+                $crate::maybe_await!(self.call("getblockstats", &$crate::client_sync::args::get_block_stats_by_height(height)), $($async)?)
             }
 
-            pub fn get_block_stats_by_block_hash(&self, hash: &BlockHash) -> Result<GetBlockStats> {
-                self.call("getblockstats", &[into_json(hash)?])
+            pub $($async)? fn get_block_stats_by_block_hash(
+                &self,
+                hash: &BlockHash,
+            ) -> Result<GetBlockStats> {
+                $crate::maybe_await!(self.call("getblockstats", &$crate::client_sync::args::get_block_stats_by_block_hash(hash)), $($async)?)
             }
         }
     };
@@ -126,9 +147,11 @@ macro_rules! impl_client_v17__get_block_stats {
 /// Implements Bitcoin Core JSON-RPC API method `getchaintips`.
 #[macro_export]
 macro_rules! impl_client_v17__get_chain_tips {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_chain_tips(&self) -> Result<GetChainTips> { self.call("getchaintips", &[]) }
+            pub $($async)? fn get_chain_tips(&self) -> Result<GetChainTips> {
+                $crate::maybe_await!(self.call("getchaintips", &[]), $($async)?)
+            }
         }
     };
 }
@@ -136,10 +159,10 @@ macro_rules! impl_client_v17__get_chain_tips {
 /// Implements Bitcoin Core JSON-RPC API method `getchaintxstats`.
 #[macro_export]
 macro_rules! impl_client_v17__get_chain_tx_stats {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_chain_tx_stats(&self) -> Result<GetChainTxStats> {
-                self.call("getchaintxstats", &[])
+            pub $($async)? fn get_chain_tx_stats(&self) -> Result<GetChainTxStats> {
+                $crate::maybe_await!(self.call("getchaintxstats", &[]), $($async)?)
             }
         }
     };
@@ -148,10 +171,10 @@ macro_rules! impl_client_v17__get_chain_tx_stats {
 /// Implements Bitcoin Core JSON-RPC API method `getdifficulty`.
 #[macro_export]
 macro_rules! impl_client_v17__get_difficulty {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_difficulty(&self) -> Result<GetDifficulty> {
-                self.call("getdifficulty", &[])
+            pub $($async)? fn get_difficulty(&self) -> Result<GetDifficulty> {
+                $crate::maybe_await!(self.call("getdifficulty", &[]), $($async)?)
             }
         }
     };
@@ -160,18 +183,27 @@ macro_rules! impl_client_v17__get_difficulty {
 /// Implements Bitcoin Core JSON-RPC API method `getmempoolancestors`.
 #[macro_export]
 macro_rules! impl_client_v17__get_mempool_ancestors {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_mempool_ancestors(&self, txid: Txid) -> Result<GetMempoolAncestors> {
+            pub $($async)? fn get_mempool_ancestors(
+                &self,
+                txid: Txid,
+            ) -> Result<GetMempoolAncestors> {
                 // Equivalent to self.call("getmempoolancestors", &[into_json(txid)?, into_json(false)?])
-                self.call("getmempoolancestors", &[into_json(txid)?])
+                $crate::maybe_await!(
+                    self.call("getmempoolancestors", &[into_json(txid)?]),
+                    $($async)?
+                )
             }
 
-            pub fn get_mempool_ancestors_verbose(
+            pub $($async)? fn get_mempool_ancestors_verbose(
                 &self,
                 txid: Txid,
             ) -> Result<GetMempoolAncestorsVerbose> {
-                self.call("getmempoolancestors", &[into_json(txid)?, into_json(true)?])
+                $crate::maybe_await!(
+                    self.call("getmempoolancestors", &[into_json(txid)?, into_json(true)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -180,18 +212,27 @@ macro_rules! impl_client_v17__get_mempool_ancestors {
 /// Implements Bitcoin Core JSON-RPC API method `getmempooldescendants`.
 #[macro_export]
 macro_rules! impl_client_v17__get_mempool_descendants {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_mempool_descendants(&self, txid: Txid) -> Result<GetMempoolDescendants> {
+            pub $($async)? fn get_mempool_descendants(
+                &self,
+                txid: Txid,
+            ) -> Result<GetMempoolDescendants> {
                 // Equivalent to self.call("getmempooldescendants", &[into_json(txid)?, into_json(false)?])
-                self.call("getmempooldescendants", &[into_json(txid)?])
+                $crate::maybe_await!(
+                    self.call("getmempooldescendants", &[into_json(txid)?]),
+                    $($async)?
+                )
             }
 
-            pub fn get_mempool_descendants_verbose(
+            pub $($async)? fn get_mempool_descendants_verbose(
                 &self,
                 txid: Txid,
             ) -> Result<GetMempoolDescendantsVerbose> {
-                self.call("getmempooldescendants", &[into_json(txid)?, into_json(true)?])
+                $crate::maybe_await!(
+                    self.call("getmempooldescendants", &[into_json(txid)?, into_json(true)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -200,10 +241,10 @@ macro_rules! impl_client_v17__get_mempool_descendants {
 /// Implements Bitcoin Core JSON-RPC API method `getmempoolentry`.
 #[macro_export]
 macro_rules! impl_client_v17__get_mempool_entry {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_mempool_entry(&self, txid: Txid) -> Result<GetMempoolEntry> {
-                self.call("getmempoolentry", &[into_json(txid)?])
+            pub $($async)? fn get_mempool_entry(&self, txid: Txid) -> Result<GetMempoolEntry> {
+                $crate::maybe_await!(self.call("getmempoolentry", &$crate::client_sync::args::get_mempool_entry(txid)), $($async)?)
             }
         }
     };
@@ -212,10 +253,10 @@ macro_rules! impl_client_v17__get_mempool_entry {
 /// Implements Bitcoin Core JSON-RPC API method `getmempoolinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_mempool_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_mempool_info(&self) -> Result<GetMempoolInfo> {
-                self.call("getmempoolinfo", &[])
+            pub $($async)? fn get_mempool_info(&self) -> Result<GetMempoolInfo> {
+                $crate::maybe_await!(self.call("getmempoolinfo", &[]), $($async)?)
             }
         }
     };
@@ -224,15 +265,15 @@ macro_rules! impl_client_v17__get_mempool_info {
 /// Implements Bitcoin Core JSON-RPC API method `getrawmempool`.
 #[macro_export]
 macro_rules! impl_client_v17__get_raw_mempool {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_raw_mempool(&self) -> Result<GetRawMempool> {
+            pub $($async)? fn get_raw_mempool(&self) -> Result<GetRawMempool> {
                 // Equivalent to self.call("getrawmempool", &[into_json(false)?])
-                self.call("getrawmempool", &[])
+                $crate::maybe_await!(self.call("getrawmempool", &[]), $($async)?)
             }
 
-            pub fn get_raw_mempool_verbose(&self) -> Result<GetRawMempoolVerbose> {
-                self.call("getrawmempool", &[into_json(true)?])
+            pub $($async)? fn get_raw_mempool_verbose(&self) -> Result<GetRawMempoolVerbose> {
+                $crate::maybe_await!(self.call("getrawmempool", &[into_json(true)?]), $($async)?)
             }
         }
     };
@@ -241,10 +282,13 @@ macro_rules! impl_client_v17__get_raw_mempool {
 /// Implements Bitcoin Core JSON-RPC API method `gettxout`.
 #[macro_export]
 macro_rules! impl_client_v17__get_tx_out {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_tx_out(&self, txid: Txid, vout: u64) -> Result<GetTxOut> {
-                self.call("gettxout", &[into_json(txid)?, into_json(vout)?])
+            pub $($async)? fn get_tx_out(&self, txid: Txid, vout: u64) -> Result<GetTxOut> {
+                $crate::maybe_await!(
+                    self.call("gettxout", &$crate::client_sync::args::get_tx_out(txid, vout)),
+                    $($async)?
+                )
             }
         }
     };
@@ -253,10 +297,10 @@ macro_rules! impl_client_v17__get_tx_out {
 /// Implements Bitcoin Core JSON-RPC API method `gettxoutproof`.
 #[macro_export]
 macro_rules! impl_client_v17__get_tx_out_proof {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_tx_out_proof(&self, txids: &[Txid]) -> Result<String> {
-                self.call("gettxoutproof", &[into_json(txids)?])
+            pub $($async)? fn get_tx_out_proof(&self, txids: &[Txid]) -> Result<String> {
+                $crate::maybe_await!(self.call("gettxoutproof", &[into_json(txids)?]), $($async)?)
             }
         }
     };
@@ -265,10 +309,10 @@ macro_rules! impl_client_v17__get_tx_out_proof {
 /// Implements Bitcoin Core JSON-RPC API method `gettxoutsetinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_tx_out_set_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_tx_out_set_info(&self) -> Result<GetTxOutSetInfo> {
-                self.call("gettxoutsetinfo", &[])
+            pub $($async)? fn get_tx_out_set_info(&self) -> Result<GetTxOutSetInfo> {
+                $crate::maybe_await!(self.call("gettxoutsetinfo", &[]), $($async)?)
             }
         }
     };
@@ -277,10 +321,13 @@ macro_rules! impl_client_v17__get_tx_out_set_info {
 /// Implements Bitcoin Core JSON-RPC API method `preciousblock`.
 #[macro_export]
 macro_rules! impl_client_v17__precious_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn precious_block(&self, hash: BlockHash) -> Result<()> {
-                match self.call("preciousblock", &[into_json(hash)?]) {
+            pub $($async)? fn precious_block(&self, hash: BlockHash) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("preciousblock", &[into_json(hash)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -293,11 +340,11 @@ macro_rules! impl_client_v17__precious_block {
 /// Implements Bitcoin Core JSON-RPC API method `pruneblockchain`.
 #[macro_export]
 macro_rules! impl_client_v17__prune_blockchain {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Instructs the node to prune the blockchain up to a specified height or timestamp.
-            pub fn prune_blockchain(&self, target: u64) -> Result<PruneBlockchain> {
-                self.call("pruneblockchain", &[target.into()])
+            pub $($async)? fn prune_blockchain(&self, target: u64) -> Result<PruneBlockchain> {
+                $crate::maybe_await!(self.call("pruneblockchain", &[target.into()]), $($async)?)
             }
         }
     };
@@ -306,10 +353,10 @@ macro_rules! impl_client_v17__prune_blockchain {
 /// Implements Bitcoin Core JSON-RPC API method `savemempool`.
 #[macro_export]
 macro_rules! impl_client_v17__save_mempool {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn save_mempool(&self) -> Result<()> {
-                match self.call("savemempool", &[]) {
+            pub $($async)? fn save_mempool(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("savemempool", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -322,24 +369,27 @@ macro_rules! impl_client_v17__save_mempool {
 /// Implements Bitcoin Core JSON-RPC API method `scantxoutset`
 #[macro_export]
 macro_rules! impl_client_v17__scan_tx_out_set {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Aborts an ongoing `scantxoutset` scan.
-            pub fn scan_tx_out_set_abort(&self) -> Result<ScanTxOutSetAbort> {
-                self.call("scantxoutset", &[into_json("abort")?])
+            pub $($async)? fn scan_tx_out_set_abort(&self) -> Result<ScanTxOutSetAbort> {
+                $crate::maybe_await!(self.call("scantxoutset", &[into_json("abort")?]), $($async)?)
             }
 
             /// Starts a scan of the UTXO set for specified descriptors.
-            pub fn scan_tx_out_set_start(
+            pub $($async)? fn scan_tx_out_set_start(
                 &self,
                 scan_objects: &[&str],
             ) -> Result<ScanTxOutSetStart> {
-                self.call("scantxoutset", &[into_json("start")?, into_json(scan_objects)?])
+                $crate::maybe_await!(
+                    self.call("scantxoutset", &[into_json("start")?, into_json(scan_objects)?]),
+                    $($async)?
+                )
             }
 
             /// Checks the status of an ongoing `scantxoutset` scan.
-            pub fn scan_tx_out_set_status(&self) -> Result<Option<ScanTxOutSetStatus>> {
-                self.call("scantxoutset", &[into_json("status")?])
+            pub $($async)? fn scan_tx_out_set_status(&self) -> Result<Option<ScanTxOutSetStatus>> {
+                $crate::maybe_await!(self.call("scantxoutset", &[into_json("status")?]), $($async)?)
             }
         }
     };
@@ -348,9 +398,11 @@ macro_rules! impl_client_v17__scan_tx_out_set {
 /// Implements Bitcoin Core JSON-RPC API method `verifychain`
 #[macro_export]
 macro_rules! impl_client_v17__verify_chain {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn verify_chain(&self) -> Result<VerifyChain> { self.call("verifychain", &[]) }
+            pub $($async)? fn verify_chain(&self) -> Result<VerifyChain> {
+                $crate::maybe_await!(self.call("verifychain", &[]), $($async)?)
+            }
         }
     };
 }
@@ -358,11 +410,14 @@ macro_rules! impl_client_v17__verify_chain {
 /// Implements Bitcoin Core JSON-RPC API method `verifytxoutproof`.
 #[macro_export]
 macro_rules! impl_client_v17__verify_tx_out_proof {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // `proof` is the hex-encoded proof generated by `gettxoutproof`.
-            pub fn verify_tx_out_proof(&self, proof: &str) -> Result<VerifyTxOutProof> {
-                self.call("verifytxoutproof", &[into_json(proof)?])
+            pub $($async)? fn verify_tx_out_proof(&self, proof: &str) -> Result<VerifyTxOutProof> {
+                $crate::maybe_await!(
+                    self.call("verifytxoutproof", &[into_json(proof)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getmemoryinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_memory_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_memory_info(&self) -> Result<GetMemoryInfoStats> {
-                self.call("getmemoryinfo", &[])
+            pub $($async)? fn get_memory_info(&self) -> Result<GetMemoryInfoStats> {
+                $crate::maybe_await!(self.call("getmemoryinfo", &[]), $($async)?)
             }
         }
     };
@@ -24,9 +24,11 @@ macro_rules! impl_client_v17__get_memory_info {
 /// Implements Bitcoin Core JSON-RPC API method `help`.
 #[macro_export]
 macro_rules! impl_client_v17__help {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn help(&self) -> Result<String> { self.call("help", &[]) }
+            pub $($async)? fn help(&self) -> Result<String> {
+                $crate::maybe_await!(self.call("help", &[]), $($async)?)
+            }
         }
     };
 }
@@ -34,9 +36,11 @@ macro_rules! impl_client_v17__help {
 /// Implements Bitcoin Core JSON-RPC API method `logging`.
 #[macro_export]
 macro_rules! impl_client_v17__logging {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn logging(&self) -> Result<Logging> { self.call("logging", &[]) }
+            pub $($async)? fn logging(&self) -> Result<Logging> {
+                $crate::maybe_await!(self.call("logging", &[]), $($async)?)
+            }
         }
     };
 }
@@ -44,9 +48,11 @@ macro_rules! impl_client_v17__logging {
 /// Implements Bitcoin Core JSON-RPC API method `stop`.
 #[macro_export]
 macro_rules! impl_client_v17__stop {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn stop(&self) -> Result<String> { self.call("stop", &[]) }
+            pub $($async)? fn stop(&self) -> Result<String> {
+                $crate::maybe_await!(self.call("stop", &[]), $($async)?)
+            }
         }
     };
 }
@@ -54,9 +60,11 @@ macro_rules! impl_client_v17__stop {
 /// Implements Bitcoin Core JSON-RPC API method `uptime`.
 #[macro_export]
 macro_rules! impl_client_v17__uptime {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn uptime(&self) -> Result<u32> { self.call("uptime", &[]) }
+            pub $($async)? fn uptime(&self) -> Result<u32> {
+                $crate::maybe_await!(self.call("uptime", &[]), $($async)?)
+            }
         }
     };
 }
@@ -12,14 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `generatetoaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__generate_to_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn generate_to_address(
+            pub $($async)? fn generate_to_address(
                 &self,
                 nblocks: usize,
                 address: &bitcoin::Address,
             ) -> Result<GenerateToAddress> {
-                self.call("generatetoaddress", &[nblocks.into(), into_json(address)?])
+                $crate::maybe_await!(
+                    self.call("generatetoaddress", &[nblocks.into(), into_json(address)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -28,10 +31,10 @@ macro_rules! impl_client_v17__generate_to_address {
 /// Implements Bitcoin Core JSON-RPC API method `generate`.
 #[macro_export]
 macro_rules! impl_client_v17__generate {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn generate(&self, nblocks: usize) -> Result<Generate> {
-                self.call("generate", &[nblocks.into()])
+            pub $($async)? fn generate(&self, nblocks: usize) -> Result<Generate> {
+                $crate::maybe_await!(self.call("generate", &[nblocks.into()]), $($async)?)
             }
         }
     };
@@ -41,10 +44,13 @@ macro_rules! impl_client_v17__generate {
 // This method does not appear in the output of `bitcoin-cli help`.
 #[macro_export]
 macro_rules! impl_client_v17__invalidate_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn invalidate_block(&self, hash: BlockHash) -> Result<()> {
-                match self.call("invalidateblock", &[into_json(hash)?]) {
+            pub $($async)? fn invalidate_block(&self, hash: BlockHash) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("invalidateblock", &[into_json(hash)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
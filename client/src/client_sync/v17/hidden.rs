@@ -12,18 +12,18 @@
 /// Implements Bitcoin Core JSON-RPC API method `estimaterawfee`.
 #[macro_export]
 macro_rules! impl_client_v17__estimate_raw_fee {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// # Panics
             ///
             /// * Panics if `conf_target` is outside the range [1, 1008].
-            pub fn estimate_raw_fee(&self, conf_target: u32) -> Result<EstimateRawFee> {
+            pub $($async)? fn estimate_raw_fee(&self, conf_target: u32) -> Result<EstimateRawFee> {
                 assert!(
                     (1..=1008).contains(&conf_target),
                     "invalid conf_target, must be between 1 and 1008 inclusive"
                 );
 
-                self.call("estimaterawfee", &[conf_target.into()])
+                $crate::maybe_await!(self.call("estimaterawfee", &[conf_target.into()]), $($async)?)
             }
         }
     };
@@ -32,10 +32,13 @@ macro_rules! impl_client_v17__estimate_raw_fee {
 /// Implements Bitcoin Core JSON-RPC API method `waitforblock`.
 #[macro_export]
 macro_rules! impl_client_v17__wait_for_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wait_for_block(&self, hash: &bitcoin::BlockHash) -> Result<WaitForBlock> {
-                self.call("waitforblock", &[into_json(hash)?])
+            pub $($async)? fn wait_for_block(
+                &self,
+                hash: &bitcoin::BlockHash,
+            ) -> Result<WaitForBlock> {
+                $crate::maybe_await!(self.call("waitforblock", &[into_json(hash)?]), $($async)?)
             }
         }
     };
@@ -44,10 +47,16 @@ macro_rules! impl_client_v17__wait_for_block {
 /// Implements Bitcoin Core JSON-RPC API method `waitforblockheight`.
 #[macro_export]
 macro_rules! impl_client_v17__wait_for_block_height {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wait_for_block_height(&self, height: u64) -> Result<WaitForBlockHeight> {
-                self.call("waitforblockheight", &[into_json(height)?])
+            pub $($async)? fn wait_for_block_height(
+                &self,
+                height: u64,
+            ) -> Result<WaitForBlockHeight> {
+                $crate::maybe_await!(
+                    self.call("waitforblockheight", &[into_json(height)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -56,10 +65,10 @@ macro_rules! impl_client_v17__wait_for_block_height {
 /// Implements Bitcoin Core JSON-RPC API method `waitfornewblock`.
 #[macro_export]
 macro_rules! impl_client_v17__wait_for_new_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wait_for_new_block(&self) -> Result<WaitForNewBlock> {
-                self.call("waitfornewblock", &[])
+            pub $($async)? fn wait_for_new_block(&self) -> Result<WaitForNewBlock> {
+                $crate::maybe_await!(self.call("waitfornewblock", &[]), $($async)?)
             }
         }
     };
@@ -68,10 +77,10 @@ macro_rules! impl_client_v17__wait_for_new_block {
 /// Implements Bitcoin Core JSON-RPC API method `syncwithvalidationinterfacequeue`.
 #[macro_export]
 macro_rules! impl_client_v17__sync_with_validation_interface_queue {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn sync_with_validation_interface_queue(&self) -> Result<()> {
-                self.call("syncwithvalidationinterfacequeue", &[])
+            pub $($async)? fn sync_with_validation_interface_queue(&self) -> Result<()> {
+                $crate::maybe_await!(self.call("syncwithvalidationinterfacequeue", &[]), $($async)?)
             }
         }
     };
@@ -80,10 +89,13 @@ macro_rules! impl_client_v17__sync_with_validation_interface_queue {
 /// Implements Bitcoin Core JSON-RPC API method `reconsiderblock`.
 #[macro_export]
 macro_rules! impl_client_v17__reconsider_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn reconsider_block(&self, blockhash: bitcoin::BlockHash) -> Result<()> {
-                self.call("reconsiderblock", &[into_json(blockhash)?])
+            pub $($async)? fn reconsider_block(&self, blockhash: bitcoin::BlockHash) -> Result<()> {
+                $crate::maybe_await!(
+                    self.call("reconsiderblock", &[into_json(blockhash)?]),
+                    $($async)?
+                )
             }
         }
     };
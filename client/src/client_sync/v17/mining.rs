@@ -12,13 +12,16 @@
 /// Implements Bitcoin Core JSON-RPC API method `getblocktemplate`.
 #[macro_export]
 macro_rules! impl_client_v17__get_block_template {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_template(
+            pub $($async)? fn get_block_template(
                 &self,
                 request: &TemplateRequest,
             ) -> Result<GetBlockTemplate> {
-                self.call("getblocktemplate", &[into_json(request)?])
+                $crate::maybe_await!(
+                    self.call("getblocktemplate", &[into_json(request)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -27,10 +30,10 @@ macro_rules! impl_client_v17__get_block_template {
 /// Implements Bitcoin Core JSON-RPC API method `getmininginfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_mining_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_mining_info(&self) -> Result<GetMiningInfo> {
-                self.call("getmininginfo", &[])
+            pub $($async)? fn get_mining_info(&self) -> Result<GetMiningInfo> {
+                $crate::maybe_await!(self.call("getmininginfo", &[]), $($async)?)
             }
         }
     };
@@ -39,9 +42,11 @@ macro_rules! impl_client_v17__get_mining_info {
 /// Implements Bitcoin Core JSON-RPC API method `getnetworkhashps`.
 #[macro_export]
 macro_rules! impl_client_v17__get_network_hashes_per_second {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_network_hash_ps(&self) -> Result<f64> { self.call("getnetworkhashps", &[]) }
+            pub $($async)? fn get_network_hash_ps(&self) -> Result<f64> {
+                $crate::maybe_await!(self.call("getnetworkhashps", &[]), $($async)?)
+            }
         }
     };
 }
@@ -49,15 +54,18 @@ macro_rules! impl_client_v17__get_network_hashes_per_second {
 /// Implements Bitcoin Core JSON-RPC API method `prioritisetransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__prioritise_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn prioritise_transaction(
+            pub $($async)? fn prioritise_transaction(
                 &self,
                 txid: &Txid,
                 fee_delta: bitcoin::SignedAmount,
             ) -> Result<bool> {
                 let sats = fee_delta.to_sat();
-                self.call("prioritisetransaction", &[into_json(txid)?, 0.into(), sats.into()])
+                $crate::maybe_await!(
+                    self.call("prioritisetransaction", &[into_json(txid)?, 0.into(), sats.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -66,11 +74,14 @@ macro_rules! impl_client_v17__prioritise_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `submitblock`.
 #[macro_export]
 macro_rules! impl_client_v17__submit_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn submit_block(&self, block: &Block) -> Result<()> {
+            pub $($async)? fn submit_block(&self, block: &Block) -> Result<()> {
                 let hex: String = bitcoin::consensus::encode::serialize_hex(block);
-                match self.call("submitblock", &[into_json(hex)?]) {
+                match $crate::maybe_await!(
+                    self.call("submitblock", &[into_json(hex)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
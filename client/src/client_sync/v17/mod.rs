@@ -2,7 +2,9 @@
 
 //! A JSON-RPC client for testing against Bitcoin Core `v0.17`.
 //!
-//! We ignore option arguments unless they effect the shape of the returned JSON data.
+//! We ignore option arguments unless they effect the shape of the returned JSON data. A few methods
+//! with many optional arguments also have a variant taking one of the structs in
+//! [`crate::client_sync::options`].
 
 pub mod blockchain;
 pub mod control;
@@ -23,9 +25,11 @@ use bitcoin::{sign_message, Amount, Block, BlockHash, PublicKey, Txid};
 use serde::{Deserialize, Serialize, Serializer};
 
 use crate::client_sync::into_json;
+pub use crate::client_sync::WalletCreateFundedPsbtInput;
 use crate::types::v17::*;
 
 crate::define_jsonrpc_bitreq_client!("v17");
+crate::impl_client_v17__batch!();
 crate::impl_client_check_expected_server_version!({ [170200] });
 
 // == Blockchain ==
@@ -248,18 +252,6 @@ impl Output {
     }
 }
 
-/// An element in the `inputs` argument of method `walletcreatefundedpsbt`.
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
-pub struct WalletCreateFundedPsbtInput {
-    txid: Txid,
-    vout: u32,
-}
-
-impl WalletCreateFundedPsbtInput {
-    /// Create a new walletcreatefundedpsbt input entry.
-    pub fn new(txid: Txid, vout: u32) -> Self { Self { txid, vout } }
-}
-
 /// Args for the `addnode` method.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -12,10 +12,13 @@
 /// Implements Bitcoin Core JSON-RPC API method `addnode`.
 #[macro_export]
 macro_rules! impl_client_v17__add_node {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn add_node(&self, node: &str, command: AddNodeCommand) -> Result<()> {
-                match self.call("addnode", &[into_json(node)?, into_json(command)?]) {
+            pub $($async)? fn add_node(&self, node: &str, command: AddNodeCommand) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("addnode", &[into_json(node)?, into_json(command)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -28,10 +31,10 @@ macro_rules! impl_client_v17__add_node {
 /// Implements Bitcoin Core JSON-RPC API method `clearbanned`.
 #[macro_export]
 macro_rules! impl_client_v17__clear_banned {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn clear_banned(&self) -> Result<()> {
-                match self.call("clearbanned", &[]) {
+            pub $($async)? fn clear_banned(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("clearbanned", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -44,10 +47,13 @@ macro_rules! impl_client_v17__clear_banned {
 /// Implements Bitcoin Core JSON-RPC API method `disconnectnode`.
 #[macro_export]
 macro_rules! impl_client_v17__disconnect_node {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn disconnect_node(&self, address: &str) -> Result<()> {
-                match self.call("disconnectnode", &[into_json(address)?]) {
+            pub $($async)? fn disconnect_node(&self, address: &str) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("disconnectnode", &[into_json(address)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -60,10 +66,10 @@ macro_rules! impl_client_v17__disconnect_node {
 /// Implements Bitcoin Core JSON-RPC API method `getaddednodeinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_added_node_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_added_node_info(&self) -> Result<GetAddedNodeInfo> {
-                self.call("getaddednodeinfo", &[])
+            pub $($async)? fn get_added_node_info(&self) -> Result<GetAddedNodeInfo> {
+                $crate::maybe_await!(self.call("getaddednodeinfo", &[]), $($async)?)
             }
         }
     };
@@ -72,10 +78,10 @@ macro_rules! impl_client_v17__get_added_node_info {
 /// Implements Bitcoin Core JSON-RPC API method `getconnectioncount`.
 #[macro_export]
 macro_rules! impl_client_v17__get_connection_count {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_connection_count(&self) -> Result<GetConnectionCount> {
-                self.call("getconnectioncount", &[])
+            pub $($async)? fn get_connection_count(&self) -> Result<GetConnectionCount> {
+                $crate::maybe_await!(self.call("getconnectioncount", &[]), $($async)?)
             }
         }
     };
@@ -84,9 +90,11 @@ macro_rules! impl_client_v17__get_connection_count {
 /// Implements Bitcoin Core JSON-RPC API method `getnettotals`.
 #[macro_export]
 macro_rules! impl_client_v17__get_net_totals {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_net_totals(&self) -> Result<GetNetTotals> { self.call("getnettotals", &[]) }
+            pub $($async)? fn get_net_totals(&self) -> Result<GetNetTotals> {
+                $crate::maybe_await!(self.call("getnettotals", &[]), $($async)?)
+            }
         }
     };
 }
@@ -94,16 +102,16 @@ macro_rules! impl_client_v17__get_net_totals {
 /// Implements Bitcoin Core JSON-RPC API method `getnetworkinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_network_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Returns the server version field of `GetNetworkInfo`.
-            pub fn server_version(&self) -> Result<usize> {
-                let info = self.get_network_info()?;
+            pub $($async)? fn server_version(&self) -> Result<usize> {
+                let info = $crate::maybe_await!(self.get_network_info(), $($async)?)?;
                 Ok(info.version)
             }
 
-            pub fn get_network_info(&self) -> Result<GetNetworkInfo> {
-                self.call("getnetworkinfo", &[])
+            pub $($async)? fn get_network_info(&self) -> Result<GetNetworkInfo> {
+                $crate::maybe_await!(self.call("getnetworkinfo", &[]), $($async)?)
             }
         }
     };
@@ -112,9 +120,11 @@ macro_rules! impl_client_v17__get_network_info {
 /// Implements Bitcoin Core JSON-RPC API method `getpeerinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_peer_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_peer_info(&self) -> Result<GetPeerInfo> { self.call("getpeerinfo", &[]) }
+            pub $($async)? fn get_peer_info(&self) -> Result<GetPeerInfo> {
+                $crate::maybe_await!(self.call("getpeerinfo", &[]), $($async)?)
+            }
         }
     };
 }
@@ -122,9 +132,11 @@ macro_rules! impl_client_v17__get_peer_info {
 /// Implements Bitcoin Core JSON-RPC API method `listbanned`.
 #[macro_export]
 macro_rules! impl_client_v17__list_banned {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_banned(&self) -> Result<ListBanned> { self.call("listbanned", &[]) }
+            pub $($async)? fn list_banned(&self) -> Result<ListBanned> {
+                $crate::maybe_await!(self.call("listbanned", &[]), $($async)?)
+            }
         }
     };
 }
@@ -132,10 +144,10 @@ macro_rules! impl_client_v17__list_banned {
 /// Implements Bitcoin Core JSON-RPC API method `ping`.
 #[macro_export]
 macro_rules! impl_client_v17__ping {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn ping(&self) -> Result<()> {
-                match self.call("ping", &[]) {
+            pub $($async)? fn ping(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("ping", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -148,10 +160,13 @@ macro_rules! impl_client_v17__ping {
 /// Implements Bitcoin Core JSON-RPC API method `setban`.
 #[macro_export]
 macro_rules! impl_client_v17__set_ban {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn set_ban(&self, subnet: &str, command: SetBanCommand) -> Result<()> {
-                match self.call("setban", &[into_json(subnet)?, into_json(command)?]) {
+            pub $($async)? fn set_ban(&self, subnet: &str, command: SetBanCommand) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("setban", &[into_json(subnet)?, into_json(command)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -164,10 +179,13 @@ macro_rules! impl_client_v17__set_ban {
 /// Implements Bitcoin Core JSON-RPC API method `setnetworkactive`.
 #[macro_export]
 macro_rules! impl_client_v17__set_network_active {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn set_network_active(&self, state: bool) -> Result<SetNetworkActive> {
-                self.call("setnetworkactive", &[into_json(state)?])
+            pub $($async)? fn set_network_active(&self, state: bool) -> Result<SetNetworkActive> {
+                $crate::maybe_await!(
+                    self.call("setnetworkactive", &[into_json(state)?]),
+                    $($async)?
+                )
             }
         }
     };
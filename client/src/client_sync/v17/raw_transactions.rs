@@ -12,11 +12,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `combinepsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__combine_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn combine_psbt(&self, txs: &[bitcoin::Psbt]) -> Result<CombinePsbt> {
+            pub $($async)? fn combine_psbt(&self, txs: &[bitcoin::Psbt]) -> Result<CombinePsbt> {
                 let txs = txs.iter().map(|psbt| format!("{}", psbt)).collect::<Vec<String>>();
-                self.call("combinepsbt", &[txs.into()])
+                $crate::maybe_await!(self.call("combinepsbt", &[txs.into()]), $($async)?)
             }
         }
     };
@@ -25,9 +25,9 @@ macro_rules! impl_client_v17__combine_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `combinerawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__combine_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn combine_raw_transaction(
+            pub $($async)? fn combine_raw_transaction(
                 &self,
                 txs: &[bitcoin::Transaction],
             ) -> Result<CombineRawTransaction> {
@@ -35,7 +35,10 @@ macro_rules! impl_client_v17__combine_raw_transaction {
                     .iter()
                     .map(|tx| bitcoin::consensus::encode::serialize_hex(tx))
                     .collect::<Vec<String>>();
-                self.call("combinerawtransaction", &[into_json(encoded)?])
+                $crate::maybe_await!(
+                    self.call("combinerawtransaction", &[into_json(encoded)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -44,11 +47,14 @@ macro_rules! impl_client_v17__combine_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `converttopsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__convert_to_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn convert_to_psbt(&self, tx: &bitcoin::Transaction) -> Result<ConvertToPsbt> {
+            pub $($async)? fn convert_to_psbt(
+                &self,
+                tx: &bitcoin::Transaction,
+            ) -> Result<ConvertToPsbt> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
-                self.call("converttopsbt", &[hex.into()])
+                $crate::maybe_await!(self.call("converttopsbt", &[hex.into()]), $($async)?)
             }
         }
     };
@@ -57,10 +63,17 @@ macro_rules! impl_client_v17__convert_to_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `createpsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__create_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn create_psbt(&self, inputs: &[Input], outputs: &[Output]) -> Result<CreatePsbt> {
-                self.call("createpsbt", &[into_json(inputs)?, into_json(outputs)?])
+            pub $($async)? fn create_psbt(
+                &self,
+                inputs: &[Input],
+                outputs: &[Output],
+            ) -> Result<CreatePsbt> {
+                $crate::maybe_await!(
+                    self.call("createpsbt", &[into_json(inputs)?, into_json(outputs)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -69,14 +82,17 @@ macro_rules! impl_client_v17__create_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `createrawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__create_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn create_raw_transaction(
+            pub $($async)? fn create_raw_transaction(
                 &self,
                 inputs: &[Input],
                 outputs: &[Output],
             ) -> Result<CreateRawTransaction> {
-                self.call("createrawtransaction", &[into_json(inputs)?, into_json(outputs)?])
+                $crate::maybe_await!(
+                    self.call("createrawtransaction", &[into_json(inputs)?, into_json(outputs)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -85,10 +101,10 @@ macro_rules! impl_client_v17__create_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `decodepsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__decode_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn decode_psbt(&self, psbt: &str) -> Result<DecodePsbt> {
-                self.call("decodepsbt", &[psbt.into()])
+            pub $($async)? fn decode_psbt(&self, psbt: &str) -> Result<DecodePsbt> {
+                $crate::maybe_await!(self.call("decodepsbt", &[psbt.into()]), $($async)?)
             }
         }
     };
@@ -97,12 +113,15 @@ macro_rules! impl_client_v17__decode_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `finalizepsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__finalize_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn finalize_psbt(&self, psbt: &bitcoin::Psbt) -> Result<FinalizePsbt> {
+            pub $($async)? fn finalize_psbt(&self, psbt: &bitcoin::Psbt) -> Result<FinalizePsbt> {
                 let psbt = format!("{}", psbt);
                 // Pass extract=false so Core returns the PSBT field in the response.
-                self.call("finalizepsbt", &[psbt.into(), false.into()])
+                $crate::maybe_await!(
+                    self.call("finalizepsbt", &[psbt.into(), false.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -111,14 +130,14 @@ macro_rules! impl_client_v17__finalize_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `decoderawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__decode_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn decode_raw_transaction(
+            pub $($async)? fn decode_raw_transaction(
                 &self,
                 tx: &bitcoin::Transaction,
             ) -> Result<DecodeRawTransaction> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
-                self.call("decoderawtransaction", &[hex.into()])
+                $crate::maybe_await!(self.call("decoderawtransaction", &[hex.into()]), $($async)?)
             }
         }
     };
@@ -127,11 +146,11 @@ macro_rules! impl_client_v17__decode_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `decodescript`.
 #[macro_export]
 macro_rules! impl_client_v17__decode_script {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // Arg is the hex encoded script we want to decode.
-            pub fn decode_script(&self, script: &str) -> Result<DecodeScript> {
-                self.call("decodescript", &[script.into()])
+            pub $($async)? fn decode_script(&self, script: &str) -> Result<DecodeScript> {
+                $crate::maybe_await!(self.call("decodescript", &[script.into()]), $($async)?)
             }
         }
     };
@@ -140,14 +159,23 @@ macro_rules! impl_client_v17__decode_script {
 /// Implements Bitcoin Core JSON-RPC API method `fundrawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__fund_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn fund_raw_transaction(
+            pub $($async)? fn fund_raw_transaction(
                 &self,
                 tx: &bitcoin::Transaction,
             ) -> Result<FundRawTransaction> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
-                self.call("fundrawtransaction", &[hex.into()])
+                $crate::maybe_await!(self.call("fundrawtransaction", &[hex.into()]), $($async)?)
+            }
+
+            pub $($async)? fn fund_raw_transaction_with_options(
+                &self,
+                tx: &bitcoin::Transaction,
+                options: &$crate::client_sync::options::FundRawTransactionOptions,
+            ) -> Result<FundRawTransaction> {
+                let args = options.args(tx, Self::CORE_VERSION)?.into_named();
+                $crate::maybe_await!(self.call_named("fundrawtransaction", &args), $($async)?)
             }
         }
     };
@@ -156,17 +184,26 @@ macro_rules! impl_client_v17__fund_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `getrawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__get_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_raw_transaction(&self, txid: bitcoin::Txid) -> Result<GetRawTransaction> {
-                self.call("getrawtransaction", &[into_json(&txid)?, false.into()])
+            pub $($async)? fn get_raw_transaction(
+                &self,
+                txid: bitcoin::Txid,
+            ) -> Result<GetRawTransaction> {
+                $crate::maybe_await!(
+                    self.call("getrawtransaction", &$crate::client_sync::args::get_raw_transaction(txid)),
+                    $($async)?
+                )
             }
 
-            pub fn get_raw_transaction_verbose(
+            pub $($async)? fn get_raw_transaction_verbose(
                 &self,
                 txid: Txid,
             ) -> Result<GetRawTransactionVerbose> {
-                self.call("getrawtransaction", &[into_json(&txid)?, true.into()])
+                $crate::maybe_await!(
+                    self.call("getrawtransaction", &$crate::client_sync::args::get_raw_transaction_verbose(txid)),
+                    $($async)?
+                )
             }
         }
     };
@@ -175,14 +212,14 @@ macro_rules! impl_client_v17__get_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `sendrawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__send_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn send_raw_transaction(
+            pub $($async)? fn send_raw_transaction(
                 &self,
                 tx: &bitcoin::Transaction,
             ) -> Result<SendRawTransaction> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
-                self.call("sendrawtransaction", &[hex.into()])
+                $crate::maybe_await!(self.call("sendrawtransaction", &[hex.into()]), $($async)?)
             }
         }
     };
@@ -191,14 +228,14 @@ macro_rules! impl_client_v17__send_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `signrawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__sign_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn sign_raw_transaction(
+            pub $($async)? fn sign_raw_transaction(
                 &self,
                 tx: &bitcoin::Transaction,
             ) -> Result<SignRawTransaction> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
-                self.call("signrawtransaction", &[hex.into()])
+                $crate::maybe_await!(self.call("signrawtransaction", &[hex.into()]), $($async)?)
             }
         }
     };
@@ -207,16 +244,19 @@ macro_rules! impl_client_v17__sign_raw_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `signrawtransactionwithkey`.
 #[macro_export]
 macro_rules! impl_client_v17__sign_raw_transaction_with_key {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn sign_raw_transaction_with_key(
+            pub $($async)? fn sign_raw_transaction_with_key(
                 &self,
                 tx: &bitcoin::Transaction,
                 keys: &[bitcoin::PrivateKey],
             ) -> Result<SignRawTransactionWithKey> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
                 let keys = keys.iter().map(|k| format!("{}", k)).collect::<Vec<String>>();
-                self.call("signrawtransactionwithkey", &[hex.into(), into_json(keys)?])
+                $crate::maybe_await!(
+                    self.call("signrawtransactionwithkey", &[hex.into(), into_json(keys)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -225,9 +265,9 @@ macro_rules! impl_client_v17__sign_raw_transaction_with_key {
 /// Implements Bitcoin Core JSON-RPC API method `testmempoolaccept`.
 #[macro_export]
 macro_rules! impl_client_v17__test_mempool_accept {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn test_mempool_accept(
+            pub $($async)? fn test_mempool_accept(
                 &self,
                 txs: &[bitcoin::Transaction],
             ) -> Result<TestMempoolAccept> {
@@ -235,7 +275,10 @@ macro_rules! impl_client_v17__test_mempool_accept {
                     .iter()
                     .map(|tx| bitcoin::consensus::encode::serialize_hex(tx))
                     .collect::<Vec<String>>();
-                self.call("testmempoolaccept", &[into_json(encoded)?])
+                $crate::maybe_await!(
+                    self.call("testmempoolaccept", &[into_json(encoded)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,14 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `createmultisig`.
 #[macro_export]
 macro_rules! impl_client_v17__create_multisig {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn create_multisig(
+            pub $($async)? fn create_multisig(
                 &self,
                 nrequired: u32,
                 keys: Vec<PublicKey>,
             ) -> Result<CreateMultisig> {
-                self.call("createmultisig", &[nrequired.into(), into_json(keys)?])
+                $crate::maybe_await!(
+                    self.call("createmultisig", &[nrequired.into(), into_json(keys)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -28,10 +31,10 @@ macro_rules! impl_client_v17__create_multisig {
 /// Implements Bitcoin Core JSON-RPC API method `estimatesmartfee`.
 #[macro_export]
 macro_rules! impl_client_v17__estimate_smart_fee {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn estimate_smart_fee(&self, blocks: u32) -> Result<EstimateSmartFee> {
-                self.call("estimatesmartfee", &[blocks.into()])
+            pub $($async)? fn estimate_smart_fee(&self, blocks: u32) -> Result<EstimateSmartFee> {
+                $crate::maybe_await!(self.call("estimatesmartfee", &[blocks.into()]), $($async)?)
             }
         }
     };
@@ -40,14 +43,17 @@ macro_rules! impl_client_v17__estimate_smart_fee {
 /// Implements Bitcoin Core JSON-RPC API method `signmessagewithprivkey`.
 #[macro_export]
 macro_rules! impl_client_v17__sign_message_with_priv_key {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn sign_message_with_privkey(
+            pub $($async)? fn sign_message_with_privkey(
                 &self,
                 privkey: &bitcoin::PrivateKey,
                 message: &str,
             ) -> Result<SignMessageWithPrivKey> {
-                self.call("signmessagewithprivkey", &[into_json(privkey)?, message.into()])
+                $crate::maybe_await!(
+                    self.call("signmessagewithprivkey", &[into_json(privkey)?, message.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -56,13 +62,16 @@ macro_rules! impl_client_v17__sign_message_with_priv_key {
 /// Implements Bitcoin Core JSON-RPC API method `validateaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__validate_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn validate_address(
+            pub $($async)? fn validate_address(
                 &self,
                 address: &Address<NetworkChecked>,
             ) -> Result<ValidateAddress> {
-                self.call("validateaddress", &[address.to_string().into()])
+                $crate::maybe_await!(
+                    self.call("validateaddress", &[address.to_string().into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -71,17 +80,20 @@ macro_rules! impl_client_v17__validate_address {
 /// Implements Bitcoin Core JSON-RPC API method `verifymessage`.
 #[macro_export]
 macro_rules! impl_client_v17__verify_message {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn verify_message(
+            pub $($async)? fn verify_message(
                 &self,
                 address: &Address<NetworkChecked>,
                 signature: &sign_message::MessageSignature,
                 message: &str,
             ) -> Result<VerifyMessage> {
-                self.call(
-                    "verifymessage",
-                    &[address.to_string().into(), signature.to_string().into(), message.into()],
+                $crate::maybe_await!(
+                    self.call(
+                        "verifymessage",
+                        &[address.to_string().into(), signature.to_string().into(), message.into()],
+                    ),
+                    $($async)?
                 )
             }
         }
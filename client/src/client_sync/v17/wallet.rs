@@ -12,10 +12,13 @@
 /// Implements Bitcoin Core JSON-RPC API method `abandontransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__abandon_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn abandon_transaction(&self, txid: Txid) -> Result<()> {
-                match self.call("abandontransaction", &[into_json(txid)?]) {
+            pub $($async)? fn abandon_transaction(&self, txid: Txid) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("abandontransaction", &[into_json(txid)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -28,9 +31,11 @@ macro_rules! impl_client_v17__abandon_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `abortrescan`.
 #[macro_export]
 macro_rules! impl_client_v17__abort_rescan {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn abort_rescan(&self) -> Result<AbortRescan> { self.call("abortrescan", &[]) }
+            pub $($async)? fn abort_rescan(&self) -> Result<AbortRescan> {
+                $crate::maybe_await!(self.call("abortrescan", &[]), $($async)?)
+            }
         }
     };
 }
@@ -38,22 +43,28 @@ macro_rules! impl_client_v17__abort_rescan {
 /// Implements Bitcoin Core JSON-RPC API method `addmultisigaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__add_multisig_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn add_multisig_address_with_keys(
+            pub $($async)? fn add_multisig_address_with_keys(
                 &self,
                 nrequired: u32,
                 keys: Vec<PublicKey>,
             ) -> Result<AddMultisigAddress> {
-                self.call("addmultisigaddress", &[nrequired.into(), into_json(keys)?])
+                $crate::maybe_await!(
+                    self.call("addmultisigaddress", &[nrequired.into(), into_json(keys)?]),
+                    $($async)?
+                )
             }
 
-            pub fn add_multisig_address_with_addresses(
+            pub $($async)? fn add_multisig_address_with_addresses(
                 &self,
                 nrequired: u32,
                 keys: Vec<Address>,
             ) -> Result<AddMultisigAddress> {
-                self.call("addmultisigaddress", &[nrequired.into(), into_json(keys)?])
+                $crate::maybe_await!(
+                    self.call("addmultisigaddress", &[nrequired.into(), into_json(keys)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -62,10 +73,13 @@ macro_rules! impl_client_v17__add_multisig_address {
 /// Implements Bitcoin Core JSON-RPC API method `bumpfee`.
 #[macro_export]
 macro_rules! impl_client_v17__backup_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn backup_wallet(&self, destination: &Path) -> Result<()> {
-                match self.call("backupwallet", &[into_json(destination)?]) {
+            pub $($async)? fn backup_wallet(&self, destination: &Path) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("backupwallet", &[into_json(destination)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -78,10 +92,10 @@ macro_rules! impl_client_v17__backup_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `bumpfee`.
 #[macro_export]
 macro_rules! impl_client_v17__bump_fee {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn bump_fee(&self, txid: Txid) -> Result<BumpFee> {
-                self.call("bumpfee", &[into_json(txid)?])
+            pub $($async)? fn bump_fee(&self, txid: Txid) -> Result<BumpFee> {
+                $crate::maybe_await!(self.call("bumpfee", &[into_json(txid)?]), $($async)?)
             }
         }
     };
@@ -90,10 +104,10 @@ macro_rules! impl_client_v17__bump_fee {
 /// Implements Bitcoin Core JSON-RPC API method `createwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__create_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn create_wallet(&self, wallet: &str) -> Result<CreateWallet> {
-                self.call("createwallet", &[wallet.into()])
+            pub $($async)? fn create_wallet(&self, wallet: &str) -> Result<CreateWallet> {
+                $crate::maybe_await!(self.call("createwallet", &[wallet.into()]), $($async)?)
             }
         }
     };
@@ -102,10 +116,10 @@ macro_rules! impl_client_v17__create_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `dumpprivkey`.
 #[macro_export]
 macro_rules! impl_client_v17__dump_priv_key {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn dump_priv_key(&self, address: &Address) -> Result<DumpPrivKey> {
-                self.call("dumpprivkey", &[into_json(address)?])
+            pub $($async)? fn dump_priv_key(&self, address: &Address) -> Result<DumpPrivKey> {
+                $crate::maybe_await!(self.call("dumpprivkey", &[into_json(address)?]), $($async)?)
             }
         }
     };
@@ -114,11 +128,11 @@ macro_rules! impl_client_v17__dump_priv_key {
 /// Implements Bitcoin Core JSON-RPC API method `dumpwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__dump_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // filename is either absolute or relative to bitcoind.
-            pub fn dump_wallet(&self, filename: &Path) -> Result<DumpWallet> {
-                self.call("dumpwallet", &[into_json(filename)?])
+            pub $($async)? fn dump_wallet(&self, filename: &Path) -> Result<DumpWallet> {
+                $crate::maybe_await!(self.call("dumpwallet", &[into_json(filename)?]), $($async)?)
             }
         }
     };
@@ -127,11 +141,14 @@ macro_rules! impl_client_v17__dump_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `encryptwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__encrypt_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // filename is either absolute or relative to bitcoind.
-            pub fn encrypt_wallet(&self, passphrase: &str) -> Result<EncryptWallet> {
-                self.call("encryptwallet", &[into_json(passphrase)?])
+            pub $($async)? fn encrypt_wallet(&self, passphrase: &str) -> Result<EncryptWallet> {
+                $crate::maybe_await!(
+                    self.call("encryptwallet", &[into_json(passphrase)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -140,10 +157,13 @@ macro_rules! impl_client_v17__encrypt_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `getaddressesbylabel`.
 #[macro_export]
 macro_rules! impl_client_v17__get_addresses_by_label {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_addresses_by_label(&self, label: &str) -> Result<GetAddressesByLabel> {
-                self.call("getaddressesbylabel", &[label.into()])
+            pub $($async)? fn get_addresses_by_label(
+                &self,
+                label: &str,
+            ) -> Result<GetAddressesByLabel> {
+                $crate::maybe_await!(self.call("getaddressesbylabel", &[label.into()]), $($async)?)
             }
         }
     };
@@ -152,10 +172,13 @@ macro_rules! impl_client_v17__get_addresses_by_label {
 /// Implements Bitcoin Core JSON-RPC API method `getaddressinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_address_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_address_info(&self, address: &Address) -> Result<GetAddressInfo> {
-                self.call("getaddressinfo", &[into_json(address)?])
+            pub $($async)? fn get_address_info(&self, address: &Address) -> Result<GetAddressInfo> {
+                $crate::maybe_await!(
+                    self.call("getaddressinfo", &[into_json(address)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -164,9 +187,19 @@ macro_rules! impl_client_v17__get_address_info {
 /// Implements Bitcoin Core JSON-RPC API method `getbalance`.
 #[macro_export]
 macro_rules! impl_client_v17__get_balance {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_balance(&self) -> Result<GetBalance> { self.call("getbalance", &[]) }
+            pub $($async)? fn get_balance(&self) -> Result<GetBalance> {
+                $crate::maybe_await!(self.call("getbalance", &[]), $($async)?)
+            }
+
+            pub $($async)? fn get_balance_with_options(
+                &self,
+                options: &$crate::client_sync::options::GetBalanceOptions,
+            ) -> Result<GetBalance> {
+                let args = options.args(Self::CORE_VERSION)?.into_named();
+                $crate::maybe_await!(self.call_named("getbalance", &args), $($async)?)
+            }
         }
     };
 }
@@ -174,45 +207,63 @@ macro_rules! impl_client_v17__get_balance {
 /// Implements Bitcoin Core JSON-RPC API method `getnewaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__get_new_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Gets a new address from `bitcoind` and parses it assuming its correct.
-            pub fn new_address(&self) -> Result<bitcoin::Address> {
-                let json = self.get_new_address(None, None)?;
+            pub $($async)? fn new_address(&self) -> Result<bitcoin::Address> {
+                let json = $crate::maybe_await!(self.get_new_address(None, None), $($async)?)?;
                 let model = json.into_model().unwrap();
                 Ok(model.0.assume_checked())
             }
 
             /// Gets a new address from `bitcoind` and parses it assuming its correct.
-            pub fn new_address_with_type(&self, ty: AddressType) -> Result<bitcoin::Address> {
-                let json = self.get_new_address(None, Some(ty))?;
+            pub $($async)? fn new_address_with_type(
+                &self,
+                ty: AddressType,
+            ) -> Result<bitcoin::Address> {
+                let json = $crate::maybe_await!(self.get_new_address(None, Some(ty)), $($async)?)?;
                 let model = json.into_model().unwrap();
                 Ok(model.0.assume_checked())
             }
 
             /// Gets a new address with label from `bitcoind` and parses it assuming its correct.
             // FIXME: unchecked network here is ugly and not uniform with other functions.
-            pub fn new_address_with_label(
+            pub $($async)? fn new_address_with_label(
                 &self,
                 label: &str,
             ) -> Result<bitcoin::Address<bitcoin::address::NetworkUnchecked>> {
-                let json = self.get_new_address(Some(label), None)?;
+                let json = $crate::maybe_await!(
+                    self.get_new_address(Some(label), None),
+                    $($async)?
+                )?;
                 let model = json.into_model().unwrap();
                 Ok(model.0)
             }
 
             /// Gets a new address - low level RPC call.
-            pub fn get_new_address(
+            pub $($async)? fn get_new_address(
                 &self,
                 label: Option<&str>,
                 ty: Option<AddressType>,
             ) -> Result<GetNewAddress> {
                 match (label, ty) {
                     (Some(label), Some(ty)) =>
-                        self.call("getnewaddress", &[into_json(label)?, into_json(ty)?]),
-                    (Some(label), None) => self.call("getnewaddress", &[into_json(label)?]),
-                    (None, Some(ty)) => self.call("getnewaddress", &["".into(), into_json(ty)?]),
-                    (None, None) => self.call("getnewaddress", &[]),
+                        $crate::maybe_await!(
+                            self.call("getnewaddress", &[into_json(label)?, into_json(ty)?]),
+                            $($async)?
+                        ),
+                    (Some(label), None) => $crate::maybe_await!(
+                        self.call("getnewaddress", &[into_json(label)?]),
+                        $($async)?
+                    ),
+                    (None, Some(ty)) => $crate::maybe_await!(
+                        self.call("getnewaddress", &["".into(), into_json(ty)?]),
+                        $($async)?
+                    ),
+                    (None, None) => $crate::maybe_await!(
+                        self.call("getnewaddress", &[]),
+                        $($async)?
+                    ),
                 }
             }
         }
@@ -222,10 +273,10 @@ macro_rules! impl_client_v17__get_new_address {
 /// Implements Bitcoin Core JSON-RPC API method `getrawchangeaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__get_raw_change_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_raw_change_address(&self) -> Result<GetRawChangeAddress> {
-                self.call("getrawchangeaddress", &[])
+            pub $($async)? fn get_raw_change_address(&self) -> Result<GetRawChangeAddress> {
+                $crate::maybe_await!(self.call("getrawchangeaddress", &[]), $($async)?)
             }
         }
     };
@@ -234,13 +285,16 @@ macro_rules! impl_client_v17__get_raw_change_address {
 /// Implements Bitcoin Core JSON-RPC API method `getreceivedbyaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__get_received_by_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_received_by_address(
+            pub $($async)? fn get_received_by_address(
                 &self,
                 address: &Address<NetworkChecked>,
             ) -> Result<GetReceivedByAddress> {
-                self.call("getreceivedbyaddress", &[address.to_string().into()])
+                $crate::maybe_await!(
+                    self.call("getreceivedbyaddress", &[address.to_string().into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -249,10 +303,10 @@ macro_rules! impl_client_v17__get_received_by_address {
 /// Implements Bitcoin Core JSON-RPC API method `gettransaction`.
 #[macro_export]
 macro_rules! impl_client_v17__get_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_transaction(&self, txid: Txid) -> Result<GetTransaction> {
-                self.call("gettransaction", &[into_json(txid)?])
+            pub $($async)? fn get_transaction(&self, txid: Txid) -> Result<GetTransaction> {
+                $crate::maybe_await!(self.call("gettransaction", &$crate::client_sync::args::get_transaction(txid)), $($async)?)
             }
         }
     };
@@ -261,10 +315,10 @@ macro_rules! impl_client_v17__get_transaction {
 /// Implements Bitcoin Core JSON-RPC API method `getunconfirmedbalance`.
 #[macro_export]
 macro_rules! impl_client_v17__get_unconfirmed_balance {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_unconfirmed_balance(&self) -> Result<GetUnconfirmedBalance> {
-                self.call("getunconfirmedbalance", &[])
+            pub $($async)? fn get_unconfirmed_balance(&self) -> Result<GetUnconfirmedBalance> {
+                $crate::maybe_await!(self.call("getunconfirmedbalance", &[]), $($async)?)
             }
         }
     };
@@ -273,10 +327,10 @@ macro_rules! impl_client_v17__get_unconfirmed_balance {
 /// Implements Bitcoin Core JSON-RPC API method `getwalletinfo`.
 #[macro_export]
 macro_rules! impl_client_v17__get_wallet_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_wallet_info(&self) -> Result<GetWalletInfo> {
-                self.call("getwalletinfo", &[])
+            pub $($async)? fn get_wallet_info(&self) -> Result<GetWalletInfo> {
+                $crate::maybe_await!(self.call("getwalletinfo", &[]), $($async)?)
             }
         }
     };
@@ -285,10 +339,13 @@ macro_rules! impl_client_v17__get_wallet_info {
 /// Implements Bitcoin Core JSON-RPC API method `importaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__import_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_address(&self, address: &Address) -> Result<()> {
-                match self.call("importaddress", &[into_json(address)?]) {
+            pub $($async)? fn import_address(&self, address: &Address) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("importaddress", &[into_json(address)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -301,10 +358,13 @@ macro_rules! impl_client_v17__import_address {
 /// Implements Bitcoin Core JSON-RPC API method `importmulti`.
 #[macro_export]
 macro_rules! impl_client_v17__import_multi {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_multi(&self, requests: &[ImportMultiRequest]) -> Result<ImportMulti> {
-                self.call("importmulti", &[into_json(requests)?])
+            pub $($async)? fn import_multi(
+                &self,
+                requests: &[ImportMultiRequest],
+            ) -> Result<ImportMulti> {
+                $crate::maybe_await!(self.call("importmulti", &[into_json(requests)?]), $($async)?)
             }
         }
     };
@@ -313,10 +373,13 @@ macro_rules! impl_client_v17__import_multi {
 /// Implements Bitcoin Core JSON-RPC API method `importprivkey`.
 #[macro_export]
 macro_rules! impl_client_v17__import_privkey {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_privkey(&self, privkey: &bitcoin::PrivateKey) -> Result<()> {
-                match self.call("importprivkey", &[into_json(privkey)?]) {
+            pub $($async)? fn import_privkey(&self, privkey: &bitcoin::PrivateKey) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("importprivkey", &[into_json(privkey)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -329,16 +392,19 @@ macro_rules! impl_client_v17__import_privkey {
 /// Implements Bitcoin Core JSON-RPC API method `importprunedfunds`.
 #[macro_export]
 macro_rules! impl_client_v17__import_pruned_funds {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_pruned_funds(
+            pub $($async)? fn import_pruned_funds(
                 &self,
                 raw_transaction: &str,
                 tx_out_proof: &str,
             ) -> Result<()> {
-                match self.call(
-                    "importprunedfunds",
-                    &[into_json(raw_transaction)?, into_json(tx_out_proof)?],
+                match $crate::maybe_await!(
+                    self.call(
+                        "importprunedfunds",
+                        &[into_json(raw_transaction)?, into_json(tx_out_proof)?],
+                    ),
+                    $($async)?
                 ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
@@ -352,10 +418,13 @@ macro_rules! impl_client_v17__import_pruned_funds {
 /// Implements Bitcoin Core JSON-RPC API method `importpubkey`.
 #[macro_export]
 macro_rules! impl_client_v17__import_pubkey {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_pubkey(&self, pubkey: &bitcoin::PublicKey) -> Result<()> {
-                match self.call("importpubkey", &[into_json(pubkey)?]) {
+            pub $($async)? fn import_pubkey(&self, pubkey: &bitcoin::PublicKey) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("importpubkey", &[into_json(pubkey)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -368,10 +437,13 @@ macro_rules! impl_client_v17__import_pubkey {
 /// Implements Bitcoin Core JSON-RPC API method `importwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__import_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_wallet(&self, filename: &Path) -> Result<()> {
-                match self.call("importwallet", &[into_json(filename)?]) {
+            pub $($async)? fn import_wallet(&self, filename: &Path) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("importwallet", &[into_json(filename)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -384,10 +456,10 @@ macro_rules! impl_client_v17__import_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `keypoolrefill`.
 #[macro_export]
 macro_rules! impl_client_v17__key_pool_refill {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn key_pool_refill(&self) -> Result<()> {
-                match self.call("keypoolrefill", &[]) {
+            pub $($async)? fn key_pool_refill(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("keypoolrefill", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -400,10 +472,10 @@ macro_rules! impl_client_v17__key_pool_refill {
 /// Implements Bitcoin Core JSON-RPC API method `listaddressgroupings`.
 #[macro_export]
 macro_rules! impl_client_v17__list_address_groupings {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_address_groupings(&self) -> Result<ListAddressGroupings> {
-                self.call("listaddressgroupings", &[])
+            pub $($async)? fn list_address_groupings(&self) -> Result<ListAddressGroupings> {
+                $crate::maybe_await!(self.call("listaddressgroupings", &[]), $($async)?)
             }
         }
     };
@@ -412,9 +484,11 @@ macro_rules! impl_client_v17__list_address_groupings {
 /// Implements Bitcoin Core JSON-RPC API method `listlabels`.
 #[macro_export]
 macro_rules! impl_client_v17__list_labels {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_labels(&self) -> Result<ListLabels> { self.call("listlabels", &[]) }
+            pub $($async)? fn list_labels(&self) -> Result<ListLabels> {
+                $crate::maybe_await!(self.call("listlabels", &[]), $($async)?)
+            }
         }
     };
 }
@@ -422,10 +496,10 @@ macro_rules! impl_client_v17__list_labels {
 /// Implements Bitcoin Core JSON-RPC API method `listlockunspent`.
 #[macro_export]
 macro_rules! impl_client_v17__list_lock_unspent {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_lock_unspent(&self) -> Result<ListLockUnspent> {
-                self.call("listlockunspent", &[])
+            pub $($async)? fn list_lock_unspent(&self) -> Result<ListLockUnspent> {
+                $crate::maybe_await!(self.call("listlockunspent", &[]), $($async)?)
             }
         }
     };
@@ -434,10 +508,10 @@ macro_rules! impl_client_v17__list_lock_unspent {
 /// Implements Bitcoin Core JSON-RPC API method `listreceivedbyaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__list_received_by_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_received_by_address(&self) -> Result<ListReceivedByAddress> {
-                self.call("listreceivedbyaddress", &[])
+            pub $($async)? fn list_received_by_address(&self) -> Result<ListReceivedByAddress> {
+                $crate::maybe_await!(self.call("listreceivedbyaddress", &[]), $($async)?)
             }
         }
     };
@@ -446,10 +520,10 @@ macro_rules! impl_client_v17__list_received_by_address {
 /// Implements Bitcoin Core JSON-RPC API method `listsinceblock`.
 #[macro_export]
 macro_rules! impl_client_v17__list_since_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_since_block(&self) -> Result<ListSinceBlock> {
-                self.call("listsinceblock", &[])
+            pub $($async)? fn list_since_block(&self) -> Result<ListSinceBlock> {
+                $crate::maybe_await!(self.call("listsinceblock", &[]), $($async)?)
             }
         }
     };
@@ -458,10 +532,18 @@ macro_rules! impl_client_v17__list_since_block {
 /// Implements Bitcoin Core JSON-RPC API method `listtransactions`.
 #[macro_export]
 macro_rules! impl_client_v17__list_transactions {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_transactions(&self) -> Result<ListTransactions> {
-                self.call("listtransactions", &[])
+            pub $($async)? fn list_transactions(&self) -> Result<ListTransactions> {
+                $crate::maybe_await!(self.call("listtransactions", &[]), $($async)?)
+            }
+
+            pub $($async)? fn list_transactions_with_options(
+                &self,
+                options: &$crate::client_sync::options::ListTransactionsOptions,
+            ) -> Result<ListTransactions> {
+                let args = options.args(Self::CORE_VERSION)?.into_named();
+                $crate::maybe_await!(self.call_named("listtransactions", &args), $($async)?)
             }
         }
     };
@@ -470,9 +552,19 @@ macro_rules! impl_client_v17__list_transactions {
 /// Implements Bitcoin Core JSON-RPC API method `listunspent`.
 #[macro_export]
 macro_rules! impl_client_v17__list_unspent {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_unspent(&self) -> Result<ListUnspent> { self.call("listunspent", &[]) }
+            pub $($async)? fn list_unspent(&self) -> Result<ListUnspent> {
+                $crate::maybe_await!(self.call("listunspent", &[]), $($async)?)
+            }
+
+            pub $($async)? fn list_unspent_with_query(
+                &self,
+                query: &$crate::client_sync::options::ListUnspentQuery,
+            ) -> Result<ListUnspent> {
+                let args = query.args(Self::CORE_VERSION)?.into_named();
+                $crate::maybe_await!(self.call_named("listunspent", &args), $($async)?)
+            }
         }
     };
 }
@@ -480,9 +572,11 @@ macro_rules! impl_client_v17__list_unspent {
 /// Implements Bitcoin Core JSON-RPC API method `listwallets`.
 #[macro_export]
 macro_rules! impl_client_v17__list_wallets {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_wallets(&self) -> Result<ListWallets> { self.call("listwallets", &[]) }
+            pub $($async)? fn list_wallets(&self) -> Result<ListWallets> {
+                $crate::maybe_await!(self.call("listwallets", &[]), $($async)?)
+            }
         }
     };
 }
@@ -490,10 +584,10 @@ macro_rules! impl_client_v17__list_wallets {
 /// Implements Bitcoin Core JSON-RPC API method `loadwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__load_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn load_wallet(&self, filename: &str) -> Result<LoadWallet> {
-                self.call("loadwallet", &[into_json(filename)?])
+            pub $($async)? fn load_wallet(&self, filename: &str) -> Result<LoadWallet> {
+                $crate::maybe_await!(self.call("loadwallet", &[into_json(filename)?]), $($async)?)
             }
         }
     };
@@ -502,28 +596,37 @@ macro_rules! impl_client_v17__load_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `lockunspent`.
 #[macro_export]
 macro_rules! impl_client_v17__lock_unspent {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Lock the given list of transaction outputs. Returns true on success.
             ///
             /// This wraps Core RPC: `lockunspent false [{"txid":"..","vout":n},...]`.
-            pub fn lock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<LockUnspent> {
+            pub $($async)? fn lock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<LockUnspent> {
                 let outs: Vec<_> = outputs
                     .iter()
                     .map(|(txid, vout)| serde_json::json!({"txid": txid, "vout": vout}))
                     .collect();
-                self.call("lockunspent", &[into_json(false)?, outs.into()])
+                $crate::maybe_await!(
+                    self.call("lockunspent", &[into_json(false)?, outs.into()]),
+                    $($async)?
+                )
             }
 
             /// Unlock the given list of transaction outputs. Returns true on success.
             ///
             /// This wraps Core RPC: `lockunspent true [{"txid":"..","vout":n},...]`.
-            pub fn unlock_unspent(&self, outputs: &[(Txid, u32)]) -> Result<LockUnspent> {
+            pub $($async)? fn unlock_unspent(
+                &self,
+                outputs: &[(Txid, u32)],
+            ) -> Result<LockUnspent> {
                 let outs: Vec<_> = outputs
                     .iter()
                     .map(|(txid, vout)| serde_json::json!({"txid": txid, "vout": vout}))
                     .collect();
-                self.call("lockunspent", &[into_json(true)?, outs.into()])
+                $crate::maybe_await!(
+                    self.call("lockunspent", &[into_json(true)?, outs.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -532,10 +635,13 @@ macro_rules! impl_client_v17__lock_unspent {
 /// Implements Bitcoin Core JSON-RPC API method `removeprunedfunds`.
 #[macro_export]
 macro_rules! impl_client_v17__remove_pruned_funds {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn remove_pruned_funds(&self, txid: Txid) -> Result<()> {
-                self.call("removeprunedfunds", &[into_json(txid)?])
+            pub $($async)? fn remove_pruned_funds(&self, txid: Txid) -> Result<()> {
+                $crate::maybe_await!(
+                    self.call("removeprunedfunds", &[into_json(txid)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -544,10 +650,10 @@ macro_rules! impl_client_v17__remove_pruned_funds {
 /// Implements Bitcoin Core JSON-RPC API method `rescanblockchain`.
 #[macro_export]
 macro_rules! impl_client_v17__rescan_blockchain {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn rescan_blockchain(&self) -> Result<RescanBlockchain> {
-                self.call("rescanblockchain", &[])
+            pub $($async)? fn rescan_blockchain(&self) -> Result<RescanBlockchain> {
+                $crate::maybe_await!(self.call("rescanblockchain", &[]), $($async)?)
             }
         }
     };
@@ -556,15 +662,30 @@ macro_rules! impl_client_v17__rescan_blockchain {
 /// Implements Bitcoin Core JSON-RPC API method `sendmany`.
 #[macro_export]
 macro_rules! impl_client_v17__send_many {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn send_many(&self, amounts: BTreeMap<Address, Amount>) -> Result<SendMany> {
+            pub $($async)? fn send_many(
+                &self,
+                amounts: BTreeMap<Address, Amount>,
+            ) -> Result<SendMany> {
                 let dummy = ""; // Must be set to "" for backwards compatibility.
                 let amount_btc: BTreeMap<String, f64> = amounts
                     .into_iter()
                     .map(|(addr, amount)| (addr.to_string(), amount.to_btc()))
                     .collect();
-                self.call("sendmany", &[into_json(dummy)?, into_json(amount_btc)?])
+                $crate::maybe_await!(
+                    self.call("sendmany", &[into_json(dummy)?, into_json(amount_btc)?]),
+                    $($async)?
+                )
+            }
+
+            pub $($async)? fn send_many_with_options(
+                &self,
+                amounts: &BTreeMap<Address, Amount>,
+                options: &$crate::client_sync::options::SendManyOptions,
+            ) -> Result<SendMany> {
+                let args = options.args(amounts, Self::CORE_VERSION)?.into_named();
+                $crate::maybe_await!(self.call_named("sendmany", &args), $($async)?)
             }
         }
     };
@@ -573,20 +694,20 @@ macro_rules! impl_client_v17__send_many {
 /// Implements Bitcoin Core JSON-RPC API method `sendtoaddress`.
 #[macro_export]
 macro_rules! impl_client_v17__send_to_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // Send to address - no RBF.
-            pub fn send_to_address(
+            pub $($async)? fn send_to_address(
                 &self,
                 address: &Address<NetworkChecked>,
                 amount: Amount,
             ) -> Result<SendToAddress> {
                 let args = [address.to_string().into(), into_json(amount.to_btc())?];
-                self.call("sendtoaddress", &args)
+                $crate::maybe_await!(self.call("sendtoaddress", &args), $($async)?)
             }
 
             // Send to address - with RBF.
-            pub fn send_to_address_rbf(
+            pub $($async)? fn send_to_address_rbf(
                 &self,
                 address: &Address<NetworkChecked>,
                 amount: Amount,
@@ -604,7 +725,18 @@ macro_rules! impl_client_v17__send_to_address {
                     subtract_fee_from_amount.into(),
                     replaceable.into(),
                 ];
-                self.call("sendtoaddress", &args)
+                $crate::maybe_await!(self.call("sendtoaddress", &args), $($async)?)
+            }
+
+            // Send to address - with optional arguments.
+            pub $($async)? fn send_to_address_with_options(
+                &self,
+                address: &Address<NetworkChecked>,
+                amount: Amount,
+                options: &$crate::client_sync::options::SendToAddressOptions,
+            ) -> Result<SendToAddress> {
+                let args = options.args(address, amount, Self::CORE_VERSION)?.into_named();
+                $crate::maybe_await!(self.call_named("sendtoaddress", &args), $($async)?)
             }
         }
     };
@@ -613,10 +745,10 @@ macro_rules! impl_client_v17__send_to_address {
 /// Implements Bitcoin Core JSON-RPC API method `sethdseed`.
 #[macro_export]
 macro_rules! impl_client_v17__set_hd_seed {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn set_hd_seed(&self) -> Result<()> {
-                match self.call("sethdseed", &[]) {
+            pub $($async)? fn set_hd_seed(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("sethdseed", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -629,11 +761,11 @@ macro_rules! impl_client_v17__set_hd_seed {
 /// Implements Bitcoin Core JSON-RPC API method `settxfee`.
 #[macro_export]
 macro_rules! impl_client_v17__set_tx_fee {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn set_tx_fee(&self, fee_rate: bitcoin::FeeRate) -> Result<SetTxFee> {
+            pub $($async)? fn set_tx_fee(&self, fee_rate: bitcoin::FeeRate) -> Result<SetTxFee> {
                 let fee_rate_btc_kvb = fee_rate.to_sat_per_vb_floor() as f64 / 100_000.0;
-                self.call("settxfee", &[fee_rate_btc_kvb.into()])
+                $crate::maybe_await!(self.call("settxfee", &[fee_rate_btc_kvb.into()]), $($async)?)
             }
         }
     };
@@ -642,10 +774,17 @@ macro_rules! impl_client_v17__set_tx_fee {
 /// Implements Bitcoin Core JSON-RPC API method `signmessage`.
 #[macro_export]
 macro_rules! impl_client_v17__sign_message {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn sign_message(&self, address: &Address, message: &str) -> Result<SignMessage> {
-                self.call("signmessage", &[into_json(address)?, into_json(message)?])
+            pub $($async)? fn sign_message(
+                &self,
+                address: &Address,
+                message: &str,
+            ) -> Result<SignMessage> {
+                $crate::maybe_await!(
+                    self.call("signmessage", &[into_json(address)?, into_json(message)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -654,15 +793,18 @@ macro_rules! impl_client_v17__sign_message {
 /// Implements Bitcoin Core JSON-RPC API method `signrawtransactionwithwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__sign_raw_transaction_with_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // `hexstring`: The transaction hex string.
-            pub fn sign_raw_transaction_with_wallet(
+            pub $($async)? fn sign_raw_transaction_with_wallet(
                 &self,
                 tx: &bitcoin::Transaction,
             ) -> Result<SignRawTransactionWithWallet> {
                 let hex = bitcoin::consensus::encode::serialize_hex(tx);
-                self.call("signrawtransactionwithwallet", &[into_json(hex)?])
+                $crate::maybe_await!(
+                    self.call("signrawtransactionwithwallet", &[into_json(hex)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -671,10 +813,13 @@ macro_rules! impl_client_v17__sign_raw_transaction_with_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `unloadwallet`.
 #[macro_export]
 macro_rules! impl_client_v17__unload_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn unload_wallet(&self, wallet_name: &str) -> Result<()> {
-                match self.call("unloadwallet", &[into_json(wallet_name)?]) {
+            pub $($async)? fn unload_wallet(&self, wallet_name: &str) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("unloadwallet", &[into_json(wallet_name)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -687,10 +832,17 @@ macro_rules! impl_client_v17__unload_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `walletpassphrase`.
 #[macro_export]
 macro_rules! impl_client_v17__wallet_passphrase {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wallet_passphrase(&self, passphrase: &str, timeout: u64) -> Result<()> {
-                match self.call("walletpassphrase", &[passphrase.into(), timeout.into()]) {
+            pub $($async)? fn wallet_passphrase(
+                &self,
+                passphrase: &str,
+                timeout: u64,
+            ) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("walletpassphrase", &[passphrase.into(), timeout.into()]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -703,9 +855,9 @@ macro_rules! impl_client_v17__wallet_passphrase {
 /// Implements Bitcoin Core JSON-RPC API method `walletcreatefundedpsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__wallet_create_funded_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wallet_create_funded_psbt(
+            pub $($async)? fn wallet_create_funded_psbt(
                 &self,
                 inputs: Vec<WalletCreateFundedPsbtInput>,
                 outputs: Vec<BTreeMap<Address, Amount>>,
@@ -719,7 +871,33 @@ macro_rules! impl_client_v17__wallet_create_funded_psbt {
                             .collect::<BTreeMap<_, _>>()
                     })
                     .collect();
-                self.call("walletcreatefundedpsbt", &[into_json(inputs)?, into_json(outputs_json)?])
+                $crate::maybe_await!(
+                    self.call(
+                        "walletcreatefundedpsbt",
+                        &[into_json(inputs)?, into_json(outputs_json)?]
+                    ),
+                    $($async)?
+                )
+            }
+
+            pub $($async)? fn wallet_create_funded_psbt_with_options(
+                &self,
+                inputs: Vec<WalletCreateFundedPsbtInput>,
+                outputs: Vec<BTreeMap<Address, Amount>>,
+                options: &$crate::client_sync::options::WalletCreateFundedPsbtOptions,
+            ) -> Result<WalletCreateFundedPsbt> {
+                let outputs_json: Vec<_> = outputs
+                    .into_iter()
+                    .map(|map| {
+                        map.into_iter()
+                            .map(|(addr, amt)| (addr.to_string(), amt.to_btc()))
+                            .collect::<BTreeMap<_, _>>()
+                    })
+                    .collect();
+                let args = options
+                    .args(into_json(inputs)?, into_json(outputs_json)?, Self::CORE_VERSION)?
+                    .into_named();
+                $crate::maybe_await!(self.call_named("walletcreatefundedpsbt", &args), $($async)?)
             }
         }
     };
@@ -728,10 +906,10 @@ macro_rules! impl_client_v17__wallet_create_funded_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `walletlock`.
 #[macro_export]
 macro_rules! impl_client_v17__wallet_lock {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wallet_lock(&self) -> Result<()> {
-                match self.call("walletlock", &[]) {
+            pub $($async)? fn wallet_lock(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("walletlock", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -744,16 +922,20 @@ macro_rules! impl_client_v17__wallet_lock {
 /// Implements Bitcoin Core JSON-RPC API method `walletpassphrasechange`.
 #[macro_export]
 macro_rules! impl_client_v17__wallet_passphrase_change {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wallet_passphrase_change(
+            pub $($async)? fn wallet_passphrase_change(
                 &self,
                 old_passphrase: &str,
                 new_passphrase: &str,
             ) -> Result<()> {
-                match self
-                    .call("walletpassphrasechange", &[old_passphrase.into(), new_passphrase.into()])
-                {
+                match $crate::maybe_await!(
+                    self.call(
+                        "walletpassphrasechange",
+                        &[old_passphrase.into(), new_passphrase.into()]
+                    ),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -766,14 +948,17 @@ macro_rules! impl_client_v17__wallet_passphrase_change {
 /// Implements Bitcoin Core JSON-RPC API method `walletprocesspsbt`.
 #[macro_export]
 macro_rules! impl_client_v17__wallet_process_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wallet_process_psbt(&self, psbt: &bitcoin::Psbt) -> Result<WalletProcessPsbt> {
+            pub $($async)? fn wallet_process_psbt(
+                &self,
+                psbt: &bitcoin::Psbt,
+            ) -> Result<WalletProcessPsbt> {
                 // Core expects the PSBT as a base64 string argument (same representation
                 // used by `finalizepsbt`). Serializing the struct with `into_json` produced
                 // an object which Core rejected ("Expected type string, got object").
                 let psbt = format!("{}", psbt);
-                self.call("walletprocesspsbt", &[psbt.into()])
+                $crate::maybe_await!(self.call("walletprocesspsbt", &[psbt.into()]), $($async)?)
             }
         }
     };
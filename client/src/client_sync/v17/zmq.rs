@@ -5,10 +5,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getzmqnotifications`.
 #[macro_export]
 macro_rules! impl_client_v17__get_zmq_notifications {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_zmq_notifications(&self) -> Result<Vec<GetZmqNotifications>> {
-                self.call("getzmqnotifications", &[])
+            pub $($async)? fn get_zmq_notifications(&self) -> Result<Vec<GetZmqNotifications>> {
+                $crate::maybe_await!(self.call("getzmqnotifications", &[]), $($async)?)
             }
         }
     };
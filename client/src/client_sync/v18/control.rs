@@ -10,9 +10,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `getrpcinfo`.
 #[macro_export]
 macro_rules! impl_client_v18__get_rpc_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_rpc_info(&self) -> Result<GetRpcInfo> { self.call("getrpcinfo", &[]) }
+            pub $($async)? fn get_rpc_info(&self) -> Result<GetRpcInfo> {
+                $crate::maybe_await!(self.call("getrpcinfo", &[]), $($async)?)
+            }
         }
     };
 }
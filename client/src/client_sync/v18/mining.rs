@@ -12,11 +12,14 @@
 /// Implements Bitcoin Core JSON-RPC API method `submitheader`.
 #[macro_export]
 macro_rules! impl_client_v18__submit_header {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn submit_header(&self, header: &bitcoin::block::Header) -> Result<()> {
+            pub $($async)? fn submit_header(&self, header: &bitcoin::block::Header) -> Result<()> {
                 let hexdata = bitcoin::consensus::encode::serialize_hex(header);
-                match self.call("submitheader", &[hexdata.into()]) {
+                match $crate::maybe_await!(
+                    self.call("submitheader", &[hexdata.into()]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -2,7 +2,9 @@
 
 //! A JSON-RPC client for testing against Bitcoin Core `v0.18`.
 //!
-//! We ignore option arguments unless they effect the shape of the returned JSON data.
+//! We ignore option arguments unless they effect the shape of the returned JSON data. A few methods
+//! with many optional arguments also have a variant taking one of the structs in
+//! [`crate::client_sync::options`].
 
 pub mod control;
 pub mod mining;
@@ -30,6 +32,7 @@ pub use crate::client_sync::{
 
 // This publicly re-exports `Client`.
 crate::define_jsonrpc_bitreq_client!("v18");
+crate::impl_client_v17__batch!();
 crate::impl_client_check_expected_server_version!({ [180100] });
 
 // == Blockchain ==
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getnodeaddresses`.
 #[macro_export]
 macro_rules! impl_client_v18__get_node_addresses {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_node_addresses(&self) -> Result<GetNodeAddresses> {
-                self.call("getnodeaddresses", &[])
+            pub $($async)? fn get_node_addresses(&self) -> Result<GetNodeAddresses> {
+                $crate::maybe_await!(self.call("getnodeaddresses", &[]), $($async)?)
             }
         }
     };
@@ -12,11 +12,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `analyzepsbt`.
 #[macro_export]
 macro_rules! impl_client_v18__analyze_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn analyze_psbt(&self, psbt: &bitcoin::Psbt) -> Result<AnalyzePsbt> {
+            pub $($async)? fn analyze_psbt(&self, psbt: &bitcoin::Psbt) -> Result<AnalyzePsbt> {
                 let psbt = format!("{}", psbt);
-                self.call("analyzepsbt", &[psbt.into()])
+                $crate::maybe_await!(self.call("analyzepsbt", &[psbt.into()]), $($async)?)
             }
         }
     };
@@ -25,11 +25,11 @@ macro_rules! impl_client_v18__analyze_psbt {
 /// Implements Bitcoin Core JSON-RPC API method `joinpsbts`.
 #[macro_export]
 macro_rules! impl_client_v18__join_psbts {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn join_psbts(&self, psbts: &[bitcoin::Psbt]) -> Result<JoinPsbts> {
+            pub $($async)? fn join_psbts(&self, psbts: &[bitcoin::Psbt]) -> Result<JoinPsbts> {
                 let psbts = psbts.iter().map(|psbt| format!("{}", psbt)).collect::<Vec<String>>();
-                self.call("joinpsbts", &[psbts.into()])
+                $crate::maybe_await!(self.call("joinpsbts", &[psbts.into()]), $($async)?)
             }
         }
     };
@@ -38,11 +38,14 @@ macro_rules! impl_client_v18__join_psbts {
 /// Implements Bitcoin Core JSON-RPC API method `utxoupdatepsbt`.
 #[macro_export]
 macro_rules! impl_client_v18__utxo_update_psbt {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn utxo_update_psbt(&self, psbt: &bitcoin::Psbt) -> Result<UtxoUpdatePsbt> {
+            pub $($async)? fn utxo_update_psbt(
+                &self,
+                psbt: &bitcoin::Psbt,
+            ) -> Result<UtxoUpdatePsbt> {
                 let psbt = format!("{}", psbt);
-                self.call("utxoupdatepsbt", &[psbt.into()])
+                $crate::maybe_await!(self.call("utxoupdatepsbt", &[psbt.into()]), $($async)?)
             }
         }
     };
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `deriveaddresses`.
 #[macro_export]
 macro_rules! impl_client_v18__derive_addresses {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn derive_addresses(&self, descriptor: &str) -> Result<DeriveAddresses> {
-                self.call("deriveaddresses", &[descriptor.into()])
+            pub $($async)? fn derive_addresses(&self, descriptor: &str) -> Result<DeriveAddresses> {
+                $crate::maybe_await!(self.call("deriveaddresses", &[descriptor.into()]), $($async)?)
             }
         }
     };
@@ -24,10 +24,16 @@ macro_rules! impl_client_v18__derive_addresses {
 /// Implements Bitcoin Core JSON-RPC API method `getdescriptorinfo`.
 #[macro_export]
 macro_rules! impl_client_v18__get_descriptor_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_descriptor_info(&self, descriptor: &str) -> Result<GetDescriptorInfo> {
-                self.call("getdescriptorinfo", &[descriptor.into()])
+            pub $($async)? fn get_descriptor_info(
+                &self,
+                descriptor: &str,
+            ) -> Result<GetDescriptorInfo> {
+                $crate::maybe_await!(
+                    self.call("getdescriptorinfo", &[descriptor.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -11,10 +11,13 @@
 /// Implements Bitcoin Core JSON-RPC API method `getreceivedbylabel`.
 #[macro_export]
 macro_rules! impl_client_v18__get_received_by_label {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_received_by_label(&self, label: &str) -> Result<GetReceivedByLabel> {
-                self.call("getreceivedbylabel", &[label.into()])
+            pub $($async)? fn get_received_by_label(
+                &self,
+                label: &str,
+            ) -> Result<GetReceivedByLabel> {
+                $crate::maybe_await!(self.call("getreceivedbylabel", &[label.into()]), $($async)?)
             }
         }
     };
@@ -23,10 +26,10 @@ macro_rules! impl_client_v18__get_received_by_label {
 /// Implements Bitcoin Core JSON-RPC API method `listreceivedbylabel`.
 #[macro_export]
 macro_rules! impl_client_v18__list_received_by_label {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_received_by_label(&self) -> Result<ListReceivedByLabel> {
-                self.call("listreceivedbylabel", &[])
+            pub $($async)? fn list_received_by_label(&self) -> Result<ListReceivedByLabel> {
+                $crate::maybe_await!(self.call("listreceivedbylabel", &[]), $($async)?)
             }
         }
     };
@@ -35,10 +38,10 @@ macro_rules! impl_client_v18__list_received_by_label {
 /// Implements Bitcoin Core JSON-RPC API method `listwalletdir`.
 #[macro_export]
 macro_rules! impl_client_v18__list_wallet_dir {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_wallet_dir(&self) -> Result<ListWalletDir> {
-                self.call("listwalletdir", &[])
+            pub $($async)? fn list_wallet_dir(&self) -> Result<ListWalletDir> {
+                $crate::maybe_await!(self.call("listwalletdir", &[]), $($async)?)
             }
         }
     };
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getblockfilter`.
 #[macro_export]
 macro_rules! impl_client_v19__get_block_filter {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_filter(&self, block: BlockHash) -> Result<GetBlockFilter> {
-                self.call("getblockfilter", &[into_json(block)?])
+            pub $($async)? fn get_block_filter(&self, block: BlockHash) -> Result<GetBlockFilter> {
+                $crate::maybe_await!(self.call("getblockfilter", &[into_json(block)?]), $($async)?)
             }
         }
     };
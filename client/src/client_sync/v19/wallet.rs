@@ -12,9 +12,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `getbalances`.
 #[macro_export]
 macro_rules! impl_client_v19__get_balances {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_balances(&self) -> Result<GetBalances> { self.call("getbalances", &[]) }
+            pub $($async)? fn get_balances(&self) -> Result<GetBalances> {
+                $crate::maybe_await!(self.call("getbalances", &[]), $($async)?)
+            }
         }
     };
 }
@@ -22,10 +24,10 @@ macro_rules! impl_client_v19__get_balances {
 /// Implements Bitcoin Core JSON-RPC API method `setwalletflag`.
 #[macro_export]
 macro_rules! impl_client_v19__set_wallet_flag {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn set_wallet_flag(&self, flag: &str) -> Result<SetWalletFlag> {
-                self.call("setwalletflag", &[into_json(flag)?])
+            pub $($async)? fn set_wallet_flag(&self, flag: &str) -> Result<SetWalletFlag> {
+                $crate::maybe_await!(self.call("setwalletflag", &[into_json(flag)?]), $($async)?)
             }
         }
     };
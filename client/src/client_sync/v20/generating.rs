@@ -12,14 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `generatetodescriptor`.
 #[macro_export]
 macro_rules! impl_client_v20__generate_to_descriptor {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn generate_to_descriptor(
+            pub $($async)? fn generate_to_descriptor(
                 &self,
                 nblocks: usize,
                 descriptor: &str,
             ) -> Result<GenerateToDescriptor> {
-                self.call("generatetodescriptor", &[nblocks.into(), descriptor.into()])
+                $crate::maybe_await!(
+                    self.call("generatetodescriptor", &[nblocks.into(), descriptor.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,10 +12,13 @@
 /// Implements Bitcoin Core JSON-RPC API method `mockscheduler`.
 #[macro_export]
 macro_rules! impl_client_v20__mock_scheduler {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn mock_scheduler(&self, delta_time: u64) -> Result<()> {
-                self.call("mockscheduler", &[into_json(delta_time)?])
+            pub $($async)? fn mock_scheduler(&self, delta_time: u64) -> Result<()> {
+                $crate::maybe_await!(
+                    self.call("mockscheduler", &[into_json(delta_time)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,19 +12,22 @@
 /// Implements Bitcoin Core JSON-RPC API method `getrawmempool`.
 #[macro_export]
 macro_rules! impl_client_v21__get_raw_mempool {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_raw_mempool(&self) -> Result<GetRawMempool> {
+            pub $($async)? fn get_raw_mempool(&self) -> Result<GetRawMempool> {
                 // Equivalent to self.call("getrawmempool", &[into_json(false)?])
-                self.call("getrawmempool", &[])
+                $crate::maybe_await!(self.call("getrawmempool", &[]), $($async)?)
             }
 
-            pub fn get_raw_mempool_verbose(&self) -> Result<GetRawMempoolVerbose> {
-                self.call("getrawmempool", &[into_json(true)?])
+            pub $($async)? fn get_raw_mempool_verbose(&self) -> Result<GetRawMempoolVerbose> {
+                $crate::maybe_await!(self.call("getrawmempool", &[into_json(true)?]), $($async)?)
             }
 
-            pub fn get_raw_mempool_sequence(&self) -> Result<GetRawMempoolSequence> {
-                self.call("getrawmempool", &[into_json(false)?, into_json(true)?])
+            pub $($async)? fn get_raw_mempool_sequence(&self) -> Result<GetRawMempoolSequence> {
+                $crate::maybe_await!(
+                    self.call("getrawmempool", &[into_json(false)?, into_json(true)?]),
+                    $($async)?
+                )
             }
         }
     };
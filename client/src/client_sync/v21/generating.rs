@@ -12,14 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `generateblock`.
 #[macro_export]
 macro_rules! impl_client_v21__generate_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn generate_block(
+            pub $($async)? fn generate_block(
                 &self,
                 output: &str,
                 transactions: &[String],
             ) -> Result<GenerateBlock> {
-                self.call("generateblock", &[into_json(output)?, into_json(transactions)?])
+                $crate::maybe_await!(
+                    self.call("generateblock", &[into_json(output)?, into_json(transactions)?]),
+                    $($async)?
+                )
             }
         }
     };
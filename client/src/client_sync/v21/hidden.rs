@@ -12,10 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `addpeeraddress`.
 #[macro_export]
 macro_rules! impl_client_v21__add_peer_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn add_peer_address(&self, address: &str, port: u16) -> Result<AddPeerAddress> {
-                self.call("addpeeraddress", &[address.into(), port.into()])
+            pub $($async)? fn add_peer_address(
+                &self,
+                address: &str,
+                port: u16,
+            ) -> Result<AddPeerAddress> {
+                $crate::maybe_await!(
+                    self.call("addpeeraddress", &[address.into(), port.into()]),
+                    $($async)?
+                )
             }
         }
     };
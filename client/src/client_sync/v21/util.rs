@@ -12,9 +12,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `getindexinfo`.
 #[macro_export]
 macro_rules! impl_client_v21__get_index_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_index_info(&self) -> Result<GetIndexInfo> { self.call("getindexinfo", &[]) }
+            pub $($async)? fn get_index_info(&self) -> Result<GetIndexInfo> {
+                $crate::maybe_await!(self.call("getindexinfo", &[]), $($async)?)
+            }
         }
     };
 }
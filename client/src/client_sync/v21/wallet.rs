@@ -12,14 +12,14 @@
 /// Implements Bitcoin Core JSON-RPC API method `createwallet`.
 #[macro_export]
 macro_rules! impl_client_v21__create_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Calls `createwallet` with `wallet` as the only argument.
             ///
             /// In v21 and v22 this creates a legacy wallet. Use `create_descriptor_wallet` to create
             /// a descriptor wallet.
-            pub fn create_wallet(&self, wallet: &str) -> Result<CreateWallet> {
-                self.call("createwallet", &[wallet.into()])
+            pub $($async)? fn create_wallet(&self, wallet: &str) -> Result<CreateWallet> {
+                $crate::maybe_await!(self.call("createwallet", &[wallet.into()]), $($async)?)
             }
 
             /// Creates a wallet with descriptors=true (descriptor wallet).
@@ -36,23 +36,29 @@ macro_rules! impl_client_v21__create_wallet {
             /// > 5. avoid_reuse             (boolean, optional, default=false) Keep track of coin reuse, and treat dirty and clean coins differently with privacy considerations in mind.
             /// > 6. descriptors             (boolean, optional, default=true) Create a native descriptor wallet. The wallet will use descriptors internally to handle address creation
             /// > 7. load_on_startup         (boolean, optional) Save wallet name to persistent settings and load on startup. True to add wallet to startup list, false to remove, null to leave unchanged.
-            pub fn create_descriptor_wallet(&self, wallet: &str) -> Result<CreateWallet> {
+            pub $($async)? fn create_descriptor_wallet(
+                &self,
+                wallet: &str,
+            ) -> Result<CreateWallet> {
                 let disable_private_keys = false;
                 let blank = false;
                 let passphrase = String::new();
                 let avoid_reuse = false;
                 let descriptors = true;
 
-                self.call(
-                    "createwallet",
-                    &[
-                        wallet.into(),
-                        disable_private_keys.into(),
-                        blank.into(),
-                        passphrase.into(),
-                        avoid_reuse.into(),
-                        descriptors.into(),
-                    ],
+                $crate::maybe_await!(
+                    self.call(
+                        "createwallet",
+                        &[
+                            wallet.into(),
+                            disable_private_keys.into(),
+                            blank.into(),
+                            passphrase.into(),
+                            avoid_reuse.into(),
+                            descriptors.into(),
+                        ],
+                    ),
+                    $($async)?
                 )
             }
         }
@@ -62,13 +68,16 @@ macro_rules! impl_client_v21__create_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `importdescriptors`.
 #[macro_export]
 macro_rules! impl_client_v21__import_descriptors {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_descriptors(
+            pub $($async)? fn import_descriptors(
                 &self,
                 requests: &[ImportDescriptorsRequest],
             ) -> Result<ImportDescriptors> {
-                self.call("importdescriptors", &[into_json(requests)?])
+                $crate::maybe_await!(
+                    self.call("importdescriptors", &[into_json(requests)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -77,10 +86,10 @@ macro_rules! impl_client_v21__import_descriptors {
 /// Implements Bitcoin Core JSON-RPC API method `psbtbumpfee`.
 #[macro_export]
 macro_rules! impl_client_v21__psbt_bump_fee {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn psbt_bump_fee(&self, txid: &bitcoin::Txid) -> Result<PsbtBumpFee> {
-                self.call("psbtbumpfee", &[into_json(txid)?])
+            pub $($async)? fn psbt_bump_fee(&self, txid: &bitcoin::Txid) -> Result<PsbtBumpFee> {
+                $crate::maybe_await!(self.call("psbtbumpfee", &[into_json(txid)?]), $($async)?)
             }
         }
     };
@@ -89,10 +98,10 @@ macro_rules! impl_client_v21__psbt_bump_fee {
 /// Implements Bitcoin Core JSON-RPC API method `send`.
 #[macro_export]
 macro_rules! impl_client_v21__send {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn send(&self, outputs: &BTreeMap<String, f64>) -> Result<Send> {
-                self.call("send", &[into_json(outputs)?])
+            pub $($async)? fn send(&self, outputs: &BTreeMap<String, f64>) -> Result<Send> {
+                $crate::maybe_await!(self.call("send", &[into_json(outputs)?]), $($async)?)
             }
         }
     };
@@ -101,9 +110,9 @@ macro_rules! impl_client_v21__send {
 /// Implements Bitcoin Core JSON-RPC API method `sendmany` with `verbose=true` (v21+).
 #[macro_export]
 macro_rules! impl_client_v21__send_many_verbose {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn send_many_verbose(
+            pub $($async)? fn send_many_verbose(
                 &self,
                 amounts: BTreeMap<Address, Amount>,
             ) -> Result<SendManyVerbose> {
@@ -120,20 +129,23 @@ macro_rules! impl_client_v21__send_many_verbose {
                 let estimate_mode = "unset";
                 let fee_rate = serde_json::Value::Null;
                 let verbose = true;
-                self.call(
-                    "sendmany",
-                    &[
-                        into_json(dummy)?,
-                        into_json(amount_btc)?,
-                        minconf.into(),
-                        comment.into(),
-                        into_json(subtract_fee_from)?,
-                        replaceable.into(),
-                        conf_target.into(),
-                        estimate_mode.into(),
-                        fee_rate,
-                        verbose.into(),
-                    ],
+                $crate::maybe_await!(
+                    self.call(
+                        "sendmany",
+                        &[
+                            into_json(dummy)?,
+                            into_json(amount_btc)?,
+                            minconf.into(),
+                            comment.into(),
+                            into_json(subtract_fee_from)?,
+                            replaceable.into(),
+                            conf_target.into(),
+                            estimate_mode.into(),
+                            fee_rate,
+                            verbose.into(),
+                        ],
+                    ),
+                    $($async)?
                 )
             }
         }
@@ -143,10 +155,10 @@ macro_rules! impl_client_v21__send_many_verbose {
 /// Implements Bitcoin Core JSON-RPC API method `unloadwallet`.
 #[macro_export]
 macro_rules! impl_client_v21__unload_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn unload_wallet(&self, wallet: &str) -> Result<UnloadWallet> {
-                self.call("unloadwallet", &[wallet.into()])
+            pub $($async)? fn unload_wallet(&self, wallet: &str) -> Result<UnloadWallet> {
+                $crate::maybe_await!(self.call("unloadwallet", &[wallet.into()]), $($async)?)
             }
         }
     };
@@ -155,10 +167,10 @@ macro_rules! impl_client_v21__unload_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `upgradewallet`.
 #[macro_export]
 macro_rules! impl_client_v21__upgrade_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn upgrade_wallet(&self) -> Result<UpgradeWallet> {
-                self.call("upgradewallet", &[])
+            pub $($async)? fn upgrade_wallet(&self) -> Result<UpgradeWallet> {
+                $crate::maybe_await!(self.call("upgradewallet", &[]), $($async)?)
             }
         }
     };
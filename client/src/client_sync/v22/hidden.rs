@@ -12,14 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `addconnection`.
 #[macro_export]
 macro_rules! impl_client_v22__add_connection {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn add_connection(
+            pub $($async)? fn add_connection(
                 &self,
                 address: &str,
                 connection_type: &str,
             ) -> Result<AddConnection> {
-                self.call("addconnection", &[into_json(address)?, into_json(connection_type)?])
+                $crate::maybe_await!(
+                    self.call("addconnection", &[into_json(address)?, into_json(connection_type)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `enumeratesigners`
 #[macro_export]
 macro_rules! impl_client_v22__enumerate_signers {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn enumerate_signers(&self) -> Result<EnumerateSigners> {
-                self.call("enumeratesigners", &[])
+            pub $($async)? fn enumerate_signers(&self) -> Result<EnumerateSigners> {
+                $crate::maybe_await!(self.call("enumeratesigners", &[]), $($async)?)
             }
         }
     };
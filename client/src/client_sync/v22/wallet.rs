@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `listdescriptors`.
 #[macro_export]
 macro_rules! impl_client_v22__list_descriptors {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn list_descriptors(&self) -> Result<ListDescriptors> {
-                self.call("listdescriptors", &[])
+            pub $($async)? fn list_descriptors(&self) -> Result<ListDescriptors> {
+                $crate::maybe_await!(self.call("listdescriptors", &[]), $($async)?)
             }
         }
     };
@@ -24,10 +24,10 @@ macro_rules! impl_client_v22__list_descriptors {
 /// Implements Bitcoin Core JSON-RPC API method `loadwallet`.
 #[macro_export]
 macro_rules! impl_client_v22__load_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn load_wallet(&self, wallet: &str) -> Result<LoadWallet> {
-                self.call("loadwallet", &[wallet.into()])
+            pub $($async)? fn load_wallet(&self, wallet: &str) -> Result<LoadWallet> {
+                $crate::maybe_await!(self.call("loadwallet", &[wallet.into()]), $($async)?)
             }
         }
     };
@@ -36,10 +36,16 @@ macro_rules! impl_client_v22__load_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `walletdisplayaddress`.
 #[macro_export]
 macro_rules! impl_client_v22__wallet_display_address {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn wallet_display_address(&self, address: &str) -> Result<WalletDisplayAddress> {
-                self.call("walletdisplayaddress", &[address.into()])
+            pub $($async)? fn wallet_display_address(
+                &self,
+                address: &str,
+            ) -> Result<WalletDisplayAddress> {
+                $crate::maybe_await!(
+                    self.call("walletdisplayaddress", &[address.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,10 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `getblockfrompeer`.
 #[macro_export]
 macro_rules! impl_client_v23__get_block_from_peer {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_block_from_peer(&self, blockhash: BlockHash, peer_id: u32) -> Result<()> {
-                match self.call("getblockfrompeer", &[into_json(blockhash)?, into_json(peer_id)?]) {
+            pub $($async)? fn get_block_from_peer(
+                &self,
+                blockhash: BlockHash,
+                peer_id: u32,
+            ) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("getblockfrompeer", &[into_json(blockhash)?, into_json(peer_id)?]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Object(ref map)) if map.is_empty() => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -28,10 +35,16 @@ macro_rules! impl_client_v23__get_block_from_peer {
 /// Implements Bitcoin Core JSON-RPC API method `getdeploymentinfo`.
 #[macro_export]
 macro_rules! impl_client_v23__get_deployment_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_deployment_info(&self, blockhash: &BlockHash) -> Result<GetDeploymentInfo> {
-                self.call("getdeploymentinfo", &[into_json(blockhash)?])
+            pub $($async)? fn get_deployment_info(
+                &self,
+                blockhash: &BlockHash,
+            ) -> Result<GetDeploymentInfo> {
+                $crate::maybe_await!(
+                    self.call("getdeploymentinfo", &[into_json(blockhash)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -40,9 +53,11 @@ macro_rules! impl_client_v23__get_deployment_info {
 /// Implements Bitcoin Core JSON-RPC API method `savemempool`.
 #[macro_export]
 macro_rules! impl_client_v23__save_mempool {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn save_mempool(&self) -> Result<SaveMempool> { self.call("savemempool", &[]) }
+            pub $($async)? fn save_mempool(&self) -> Result<SaveMempool> {
+                $crate::maybe_await!(self.call("savemempool", &[]), $($async)?)
+            }
         }
     };
 }
@@ -2,7 +2,9 @@
 
 //! A JSON-RPC client for testing against Bitcoin Core `v23`.
 //!
-//! We ignore option arguments unless they effect the shape of the returned JSON data.
+//! We ignore option arguments unless they effect the shape of the returned JSON data. A few methods
+//! with many optional arguments also have a variant taking one of the structs in
+//! [`crate::client_sync::options`].
 
 pub mod blockchain;
 pub mod wallet;
@@ -12,13 +14,13 @@ use std::path::Path;
 
 use bitcoin::address::{Address, NetworkChecked};
 use bitcoin::{sign_message, Amount, Block, BlockHash, PublicKey, Txid};
-use serde::{Deserialize, Serialize};
 
 use crate::client_sync::into_json;
 use crate::types::v23::*;
 
 #[rustfmt::skip]                // Keep public re-exports separate.
 pub use crate::client_sync::{
+    AddressType,
     v17::{
         AddNodeCommand, ImportMultiRequest, ImportMultiScriptPubKey, ImportMultiTimestamp, Input, Output, SetBanCommand, TemplateRequest, TemplateRules,
         WalletCreateFundedPsbtInput,
@@ -27,6 +29,7 @@ pub use crate::client_sync::{
 };
 
 crate::define_jsonrpc_bitreq_client!("v23");
+crate::impl_client_v17__batch!();
 crate::impl_client_check_expected_server_version!({ [230200] });
 
 // == Blockchain ==
@@ -206,29 +209,3 @@ crate::impl_client_v17__wallet_process_psbt!();
 
 // == Zmq ==
 crate::impl_client_v17__get_zmq_notifications!();
-
-/// Argument to the `Client::get_new_address_with_type` function.
-///
-/// For Core v23 and onwards. For earlier versions use `v17::AddressType`.
-#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum AddressType {
-    Legacy,
-    P2shSegwit,
-    Bech32,
-    Bech32m, // Field added in Core v23
-}
-
-impl fmt::Display for AddressType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use AddressType::*;
-
-        let s = match *self {
-            Legacy => "legacy",
-            P2shSegwit => "p2sh-segwit",
-            Bech32 => "bech32",
-            Bech32m => "bech32m",
-        };
-        fmt::Display::fmt(s, f)
-    }
-}
@@ -12,14 +12,14 @@
 /// Implements Bitcoin Core JSON-RPC API method `createwallet`.
 #[macro_export]
 macro_rules! impl_client_v23__create_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Calls `createwallet` with `wallet` as the only argument.
             ///
             /// In v23 and later this creates a descriptor wallet. Use `create_legacy_wallet` to create
             /// a legacy wallet.
-            pub fn create_wallet(&self, wallet: &str) -> Result<CreateWallet> {
-                self.call("createwallet", &[wallet.into()])
+            pub $($async)? fn create_wallet(&self, wallet: &str) -> Result<CreateWallet> {
+                $crate::maybe_await!(self.call("createwallet", &[wallet.into()]), $($async)?)
             }
 
             /// Creates a legacy wallet (i.e not a native descriptor wallet).
@@ -37,23 +37,26 @@ macro_rules! impl_client_v23__create_wallet {
             /// > 6. descriptors             (boolean, optional, default=true) Create a native descriptor wallet. The wallet will use descriptors internally to handle address creation
             /// > 7. load_on_startup         (boolean, optional) Save wallet name to persistent settings and load on startup. True to add wallet to startup list, false to remove, null to leave unchanged.
             /// > 8. external_signer         (boolean, optional, default=false) Use an external signer such as a hardware wallet. Requires -signer to be configured. Wallet creation will fail if keys cannot be fetched. Requires disable_private_keys and descriptors set to true.
-            pub fn create_legacy_wallet(&self, wallet: &str) -> Result<CreateWallet> {
+            pub $($async)? fn create_legacy_wallet(&self, wallet: &str) -> Result<CreateWallet> {
                 let disable_private_keys = false;
                 let blank = false;
                 let passphrase = String::new();
                 let avoid_reuse = false;
                 let descriptors = false;
 
-                self.call(
-                    "createwallet",
-                    &[
-                        wallet.into(),
-                        disable_private_keys.into(),
-                        blank.into(),
-                        passphrase.into(),
-                        avoid_reuse.into(),
-                        descriptors.into(),
-                    ],
+                $crate::maybe_await!(
+                    self.call(
+                        "createwallet",
+                        &[
+                            wallet.into(),
+                            disable_private_keys.into(),
+                            blank.into(),
+                            passphrase.into(),
+                            avoid_reuse.into(),
+                            descriptors.into(),
+                        ],
+                    ),
+                    $($async)?
                 )
             }
         }
@@ -63,7 +66,7 @@ macro_rules! impl_client_v23__create_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `newkeypool`.
 #[macro_export]
 macro_rules! impl_client_v23__new_keypool {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Calls `newkeypool` for the loaded wallet.
             ///
@@ -71,8 +74,8 @@ macro_rules! impl_client_v23__new_keypool {
             /// >
             /// > Entirely clears and refills the keypool.
             /// > Requires wallet passphrase to be set if wallet is encrypted.
-            pub fn new_keypool(&self) -> Result<()> {
-                match self.call("newkeypool", &[]) {
+            pub $($async)? fn new_keypool(&self) -> Result<()> {
+                match $crate::maybe_await!(self.call("newkeypool", &[]), $($async)?) {
                     Ok(serde_json::Value::Null) => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -85,17 +88,20 @@ macro_rules! impl_client_v23__new_keypool {
 /// Implements Bitcoin Core JSON-RPC API method `restorewallet`.
 #[macro_export]
 macro_rules! impl_client_v23__restore_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Calls `restorewallet` with required and optional arguments.
             ///
             /// > restorewallet "wallet_name" "backup_file" ( load_on_startup )
-            pub fn restore_wallet(
+            pub $($async)? fn restore_wallet(
                 &self,
                 wallet_name: &str,
                 backup_file: &Path,
             ) -> Result<RestoreWallet> {
-                self.call("restorewallet", &[wallet_name.into(), into_json(backup_file)?])
+                $crate::maybe_await!(
+                    self.call("restorewallet", &[wallet_name.into(), into_json(backup_file)?]),
+                    $($async)?
+                )
             }
         }
     };
@@ -12,9 +12,9 @@
 /// Implements Bitcoin Core JSON-RPC API method `gettxspendingprevout`
 #[macro_export]
 macro_rules! impl_client_v24__get_tx_spending_prevout {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_tx_spending_prevout(
+            pub $($async)? fn get_tx_spending_prevout(
                 &self,
                 outputs: &[bitcoin::OutPoint],
             ) -> Result<GetTxSpendingPrevout> {
@@ -24,7 +24,10 @@ macro_rules! impl_client_v24__get_tx_spending_prevout {
                         "vout": out.vout,
                     })
                 }).collect();
-                self.call("gettxspendingprevout", &[json_outputs.into()])
+                $crate::maybe_await!(
+                    self.call("gettxspendingprevout", &[json_outputs.into()]),
+                    $($async)?
+                )
             }
         }
     };
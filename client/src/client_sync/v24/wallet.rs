@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `migratewallet`.
 #[macro_export]
 macro_rules! impl_client_v24__migrate_wallet {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn migrate_wallet(&self, wallet_name: &str) -> Result<MigrateWallet> {
-                self.call("migratewallet", &[wallet_name.into()])
+            pub $($async)? fn migrate_wallet(&self, wallet_name: &str) -> Result<MigrateWallet> {
+                $crate::maybe_await!(self.call("migratewallet", &[wallet_name.into()]), $($async)?)
             }
         }
     };
@@ -24,10 +24,10 @@ macro_rules! impl_client_v24__migrate_wallet {
 /// Implements Bitcoin Core JSON-RPC API method `sendall`.
 #[macro_export]
 macro_rules! impl_client_v24__send_all {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn send_all(&self, recipients: &[Address]) -> Result<SendAll> {
-                self.call("sendall", &[into_json(recipients)?])
+            pub $($async)? fn send_all(&self, recipients: &[Address]) -> Result<SendAll> {
+                $crate::maybe_await!(self.call("sendall", &[into_json(recipients)?]), $($async)?)
             }
         }
     };
@@ -36,13 +36,16 @@ macro_rules! impl_client_v24__send_all {
 /// Implements Bitcoin Core JSON-RPC API method `simulaterawtransaction`.
 #[macro_export]
 macro_rules! impl_client_v24__simulate_raw_transaction {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn simulate_raw_transaction(
+            pub $($async)? fn simulate_raw_transaction(
                 &self,
                 rawtxs: &[String],
             ) -> Result<SimulateRawTransaction> {
-                self.call("simulaterawtransaction", &[into_json(rawtxs)?])
+                $crate::maybe_await!(
+                    self.call("simulaterawtransaction", &[into_json(rawtxs)?]),
+                    $($async)?
+                )
             }
         }
     };
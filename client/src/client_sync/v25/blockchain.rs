@@ -12,21 +12,27 @@
 /// Implements Bitcoin Core JSON-RPC API method `scanblocks`
 #[macro_export]
 macro_rules! impl_client_v25__scan_blocks {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Aborts an ongoing `scanblocks` scan.
-            pub fn scan_blocks_abort(&self) -> Result<ScanBlocksAbort> {
-                self.call("scanblocks", &[into_json("abort")?])
+            pub $($async)? fn scan_blocks_abort(&self) -> Result<ScanBlocksAbort> {
+                $crate::maybe_await!(self.call("scanblocks", &[into_json("abort")?]), $($async)?)
             }
 
             /// Starts a scan of blocks for specified descriptors.
-            pub fn scan_blocks_start(&self, scan_objects: &[&str]) -> Result<ScanBlocksStart> {
-                self.call("scanblocks", &[into_json("start")?, into_json(scan_objects)?])
+            pub $($async)? fn scan_blocks_start(
+                &self,
+                scan_objects: &[&str],
+            ) -> Result<ScanBlocksStart> {
+                $crate::maybe_await!(
+                    self.call("scanblocks", &[into_json("start")?, into_json(scan_objects)?]),
+                    $($async)?
+                )
             }
 
             /// Checks the status of an ongoing `scanblocks` scan.
-            pub fn scan_blocks_status(&self) -> Result<Option<ScanBlocksStatus>> {
-                self.call("scanblocks", &[into_json("status")?])
+            pub $($async)? fn scan_blocks_status(&self) -> Result<Option<ScanBlocksStatus>> {
+                $crate::maybe_await!(self.call("scanblocks", &[into_json("status")?]), $($async)?)
             }
         }
     };
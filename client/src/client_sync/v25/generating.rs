@@ -12,17 +12,20 @@
 /// Implements Bitcoin Core JSON-RPC API method `generateblock`.
 #[macro_export]
 macro_rules! impl_client_v25__generate_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn generate_block(
+            pub $($async)? fn generate_block(
                 &self,
                 output: &str,
                 transactions: &[String],
                 submit: bool,
             ) -> Result<GenerateBlock> {
-                self.call(
-                    "generateblock",
-                    &[into_json(output)?, into_json(transactions)?, into_json(submit)?],
+                $crate::maybe_await!(
+                    self.call(
+                        "generateblock",
+                        &[into_json(output)?, into_json(transactions)?, into_json(submit)?],
+                    ),
+                    $($async)?
                 )
             }
         }
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `dumptxoutset`.
 #[macro_export]
 macro_rules! impl_client_v26__dump_tx_out_set {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn dump_tx_out_set(&self, path: &str) -> Result<DumpTxOutSet> {
-                self.call("dumptxoutset", &[path.into()])
+            pub $($async)? fn dump_tx_out_set(&self, path: &str) -> Result<DumpTxOutSet> {
+                $crate::maybe_await!(self.call("dumptxoutset", &[path.into()]), $($async)?)
             }
         }
     };
@@ -24,10 +24,10 @@ macro_rules! impl_client_v26__dump_tx_out_set {
 /// Implements Bitcoin Core JSON-RPC API method `getchainstates`.
 #[macro_export]
 macro_rules! impl_client_v26__get_chain_states {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_chain_states(&self) -> Result<GetChainStates> {
-                self.call("getchainstates", &[])
+            pub $($async)? fn get_chain_states(&self) -> Result<GetChainStates> {
+                $crate::maybe_await!(self.call("getchainstates", &[]), $($async)?)
             }
         }
     };
@@ -36,10 +36,10 @@ macro_rules! impl_client_v26__get_chain_states {
 /// Implements Bitcoin Core JSON-RPC API method `gettxoutsetinfo`.
 #[macro_export]
 macro_rules! impl_client_v26__get_tx_out_set_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_tx_out_set_info(&self) -> Result<GetTxOutSetInfo> {
-                self.call("gettxoutsetinfo", &[])
+            pub $($async)? fn get_tx_out_set_info(&self) -> Result<GetTxOutSetInfo> {
+                $crate::maybe_await!(self.call("gettxoutsetinfo", &[]), $($async)?)
             }
         }
     };
@@ -48,10 +48,13 @@ macro_rules! impl_client_v26__get_tx_out_set_info {
 /// Implements Bitcoin Core JSON-RPC API method `importmempool`.
 #[macro_export]
 macro_rules! impl_client_v26__import_mempool {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn import_mempool(&self, filepath: &str) -> Result<()> {
-                match self.call("importmempool", &[filepath.into()]) {
+            pub $($async)? fn import_mempool(&self, filepath: &str) -> Result<()> {
+                match $crate::maybe_await!(
+                    self.call("importmempool", &[filepath.into()]),
+                    $($async)?
+                ) {
                     Ok(serde_json::Value::Object(ref map)) if map.is_empty() => Ok(()),
                     Ok(res) => Err(Error::Returned(res.to_string())),
                     Err(err) => Err(err.into()),
@@ -64,10 +67,10 @@ macro_rules! impl_client_v26__import_mempool {
 /// Implements Bitcoin Core JSON-RPC API method `loadtxoutset`.
 #[macro_export]
 macro_rules! impl_client_v26__load_tx_out_set {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn load_tx_out_set(&self, path: &str) -> Result<LoadTxOutSet> {
-                self.call("loadtxoutset", &[path.into()])
+            pub $($async)? fn load_tx_out_set(&self, path: &str) -> Result<LoadTxOutSet> {
+                $crate::maybe_await!(self.call("loadtxoutset", &[path.into()]), $($async)?)
             }
         }
     };
@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getrawaddrman`.
 #[macro_export]
 macro_rules! impl_client_v26__get_raw_addrman {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_raw_addrman(&self) -> Result<GetRawAddrMan> {
-                self.call("getrawaddrman", &[])
+            pub $($async)? fn get_raw_addrman(&self) -> Result<GetRawAddrMan> {
+                $crate::maybe_await!(self.call("getrawaddrman", &[]), $($async)?)
             }
         }
     };
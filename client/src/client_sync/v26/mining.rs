@@ -12,10 +12,12 @@
 /// Implements Bitcoin Core JSON-RPC API method `getprioritisedtransactions`.
 #[macro_export]
 macro_rules! impl_client_v26__get_prioritised_transactions {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_prioritised_transactions(&self) -> Result<GetPrioritisedTransactions> {
-                self.call("getprioritisedtransactions", &[])
+            pub $($async)? fn get_prioritised_transactions(
+                &self,
+            ) -> Result<GetPrioritisedTransactions> {
+                $crate::maybe_await!(self.call("getprioritisedtransactions", &[]), $($async)?)
             }
         }
     };
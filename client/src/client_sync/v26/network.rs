@@ -12,10 +12,10 @@
 /// Implements Bitcoin Core JSON-RPC API method `getaddrmaninfo`.
 #[macro_export]
 macro_rules! impl_client_v26__get_addr_man_info {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_addr_man_info(&self) -> Result<GetAddrManInfo> {
-                self.call("getaddrmaninfo", &[])
+            pub $($async)? fn get_addr_man_info(&self) -> Result<GetAddrManInfo> {
+                $crate::maybe_await!(self.call("getaddrmaninfo", &[]), $($async)?)
             }
         }
     };
@@ -12,9 +12,9 @@
 /// Implements Bitcoin Core JSON-RPC API method `submitpackage`.
 #[macro_export]
 macro_rules! impl_client_v26__submit_package {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn submit_package(
+            pub $($async)? fn submit_package(
                 &self,
                 package: &[bitcoin::Transaction],
             ) -> Result<SubmitPackage> {
@@ -22,7 +22,7 @@ macro_rules! impl_client_v26__submit_package {
                     .into_iter()
                     .map(|tx| bitcoin::consensus::encode::serialize_hex(tx))
                     .collect::<Vec<_>>();
-                self.call("submitpackage", &[package_txs.into()])
+                $crate::maybe_await!(self.call("submitpackage", &[package_txs.into()]), $($async)?)
             }
         }
     };
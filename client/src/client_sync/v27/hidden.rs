@@ -12,17 +12,24 @@
 /// Implements Bitcoin Core JSON-RPC API method `addconnection`.
 #[macro_export]
 macro_rules! impl_client_v27__add_connection {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn add_connection(
+            pub $($async)? fn add_connection(
                 &self,
                 address: &str,
                 connection_type: &str,
                 v2transport: bool,
             ) -> Result<AddConnection> {
-                self.call(
-                    "addconnection",
-                    &[into_json(address)?, into_json(connection_type)?, into_json(v2transport)?],
+                $crate::maybe_await!(
+                    self.call(
+                        "addconnection",
+                        &[
+                            into_json(address)?,
+                            into_json(connection_type)?,
+                            into_json(v2transport)?,
+                        ],
+                    ),
+                    $($async)?
                 )
             }
         }
@@ -2,7 +2,9 @@
 
 //! A JSON-RPC client for testing against Bitcoin Core `v28`.
 //!
-//! We ignore option arguments unless they effect the shape of the returned JSON data.
+//! We ignore option arguments unless they effect the shape of the returned JSON data. A few methods
+//! with many optional arguments also have a variant taking one of the structs in
+//! [`crate::client_sync::options`].
 
 pub mod raw_transactions;
 pub mod wallet;
@@ -27,6 +29,7 @@ pub use crate::client_sync::{
 };
 
 crate::define_jsonrpc_bitreq_client!("v28");
+crate::impl_client_v17__batch!();
 crate::impl_client_check_expected_server_version!({ [280000, 280100, 280200] });
 
 // == Blockchain ==
@@ -12,9 +12,9 @@
 /// Implements Bitcoin Core JSON-RPC API method `submitpackage`.
 #[macro_export]
 macro_rules! impl_client_v28__submit_package {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn submit_package(
+            pub $($async)? fn submit_package(
                 &self,
                 package: &[bitcoin::Transaction],
                 max_fee_rate: Option<bitcoin::FeeRate>,
@@ -27,9 +27,16 @@ macro_rules! impl_client_v28__submit_package {
                 let max_fee_rate_btc_kvb =
                     max_fee_rate.map(|r| r.to_sat_per_vb_floor() as f64 / 100_000.0);
                 let max_burn_amount_btc = max_burn_amount.map(|a| a.to_btc());
-                self.call(
-                    "submitpackage",
-                    &[package_txs.into(), max_fee_rate_btc_kvb.into(), max_burn_amount_btc.into()],
+                $crate::maybe_await!(
+                    self.call(
+                        "submitpackage",
+                        &[
+                            package_txs.into(),
+                            max_fee_rate_btc_kvb.into(),
+                            max_burn_amount_btc.into(),
+                        ],
+                    ),
+                    $($async)?
                 )
             }
         }
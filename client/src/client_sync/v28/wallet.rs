@@ -12,9 +12,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `gethdkeys`.
 #[macro_export]
 macro_rules! impl_client_v28__get_hd_keys {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_hd_keys(&self) -> Result<GetHdKeys> { self.call("gethdkeys", &[]) }
+            pub $($async)? fn get_hd_keys(&self) -> Result<GetHdKeys> {
+                $crate::maybe_await!(self.call("gethdkeys", &[]), $($async)?)
+            }
         }
     };
 }
@@ -22,11 +24,18 @@ macro_rules! impl_client_v28__get_hd_keys {
 /// Implements Bitcoin Core JSON-RPC API method `createwalletdescriptor`.
 #[macro_export]
 macro_rules! impl_client_v28__create_wallet_descriptor {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn create_wallet_descriptor(&self, address_type: &str, hdkey: &str) -> Result<CreateWalletDescriptor> {
+            pub $($async)? fn create_wallet_descriptor(
+                &self,
+                address_type: &str,
+                hdkey: &str,
+            ) -> Result<CreateWalletDescriptor> {
                 let hdkey = serde_json::json!({ "hdkey": hdkey });
-                self.call("createwalletdescriptor", &[address_type.into(), hdkey.into()])
+                $crate::maybe_await!(
+                    self.call("createwalletdescriptor", &[address_type.into(), hdkey.into()]),
+                    $($async)?
+                )
             }
         }
     };
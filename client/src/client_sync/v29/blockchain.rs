@@ -12,10 +12,17 @@
 /// Implements Bitcoin Core JSON-RPC API method `dumptxoutset`.
 #[macro_export]
 macro_rules! impl_client_v29__dump_tx_out_set {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn dump_tx_out_set(&self, path: &str, snapshot_type: &str) -> Result<DumpTxOutSet> {
-                self.call("dumptxoutset", &[path.into(), snapshot_type.into()])
+            pub $($async)? fn dump_tx_out_set(
+                &self,
+                path: &str,
+                snapshot_type: &str,
+            ) -> Result<DumpTxOutSet> {
+                $crate::maybe_await!(
+                    self.call("dumptxoutset", &[path.into(), snapshot_type.into()]),
+                    $($async)?
+                )
             }
         }
     };
@@ -24,14 +31,14 @@ macro_rules! impl_client_v29__dump_tx_out_set {
 /// Implements Bitcoin Core JSON-RPC API method `getdescriptoractivity`.
 #[macro_export]
 macro_rules! impl_client_v29__get_descriptor_activity {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_descriptor_activity(&self) -> Result<GetDescriptorActivity> {
+            pub $($async)? fn get_descriptor_activity(&self) -> Result<GetDescriptorActivity> {
                 let block_hashes: &[BlockHash] = &[];
                 let scan_objects: &[&str] = &[];
                 // FIXME: Core errors if we don't pass empty arrays?
                 let params = vec![json!(block_hashes), json!(scan_objects)];
-                self.call("getdescriptoractivity", &params)
+                $crate::maybe_await!(self.call("getdescriptoractivity", &params), $($async)?)
             }
         }
     };
@@ -40,32 +47,56 @@ macro_rules! impl_client_v29__get_descriptor_activity {
 /// Implements Bitcoin Core JSON-RPC API method `getblock`.
 #[macro_export]
 macro_rules! impl_client_v29__get_block {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             /// Gets a block by blockhash. Kept for compatibility; uses verbose set to 0.
-            pub fn get_block(&self, hash: BlockHash) -> Result<Block> {
-                let json = self.get_block_verbose_zero(hash)?;
+            pub $($async)? fn get_block(&self, hash: BlockHash) -> Result<Block> {
+                let json = $crate::maybe_await!(self.get_block_verbose_zero(hash), $($async)?)?;
                 Ok(json.block()?)
             }
 
             /// Gets a block by blockhash with verbose set to 0.
-            pub fn get_block_verbose_zero(&self, hash: BlockHash) -> Result<GetBlockVerboseZero> {
-                self.call("getblock", &[into_json(hash)?, 0.into()])
+            pub $($async)? fn get_block_verbose_zero(
+                &self,
+                hash: BlockHash,
+            ) -> Result<GetBlockVerboseZero> {
+                $crate::maybe_await!(
+                    self.call("getblock", &$crate::client_sync::args::get_block_verbose_zero(hash)),
+                    $($async)?
+                )
             }
 
             /// Gets a block by blockhash with verbose set to 1.
-            pub fn get_block_verbose_one(&self, hash: BlockHash) -> Result<GetBlockVerboseOne> {
-                self.call("getblock", &[into_json(hash)?, 1.into()])
+            pub $($async)? fn get_block_verbose_one(
+                &self,
+                hash: BlockHash,
+            ) -> Result<GetBlockVerboseOne> {
+                $crate::maybe_await!(
+                    self.call("getblock", &$crate::client_sync::args::get_block_verbose_one(hash)),
+                    $($async)?
+                )
             }
 
             /// Gets a block by blockhash with verbose set to 2.
-            pub fn get_block_verbose_two(&self, hash: BlockHash) -> Result<GetBlockVerboseTwo> {
-                self.call("getblock", &[into_json(hash)?, 2.into()])
+            pub $($async)? fn get_block_verbose_two(
+                &self,
+                hash: BlockHash,
+            ) -> Result<GetBlockVerboseTwo> {
+                $crate::maybe_await!(
+                    self.call("getblock", &[into_json(hash)?, 2.into()]),
+                    $($async)?
+                )
             }
 
             /// Gets a block by blockhash with verbose set to 3.
-            pub fn get_block_verbose_three(&self, hash: BlockHash) -> Result<GetBlockVerboseThree> {
-                self.call("getblock", &[into_json(hash)?, 3.into()])
+            pub $($async)? fn get_block_verbose_three(
+                &self,
+                hash: BlockHash,
+            ) -> Result<GetBlockVerboseThree> {
+                $crate::maybe_await!(
+                    self.call("getblock", &[into_json(hash)?, 3.into()]),
+                    $($async)?
+                )
             }
         }
     };
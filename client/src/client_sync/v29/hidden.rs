@@ -12,9 +12,11 @@
 /// Implements Bitcoin Core JSON-RPC API method `getorphantxs` verbosity level 0.
 #[macro_export]
 macro_rules! impl_client_v29__get_orphan_txs {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_orphan_txs(&self) -> Result<GetOrphanTxs> { self.call("getorphantxs", &[]) }
+            pub $($async)? fn get_orphan_txs(&self) -> Result<GetOrphanTxs> {
+                $crate::maybe_await!(self.call("getorphantxs", &[]), $($async)?)
+            }
         }
     };
 }
@@ -22,10 +24,10 @@ macro_rules! impl_client_v29__get_orphan_txs {
 /// Implements Bitcoin Core JSON-RPC API method `getorphantxs` verbosity level 1.
 #[macro_export]
 macro_rules! impl_client_v29__get_orphan_txs_verbosity_1 {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_orphan_txs_verbosity_1(&self) -> Result<GetOrphanTxsVerboseOne> {
-                self.call("getorphantxs", &[into_json(1)?])
+            pub $($async)? fn get_orphan_txs_verbosity_1(&self) -> Result<GetOrphanTxsVerboseOne> {
+                $crate::maybe_await!(self.call("getorphantxs", &[into_json(1)?]), $($async)?)
             }
         }
     };
@@ -34,10 +36,10 @@ macro_rules! impl_client_v29__get_orphan_txs_verbosity_1 {
 /// Implements Bitcoin Core JSON-RPC API method `getorphantxs` verbosity level 2.
 #[macro_export]
 macro_rules! impl_client_v29__get_orphan_txs_verbosity_2 {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
-            pub fn get_orphan_txs_verbosity_2(&self) -> Result<GetOrphanTxsVerboseTwo> {
-                self.call("getorphantxs", &[into_json(2)?])
+            pub $($async)? fn get_orphan_txs_verbosity_2(&self) -> Result<GetOrphanTxsVerboseTwo> {
+                $crate::maybe_await!(self.call("getorphantxs", &[into_json(2)?]), $($async)?)
             }
         }
     };
@@ -12,21 +12,24 @@
 /// Implements Bitcoin Core JSON-RPC API method `deriveaddresses`.
 #[macro_export]
 macro_rules! impl_client_v29__derive_addresses {
-    () => {
+    ($($async:tt)?) => {
         impl Client {
             // For single derivation descriptors.
-            pub fn derive_addresses(&self, descriptor: &str) -> Result<DeriveAddresses> {
-                self.call("deriveaddresses", &[descriptor.into()])
+            pub $($async)? fn derive_addresses(&self, descriptor: &str) -> Result<DeriveAddresses> {
+                $crate::maybe_await!(self.call("deriveaddresses", &[descriptor.into()]), $($async)?)
             }
 
             // For multipath descriptors.
-            pub fn derive_addresses_multipath(
+            pub $($async)? fn derive_addresses_multipath(
                 &self,
                 descriptor: &str,
                 range: (u32, u32),
             ) -> Result<DeriveAddressesMultipath> {
                 let range = json!([range.0, range.1]);
-                self.call("deriveaddresses", &[descriptor.into(), range.into()])
+                $crate::maybe_await!(
+                    self.call("deriveaddresses", &[descriptor.into(), range.into()]),
+                    $($async)?
+                )
             }
         }
     };
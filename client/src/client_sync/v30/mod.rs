@@ -2,7 +2,9 @@
 
 //! A JSON-RPC client for testing against Bitcoin Core `v30`.
 //!
-//! We ignore option arguments unless they effect the shape of the returned JSON data.
+//! We ignore option arguments unless they effect the shape of the returned JSON data. A few methods
+//! with many optional arguments also have a variant taking one of the structs in
+//! [`crate::client_sync::options`].
 
 use std::collections::BTreeMap;
 use std::path::Path;
@@ -23,6 +25,7 @@ pub use crate::client_sync::{
 };
 
 crate::define_jsonrpc_bitreq_client!("v30");
+crate::impl_client_v17__batch!();
 crate::impl_client_check_expected_server_version!({ [300000, 300100, 300200] });
 
 // == Blockchain ==
@@ -1117,6 +1117,34 @@ fn wallet__wallet_create_funded_psbt__modelled() {
     assert!(!psbt.psbt.inputs.is_empty());
 }
 
+#[test]
+fn wallet__wallet_create_funded_psbt_with_options() {
+    use node::client::client_sync::options::{
+        FundRawTransactionOptions, WalletCreateFundedPsbtOptions,
+    };
+
+    let node = Node::with_wallet(Wallet::Default, &[]);
+    node.fund_wallet();
+
+    let addr = node.client.new_address().expect("newaddress");
+    let outputs = BTreeMap::from([(addr, Amount::from_sat(100_000))]);
+    let options = WalletCreateFundedPsbtOptions {
+        fund: FundRawTransactionOptions { replaceable: Some(true), ..Default::default() },
+        bip32derivs: Some(true),
+        ..Default::default()
+    };
+    let json: WalletCreateFundedPsbt = node
+        .client
+        .wallet_create_funded_psbt_with_options(vec![], vec![outputs], &options)
+        .expect("walletcreatefundedpsbt");
+
+    let model: Result<mtype::WalletCreateFundedPsbt, WalletCreateFundedPsbtError> =
+        json.into_model();
+    let psbt = model.unwrap().psbt;
+
+    assert!(psbt.unsigned_tx.input.iter().all(|input| input.sequence.is_rbf()));
+}
+
 #[test]
 fn wallet__wallet_process_psbt__modelled() {
     let node = Node::with_wallet(Wallet::Default, &[]);
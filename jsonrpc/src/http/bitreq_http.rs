@@ -119,6 +119,26 @@ impl Builder {
         Ok(self)
     }
 
+    /// Sends requests over the Unix domain socket at `path` instead of TCP, eg. to talk to a
+    /// bitcoind whose RPC server is only exposed on a socket.
+    ///
+    /// This replaces the URL of the transport with an `http+unix://` URL for the socket.
+    #[cfg(unix)]
+    pub fn unix_socket<P: AsRef<std::path::Path>>(mut self, path: P) -> Self {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut url = String::from("http+unix://");
+        for &byte in path.as_ref().as_os_str().as_bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                url.push(byte as char);
+            } else {
+                url.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        self.tp.url = url;
+        self
+    }
+
     /// Adds authentication information to the transport.
     pub fn basic_auth(mut self, user: String, pass: Option<String>) -> Self {
         let mut s = user;
@@ -271,4 +291,11 @@ mod tests {
             .build();
         let _ = Client::with_transport(tp);
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn unix_socket_url() {
+        let tp = Builder::new().unix_socket("/home/user/.bitcoin/rpc sock").build();
+        assert_eq!(tp.url, "http+unix://%2Fhome%2Fuser%2F.bitcoin%2Frpc%20sock");
+    }
 }
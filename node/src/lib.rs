@@ -351,19 +351,18 @@ impl Node {
 
             let client_base = Self::create_client_base(&rpc_url, &auth)?;
             let client = match &conf.wallet {
-                Some(wallet) =>
-                    match Self::create_client_wallet(&client_base, &rpc_url, &auth, wallet) {
-                        Ok(client) => client,
-                        Err(e) =>
-                            if attempt == conf.attempts - 1 {
-                                return Err(e);
-                            } else {
-                                // If the wallet cannot be created or loaded, there might be an issue
-                                // with the work_dir or process. Kill the process and retry.
-                                let _ = process.kill();
-                                continue;
-                            },
-                    },
+                Some(wallet) => match Self::create_client_wallet(&client_base, wallet) {
+                    Ok(client) => client,
+                    Err(e) =>
+                        if attempt == conf.attempts - 1 {
+                            return Err(e);
+                        } else {
+                            // If the wallet cannot be created or loaded, there might be an issue
+                            // with the work_dir or process. Kill the process and retry.
+                            let _ = process.kill();
+                            continue;
+                        },
+                },
                 None => client_base,
             };
             if Self::wait_for_client(&client, Duration::from_secs(5)).is_err() {
@@ -508,19 +507,12 @@ impl Node {
     /// If the wallet already exists, it will load it.
     ///
     /// The client or wallet may not be immediately available, so retry up to 10 times.
-    fn create_client_wallet(
-        client_base: &Client,
-        rpc_url: &str,
-        auth: &Auth,
-        wallet: &str,
-    ) -> anyhow::Result<Client> {
+    fn create_client_wallet(client_base: &Client, wallet: &str) -> anyhow::Result<Client> {
         for _ in 0..10 {
             // Try to create the wallet, or if that fails it might already exist so try to load it.
             if client_base.create_wallet(wallet).is_ok() || client_base.load_wallet(wallet).is_ok()
             {
-                let url = format!("{}/wallet/{}", rpc_url, wallet);
-                return Client::new_with_auth(&url, auth.clone())
-                    .map_err(|e| Error::NoBitcoindInstance(e.to_string()).into());
+                return Ok(client_base.wallet(wallet));
             }
             thread::sleep(Duration::from_millis(200));
         }
@@ -664,6 +656,7 @@ pub fn validate_args(args: Vec<&str>) -> anyhow::Result<Vec<&str>> {
 mod test {
     use std::net::SocketAddrV4;
 
+    use corepc_client::client_sync::RpcAuth;
     use tempfile::TempDir;
 
     use super::*;
@@ -893,6 +886,23 @@ mod test {
         assert_eq!(1, info.blocks);
     }
 
+    #[test]
+    fn test_node_generated_rpcauth() {
+        let exe = init();
+
+        let rpcauth = RpcAuth::with_salt("alice", "hunter2", "0123456789abcdef0123456789abcdef");
+        let auth = Auth::RpcAuth(rpcauth, "hunter2".to_owned());
+        let arg = auth.rpcauth().unwrap().to_arg();
+        let mut conf = Conf::default();
+        conf.args.push(&arg);
+
+        let node = Node::with_conf(exe, &conf).unwrap();
+
+        let client = Client::new_with_auth(node.rpc_url().as_str(), auth).unwrap();
+        let info = client.get_blockchain_info().unwrap();
+        assert_eq!(0, info.blocks);
+    }
+
     #[test]
     fn test_get_cookie_user_and_pass() {
         let exe = init();